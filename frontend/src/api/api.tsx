@@ -41,6 +41,9 @@ export const Api = {
             resetByUuid: (uuid: UUID, req: ResetPasswordRequest) =>
                 handleError(commonApi.resetPasswordByUuid({ uuid, ResetPasswordRequest: req })),
         },
+        recoveryEmail: {
+            verify: (token: string) => verifyRecoveryEmail(token),
+        },
         me: {
             get: () => commonApi.getMe(),
             update: (req: UpdateMeRequest) => handleError(commonApi.updateMe({ UpdateMeRequest: req })),
@@ -52,6 +55,25 @@ export const Api = {
     },
 };
 
+/**
+ * Verify a recovery email using the token from the link sent to it
+ *
+ * The endpoint isn't part of the generated common SDK, so the request is sent manually.
+ *
+ * @param token The token from the verification link
+ */
+async function verifyRecoveryEmail(token: string): Promise<void> {
+    const res = await fetch("/api/v1/frontend/recovery-email/verify", {
+        method: "POST",
+        body: JSON.stringify({ token }),
+        headers: { "Content-Type": "application/json" },
+    });
+
+    if (!res.ok) {
+        throw await parseError(res);
+    }
+}
+
 /**
  * Wraps a promise returned by the generated SDK which handles its errors and returns a {@link Result}
  *
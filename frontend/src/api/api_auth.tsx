@@ -4,11 +4,57 @@ import { parseError } from "src/api/error";
 
 const authApi = new DefaultApi(new Configuration({ basePath: window.location.origin }));
 
+/**
+ * A pending device authorization request
+ */
+export type DeviceVerification = {
+    /** Name of the requesting client */
+    client_name: string;
+    /** The scopes the client requests */
+    scopes: Array<string>;
+    /** The point in time the request expires */
+    expires_at: string;
+};
+
 export const AuthApi = {
     login: (username: string, password: string) => authApi.signIn({ SignInRequest: { username, password } }),
     logout: () => handleError(authApi.signOut()),
+    device: {
+        get: (userCode: string) => deviceVerification<DeviceVerification>(userCode, "GET"),
+        decide: (userCode: string, approve: boolean) =>
+            deviceVerification<void>(userCode, "POST", JSON.stringify({ approve })),
+    },
 };
 
+/**
+ * Sends a request to the device verification endpoint
+ *
+ * The device flow isn't part of the generated auth SDK, so the request is sent manually.
+ *
+ * @param userCode The user code displayed by the device
+ * @param method The http method to use
+ * @param body The optional json body
+ *
+ * @returns a promise which throws `"Unauthenticated"` if there is no session
+ */
+async function deviceVerification<T>(userCode: string, method: "GET" | "POST", body?: string): Promise<T> {
+    const res = await fetch(`/api/v1/auth/device/verify/${encodeURIComponent(userCode)}`, {
+        method,
+        body,
+        headers: body !== undefined ? { "Content-Type": "application/json" } : undefined,
+    });
+
+    if (res.status === 401) {
+        throw "Unauthenticated";
+    }
+    if (!res.ok) {
+        throw await parseError(res);
+    }
+
+    const text = await res.text();
+    return (text === "" ? undefined : JSON.parse(text)) as T;
+}
+
 /**
  * Wraps a promise returned by the generated SDK which handles its errors and returns a {@link Result}
  *
@@ -19,6 +19,8 @@ import { Route as LinksResetIndexRouteImport } from './routes/links/reset/index'
 import { Route as LinksResetUuidRouteImport } from './routes/links/reset/$uuid'
 import { Route as LinksOidcErrorRouteImport } from './routes/links/oidc/error'
 import { Route as LinksOidcAuthRouteImport } from './routes/links/oidc/auth'
+import { Route as LinksRecoveryEmailVerifyRouteImport } from './routes/links/recovery-email/verify'
+import { Route as LinksDeviceRouteImport } from './routes/links/device'
 import { Route as LinksInviteInviteIdRouteImport } from './routes/links/invite/$inviteId'
 import { Route as MenuProfileProfileRouteImport } from './routes/_menu/profile/_profile'
 import { Route as MenuMDashboardRouteImport } from './routes/_menu/m/dashboard'
@@ -85,6 +87,16 @@ const LinksOidcAuthRoute = LinksOidcAuthRouteImport.update({
   path: '/links/oidc/auth',
   getParentRoute: () => rootRouteImport,
 } as any)
+const LinksRecoveryEmailVerifyRoute = LinksRecoveryEmailVerifyRouteImport.update({
+  id: '/links/recovery-email/verify',
+  path: '/links/recovery-email/verify',
+  getParentRoute: () => rootRouteImport,
+} as any)
+const LinksDeviceRoute = LinksDeviceRouteImport.update({
+  id: '/links/device',
+  path: '/links/device',
+  getParentRoute: () => rootRouteImport,
+} as any)
 const LinksInviteInviteIdRoute = LinksInviteInviteIdRouteImport.update({
   id: '/links/invite/$inviteId',
   path: '/links/invite/$inviteId',
@@ -197,6 +209,8 @@ export interface FileRoutesByFullPath {
   '/profile': typeof MenuProfileProfileRouteWithChildren
   '/links/invite/$inviteId': typeof LinksInviteInviteIdRoute
   '/links/oidc/auth': typeof LinksOidcAuthRoute
+  '/links/recovery-email/verify': typeof LinksRecoveryEmailVerifyRoute
+  '/links/device': typeof LinksDeviceRoute
   '/links/oidc/error': typeof LinksOidcErrorRoute
   '/links/reset/$uuid': typeof LinksResetUuidRoute
   '/links/reset/': typeof LinksResetIndexRoute
@@ -225,6 +239,8 @@ export interface FileRoutesByTo {
   '/m/dashboard': typeof MenuMDashboardRoute
   '/links/invite/$inviteId': typeof LinksInviteInviteIdRoute
   '/links/oidc/auth': typeof LinksOidcAuthRoute
+  '/links/recovery-email/verify': typeof LinksRecoveryEmailVerifyRoute
+  '/links/device': typeof LinksDeviceRoute
   '/links/oidc/error': typeof LinksOidcErrorRoute
   '/links/reset/$uuid': typeof LinksResetUuidRoute
   '/links/reset': typeof LinksResetIndexRoute
@@ -255,6 +271,8 @@ export interface FileRoutesById {
   '/_menu/profile/_profile': typeof MenuProfileProfileRouteWithChildren
   '/links/invite/$inviteId': typeof LinksInviteInviteIdRoute
   '/links/oidc/auth': typeof LinksOidcAuthRoute
+  '/links/recovery-email/verify': typeof LinksRecoveryEmailVerifyRoute
+  '/links/device': typeof LinksDeviceRoute
   '/links/oidc/error': typeof LinksOidcErrorRoute
   '/links/reset/$uuid': typeof LinksResetUuidRoute
   '/links/reset/': typeof LinksResetIndexRoute
@@ -286,6 +304,8 @@ export interface FileRouteTypes {
     | '/profile'
     | '/links/invite/$inviteId'
     | '/links/oidc/auth'
+    | '/links/recovery-email/verify'
+    | '/links/device'
     | '/links/oidc/error'
     | '/links/reset/$uuid'
     | '/links/reset/'
@@ -314,6 +334,8 @@ export interface FileRouteTypes {
     | '/m/dashboard'
     | '/links/invite/$inviteId'
     | '/links/oidc/auth'
+    | '/links/recovery-email/verify'
+    | '/links/device'
     | '/links/oidc/error'
     | '/links/reset/$uuid'
     | '/links/reset'
@@ -343,6 +365,8 @@ export interface FileRouteTypes {
     | '/_menu/profile/_profile'
     | '/links/invite/$inviteId'
     | '/links/oidc/auth'
+    | '/links/recovery-email/verify'
+    | '/links/device'
     | '/links/oidc/error'
     | '/links/reset/$uuid'
     | '/links/reset/'
@@ -371,6 +395,8 @@ export interface RootRouteChildren {
   OidcErrorRoute: typeof OidcErrorRoute
   LinksInviteInviteIdRoute: typeof LinksInviteInviteIdRoute
   LinksOidcAuthRoute: typeof LinksOidcAuthRoute
+  LinksRecoveryEmailVerifyRoute: typeof LinksRecoveryEmailVerifyRoute
+  LinksDeviceRoute: typeof LinksDeviceRoute
   LinksOidcErrorRoute: typeof LinksOidcErrorRoute
   LinksResetUuidRoute: typeof LinksResetUuidRoute
   LinksResetIndexRoute: typeof LinksResetIndexRoute
@@ -441,6 +467,20 @@ declare module '@tanstack/react-router' {
       preLoaderRoute: typeof LinksOidcAuthRouteImport
       parentRoute: typeof rootRouteImport
     }
+    '/links/recovery-email/verify': {
+      id: '/links/recovery-email/verify'
+      path: '/links/recovery-email/verify'
+      fullPath: '/links/recovery-email/verify'
+      preLoaderRoute: typeof LinksRecoveryEmailVerifyRouteImport
+      parentRoute: typeof rootRouteImport
+    }
+    '/links/device': {
+      id: '/links/device'
+      path: '/links/device'
+      fullPath: '/links/device'
+      preLoaderRoute: typeof LinksDeviceRouteImport
+      parentRoute: typeof rootRouteImport
+    }
     '/links/invite/$inviteId': {
       id: '/links/invite/$inviteId'
       path: '/links/invite/$inviteId'
@@ -659,6 +699,8 @@ const rootRouteChildren: RootRouteChildren = {
   OidcErrorRoute: OidcErrorRoute,
   LinksInviteInviteIdRoute: LinksInviteInviteIdRoute,
   LinksOidcAuthRoute: LinksOidcAuthRoute,
+  LinksRecoveryEmailVerifyRoute: LinksRecoveryEmailVerifyRoute,
+  LinksDeviceRoute: LinksDeviceRoute,
   LinksOidcErrorRoute: LinksOidcErrorRoute,
   LinksResetUuidRoute: LinksResetUuidRoute,
   LinksResetIndexRoute: LinksResetIndexRoute,
@@ -0,0 +1,179 @@
+import { createFileRoute, Navigate } from "@tanstack/react-router";
+import { useTranslation } from "react-i18next";
+import { Api } from "src/api/api";
+import { DeviceVerification } from "src/api/api_auth";
+import Form from "src/components/base/form";
+import { ErrorMessage, Field, FieldGroup, RequiredLabel } from "src/components/base/fieldset";
+import { useForm } from "@tanstack/react-form";
+import { Input } from "src/components/base/input";
+import { Button, PrimaryButton } from "src/components/base/button";
+import { Text, TextLink } from "src/components/base/text";
+import { Heading } from "src/components/base/heading";
+import React from "react";
+import { AuthLayout } from "src/components/base/auth-layout";
+
+/**
+ * Props for {@link DeviceVerificationPage}
+ */
+export type DeviceVerificationPageProps = {};
+
+/**
+ * Page to approve or deny the login of a device using its user code
+ */
+export default function DeviceVerificationPage(props: DeviceVerificationPageProps) {
+    const [t] = useTranslation("device");
+    const search = Route.useSearch();
+
+    const [step, setStep] = React.useState<"code" | "decide" | "approved" | "denied">("code");
+    const [userCode, setUserCode] = React.useState("");
+    const [request, setRequest] = React.useState<DeviceVerification>();
+    const [error, setError] = React.useState<string>();
+    const [unauthenticated, setUnauthenticated] = React.useState(false);
+
+    /**
+     * Load the pending request of a user code
+     *
+     * @param code The user code displayed by the device
+     */
+    const load = async (code: string) => {
+        setError(undefined);
+        try {
+            setRequest(await Api.auth.device.get(code));
+            setUserCode(code);
+            setStep("decide");
+        } catch (e) {
+            if (e === "Unauthenticated") {
+                setUnauthenticated(true);
+            } else {
+                setError(t("error.invalid-code"));
+            }
+        }
+    };
+
+    /**
+     * Send the decision of the user
+     *
+     * @param approve Whether the device is granted access
+     */
+    const decide = async (approve: boolean) => {
+        setError(undefined);
+        try {
+            await Api.auth.device.decide(userCode, approve);
+            setStep(approve ? "approved" : "denied");
+        } catch (e) {
+            if (e === "Unauthenticated") {
+                setUnauthenticated(true);
+            } else {
+                setError(t("error.decision-failed"));
+            }
+        }
+    };
+
+    React.useEffect(() => {
+        if (search.user_code) {
+            load(search.user_code).then();
+        }
+    }, [search.user_code]);
+
+    const codeForm = useForm({
+        defaultValues: { code: search.user_code ?? "" },
+        onSubmit: async ({ value }) => await load(value.code.trim()),
+    });
+
+    if (unauthenticated) {
+        return (
+            <Navigate
+                to={"/oidc/auth"}
+                search={{ redirect_url: window.location.pathname + window.location.search, external: false }}
+                replace={true}
+            />
+        );
+    }
+
+    if (step === "approved" || step === "denied") {
+        return (
+            <AuthLayout>
+                <div className={"flex flex-col gap-8"}>
+                    <Heading>{t(`heading.${step}`)}</Heading>
+                    <Text className={"max-w-[60ch]"}>{t(`description.${step}`)}</Text>
+                </div>
+            </AuthLayout>
+        );
+    }
+
+    if (step === "decide" && request) {
+        return (
+            <AuthLayout>
+                <div className={"grid w-full max-w-sm grid-cols-1 gap-8"}>
+                    <Heading>{t("heading.decide", { name: request.client_name })}</Heading>
+                    <Text>{t("description.decide", { code: userCode })}</Text>
+                    <ul className={"list-inside list-disc text-sm dark:text-zinc-300"}>
+                        {request.scopes.map((scope) => (
+                            <li key={scope}>{scope}</li>
+                        ))}
+                    </ul>
+                    {error && <ErrorMessage>{error}</ErrorMessage>}
+                    <div className={"flex gap-4"}>
+                        <Button outline={true} className={"w-full"} onClick={() => decide(false)}>
+                            {t("button.deny")}
+                        </Button>
+                        <PrimaryButton className={"w-full"} onClick={() => decide(true)}>
+                            {t("button.approve")}
+                        </PrimaryButton>
+                    </div>
+                </div>
+            </AuthLayout>
+        );
+    }
+
+    return (
+        <AuthLayout>
+            <Form onSubmit={codeForm.handleSubmit} className={"grid w-full max-w-sm grid-cols-1 gap-8"}>
+                <Heading>{t("heading.enter-code")}</Heading>
+                <Text>{t("description.enter-code")}</Text>
+                <FieldGroup>
+                    <codeForm.Field name={"code"}>
+                        {(fieldApi) => (
+                            <Field>
+                                <RequiredLabel>{t("label.code")}</RequiredLabel>
+                                <Input
+                                    autoFocus={true}
+                                    required={true}
+                                    className={"font-mono text-2xl tracking-widest"}
+                                    value={fieldApi.state.value}
+                                    onChange={(e) => fieldApi.handleChange(e.target.value)}
+                                />
+                                {error && <ErrorMessage>{error}</ErrorMessage>}
+                            </Field>
+                        )}
+                    </codeForm.Field>
+
+                    <PrimaryButton type={"submit"} className={"w-full"}>
+                        {t("button.continue")}
+                    </PrimaryButton>
+                </FieldGroup>
+
+                <TextLink className={"text-sm"} href={"/"}>
+                    {t("button.back")}
+                </TextLink>
+            </Form>
+        </AuthLayout>
+    );
+}
+
+/**
+ * Search parameters for the device verification route
+ */
+type SearchParams = {
+    /** The user code displayed by the device */
+    user_code?: string;
+};
+
+export const Route = createFileRoute("/links/device")({
+    component: DeviceVerificationPage,
+    validateSearch: (search: Record<string, unknown>): SearchParams => {
+        return {
+            user_code: (search?.user_code as string) || undefined,
+        };
+    },
+});
@@ -0,0 +1,67 @@
+import { createFileRoute } from "@tanstack/react-router";
+import { useTranslation } from "react-i18next";
+import { Api } from "src/api/api";
+import { Text, TextLink } from "src/components/base/text";
+import { Heading } from "src/components/base/heading";
+import React from "react";
+import { AuthLayout } from "src/components/base/auth-layout";
+
+/**
+ * Props for {@link VerifyRecoveryEmail}
+ */
+export type VerifyRecoveryEmailProps = {};
+
+/**
+ * Page verifying a recovery email using the link sent to it
+ */
+export default function VerifyRecoveryEmail(props: VerifyRecoveryEmailProps) {
+    const [t] = useTranslation("recovery-email");
+    const { token } = Route.useSearch();
+
+    const [state, setState] = React.useState<"loading" | "verified" | "invalid">("loading");
+
+    React.useEffect(() => {
+        if (!token) {
+            setState("invalid");
+            return;
+        }
+
+        Api.common.recoveryEmail
+            .verify(token)
+            .then(() => setState("verified"))
+            .catch(() => setState("invalid"));
+    }, [token]);
+
+    if (state === "loading") {
+        return <div></div>;
+    }
+
+    return (
+        <AuthLayout>
+            <div className={"flex flex-col gap-8"}>
+                <Heading>{t(`heading.${state}`)}</Heading>
+                <Text className={"max-w-[60ch]"}>{t(`description.${state}`)}</Text>
+                <TextLink className={"text-sm"} href={"/"}>
+                    {t("button.back")}
+                </TextLink>
+            </div>
+        </AuthLayout>
+    );
+}
+
+/**
+ * Search parameters for the recovery email verification route
+ */
+type SearchParams = {
+    /** The token from the link sent to the recovery email */
+    token?: string;
+};
+
+export const Route = createFileRoute("/links/recovery-email/verify")({
+    component: VerifyRecoveryEmail,
+    validateSearch: (search: Record<string, unknown>): SearchParams => {
+        return {
+            token: (search?.token as string) || undefined,
+        };
+    },
+});
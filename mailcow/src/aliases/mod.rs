@@ -0,0 +1,55 @@
+//! Endpoints for managing aliases in mailcow
+
+use tracing::instrument;
+
+use crate::MailcowClient;
+use crate::aliases::schema::CreateAliasRequest;
+use crate::aliases::schema::GetAliasesResponse;
+use crate::aliases::schema::InnerCreateAliasRequest;
+use crate::aliases::schema::MailcowAlias;
+use crate::error::MailcowResult;
+
+pub mod schema;
+
+impl MailcowClient {
+    /// Retrieves all aliases from the Mailcow API
+    #[instrument(name = "MailcowClient::get_all_aliases", skip(self))]
+    pub async fn get_all_aliases(&self) -> MailcowResult<Vec<MailcowAlias>> {
+        let aliases = self
+            .get("/api/v1/get/alias/all")
+            .send::<GetAliasesResponse>()
+            .await?;
+
+        match aliases {
+            GetAliasesResponse::List(aliases) => Ok(aliases),
+            GetAliasesResponse::Empty(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// Create a new alias forwarding mails to a list of addresses
+    #[instrument(name = "MailcowClient::create_alias", skip(self))]
+    pub async fn create_alias(&self, req: CreateAliasRequest) -> MailcowResult<()> {
+        self.post("/api/v1/add/alias")
+            .body(&InnerCreateAliasRequest {
+                active: "1".to_string(),
+                address: req.address,
+                goto: req.goto.join(","),
+            })
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete aliases by their ids
+    #[instrument(name = "MailcowClient::delete_aliases", skip(self))]
+    pub async fn delete_aliases(&self, ids: Vec<u64>) -> MailcowResult<()> {
+        let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<String>>();
+        self.post("/api/v1/delete/alias")
+            .body(&ids)
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+//! Schema for mailcow alias endpoints
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// An alias in mailcow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailcowAlias {
+    /// ID of the alias
+    pub id: u64,
+    /// Address of the alias
+    pub address: String,
+    /// Comma separated list of addresses mails are forwarded to
+    pub goto: String,
+}
+
+impl MailcowAlias {
+    /// Check whether the alias forwards mails to an address
+    pub fn forwards_to(&self, address: &str) -> bool {
+        self.goto
+            .split(',')
+            .any(|goto| goto.trim().eq_ignore_ascii_case(address))
+    }
+}
+
+/// Create a new alias
+#[derive(Debug, Clone)]
+pub struct CreateAliasRequest {
+    /// Address of the alias
+    pub address: String,
+    /// Addresses mails are forwarded to
+    pub goto: Vec<String>,
+}
+
+/// Options for get aliases responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetAliasesResponse {
+    /// List of aliases
+    List(Vec<MailcowAlias>),
+    /// Empty response
+    Empty(HashMap<String, String>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InnerCreateAliasRequest {
+    pub active: String,
+    pub address: String,
+    pub goto: String,
+}
@@ -0,0 +1,45 @@
+//! Endpoints for managing DKIM keys in mailcow
+
+use tracing::instrument;
+
+use crate::MailcowClient;
+use crate::dkim::schema::CreateDkimRequest;
+use crate::dkim::schema::GetDkimResponse;
+use crate::dkim::schema::InnerCreateDkimRequest;
+use crate::dkim::schema::MailcowDkim;
+use crate::error::MailcowResult;
+
+pub mod schema;
+
+impl MailcowClient {
+    /// Retrieves the DKIM key of a domain
+    ///
+    /// Returns `None` if no key was generated for the domain.
+    #[instrument(name = "MailcowClient::get_dkim", skip(self))]
+    pub async fn get_dkim(&self, domain: &str) -> MailcowResult<Option<MailcowDkim>> {
+        let dkim = self
+            .get(&format!("/api/v1/get/dkim/{domain}"))
+            .send::<GetDkimResponse>()
+            .await?;
+
+        match dkim {
+            GetDkimResponse::Dkim(dkim) => Ok(Some(dkim)),
+            GetDkimResponse::Empty(_) => Ok(None),
+        }
+    }
+
+    /// Generate a DKIM key for a domain
+    #[instrument(name = "MailcowClient::create_dkim", skip(self))]
+    pub async fn create_dkim(&self, req: CreateDkimRequest) -> MailcowResult<()> {
+        self.post("/api/v1/add/dkim")
+            .body(&InnerCreateDkimRequest {
+                domains: req.domain,
+                dkim_selector: req.dkim_selector,
+                key_size: req.key_size,
+            })
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
+}
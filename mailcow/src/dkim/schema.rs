@@ -0,0 +1,43 @@
+//! Schema for mailcow DKIM endpoints
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The DKIM key of a domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailcowDkim {
+    /// Selector of the key
+    pub dkim_selector: String,
+    /// Content of the TXT record publishing the public key
+    pub dkim_txt: String,
+    /// Length of the key in bits (string in Mailcow API)
+    pub length: String,
+}
+
+/// Options for get DKIM responses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum GetDkimResponse {
+    /// The DKIM key
+    Dkim(MailcowDkim),
+    /// Empty response if the domain has no key
+    Empty(serde::de::IgnoredAny),
+}
+
+/// Generate a DKIM key for a domain
+#[derive(Debug, Clone)]
+pub struct CreateDkimRequest {
+    /// Domain to generate the key for
+    pub domain: String,
+    /// Selector of the key
+    pub dkim_selector: String,
+    /// Length of the key in bits
+    pub key_size: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InnerCreateDkimRequest {
+    pub domains: String,
+    pub dkim_selector: String,
+    pub key_size: u16,
+}
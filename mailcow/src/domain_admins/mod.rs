@@ -4,12 +4,29 @@ use tracing::instrument;
 
 use crate::MailcowClient;
 use crate::domain_admins::schema::CreateDomainAdminRequest;
+use crate::domain_admins::schema::EditDomainAdminPasswordRequest;
 use crate::domain_admins::schema::EditDomainAdminsRequest;
+use crate::domain_admins::schema::GetDomainAdminsResponse;
+use crate::domain_admins::schema::MailcowDomainAdmin;
 use crate::error::MailcowResult;
 
 pub mod schema;
 
 impl MailcowClient {
+    /// Retrieves all domain admins from the Mailcow API
+    #[instrument(name = "MailcowClient::get_all_domain_admins", skip(self))]
+    pub async fn get_all_domain_admins(&self) -> MailcowResult<Vec<MailcowDomainAdmin>> {
+        let admins = self
+            .get("/api/v1/get/domain-admin/all")
+            .send::<GetDomainAdminsResponse>()
+            .await?;
+
+        match admins {
+            GetDomainAdminsResponse::List(admins) => Ok(admins),
+            GetDomainAdminsResponse::Empty(_) => Ok(Vec::new()),
+        }
+    }
+
     /// Create a new domain admin
     #[instrument(name = "MailcowClient::create_domain_admin", skip(self))]
     pub async fn create_domain_admin(&self, req: CreateDomainAdminRequest) -> MailcowResult<()> {
@@ -42,4 +59,18 @@ impl MailcowClient {
 
         Ok(())
     }
+
+    /// Set the password of a list of domain admins
+    #[instrument(name = "MailcowClient::edit_domain_admin_password", skip_all)]
+    pub async fn edit_domain_admin_password(
+        &self,
+        req: EditDomainAdminPasswordRequest,
+    ) -> MailcowResult<()> {
+        self.post("/api/v1/edit/domain-admin")
+            .body(&req)
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
 }
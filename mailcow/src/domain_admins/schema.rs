@@ -3,6 +3,26 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+/// A domain admin retrieved from mailcow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailcowDomainAdmin {
+    /// Username of the domain admin
+    pub username: String,
+    /// Domains the domain admin manages
+    #[serde(default)]
+    pub selected_domains: Vec<String>,
+}
+
+/// Options for get domain admins responses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum GetDomainAdminsResponse {
+    /// List of domain admins
+    List(Vec<MailcowDomainAdmin>),
+    /// Empty response
+    Empty(serde::de::IgnoredAny),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(missing_docs)]
 pub struct CreateDomainAdminRequest {
@@ -25,3 +45,17 @@ pub struct EditDomainAdminsRequest {
     pub attr: EditDomainAdminsChanges,
     pub items: Vec<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct EditDomainAdminPasswordChanges {
+    pub password: String,
+    pub password2: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct EditDomainAdminPasswordRequest {
+    pub attr: EditDomainAdminPasswordChanges,
+    pub items: Vec<String>,
+}
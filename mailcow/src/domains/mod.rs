@@ -3,6 +3,9 @@
 use tracing::instrument;
 
 use crate::MailcowClient;
+use crate::domains::schema::CreateDomainRequest;
+use crate::domains::schema::EditDomainRequest;
+use crate::domains::schema::InnerCreateDomainRequest;
 use crate::domains::schema::MailcowDomain;
 use crate::error::MailcowResult;
 
@@ -25,4 +28,49 @@ impl MailcowClient {
             .send()
             .await
     }
+
+    /// Create a new domain
+    #[instrument(name = "MailcowClient::create_domain", skip(self))]
+    pub async fn create_domain(&self, req: CreateDomainRequest) -> MailcowResult<()> {
+        self.post("/api/v1/add/domain")
+            .body(&InnerCreateDomainRequest {
+                active: "1".to_string(),
+                domain: req.domain,
+                description: req.description,
+                aliases: req.max_aliases,
+                mailboxes: req.max_mailboxes,
+                defquota: req.def_quota_for_mbox,
+                maxquota: req.max_quota_for_mbox,
+                quota: req.max_quota_for_domain,
+                restart_sogo: "1".to_string(),
+            })
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Edit the settings of a list of domains
+    #[instrument(name = "MailcowClient::edit_domains", skip(self))]
+    pub async fn edit_domains(&self, req: EditDomainRequest) -> MailcowResult<()> {
+        self.post("/api/v1/edit/domain")
+            .body(&req)
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete domains
+    ///
+    /// Mailcow refuses to delete domains which still have mailboxes.
+    #[instrument(name = "MailcowClient::delete_domains", skip(self))]
+    pub async fn delete_domains(&self, domains: Vec<String>) -> MailcowResult<()> {
+        self.post("/api/v1/delete/domain")
+            .body(&domains)
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
 }
@@ -30,6 +30,69 @@ pub struct MailcowDomain {
     pub max_num_mboxes_for_domain: u64,
 }
 
+/// Create a new domain
+#[derive(Debug, Clone)]
+pub struct CreateDomainRequest {
+    /// Domain
+    pub domain: String,
+    /// Description shown in mailcow
+    pub description: String,
+    /// Maximum number of aliases on the domain
+    pub max_aliases: u64,
+    /// Maximum number of mailboxes on the domain
+    pub max_mailboxes: u64,
+    /// Default quota for new mailboxes in MiB
+    pub def_quota_for_mbox: u64,
+    /// Maximal quota for a mailbox in MiB
+    pub max_quota_for_mbox: u64,
+    /// Maximum total quota for the domain in MiB
+    pub max_quota_for_domain: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InnerCreateDomainRequest {
+    pub active: String,
+    pub domain: String,
+    pub description: String,
+    pub aliases: u64,
+    pub mailboxes: u64,
+    pub defquota: u64,
+    pub maxquota: u64,
+    pub quota: u64,
+    pub restart_sogo: String,
+}
+
+/// Changes to apply to a list of domains
+///
+/// Settings left at `None` are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditDomainChanges {
+    /// Maximum number of aliases on the domain
+    #[serde(rename = "aliases", skip_serializing_if = "Option::is_none")]
+    pub max_aliases: Option<u64>,
+    /// Maximum number of mailboxes on the domain
+    #[serde(rename = "mailboxes", skip_serializing_if = "Option::is_none")]
+    pub max_mailboxes: Option<u64>,
+    /// Default quota for new mailboxes in MiB
+    #[serde(rename = "defquota", skip_serializing_if = "Option::is_none")]
+    pub def_quota_for_mbox: Option<u64>,
+    /// Maximal quota for a mailbox in MiB
+    #[serde(rename = "maxquota", skip_serializing_if = "Option::is_none")]
+    pub max_quota_for_mbox: Option<u64>,
+    /// Maximum total quota for the domain in MiB
+    #[serde(rename = "quota", skip_serializing_if = "Option::is_none")]
+    pub max_quota_for_domain: Option<u64>,
+}
+
+/// Edit the settings of a list of domains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditDomainRequest {
+    /// The changes to apply
+    pub attr: EditDomainChanges,
+    /// The domains to edit
+    pub items: Vec<String>,
+}
+
 fn deserialize_string_to_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -44,6 +44,8 @@ use tracing::instrument;
 
 use crate::error::MailcowResult;
 
+pub mod aliases;
+pub mod dkim;
 pub mod domain_admins;
 pub mod domains;
 pub mod error;
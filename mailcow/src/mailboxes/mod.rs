@@ -5,6 +5,7 @@ use tracing::instrument;
 use crate::MailcowClient;
 use crate::error::MailcowResult;
 use crate::mailboxes::schema::GetAppPasswordsResponse;
+use crate::mailboxes::schema::GetMailboxResponse;
 
 pub mod schema;
 
@@ -22,6 +23,25 @@ impl MailcowClient {
             .await
     }
 
+    /// Retrieves a single mailbox
+    ///
+    /// Returns `None` if the mailbox doesn't exist.
+    #[instrument(skip(self), name = "MailcowClient::get_mailbox")]
+    pub async fn get_mailbox(
+        &self,
+        mailbox: &str,
+    ) -> MailcowResult<Option<schema::MailcowMailbox>> {
+        let mailbox = self
+            .get(&format!("/api/v1/get/mailbox/{mailbox}"))
+            .send::<GetMailboxResponse>()
+            .await?;
+
+        match mailbox {
+            GetMailboxResponse::Mailbox(mailbox) => Ok(Some(mailbox)),
+            GetMailboxResponse::Empty(_) => Ok(None),
+        }
+    }
+
     /// Delete mailboxes
     ///
     /// **mailboxes**: List of mails to delete
@@ -35,6 +55,28 @@ impl MailcowClient {
         Ok(())
     }
 
+    /// Activate or deactivate mailboxes
+    ///
+    /// Deactivated mailboxes neither accept logins nor receive mails.
+    #[instrument(skip(self), name = "MailcowClient::set_mailboxes_active")]
+    pub async fn set_mailboxes_active(
+        &self,
+        mailboxes: Vec<String>,
+        active: bool,
+    ) -> MailcowResult<()> {
+        self.post("/api/v1/edit/mailbox")
+            .body(&schema::InnerEditMailboxesActiveRequest {
+                attr: schema::InnerEditMailboxesActiveChanges {
+                    active: if active { "1" } else { "0" }.to_string(),
+                },
+                items: mailboxes,
+            })
+            .send::<serde::de::IgnoredAny>()
+            .await?;
+
+        Ok(())
+    }
+
     /// Get all app passwords for a mailbox
     #[instrument(skip(self), name = "MailcowClient::get_app_passwords")]
     pub async fn get_app_passwords(
@@ -18,6 +18,16 @@ pub struct MailcowMailbox {
     pub messages: u64,
 }
 
+/// Options for get mailbox responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetMailboxResponse {
+    /// The mailbox
+    Mailbox(MailcowMailbox),
+    /// Empty response if the mailbox doesn't exist
+    Empty(HashMap<String, String>),
+}
+
 /// Create a new app password
 pub struct CreateAppPasswordRequest {
     /// Username of the mailbox
@@ -58,3 +68,14 @@ pub(crate) struct InnerCreateAppPasswordRequest {
     pub app_passwd2: String,
     pub protocols: Vec<String>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InnerEditMailboxesActiveChanges {
+    pub active: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InnerEditMailboxesActiveRequest {
+    pub attr: InnerEditMailboxesActiveChanges,
+    pub items: Vec<String>,
+}
@@ -3,6 +3,8 @@
 use clap::Parser;
 use clap::Subcommand;
 
+use crate::modules::mailcow::reconciler::ReconcileCategory;
+
 /// The cli
 #[derive(Parser)]
 pub struct Cli {
@@ -45,4 +47,10 @@ pub enum Command {
         /// Path to the file where to read the data from
         filename: String,
     },
+    /// Compare the database with mailcow and print the differences
+    Reconcile {
+        /// Repair all issues of these categories
+        #[clap(long, value_enum, value_delimiter = ',')]
+        repair: Vec<ReconcileCategory>,
+    },
 }
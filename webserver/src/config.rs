@@ -10,6 +10,11 @@ use galvyn::core::stuff::env::EnvVar;
 use galvyn::rorm::DatabaseDriver;
 use url::Url;
 
+use crate::modules::mailer::Language;
+use crate::modules::mailer::SmtpTls;
+use crate::utils::client_ip::ClientIpHeader;
+use crate::utils::client_ip::TrustedProxies;
+
 /// Load all environment variables declared in this module
 ///
 /// Called at the beginning of `main` to gather and report all env errors at once.
@@ -20,6 +25,8 @@ pub fn load_env() -> Result<(), Vec<&'static EnvError>> {
         LISTEN_ADDRESS.load(),
         LISTEN_PORT.load(),
         ORIGIN.load(),
+        WEBAUTHN_ID.load(),
+        WEBAUTHN_ORIGIN.load(),
         STATE_DIR.load(),
         POSTGRES_HOST.load(),
         POSTGRES_DB.load(),
@@ -28,6 +35,23 @@ pub fn load_env() -> Result<(), Vec<&'static EnvError>> {
         POSTGRES_PASSWORD.load(),
         MAILCOW_BASE_URL.load(),
         MAILCOW_API_KEY.load(),
+        MAIL_HOSTNAME.load(),
+        DOMAIN_SYNC_MAX_REMOVAL_PERCENT.load(),
+        DOMAIN_SYNC_GRACE_PERIOD_DAYS.load(),
+        SMTP_HOST.load(),
+        SMTP_PORT.load(),
+        SMTP_TLS.load(),
+        SMTP_USERNAME.load(),
+        SMTP_PASSWORD.load(),
+        MAIL_FROM.load(),
+        MAIL_LANGUAGE.load(),
+        REQUIRE_2FA_SUPERADMINS.load(),
+        REQUIRE_2FA_CLUB_ADMINS.load(),
+        PASSWORD_MIN_LENGTH.load(),
+        PASSWORD_MIN_SCORE.load(),
+        PASSWORD_BREACH_FILE.load(),
+        TRUSTED_PROXIES.load(),
+        CLIENT_IP_HEADER.load(),
         OTEL_EXPORTER_OTLP_ENDPOINT.load(),
     ] {
         errors.extend(result.err());
@@ -54,6 +78,19 @@ pub static LISTEN_PORT: EnvVar<u16> = EnvVar::optional("LISTEN_PORT", || 8080);
 /// - defaulting [`WEBAUTHN_ID`] and [`WEBAUTHN_ORIGIN`]
 pub static ORIGIN: EnvVar<Url> = EnvVar::required("ORIGIN");
 
+/// The relying party id used for WebAuthn
+///
+/// Defaults to the domain of [`ORIGIN`].
+/// Changing it invalidates all registered passkeys.
+pub static WEBAUTHN_ID: EnvVar = EnvVar::optional("WEBAUTHN_ID", || {
+    ORIGIN.domain().unwrap_or_default().to_string()
+});
+
+/// The origin WebAuthn ceremonies are performed on
+///
+/// Defaults to [`ORIGIN`].
+pub static WEBAUTHN_ORIGIN: EnvVar<Url> = EnvVar::optional("WEBAUTHN_ORIGIN", || ORIGIN.clone());
+
 /// A directory bnv-manager puts files it creates.
 ///
 /// Most noteworthy, this will contain a `/media` where file uploaded by users will be stored.
@@ -70,6 +107,106 @@ pub static MAILCOW_BASE_URL: EnvVar<Url> = EnvVar::required("MAILCOW_BASE_URL");
 /// API key of the mailcow user
 pub static MAILCOW_API_KEY: EnvVar = EnvVar::required("MAILCOW_API_KEY");
 
+/// The hostname of the mail server, as announced in the MX and SPF records of the domains
+///
+/// Defaults to the domain of [`MAILCOW_BASE_URL`].
+pub static MAIL_HOSTNAME: EnvVar = EnvVar::optional("MAIL_HOSTNAME", || {
+    MAILCOW_BASE_URL.domain().unwrap_or_default().to_string()
+});
+
+/// Maximum percentage of domains which may go missing from mailcow in a single sync
+///
+/// If more domains are missing, e.g. due to a misconfigured API key, none of them are removed
+/// until a superadmin forces a sync.
+pub static DOMAIN_SYNC_MAX_REMOVAL_PERCENT: EnvVar<u8> =
+    EnvVar::optional("DOMAIN_SYNC_MAX_REMOVAL_PERCENT", || 20);
+
+/// Number of days domains missing from mailcow are kept including their club association
+pub static DOMAIN_SYNC_GRACE_PERIOD_DAYS: EnvVar<u16> =
+    EnvVar::optional("DOMAIN_SYNC_GRACE_PERIOD_DAYS", || 7);
+
+/// The SMTP server to send mails with
+///
+/// Leave empty to disable sending mails, they are kept in the outbox in that case.
+pub static SMTP_HOST: EnvVar = EnvVar::optional("SMTP_HOST", String::new);
+
+/// The port of the SMTP server
+pub static SMTP_PORT: EnvVar<u16> = EnvVar::optional("SMTP_PORT", || 587);
+
+/// How to secure the connection to the SMTP server
+///
+/// One of `none`, `starttls` or `tls`.
+/// `none` should only be used with a local SMTP sink for testing.
+pub static SMTP_TLS: EnvVar<SmtpTls> = EnvVar::optional("SMTP_TLS", || SmtpTls::StartTls);
+
+/// The user to authenticate at the SMTP server with
+///
+/// Leave empty to send mails without authentication.
+pub static SMTP_USERNAME: EnvVar = EnvVar::optional("SMTP_USERNAME", String::new);
+
+/// The password of [`SMTP_USERNAME`]
+pub static SMTP_PASSWORD: EnvVar = EnvVar::optional("SMTP_PASSWORD", String::new);
+
+/// The sender of all mails
+///
+/// Defaults to `noreply` at the domain of [`ORIGIN`].
+pub static MAIL_FROM: EnvVar = EnvVar::optional("MAIL_FROM", || {
+    format!(
+        "BNV Manager <noreply@{}>",
+        ORIGIN.domain().unwrap_or("localhost")
+    )
+});
+
+/// The language mails are written in
+///
+/// Either `de` or `en`.
+pub static MAIL_LANGUAGE: EnvVar<Language> = EnvVar::optional("MAIL_LANGUAGE", || Language::German);
+
+/// Require superadmins to use a second factor
+///
+/// Superadmins without a second factor can still sign in,
+/// but are only allowed to enroll one until they have done so.
+pub static REQUIRE_2FA_SUPERADMINS: EnvVar<bool> =
+    EnvVar::optional("REQUIRE_2FA_SUPERADMINS", || false);
+
+/// Require club admins to use a second factor
+///
+/// See [`REQUIRE_2FA_SUPERADMINS`] for details.
+pub static REQUIRE_2FA_CLUB_ADMINS: EnvVar<bool> =
+    EnvVar::optional("REQUIRE_2FA_CLUB_ADMINS", || false);
+
+/// Minimal number of characters of a password
+pub static PASSWORD_MIN_LENGTH: EnvVar<usize> = EnvVar::optional("PASSWORD_MIN_LENGTH", || 10);
+
+/// Minimal strength of a password as estimated by zxcvbn on a scale from 0 to 4
+pub static PASSWORD_MIN_SCORE: EnvVar<u8> = EnvVar::optional("PASSWORD_MIN_SCORE", || 3);
+
+/// Path to a list of breached passwords
+///
+/// The file must contain upper case hex encoded SHA1 hashes ordered by hash, as assembled
+/// from the "Have I Been Pwned" k-anonymity range API by its downloader.
+/// The list is searched locally, passwords or their hashes never leave the server.
+/// Leave empty to disable the check.
+pub static PASSWORD_BREACH_FILE: EnvVar = EnvVar::optional("PASSWORD_BREACH_FILE", String::new);
+
+/// Comma separated list of CIDRs of reverse proxies whose client ip header is trusted
+///
+/// Defaults to loopback only. A proxy running in another container, like the frontend's
+/// nginx in the provided compose file, has to be added explicitly, e.g. `172.16.0.0/12`.
+pub static TRUSTED_PROXIES: EnvVar<TrustedProxies> = EnvVar::optional("TRUSTED_PROXIES", || {
+    #[allow(clippy::expect_used)]
+    "127.0.0.0/8,::1/128"
+        .parse()
+        .expect("Static CIDRs are valid")
+});
+
+/// The header the trusted proxies put the client's address into
+///
+/// One of `x-real-ip`, `x-forwarded-for` or `forwarded`.
+/// Make sure the proxy overwrites or appends to this header instead of passing it through.
+pub static CLIENT_IP_HEADER: EnvVar<ClientIpHeader> =
+    EnvVar::optional("CLIENT_IP_HEADER", || ClientIpHeader::XRealIp);
+
 /// The address of the database server
 pub static POSTGRES_HOST: EnvVar = EnvVar::optional("POSTGRES_HOST", || "postgres".to_string());
 
@@ -0,0 +1,92 @@
+//! Extractor to report the outcome of an authentication attempt to the rate limiter.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::http::request::Parts;
+
+use crate::models::account::Account;
+
+/// The outcome of an authentication attempt
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum AuthOutcome {
+    /// The handler didn't check any credentials, e.g. because the request was malformed
+    #[default]
+    Unknown,
+    /// The provided credentials were invalid
+    Failed {
+        /// The username of the targeted account, if it could be resolved
+        username: Option<String>,
+    },
+    /// The password was valid, but the sign in still needs a second factor
+    Pending,
+    /// The account has signed in
+    Succeeded {
+        /// The username of the account
+        username: String,
+    },
+}
+
+/// Extractor to report the outcome of an authentication attempt.
+///
+/// [`AuthRateLimit`](crate::http::middlewares::AuthRateLimit) only counts attempts
+/// which were reported as failed. Failures against an account are counted per account,
+/// regardless of whether its username or email was used.
+///
+/// Outside a rate limited route, reports are discarded.
+#[derive(Debug, Clone, Default)]
+pub struct AuthAttempt(Arc<Mutex<AuthOutcome>>);
+
+impl AuthAttempt {
+    /// The credentials provided for an account were invalid
+    pub fn failed(&self, account: &Account) {
+        self.set(AuthOutcome::Failed {
+            username: Some(account.username().to_string()),
+        });
+    }
+
+    /// The provided credentials didn't match any account
+    pub fn failed_unknown(&self) {
+        self.set(AuthOutcome::Failed { username: None });
+    }
+
+    /// The password of an account was valid, but a second factor is required
+    pub fn pending(&self) {
+        self.set(AuthOutcome::Pending);
+    }
+
+    /// The account has signed in
+    pub fn succeeded(&self, account: &Account) {
+        self.set(AuthOutcome::Succeeded {
+            username: account.username().to_string(),
+        });
+    }
+
+    /// The reported outcome
+    pub fn outcome(&self) -> AuthOutcome {
+        self.0
+            .lock()
+            .map(|outcome| outcome.clone())
+            .unwrap_or_default()
+    }
+
+    fn set(&self, outcome: AuthOutcome) {
+        if let Ok(mut current) = self.0.lock() {
+            *current = outcome;
+        }
+    }
+}
+
+impl<S: Sync + Send> FromRequestParts<S> for AuthAttempt {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<AuthAttempt>()
+            .cloned()
+            .unwrap_or_default())
+    }
+}
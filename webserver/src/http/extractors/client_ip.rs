@@ -0,0 +1,50 @@
+//! Extractor for the client's ip address.
+
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::sync::Once;
+
+use galvyn::core::re_exports::axum::extract::ConnectInfo;
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::http::Extensions;
+use galvyn::core::re_exports::axum::http::HeaderMap;
+use galvyn::core::re_exports::axum::http::request::Parts;
+use tracing::error;
+
+use crate::utils::client_ip::resolve_client_ip;
+
+/// Extractor for the client's ip address.
+///
+/// See [`resolve_client_ip`] for how the address is determined.
+/// It is `None` if neither a trusted header nor the peer address is available.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl ClientIp {
+    /// Resolve the client's ip address from a request's headers and extensions
+    pub fn from_request(headers: &HeaderMap, extensions: &Extensions) -> Self {
+        let peer = extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|connect_info| connect_info.0);
+
+        if peer.is_none() {
+            static MISSING_CONNECT_INFO: Once = Once::new();
+            MISSING_CONNECT_INFO.call_once(|| {
+                error!(
+                    "The listener doesn't provide the peer address, client ips can't be resolved"
+                );
+            });
+        }
+
+        Self(resolve_client_ip(headers, peer))
+    }
+}
+
+impl<S: Sync + Send> FromRequestParts<S> for ClientIp {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_request(&parts.headers, &parts.extensions))
+    }
+}
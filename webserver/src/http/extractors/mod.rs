@@ -1,3 +1,6 @@
 //! Extractors are defined in this module
 
+pub mod auth_attempt;
+pub mod client_ip;
 pub mod session_user;
+pub mod user_agent;
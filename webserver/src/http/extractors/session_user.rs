@@ -1,38 +1,251 @@
 //! Extractors for the session user.
+//!
+//! Besides the session, a user may authenticate using a personal [`ApiToken`]
+//! passed in the `Authorization: Bearer` header.
 
+use galvyn::core::Module;
 use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::http::header;
 use galvyn::core::re_exports::axum::http::request::Parts;
 use galvyn::core::session::Session;
 use galvyn::core::stuff::api_error::ApiError;
+use galvyn::rorm::Database;
+use galvyn::rorm::fields::types::MaxStr;
 use serde::Deserialize;
 use serde::Serialize;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::info;
 
+use crate::models::account::Account;
 use crate::models::account::AccountUuid;
+use crate::models::account::AuthenticationMethod;
+use crate::models::api_token::ApiToken;
+use crate::models::api_token::ApiTokenScope;
+use crate::models::club::ClubUuid;
 
 /// Extractor for the session user.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionUser {
     /// The user's UUID.
     pub uuid: AccountUuid,
+    /// The methods the user has authenticated with in this session.
+    #[serde(default)]
+    pub auth_methods: Vec<AuthenticationMethod>,
+    /// The club the user has switched to.
+    ///
+    /// Unset until the user switches clubs, the account's default club applies then.
+    #[serde(default)]
+    pub active_club: Option<ClubUuid>,
+}
+
+impl SessionUser {
+    /// Whether the user has used a second factor in this session.
+    pub fn has_second_factor(&self) -> bool {
+        AuthenticationMethod::is_multi_factor(&self.auth_methods)
+    }
 }
 
 /// The key of the session user in the session.
 pub const SESSION_USER: &str = "session_user";
 
+/// A superadmin acting as another account
+///
+/// While this is stored in the session, the [`SessionUser`] is the impersonated account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Impersonation {
+    /// The session user of the superadmin, restored once the impersonation ends
+    pub impersonator: SessionUser,
+    /// The username of the superadmin
+    pub impersonator_username: MaxStr<255>,
+    /// Unix timestamp of the point in time the impersonation started
+    pub started_at: i64,
+}
+
+impl Impersonation {
+    /// Whether the impersonation has exceeded [`IMPERSONATION_DURATION`]
+    pub fn is_expired(&self) -> bool {
+        OffsetDateTime::now_utc().unix_timestamp() - self.started_at
+            > IMPERSONATION_DURATION.whole_seconds()
+    }
+}
+
+/// The key of the impersonation in the session.
+pub const SESSION_IMPERSONATION: &str = "impersonation";
+
+/// Impersonations end automatically after this duration
+pub const IMPERSONATION_DURATION: Duration = Duration::hours(1);
+
+/// Extractor for the user of a request, authenticated by either the session or an api token.
+///
+/// Unlike [`SessionUser`], this doesn't check the scopes of the api token.
+#[derive(Debug, Clone)]
+pub struct RequestUser {
+    /// The authenticated user
+    pub user: SessionUser,
+    /// The api token the request was authenticated with
+    pub api_token: Option<ApiToken>,
+}
+
+impl RequestUser {
+    /// Whether the request may access a part of the api
+    ///
+    /// Requests authenticated by the session may access every part.
+    pub fn allows(&self, scope: ApiTokenScope) -> bool {
+        self.api_token
+            .as_ref()
+            .is_none_or(|api_token| api_token.allows(scope))
+    }
+}
+
+/// Marker placed in the request's extensions by the auth middlewares
+///
+/// It signals that the scope of the api token was already checked for the current router,
+/// so [`SessionUser`] doesn't require the [`ApiTokenScope::Common`] scope.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiTokenScopeGranted;
+
+impl<S: Sync + Send> FromRequestParts<S> for RequestUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(request_user) = parts.extensions.get::<RequestUser>() {
+            return Ok(request_user.clone());
+        }
+
+        let bearer = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .map(str::to_string)
+                    .ok_or(ApiError::unauthorized("Invalid Authorization header"))
+            })
+            .transpose()?;
+
+        let request_user = if let Some(secret) = bearer {
+            let mut tx = Database::global().start_transaction().await?;
+
+            let api_token = ApiToken::authenticate(&mut tx, &secret)
+                .await?
+                .ok_or(ApiError::unauthorized("Invalid api token"))?;
+
+            let account = Account::get_by_uuid(&mut tx, api_token.account)
+                .await?
+                .ok_or(ApiError::unauthorized("Invalid api token"))?;
+            if account.is_locked() {
+                return Err(ApiError::unauthorized("Account is locked"));
+            }
+
+            tx.commit().await?;
+
+            RequestUser {
+                user: SessionUser {
+                    uuid: api_token.account,
+                    auth_methods: api_token.auth_methods.clone(),
+                    active_club: None,
+                },
+                api_token: Some(api_token),
+            }
+        } else {
+            let session = Session::from_request_parts(parts, state)
+                .await
+                .map_err(|_| ApiError::server_error("Session error"))?;
+
+            let session_user: SessionUser = session
+                .get(SESSION_USER)
+                .await
+                .map_err(|_| ApiError::server_error("Session error"))?
+                .ok_or(ApiError::unauthorized(""))?;
+
+            let impersonation: Option<Impersonation> = session
+                .get(SESSION_IMPERSONATION)
+                .await
+                .map_err(|_| ApiError::server_error("Session error"))?;
+            if let Some(impersonation) = impersonation.filter(Impersonation::is_expired) {
+                info!(
+                    impersonator = ?impersonation.impersonator.uuid,
+                    account = ?session_user.uuid,
+                    "Impersonation expired"
+                );
+
+                // The next request is made as the superadmin again
+                session
+                    .remove::<Impersonation>(SESSION_IMPERSONATION)
+                    .await
+                    .map_err(|_| ApiError::server_error("Session error"))?;
+                session
+                    .insert(SESSION_USER, impersonation.impersonator)
+                    .await
+                    .map_err(|_| ApiError::server_error("Session error"))?;
+
+                return Err(ApiError::unauthorized("Impersonation expired"));
+            }
+
+            RequestUser {
+                user: session_user,
+                api_token: None,
+            }
+        };
+
+        parts.extensions.insert(request_user.clone());
+
+        Ok(request_user)
+    }
+}
+
 impl<S: Sync + Send> FromRequestParts<S> for SessionUser {
     type Rejection = ApiError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let request_user = RequestUser::from_request_parts(parts, state).await?;
+
+        if parts.extensions.get::<ApiTokenScopeGranted>().is_none()
+            && !request_user.allows(ApiTokenScope::Common)
+        {
+            return Err(ApiError::unauthorized("Api token lacks the required scope"));
+        }
+
+        Ok(request_user.user)
+    }
+}
+
+/// Extractor for a user changing the credentials of its own account
+///
+/// Impersonated sessions are rejected, so a superadmin can't add credentials
+/// which outlast the impersonation. Requests authenticated by an api token are rejected as well.
+#[derive(Debug, Clone)]
+pub struct CredentialUser(pub SessionUser);
+
+impl<S: Sync + Send> FromRequestParts<S> for CredentialUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let request_user = RequestUser::from_request_parts(parts, state).await?;
+
+        if request_user.api_token.is_some() {
+            return Err(ApiError::unauthorized(
+                "Credentials can't be changed using an api token",
+            ));
+        }
+
         let session = Session::from_request_parts(parts, state)
             .await
             .map_err(|_| ApiError::server_error("Session error"))?;
-
-        let session_user: SessionUser = session
-            .get(SESSION_USER)
+        if session
+            .get::<Impersonation>(SESSION_IMPERSONATION)
             .await
             .map_err(|_| ApiError::server_error("Session error"))?
-            .ok_or(ApiError::unauthorized(""))?;
+            .is_some()
+        {
+            return Err(ApiError::bad_request(
+                "Credentials can't be changed while impersonating",
+            ));
+        }
 
-        Ok(session_user)
+        Ok(Self(request_user.user))
     }
 }
@@ -0,0 +1,27 @@
+//! Extractor for the client's user agent.
+
+use std::convert::Infallible;
+
+use galvyn::core::re_exports::axum::extract::FromRequestParts;
+use galvyn::core::re_exports::axum::http::header;
+use galvyn::core::re_exports::axum::http::request::Parts;
+
+/// Extractor for the `User-Agent` header.
+///
+/// It is `None` if the header is missing or isn't valid UTF-8.
+#[derive(Debug, Clone)]
+pub struct UserAgent(pub Option<String>);
+
+impl<S: Sync + Send> FromRequestParts<S> for UserAgent {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(
+            parts
+                .headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        ))
+    }
+}
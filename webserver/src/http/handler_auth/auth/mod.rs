@@ -10,20 +10,37 @@ use galvyn::core::stuff::api_json::ApiJson;
 use galvyn::get;
 use galvyn::post;
 use galvyn::rorm::Database;
+use serde::Deserialize;
+use serde::Serialize;
+use time::Duration;
+use time::OffsetDateTime;
 use tracing::error;
 use tracing::info;
 use tracing::instrument;
 use tracing::warn;
 
+use crate::http::extractors::auth_attempt::AuthAttempt;
+use crate::http::extractors::client_ip::ClientIp;
+use crate::http::extractors::session_user::Impersonation;
+use crate::http::extractors::session_user::SESSION_IMPERSONATION;
 use crate::http::extractors::session_user::SESSION_USER;
 use crate::http::extractors::session_user::SessionUser;
+use crate::http::extractors::user_agent::UserAgent;
 use crate::http::handler_auth::auth::schema::AuthQuery;
+use crate::http::handler_auth::auth::schema::SecondFactorRequest;
 use crate::http::handler_auth::auth::schema::SignInRequest;
+use crate::http::handler_auth::auth::schema::SignInResponse;
 use crate::models::account::Account;
-use crate::models::account::ClubAccount;
+use crate::models::account::AccountUuid;
+use crate::models::account::AuthenticationMethod;
+use crate::models::login_event::LoginEvent;
+use crate::models::login_event::LoginMethod;
+use crate::models::login_event::RecordLoginParams;
 use crate::models::oidc_provider::CreateOidcAuthenticationToken;
 use crate::models::oidc_provider::OidcAuthenticationToken;
 use crate::models::oidc_provider::OidcClient;
+use crate::models::passkey::Passkey;
+use crate::modules::mailer::Mailer;
 use crate::utils::links::Link;
 
 pub mod schema;
@@ -31,6 +48,66 @@ pub mod schema;
 /// Access the original auth query using this variable
 pub const SESSION_OIDC_AUTH: &str = "oidc-auth";
 
+/// Access the sign in waiting for a second factor using this variable
+pub const SESSION_PENDING_SECOND_FACTOR: &str = "pending-second-factor";
+
+/// Time a user has to provide the second factor after entering the password
+const SECOND_FACTOR_TIMEOUT: Duration = Duration::minutes(5);
+
+/// Number of invalid codes after which the sign in has to be restarted
+const MAX_SECOND_FACTOR_ATTEMPTS: u8 = 5;
+
+/// A sign in whose password was verified, but which still lacks the second factor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSecondFactor {
+    /// The account that is signing in
+    pub uuid: AccountUuid,
+    /// Unix timestamp after which the sign in has to be restarted
+    pub expires_at: i64,
+    /// Number of invalid codes that have been provided
+    pub attempts: u8,
+}
+
+/// Record a sign in of an account
+///
+/// The owner of the account is notified about successful sign ins from unseen ip ranges.
+pub async fn record_login(
+    account: AccountUuid,
+    method: LoginMethod,
+    success: bool,
+    ClientIp(ip): ClientIp,
+    UserAgent(user_agent): UserAgent,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let event = LoginEvent::record(
+        &mut tx,
+        RecordLoginParams {
+            account,
+            method,
+            success,
+            ip,
+            user_agent,
+        },
+    )
+    .await?;
+
+    if event.new_ip_range {
+        warn!(account = ?account, ip = ?event.ip, "Sign in from unseen ip range");
+
+        if let Some(account) = Account::get_by_uuid(&mut tx, account).await? {
+            Mailer::global()
+                .notify_new_login_location(&mut tx, &account, &event)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+    Mailer::global().deliver_enqueued();
+
+    Ok(())
+}
+
 #[get("/auth")]
 #[instrument(name = "Api::auth::auth")]
 pub async fn auth(Query(auth_query): Query<AuthQuery>, session: Session) -> ApiResult<Redirect> {
@@ -96,15 +173,16 @@ pub async fn auth(Query(auth_query): Query<AuthQuery>, session: Session) -> ApiR
 #[instrument(name = "Api::auth::sign_in", skip(password))]
 pub async fn sign_in(
     session: Session,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    attempt: AuthAttempt,
     ApiJson(SignInRequest { username, password }): ApiJson<SignInRequest>,
-) -> ApiResult<()> {
+) -> ApiResult<ApiJson<SignInResponse>> {
     let mut tx = Database::global().start_transaction().await?;
 
     let lookup_result = match Account::get_by_username(&mut tx, &username).await? {
         Some(account) => Some(account),
-        None => ClubAccount::get_by_email(&mut tx, &username)
-            .await?
-            .map(Account::ClubMember),
+        None => Account::get_by_email(&mut tx, &username).await?,
     };
     let Some(account) = lookup_result else {
         // dummy bcrypt to not allow timing attacks for username enumeration
@@ -113,29 +191,155 @@ pub async fn sign_in(
             "$2b$12$YtivXzIrL86Uhnk5pXF5oOwNemHmre4qFeTGNPmFUFZlJE8kcv3a2",
         )
         .map_err(ApiError::map_server_error("Bcrypt error in dummy check"))?;
+        attempt.failed_unknown();
         return Err(ApiError::bad_request("Username not found"));
     };
 
+    let uuid = account.uuid();
+    let has_passkeys = !Passkey::find_all_by_account(&mut tx, uuid)
+        .await?
+        .is_empty();
+
     tx.commit().await?;
 
     if !account.check_password(&password)? {
+        attempt.failed(&account);
+        record_login(uuid, LoginMethod::Password, false, client_ip, user_agent).await?;
         return Err(ApiError::bad_request("Invalid password"));
     }
 
+    if account.is_locked() {
+        attempt.failed(&account);
+        record_login(uuid, LoginMethod::Password, false, client_ip, user_agent).await?;
+        return Err(ApiError::bad_request("Account is locked"));
+    }
+
+    if account.totp_enabled() || has_passkeys {
+        session.remove::<SessionUser>(SESSION_USER).await?;
+        session
+            .insert(
+                SESSION_PENDING_SECOND_FACTOR,
+                PendingSecondFactor {
+                    uuid,
+                    expires_at: (OffsetDateTime::now_utc() + SECOND_FACTOR_TIMEOUT)
+                        .unix_timestamp(),
+                    attempts: 0,
+                },
+            )
+            .await?;
+
+        // The password alone doesn't finish the sign in,
+        // so the failures against the account are kept until the second factor is verified
+        attempt.pending();
+        return Ok(ApiJson(SignInResponse::SecondFactorRequired {
+            totp: account.totp_enabled(),
+            passkey: has_passkeys,
+        }));
+    }
+
+    attempt.succeeded(&account);
+    record_login(uuid, LoginMethod::Password, true, client_ip, user_agent).await?;
+
     session
         .insert(
             SESSION_USER,
             SessionUser {
-                uuid: match &account {
-                    Account::ClubMember(club_member) => club_member.uuid(),
-                    Account::ClubAdmin(club_admin) => club_admin.uuid(),
-                    Account::Superadmin(superadmin) => superadmin.uuid(),
-                },
+                uuid,
+                auth_methods: vec![AuthenticationMethod::Password],
+                active_club: None,
             },
         )
         .await?;
 
-    Ok(())
+    Ok(ApiJson(SignInResponse::Finished {
+        second_factor_setup_required: account.second_factor_enforced(),
+    }))
+}
+
+#[post("/sign-in/second-factor")]
+#[instrument(name = "Api::auth::sign_in_second_factor", skip(code))]
+pub async fn sign_in_second_factor(
+    session: Session,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    attempt: AuthAttempt,
+    ApiJson(SecondFactorRequest { code }): ApiJson<SecondFactorRequest>,
+) -> ApiResult<ApiJson<SignInResponse>> {
+    let mut pending: PendingSecondFactor = session
+        .remove(SESSION_PENDING_SECOND_FACTOR)
+        .await?
+        .ok_or(ApiError::bad_request("No sign in pending"))?;
+
+    if pending.expires_at < OffsetDateTime::now_utc().unix_timestamp() {
+        return Err(ApiError::bad_request("Sign in has expired"));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::get_by_uuid(&mut tx, pending.uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account not found"))?;
+
+    let is_totp_code = code.len() == 6 && code.chars().all(|c| c.is_ascii_digit());
+    let valid = if is_totp_code {
+        account.verify_totp(&mut tx, &code).await?
+    } else {
+        let valid = account.use_recovery_code(&mut tx, &code).await?;
+        if valid {
+            info!(
+                remaining = account.remaining_recovery_codes(),
+                "Recovery code used"
+            );
+        }
+        valid
+    };
+
+    tx.commit().await?;
+
+    if !valid {
+        attempt.failed(&account);
+        record_login(
+            pending.uuid,
+            LoginMethod::Password,
+            false,
+            client_ip,
+            user_agent,
+        )
+        .await?;
+
+        pending.attempts += 1;
+        if pending.attempts < MAX_SECOND_FACTOR_ATTEMPTS {
+            session
+                .insert(SESSION_PENDING_SECOND_FACTOR, pending)
+                .await?;
+        }
+        return Err(ApiError::bad_request("Invalid code"));
+    }
+
+    attempt.succeeded(&account);
+    record_login(
+        pending.uuid,
+        LoginMethod::Password,
+        true,
+        client_ip,
+        user_agent,
+    )
+    .await?;
+
+    session
+        .insert(
+            SESSION_USER,
+            SessionUser {
+                uuid: pending.uuid,
+                auth_methods: vec![AuthenticationMethod::Password, AuthenticationMethod::Otp],
+                active_club: None,
+            },
+        )
+        .await?;
+
+    Ok(ApiJson(SignInResponse::Finished {
+        second_factor_setup_required: false,
+    }))
 }
 
 /// Allowed scopes
@@ -143,7 +347,11 @@ pub const ALLOWED_SCOPES: &[&str] = &["openid", "profile", "email", "mailcow_tem
 
 #[get("/finish-auth")]
 #[instrument(name = "Api::auth::finish-auth")]
-pub async fn finish_auth(session: Session) -> ApiResult<Redirect> {
+pub async fn finish_auth(
+    session: Session,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+) -> ApiResult<Redirect> {
     let mut tx = Database::global().start_transaction().await?;
     let auth_query: AuthQuery = session
         .remove(SESSION_OIDC_AUTH)
@@ -155,6 +363,17 @@ pub async fn finish_auth(session: Session) -> ApiResult<Redirect> {
         .await?
         .ok_or(ApiError::bad_request("Missing session user"))?;
 
+    // Tokens would grant the superadmin access to the impersonated account's mailbox
+    if session
+        .get::<Impersonation>(SESSION_IMPERSONATION)
+        .await?
+        .is_some()
+    {
+        return Ok(Redirect::temporary(
+            Link::oidc_failed("OIDC can't be used while impersonating").as_str(),
+        ));
+    }
+
     let provider = OidcClient::find_by_client_id(&mut tx, auth_query.client_id)
         .await?
         .ok_or(ApiError::server_error(
@@ -178,12 +397,28 @@ pub async fn finish_auth(session: Session) -> ApiResult<Redirect> {
         .await?
         .ok_or(ApiError::server_error("Invalid state"))?;
 
-    if !matches!(account, Account::ClubMember(_)) {
+    if account.membership.is_none() {
         return Ok(Redirect::temporary(
             Link::oidc_failed("Only members of a club are allowed to use OIDC").as_str(),
         ));
     }
 
+    if account.is_membership_locked() {
+        tx.commit().await?;
+        record_login(
+            account.uuid(),
+            LoginMethod::Oidc,
+            false,
+            client_ip,
+            user_agent,
+        )
+        .await?;
+
+        return Ok(Redirect::temporary(
+            Link::oidc_failed("The account is locked").as_str(),
+        ));
+    }
+
     // Create a new token
     let auth_token = OidcAuthenticationToken::create(
         &mut tx,
@@ -197,12 +432,22 @@ pub async fn finish_auth(session: Session) -> ApiResult<Redirect> {
                 .map(|x| x.to_string())
                 .collect(),
             code_challenge: auth_query.code_challenge,
+            auth_methods: session_user.auth_methods,
         },
     )
     .await?;
 
     tx.commit().await?;
 
+    record_login(
+        account.uuid(),
+        LoginMethod::Oidc,
+        true,
+        client_ip,
+        user_agent,
+    )
+    .await?;
+
     let mut redirect_uri = auth_query.redirect_uri;
     redirect_uri.set_query(Some(&format!(
         "code={code}{state}",
@@ -218,6 +463,19 @@ pub async fn finish_auth(session: Session) -> ApiResult<Redirect> {
 #[post("/sign-out")]
 #[instrument(name = "Api::auth::sign_out")]
 pub async fn sign_out(session: Session) -> ApiResult<()> {
-    session.remove::<SessionUser>(SESSION_USER).await?;
+    let session_user = session.remove::<SessionUser>(SESSION_USER).await?;
+    if let Some(impersonation) = session
+        .remove::<Impersonation>(SESSION_IMPERSONATION)
+        .await?
+    {
+        info!(
+            impersonator = ?impersonation.impersonator.uuid,
+            account = ?session_user.map(|user| user.uuid),
+            "Impersonation ended by signing out"
+        );
+    }
+    session
+        .remove::<PendingSecondFactor>(SESSION_PENDING_SECOND_FACTOR)
+        .await?;
     Ok(())
 }
@@ -18,6 +18,32 @@ pub struct SignInRequest {
     pub password: MaxStr<72>,
 }
 
+/// Result of a sign in request
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum SignInResponse {
+    /// The user is signed in
+    Finished {
+        /// The account has to enroll a second factor before being allowed to use
+        /// its administrative privileges
+        second_factor_setup_required: bool,
+    },
+    /// The password was correct, but a second factor has to be provided
+    SecondFactorRequired {
+        /// TOTP or a recovery code can be provided using `/sign-in/second-factor`
+        totp: bool,
+        /// A passkey can be used with `/sign-in/second-factor/passkey/start`
+        passkey: bool,
+    },
+}
+
+/// Second step of the sign in for accounts with a second factor
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SecondFactorRequest {
+    /// Either a 6-digit TOTP code or one of the recovery codes
+    pub code: MaxStr<32>,
+}
+
 /// Query parameters for the auth endpoint
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AuthQuery {
@@ -0,0 +1,219 @@
+//! Device authorization grant (RFC 8628)
+//!
+//! Clients that can't handle a browser redirect request a device code and display
+//! a user code. The user enters the code on the verification page while being logged
+//! in through the regular session login and approves or denies the request.
+//! Meanwhile, the device polls the token endpoint with its device code.
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::Form;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::get;
+use galvyn::post;
+use galvyn::rorm::Database;
+use subtle::ConstantTimeEq;
+use time::OffsetDateTime;
+use tracing::error;
+use tracing::instrument;
+
+use crate::http::extractors::auth_attempt::AuthAttempt;
+use crate::http::extractors::client_ip::ClientIp;
+use crate::http::extractors::session_user::Impersonation;
+use crate::http::extractors::session_user::RequestUser;
+use crate::http::extractors::session_user::SESSION_IMPERSONATION;
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::extractors::user_agent::UserAgent;
+use crate::http::handler_auth::auth::ALLOWED_SCOPES;
+use crate::http::handler_auth::auth::record_login;
+use crate::http::handler_auth::device::schema::DeviceAuthorizationRequest;
+use crate::http::handler_auth::device::schema::DeviceAuthorizationResponse;
+use crate::http::handler_auth::device::schema::DeviceVerificationDecision;
+use crate::http::handler_auth::device::schema::DeviceVerificationSchema;
+use crate::models::account::Account;
+use crate::models::login_event::LoginMethod;
+use crate::models::oidc_provider::OidcClient;
+use crate::models::oidc_provider::OidcClientUuid;
+use crate::models::oidc_provider::OidcDeviceCode;
+use crate::models::oidc_provider::OidcDeviceCodeState;
+use crate::utils::links::Link;
+
+pub mod schema;
+
+#[post("/device")]
+#[instrument(name = "Api::auth::device_authorization")]
+pub async fn device_authorization(
+    Form(DeviceAuthorizationRequest {
+        client_id,
+        client_secret,
+        scope,
+    }): Form<DeviceAuthorizationRequest>,
+) -> ApiResult<ApiJson<DeviceAuthorizationResponse>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let provider = OidcClient::find_by_client_id(&mut tx, OidcClientUuid(client_id))
+        .await?
+        .ok_or(ApiError::bad_request("Invalid client_id"))?;
+
+    // Security:
+    // Use constant time equals to not leak correct secret bytes
+    if bool::from(
+        provider
+            .client_secret
+            .as_bytes()
+            .ct_ne(client_secret.as_bytes()),
+    ) {
+        return Err(ApiError::bad_request("Invalid client_secret"));
+    }
+
+    let requested_scopes: Vec<_> = scope.split(" ").collect();
+    if !requested_scopes.contains(&"openid") {
+        return Err(ApiError::bad_request("Missing required scope openid"));
+    }
+    for scope in &requested_scopes {
+        if !ALLOWED_SCOPES.contains(scope) {
+            error!(scope = *scope, "Invalid scope requested");
+            return Err(ApiError::bad_request("Invalid scope requested"));
+        }
+    }
+
+    let device_code = OidcDeviceCode::create(
+        &mut tx,
+        provider.client_id,
+        requested_scopes
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect(),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(DeviceAuthorizationResponse {
+        verification_uri: Link::device_verification(),
+        verification_uri_complete: Link::device_verification_complete(&device_code.user_code),
+        expires_in: (device_code.expires_at - OffsetDateTime::now_utc()).whole_seconds(),
+        interval: device_code.poll_interval,
+        device_code: device_code.device_code.into_inner(),
+        user_code: device_code.user_code.into_inner(),
+    }))
+}
+
+#[get("/device/verify/{user_code}")]
+#[instrument(name = "Api::auth::get_device_verification")]
+pub async fn get_device_verification(
+    Path(user_code): Path<String>,
+    attempt: AuthAttempt,
+    request_user: RequestUser,
+) -> ApiResult<ApiJson<DeviceVerificationSchema>> {
+    if request_user.api_token.is_some() {
+        return Err(ApiError::unauthorized(
+            "Device requests can't be verified using an api token",
+        ));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let Some(device_code) = OidcDeviceCode::find_by_user_code(&mut tx, &user_code).await? else {
+        attempt.failed_unknown();
+        return Err(ApiError::bad_request("Invalid or expired user code"));
+    };
+
+    if !matches!(device_code.state, OidcDeviceCodeState::Pending) {
+        return Err(ApiError::bad_request("The request was already decided on"));
+    }
+
+    let provider = OidcClient::find_by_client_id(&mut tx, device_code.client_id)
+        .await?
+        .ok_or(ApiError::server_error("Provider of device code not found"))?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(DeviceVerificationSchema {
+        client_name: provider.name,
+        scopes: device_code.scopes,
+        expires_at: SchemaDateTime(device_code.expires_at),
+    }))
+}
+
+#[post("/device/verify/{user_code}")]
+#[instrument(name = "Api::auth::decide_device_verification")]
+pub async fn decide_device_verification(
+    session: Session,
+    attempt: AuthAttempt,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    Path(user_code): Path<String>,
+    RequestUser {
+        user: SessionUser {
+            uuid, auth_methods, ..
+        },
+        api_token,
+    }: RequestUser,
+    ApiJson(DeviceVerificationDecision { approve }): ApiJson<DeviceVerificationDecision>,
+) -> ApiResult<()> {
+    // An api token must not be able to grant a device access to the account
+    if api_token.is_some() {
+        return Err(ApiError::unauthorized(
+            "Device requests can't be verified using an api token",
+        ));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let Some(mut device_code) = OidcDeviceCode::find_by_user_code(&mut tx, &user_code).await?
+    else {
+        attempt.failed_unknown();
+        return Err(ApiError::bad_request("Invalid or expired user code"));
+    };
+
+    if !matches!(device_code.state, OidcDeviceCodeState::Pending) {
+        return Err(ApiError::bad_request("The request was already decided on"));
+    }
+
+    if approve {
+        // Tokens would grant the superadmin access to the impersonated account's mailbox
+        if session
+            .get::<Impersonation>(SESSION_IMPERSONATION)
+            .await?
+            .is_some()
+        {
+            return Err(ApiError::bad_request(
+                "OIDC can't be used while impersonating",
+            ));
+        }
+
+        let account = Account::get_by_uuid(&mut tx, uuid)
+            .await?
+            .ok_or(ApiError::server_error("Invalid state"))?;
+
+        if account.membership.is_none() {
+            return Err(ApiError::bad_request(
+                "Only members of a club are allowed to use OIDC",
+            ));
+        }
+
+        if account.is_membership_locked() {
+            tx.commit().await?;
+            record_login(uuid, LoginMethod::App, false, client_ip, user_agent).await?;
+
+            return Err(ApiError::bad_request("The account is locked"));
+        }
+
+        device_code.approve(&mut tx, uuid, auth_methods).await?;
+    } else {
+        device_code.deny(&mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    if approve {
+        record_login(uuid, LoginMethod::App, true, client_ip, user_agent).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Schema for the device authorization grant
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+use url::Url;
+use uuid::Uuid;
+
+/// Device authorization request (RFC 8628 Section 3.1)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeviceAuthorizationRequest {
+    /// Client ID
+    pub client_id: Uuid,
+    /// Client secret for authenticating the client
+    pub client_secret: MaxStr<64>,
+    /// The scopes the application requests
+    pub scope: String,
+}
+
+/// Device authorization response (RFC 8628 Section 3.2)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeviceAuthorizationResponse {
+    /// The code the device uses to poll the token endpoint
+    pub device_code: String,
+    /// The code the user has to enter on the verification page
+    pub user_code: String,
+    /// The page the user has to visit
+    pub verification_uri: Url,
+    /// The page the user has to visit with the user code already filled in
+    pub verification_uri_complete: Url,
+    /// Lifetime of the codes in seconds
+    pub expires_in: i64,
+    /// Minimal number of seconds the device has to wait between polling requests
+    pub interval: i64,
+}
+
+/// A pending device authorization request as shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeviceVerificationSchema {
+    /// Name of the requesting client
+    pub client_name: MaxStr<255>,
+    /// The scopes the client requests
+    pub scopes: Vec<String>,
+    /// The point in time the request expires
+    pub expires_at: SchemaDateTime,
+}
+
+/// The decision of the user on a device authorization request
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DeviceVerificationDecision {
+    /// Whether the user grants the device access
+    pub approve: bool,
+}
@@ -8,6 +8,10 @@ use galvyn::get;
 
 use crate::config::ORIGIN;
 use crate::http::handler_auth::discovery::schema::DiscoveryResponse;
+use crate::http::handler_auth::token::schema::GRANT_TYPE_AUTHORIZATION_CODE;
+use crate::http::handler_auth::token::schema::GRANT_TYPE_DEVICE_CODE;
+use crate::models::account::ACR_MULTI_FACTOR;
+use crate::models::account::ACR_SINGLE_FACTOR;
 
 #[get("/.well-known/openid-configuration")]
 pub async fn discovery() -> ApiResult<ApiJson<DiscoveryResponse>> {
@@ -16,10 +20,16 @@ pub async fn discovery() -> ApiResult<ApiJson<DiscoveryResponse>> {
         issuer: ORIGIN.join("/api/v1/auth").unwrap(),
         authorization_endpoint: ORIGIN.join("/api/v1/auth/auth").unwrap(),
         token_endpoint: ORIGIN.join("/api/v1/auth/token").unwrap(),
+        device_authorization_endpoint: ORIGIN.join("/api/v1/auth/device").unwrap(),
         userinfo_endpoint: ORIGIN.join("/api/v1/auth/userinfo").unwrap(),
         jwks_uri: ORIGIN.join("/api/v1/auth/jwks.json").unwrap(),
         response_types_supported: vec!["code".to_string()],
+        grant_types_supported: vec![
+            GRANT_TYPE_AUTHORIZATION_CODE.to_string(),
+            GRANT_TYPE_DEVICE_CODE.to_string(),
+        ],
         subject_types_supported: vec!["public".to_string()],
         id_token_signing_alg_values_supported: vec!["RS256".to_string()],
+        acr_values_supported: vec![ACR_SINGLE_FACTOR.to_string(), ACR_MULTI_FACTOR.to_string()],
     }))
 }
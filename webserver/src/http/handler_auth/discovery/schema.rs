@@ -11,9 +11,12 @@ pub struct DiscoveryResponse {
     pub issuer: Url,
     pub authorization_endpoint: Url,
     pub token_endpoint: Url,
+    pub device_authorization_endpoint: Url,
     pub userinfo_endpoint: Url,
     pub jwks_uri: Url,
     pub response_types_supported: Vec<String>,
+    pub grant_types_supported: Vec<String>,
     pub subject_types_supported: Vec<String>,
     pub id_token_signing_alg_values_supported: Vec<String>,
+    pub acr_values_supported: Vec<String>,
 }
@@ -6,8 +6,10 @@ use galvyn::openapi::OpenapiRouterExt;
 use crate::http::middlewares::AuthRateLimit;
 
 pub mod auth;
+pub mod device;
 pub mod discovery;
 pub mod jwks;
+pub mod passkey;
 pub mod token;
 mod userinfo;
 
@@ -21,6 +23,7 @@ pub fn initialize() -> GalvynRouter {
         .handler(auth::auth)
         .handler(auth::sign_out)
         .handler(auth::finish_auth)
+        .handler(device::device_authorization)
         .handler(discovery::discovery)
         .handler(jwks::jwks)
         .handler(token::get_token)
@@ -28,6 +31,13 @@ pub fn initialize() -> GalvynRouter {
         .merge(
             GalvynRouter::new()
                 .handler(auth::sign_in)
-                .wrap(AuthRateLimit::new(25)),
+                .handler(auth::sign_in_second_factor)
+                .handler(passkey::start_passkey_sign_in)
+                .handler(passkey::finish_passkey_sign_in)
+                .handler(passkey::start_passkey_second_factor)
+                .handler(passkey::finish_passkey_second_factor)
+                .handler(device::get_device_verification)
+                .handler(device::decide_device_verification)
+                .wrap(AuthRateLimit::new("sign-in", 25)),
         )
 }
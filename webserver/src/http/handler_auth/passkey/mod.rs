@@ -0,0 +1,281 @@
+//! Signing in using passkeys
+//!
+//! Passkeys can either be used on their own (passwordless) or as second factor
+//! after the password was verified by the regular sign in.
+
+use galvyn::core::Module;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::post;
+use galvyn::rorm::Database;
+use time::OffsetDateTime;
+use tracing::info;
+use tracing::instrument;
+use webauthn_rs::prelude::DiscoverableAuthentication;
+use webauthn_rs::prelude::DiscoverableKey;
+use webauthn_rs::prelude::PasskeyAuthentication;
+use webauthn_rs::prelude::PublicKeyCredential;
+
+use crate::http::extractors::auth_attempt::AuthAttempt;
+use crate::http::extractors::client_ip::ClientIp;
+use crate::http::extractors::session_user::SESSION_USER;
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::extractors::user_agent::UserAgent;
+use crate::http::handler_auth::auth::PendingSecondFactor;
+use crate::http::handler_auth::auth::SESSION_PENDING_SECOND_FACTOR;
+use crate::http::handler_auth::auth::record_login;
+use crate::http::handler_auth::auth::schema::SignInResponse;
+use crate::http::handler_auth::passkey::schema::PasskeyCredentialRequest;
+use crate::models::account::Account;
+use crate::models::account::AuthenticationMethod;
+use crate::models::login_event::LoginMethod;
+use crate::models::passkey::Passkey;
+use crate::modules::webauthn::WebAuthn;
+
+pub mod schema;
+
+/// Access the state of a running passwordless sign in using this variable
+const SESSION_PASSKEY_SIGN_IN: &str = "passkey-sign-in";
+
+/// Access the state of a running passkey second factor check using this variable
+const SESSION_PASSKEY_SECOND_FACTOR: &str = "passkey-second-factor";
+
+#[post("/sign-in/passkey/start")]
+#[instrument(name = "Api::auth::start_passkey_sign_in")]
+pub async fn start_passkey_sign_in(session: Session) -> ApiResult<ApiJson<serde_json::Value>> {
+    let (challenge, state) = WebAuthn::global()
+        .webauthn
+        .start_discoverable_authentication()
+        .map_err(ApiError::map_server_error(
+            "Couldn't start passkey authentication",
+        ))?;
+
+    session.insert(SESSION_PASSKEY_SIGN_IN, state).await?;
+
+    Ok(ApiJson(serde_json::to_value(challenge).map_err(
+        ApiError::map_server_error("Couldn't serialize challenge"),
+    )?))
+}
+
+#[post("/sign-in/passkey/finish")]
+#[instrument(name = "Api::auth::finish_passkey_sign_in", skip(credential))]
+pub async fn finish_passkey_sign_in(
+    session: Session,
+    attempt: AuthAttempt,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    ApiJson(PasskeyCredentialRequest { credential }): ApiJson<PasskeyCredentialRequest>,
+) -> ApiResult<ApiJson<SignInResponse>> {
+    let state: DiscoverableAuthentication = session
+        .remove(SESSION_PASSKEY_SIGN_IN)
+        .await?
+        .ok_or(ApiError::bad_request("No passkey sign in in progress"))?;
+
+    let credential: PublicKeyCredential = serde_json::from_value(credential)
+        .map_err(|_| ApiError::bad_request("Invalid credential"))?;
+
+    let webauthn = &WebAuthn::global().webauthn;
+    let (user_handle, credential_id) = webauthn
+        .identify_discoverable_authentication(&credential)
+        .map_err(|_| ApiError::bad_request("Invalid credential"))?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut passkey = Passkey::find_by_credential_id(&mut tx, credential_id)
+        .await?
+        .ok_or(ApiError::bad_request("Unknown passkey"))?;
+
+    if passkey.account.0 != user_handle {
+        return Err(ApiError::bad_request("Unknown passkey"));
+    }
+
+    let account = Account::get_by_uuid(&mut tx, passkey.account)
+        .await?
+        .ok_or(ApiError::bad_request("Unknown passkey"))?;
+
+    let result = webauthn
+        .finish_discoverable_authentication(
+            &credential,
+            state,
+            &[DiscoverableKey::from(passkey.credential())],
+        )
+        .map_err(|_| {
+            attempt.failed(&account);
+            ApiError::bad_request("Passkey authentication failed")
+        })?;
+
+    if account.is_locked() {
+        attempt.failed(&account);
+        tx.commit().await?;
+        record_login(
+            passkey.account,
+            LoginMethod::Passkey,
+            false,
+            client_ip,
+            user_agent,
+        )
+        .await?;
+
+        return Err(ApiError::bad_request("Account is locked"));
+    }
+
+    passkey.record_authentication(&mut tx, &result).await?;
+
+    tx.commit().await?;
+
+    attempt.succeeded(&account);
+    record_login(
+        passkey.account,
+        LoginMethod::Passkey,
+        true,
+        client_ip,
+        user_agent,
+    )
+    .await?;
+
+    info!(account = ?passkey.account, "Signed in using passkey");
+
+    session
+        .remove::<PendingSecondFactor>(SESSION_PENDING_SECOND_FACTOR)
+        .await?;
+    session
+        .insert(
+            SESSION_USER,
+            SessionUser {
+                uuid: passkey.account,
+                auth_methods: vec![AuthenticationMethod::Passkey],
+                active_club: None,
+            },
+        )
+        .await?;
+
+    Ok(ApiJson(SignInResponse::Finished {
+        second_factor_setup_required: false,
+    }))
+}
+
+#[post("/sign-in/second-factor/passkey/start")]
+#[instrument(name = "Api::auth::start_passkey_second_factor")]
+pub async fn start_passkey_second_factor(
+    session: Session,
+) -> ApiResult<ApiJson<serde_json::Value>> {
+    let pending: PendingSecondFactor = session
+        .get(SESSION_PENDING_SECOND_FACTOR)
+        .await?
+        .ok_or(ApiError::bad_request("No sign in pending"))?;
+
+    if pending.expires_at < OffsetDateTime::now_utc().unix_timestamp() {
+        return Err(ApiError::bad_request("Sign in has expired"));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let credentials: Vec<_> = Passkey::find_all_by_account(&mut tx, pending.uuid)
+        .await?
+        .into_iter()
+        .map(|passkey| passkey.credential().clone())
+        .collect();
+
+    tx.commit().await?;
+
+    if credentials.is_empty() {
+        return Err(ApiError::bad_request("The account has no passkeys"));
+    }
+
+    let (challenge, state) = WebAuthn::global()
+        .webauthn
+        .start_passkey_authentication(&credentials)
+        .map_err(ApiError::map_server_error(
+            "Couldn't start passkey authentication",
+        ))?;
+
+    session.insert(SESSION_PASSKEY_SECOND_FACTOR, state).await?;
+
+    Ok(ApiJson(serde_json::to_value(challenge).map_err(
+        ApiError::map_server_error("Couldn't serialize challenge"),
+    )?))
+}
+
+#[post("/sign-in/second-factor/passkey/finish")]
+#[instrument(name = "Api::auth::finish_passkey_second_factor", skip(credential))]
+pub async fn finish_passkey_second_factor(
+    session: Session,
+    client_ip: ClientIp,
+    user_agent: UserAgent,
+    attempt: AuthAttempt,
+    ApiJson(PasskeyCredentialRequest { credential }): ApiJson<PasskeyCredentialRequest>,
+) -> ApiResult<ApiJson<SignInResponse>> {
+    let pending: PendingSecondFactor = session
+        .remove(SESSION_PENDING_SECOND_FACTOR)
+        .await?
+        .ok_or(ApiError::bad_request("No sign in pending"))?;
+
+    let state: PasskeyAuthentication =
+        session
+            .remove(SESSION_PASSKEY_SECOND_FACTOR)
+            .await?
+            .ok_or(ApiError::bad_request(
+                "No passkey authentication in progress",
+            ))?;
+
+    if pending.expires_at < OffsetDateTime::now_utc().unix_timestamp() {
+        return Err(ApiError::bad_request("Sign in has expired"));
+    }
+
+    let credential: PublicKeyCredential = serde_json::from_value(credential)
+        .map_err(|_| ApiError::bad_request("Invalid credential"))?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, pending.uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account not found"))?;
+
+    let result = WebAuthn::global()
+        .webauthn
+        .finish_passkey_authentication(&credential, &state)
+        .map_err(|_| {
+            attempt.failed(&account);
+            ApiError::bad_request("Passkey authentication failed")
+        })?;
+
+    let mut passkey = Passkey::find_all_by_account(&mut tx, pending.uuid)
+        .await?
+        .into_iter()
+        .find(|passkey| passkey.credential().cred_id() == result.cred_id())
+        .ok_or(ApiError::bad_request("Unknown passkey"))?;
+
+    passkey.record_authentication(&mut tx, &result).await?;
+
+    tx.commit().await?;
+
+    attempt.succeeded(&account);
+    record_login(
+        pending.uuid,
+        LoginMethod::Password,
+        true,
+        client_ip,
+        user_agent,
+    )
+    .await?;
+
+    session
+        .insert(
+            SESSION_USER,
+            SessionUser {
+                uuid: pending.uuid,
+                auth_methods: vec![
+                    AuthenticationMethod::Password,
+                    AuthenticationMethod::Passkey,
+                ],
+                active_club: None,
+            },
+        )
+        .await?;
+
+    Ok(ApiJson(SignInResponse::Finished {
+        second_factor_setup_required: false,
+    }))
+}
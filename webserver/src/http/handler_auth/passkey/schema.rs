@@ -0,0 +1,13 @@
+//! Schema for signing in using passkeys
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Response of the authenticator to a passkey challenge
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PasskeyCredentialRequest {
+    /// The `PublicKeyCredential` returned by `navigator.credentials.get()`
+    pub credential: serde_json::Value,
+}
@@ -6,29 +6,44 @@ use std::time::UNIX_EPOCH;
 use base64ct::LineEnding;
 use galvyn::core::Module;
 use galvyn::core::re_exports::axum::Form;
+use galvyn::core::re_exports::axum::http::StatusCode;
+use galvyn::core::re_exports::axum::response::IntoResponse;
+use galvyn::core::re_exports::axum::response::Response;
 use galvyn::core::stuff::api_error::ApiError;
 use galvyn::core::stuff::api_error::ApiResult;
 use galvyn::core::stuff::api_json::ApiJson;
 use galvyn::post;
 use galvyn::rorm::Database;
+use galvyn::rorm::db::transaction::Transaction;
+use galvyn::rorm::fields::types::MaxStr;
 use jsonwebtoken::Algorithm;
 use jsonwebtoken::EncodingKey;
 use jsonwebtoken::Header;
 use rsa::pkcs1::EncodeRsaPrivateKey;
 use subtle::ConstantTimeEq;
+use time::Duration;
+use time::OffsetDateTime;
 use tracing::instrument;
 
 use crate::config::MAILCOW_BASE_URL;
 use crate::config::ORIGIN;
 use crate::http::handler_auth::token::schema::Claims;
 use crate::http::handler_auth::token::schema::EmailClaim;
+use crate::http::handler_auth::token::schema::GRANT_TYPE_AUTHORIZATION_CODE;
+use crate::http::handler_auth::token::schema::GRANT_TYPE_DEVICE_CODE;
 use crate::http::handler_auth::token::schema::ProfileClaim;
+use crate::http::handler_auth::token::schema::TokenErrorCode;
+use crate::http::handler_auth::token::schema::TokenErrorResponse;
 use crate::http::handler_auth::token::schema::TokenRequest;
 use crate::http::handler_auth::token::schema::TokenResponse;
+use crate::models::account::AuthenticationMethod;
+use crate::models::account::ClubAccount;
 use crate::models::club::Club;
 use crate::models::oidc_provider::OidcAuthenticationToken;
 use crate::models::oidc_provider::OidcClient;
 use crate::models::oidc_provider::OidcClientUuid;
+use crate::models::oidc_provider::OidcDeviceCode;
+use crate::models::oidc_provider::OidcDeviceCodeState;
 use crate::modules::mailcow::Mailcow;
 use crate::modules::oidc::Oidc;
 
@@ -36,23 +51,16 @@ pub mod schema;
 
 #[post("/token")]
 #[instrument(name = "Api::auth::token")]
-pub async fn get_token(
-    Form(TokenRequest {
-        grant_type,
-        code,
-        redirect_uri,
-        client_id,
-        client_secret,
-        code_verifier,
-    }): Form<TokenRequest>,
-) -> ApiResult<ApiJson<TokenResponse>> {
+pub async fn get_token(Form(request): Form<TokenRequest>) -> ApiResult<Response> {
     let mut tx = Database::global().start_transaction().await?;
 
-    if grant_type != "authorization_code" {
+    if request.grant_type != GRANT_TYPE_AUTHORIZATION_CODE
+        && request.grant_type != GRANT_TYPE_DEVICE_CODE
+    {
         return Err(ApiError::bad_request("Unsupported grant_type"));
     }
 
-    let provider = OidcClient::find_by_client_id(&mut tx, OidcClientUuid(client_id))
+    let provider = OidcClient::find_by_client_id(&mut tx, OidcClientUuid(request.client_id))
         .await?
         .ok_or(ApiError::bad_request("Invalid client_id"))?;
 
@@ -62,12 +70,40 @@ pub async fn get_token(
         provider
             .client_secret
             .as_bytes()
-            .ct_ne(client_secret.as_bytes()),
+            .ct_ne(request.client_secret.as_bytes()),
     ) {
         return Err(ApiError::bad_request("Invalid client_secret"));
     }
 
-    let token = OidcAuthenticationToken::get_by_code(&mut tx, code).await?;
+    let result = if request.grant_type == GRANT_TYPE_DEVICE_CODE {
+        exchange_device_code(&mut tx, request).await?
+    } else {
+        Ok(exchange_authorization_code(&mut tx, request).await?)
+    };
+
+    tx.commit().await?;
+
+    Ok(match result {
+        Ok(token) => ApiJson(token).into_response(),
+        Err(error) => (StatusCode::BAD_REQUEST, ApiJson(error)).into_response(),
+    })
+}
+
+/// Exchange an authorization code for tokens
+async fn exchange_authorization_code(
+    tx: &mut Transaction,
+    TokenRequest {
+        code,
+        redirect_uri,
+        client_id,
+        code_verifier,
+        ..
+    }: TokenRequest,
+) -> ApiResult<TokenResponse> {
+    let code = code.ok_or(ApiError::bad_request("Missing code"))?;
+    let redirect_uri = redirect_uri.ok_or(ApiError::bad_request("Missing redirect_uri"))?;
+
+    let token = OidcAuthenticationToken::get_by_code(&mut *tx, code).await?;
     let Some(token) = token else {
         return Err(ApiError::bad_request("Invalid authorization token"));
     };
@@ -111,6 +147,155 @@ pub async fn get_token(
         (None, None) => {}
     }
 
+    let response = issue_tokens(
+        &token.account,
+        token.client_id,
+        token.nonce.as_ref(),
+        &token.scopes,
+        &token.auth_methods,
+    )?;
+
+    OidcAuthenticationToken::delete_by_code(&mut *tx, &token.code).await?;
+
+    // -------------
+    // APP Password Hook follows
+    // -------------
+    if token.redirect_url.domain() == MAILCOW_BASE_URL.domain() && !token.account.has_app_password {
+        let club = Club::find_by_uuid(&mut *tx, token.account.club)
+            .await?
+            .ok_or(ApiError::bad_request("Club not found"))?;
+
+        if !club.use_xauth {
+            Mailcow::global().create_app_password(token.account.email.clone());
+        }
+    }
+
+    Ok(response)
+}
+
+/// Exchange a device code for tokens (RFC 8628 Section 3.4)
+///
+/// The inner error is reported to the polling device as specified in RFC 8628 Section 3.5
+async fn exchange_device_code(
+    tx: &mut Transaction,
+    TokenRequest {
+        device_code,
+        client_id,
+        ..
+    }: TokenRequest,
+) -> ApiResult<Result<TokenResponse, TokenErrorResponse>> {
+    let device_code = device_code.ok_or(ApiError::bad_request("Missing device_code"))?;
+
+    let Some(mut request) = OidcDeviceCode::find_by_device_code(&mut *tx, &device_code).await?
+    else {
+        return Ok(Err(token_error(
+            TokenErrorCode::InvalidGrant,
+            "Invalid device_code",
+        )));
+    };
+
+    if request.client_id != OidcClientUuid(client_id) {
+        return Ok(Err(token_error(
+            TokenErrorCode::InvalidGrant,
+            "Device code was not issued to this client",
+        )));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    if request.expires_at < now {
+        request.delete(&mut *tx).await?;
+        return Ok(Err(token_error(
+            TokenErrorCode::ExpiredToken,
+            "The device code has expired",
+        )));
+    }
+
+    match &request.state {
+        OidcDeviceCodeState::Pending => {
+            let too_fast = request.last_polled_at.is_some_and(|last_polled_at| {
+                now - last_polled_at < Duration::seconds(request.poll_interval)
+            });
+
+            request.update_last_polled(&mut *tx).await?;
+
+            if too_fast {
+                request.slow_down(&mut *tx).await?;
+                return Ok(Err(token_error(
+                    TokenErrorCode::SlowDown,
+                    "Polling too fast",
+                )));
+            }
+
+            Ok(Err(token_error(
+                TokenErrorCode::AuthorizationPending,
+                "The user has not yet completed the authorization",
+            )))
+        }
+        OidcDeviceCodeState::Denied => {
+            request.delete(&mut *tx).await?;
+            Ok(Err(token_error(
+                TokenErrorCode::AccessDenied,
+                "The user denied the authorization request",
+            )))
+        }
+        OidcDeviceCodeState::Approved {
+            account,
+            auth_methods,
+        } => {
+            // Only the poll which removes the request may redeem the approval
+            if !request.delete(&mut *tx).await? {
+                return Ok(Err(token_error(
+                    TokenErrorCode::InvalidGrant,
+                    "The device code was already redeemed",
+                )));
+            }
+
+            let Some(account) = ClubAccount::get_by_uuid(&mut *tx, *account).await? else {
+                return Ok(Err(token_error(
+                    TokenErrorCode::InvalidGrant,
+                    "The approving account is no longer a member",
+                )));
+            };
+
+            if account.locked
+                || account
+                    .valid_until
+                    .is_some_and(|valid_until| valid_until < now)
+            {
+                return Ok(Err(token_error(
+                    TokenErrorCode::AccessDenied,
+                    "The approving account is locked",
+                )));
+            }
+
+            let response = issue_tokens(
+                &account,
+                request.client_id,
+                None,
+                &request.scopes,
+                auth_methods,
+            )?;
+
+            Ok(Ok(response))
+        }
+    }
+}
+
+fn token_error(error: TokenErrorCode, description: &str) -> TokenErrorResponse {
+    TokenErrorResponse {
+        error,
+        error_description: Some(description.to_string()),
+    }
+}
+
+/// Create and sign the id and access token for an account
+fn issue_tokens(
+    account: &ClubAccount,
+    client_id: OidcClientUuid,
+    nonce: Option<&MaxStr<255>>,
+    scopes: &[String],
+    auth_methods: &[AuthenticationMethod],
+) -> ApiResult<TokenResponse> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(ApiError::map_server_error("Error calculating system time"))?
@@ -120,24 +305,26 @@ pub async fn get_token(
     #[allow(clippy::expect_used)]
     let mut claims = Claims {
         iss: ORIGIN.to_string(),
-        sub: token.account.uuid().0.to_string(),
-        aud: token.client_id.0.to_string(),
+        sub: account.uuid().0.to_string(),
+        aud: client_id.0.to_string(),
         iat: now,
         exp,
-        nonce: token.nonce.map(|x| x.to_string()),
+        nonce: nonce.map(|x| x.to_string()),
+        amr: auth_methods.to_vec(),
+        acr: Some(AuthenticationMethod::acr(auth_methods).to_string()),
         ..Default::default()
     };
 
-    if token.scopes.iter().any(|x| x == "profile") {
+    if scopes.iter().any(|x| x == "profile") {
         claims.profile_claim = Some(ProfileClaim {
-            preferred_username: token.account.username.to_string(),
-            name: token.account.display_name.to_string(),
+            preferred_username: account.username.to_string(),
+            name: account.display_name.to_string(),
         });
     }
 
-    if token.scopes.iter().any(|x| x == "email") {
+    if scopes.iter().any(|x| x == "email") {
         claims.email_claim = Some(EmailClaim {
-            email: token.account.email.to_string(),
+            email: account.email.to_string(),
             email_verified: true,
         });
     }
@@ -169,27 +356,10 @@ pub async fn get_token(
     let access_token = jsonwebtoken::encode(&header, &claims, &encoding_key)
         .map_err(ApiError::map_server_error("Couldn't encode JWT"))?;
 
-    OidcAuthenticationToken::delete_by_code(&mut tx, &token.code).await?;
-
-    // -------------
-    // APP Password Hook follows
-    // -------------
-    if token.redirect_url.domain() == MAILCOW_BASE_URL.domain() && !token.account.has_app_password {
-        let club = Club::find_by_uuid(&mut tx, token.account.club)
-            .await?
-            .ok_or(ApiError::bad_request("Club not found"))?;
-
-        if !club.use_xauth {
-            Mailcow::global().create_app_password(token.account.email.clone());
-        }
-    }
-
-    tx.commit().await?;
-
-    Ok(ApiJson(TokenResponse {
+    Ok(TokenResponse {
         access_token,
         id_token,
         token_type: "Bearer".to_string(),
         expires_in: 300,
-    }))
+    })
 }
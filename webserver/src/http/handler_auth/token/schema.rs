@@ -7,15 +7,31 @@ use serde::Deserialize;
 use serde::Serialize;
 use uuid::Uuid;
 
+use crate::models::account::AuthenticationMethod;
+
+/// Grant type of the authorization code flow
+pub const GRANT_TYPE_AUTHORIZATION_CODE: &str = "authorization_code";
+
+/// Grant type of the device authorization flow (RFC 8628)
+pub const GRANT_TYPE_DEVICE_CODE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
 /// Request for a token
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TokenRequest {
     /// Type of the grant
     pub grant_type: String,
     /// Code to exchange for a token
-    pub code: MaxStr<64>,
+    ///
+    /// Required for the `authorization_code` grant
+    pub code: Option<MaxStr<64>>,
     /// Redirect url of the initial request
-    pub redirect_uri: String,
+    ///
+    /// Required for the `authorization_code` grant
+    pub redirect_uri: Option<String>,
+    /// Device code to exchange for a token
+    ///
+    /// Required for the `urn:ietf:params:oauth:grant-type:device_code` grant
+    pub device_code: Option<MaxStr<64>>,
     /// Client ID
     pub client_id: Uuid,
     /// Client secret for authenticating the client
@@ -37,6 +53,31 @@ pub struct TokenResponse {
     pub expires_in: usize,
 }
 
+/// Error response of the token endpoint (RFC 6749 Section 5.2)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TokenErrorResponse {
+    /// Error code
+    pub error: TokenErrorCode,
+    /// Human-readable description of the error
+    pub error_description: Option<String>,
+}
+
+/// Error codes of the token endpoint which are relevant for the device authorization grant
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenErrorCode {
+    /// The user has not yet completed the authorization
+    AuthorizationPending,
+    /// The device is polling too fast and should increase its interval by 5 seconds
+    SlowDown,
+    /// The user denied the authorization request
+    AccessDenied,
+    /// The device code has expired
+    ExpiredToken,
+    /// The device code is unknown or was issued to another client
+    InvalidGrant,
+}
+
 /// Data for all claims
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Claims {
@@ -52,6 +93,11 @@ pub struct Claims {
     pub iat: usize,
     /// Optional nonce
     pub nonce: Option<String>,
+    /// Authentication methods used (RFC 8176)
+    #[serde(default)]
+    pub amr: Vec<AuthenticationMethod>,
+    /// Authentication context class
+    pub acr: Option<String>,
     /// Optional email claims
     #[serde(flatten)]
     pub email_claim: Option<EmailClaim>,
@@ -4,13 +4,17 @@ use galvyn::core::stuff::api_error::ApiError;
 use galvyn::core::stuff::api_error::ApiResult;
 use galvyn::core::stuff::api_json::ApiJson;
 use galvyn::get;
+use galvyn::rorm::Database;
 use jsonwebtoken::Algorithm;
 use jsonwebtoken::Validation;
 use rsa::pkcs1::LineEnding;
 use rsa::pkcs8::EncodePublicKey;
 use tracing::instrument;
+use uuid::Uuid;
 
 use crate::config::ORIGIN;
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
 use crate::modules::oidc::Oidc;
 
 mod schema;
@@ -51,6 +55,16 @@ pub async fn get_userinfo(headers: HeaderMap) -> ApiResult<ApiJson<schema::Claim
     )
     .map_err(ApiError::map_server_error("Invalid token"))?;
 
+    // The token might have been issued before the account was locked
+    let account_uuid = Uuid::parse_str(&token.claims.sub)
+        .map_err(ApiError::map_server_error("Invalid subject"))?;
+    let account = Account::get_by_uuid(Database::global(), AccountUuid(account_uuid))
+        .await?
+        .ok_or(ApiError::unauthorized("Account not found"))?;
+    if account.is_membership_locked() {
+        return Err(ApiError::unauthorized("Account is locked"));
+    }
+
     Ok(ApiJson(schema::Claims {
         sub: token.claims.sub,
         email_claim: token.claims.email_claim,
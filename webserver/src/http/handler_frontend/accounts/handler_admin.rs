@@ -8,26 +8,36 @@ use galvyn::core::stuff::api_json::ApiJson;
 use galvyn::delete;
 use galvyn::get;
 use galvyn::post;
+use galvyn::put;
 use galvyn::rorm::Database;
+use tracing::error;
 use tracing::instrument;
 
 use crate::http::handler_frontend::accounts::CredentialResetSchema;
-use crate::http::handler_frontend::accounts::SimpleAccountSchema;
+use crate::http::handler_frontend::accounts::SetLockedRequest;
+use crate::http::handler_frontend::accounts::SuperadminSchema;
+use crate::http::handler_frontend::invites::GetInvite;
 use crate::models::account::Account;
 use crate::models::account::AccountUuid;
 use crate::models::account::AdministrativeAccount;
 use crate::models::account::ClubAdminAccount;
+use crate::models::club::Club;
+use crate::models::invite::Invite;
+use crate::models::recovery_email::RecoveryEmail;
+use crate::models::session::StoredSession;
 use crate::modules::mailcow::Mailcow;
+use crate::modules::mailer::MailTemplate;
+use crate::modules::mailer::Mailer;
 
 #[get("/superadmins")]
 #[instrument(name = "Api::admin::get_all_superadmins")]
-pub async fn get_all_superadmins() -> ApiResult<ApiJson<Vec<SimpleAccountSchema>>> {
+pub async fn get_all_superadmins() -> ApiResult<ApiJson<Vec<SuperadminSchema>>> {
     let mut tx = Database::global().start_transaction().await?;
 
     let accounts = AdministrativeAccount::get_all(&mut tx)
         .await?
         .into_iter()
-        .map(SimpleAccountSchema::from)
+        .map(SuperadminSchema::from)
         .collect();
 
     tx.commit().await?;
@@ -35,24 +45,151 @@ pub async fn get_all_superadmins() -> ApiResult<ApiJson<Vec<SimpleAccountSchema>
     Ok(ApiJson(accounts))
 }
 
+#[get("/superadmins/invites")]
+#[instrument(name = "Api::admin::get_superadmin_invites")]
+pub async fn get_superadmin_invites() -> ApiResult<ApiJson<Vec<GetInvite>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let invites = Invite::find_superadmin_invites(&mut tx)
+        .await?
+        .into_iter()
+        .map(GetInvite::from)
+        .collect();
+
+    tx.commit().await?;
+
+    Ok(ApiJson(invites))
+}
+
+/// Remove the superadmin privileges of an account
+///
+/// Accounts which don't hold any other role are deleted.
+#[delete("/superadmins/{uuid}")]
+#[instrument(name = "Api::admin::delete_superadmin")]
+pub async fn delete_superadmin(Path(account_uuid): Path<AccountUuid>) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let superadmin = AdministrativeAccount::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Superadmin doesn't exist"))?;
+
+    if !superadmin.locked && AdministrativeAccount::count_active(&mut tx).await? <= 1 {
+        return Err(ApiError::bad_request(
+            "Can't delete the last active superadmin",
+        ));
+    }
+
+    superadmin.delete(&mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Lock or unlock a superadmin
+///
+/// All sessions of a locked superadmin are revoked.
+#[put("/superadmins/{uuid}/locked")]
+#[instrument(name = "Api::admin::set_superadmin_locked")]
+pub async fn set_superadmin_locked(
+    Path(account_uuid): Path<AccountUuid>,
+    ApiJson(SetLockedRequest { locked }): ApiJson<SetLockedRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .filter(|account| account.superadmin)
+        .ok_or(ApiError::bad_request("Superadmin doesn't exist"))?;
+
+    if locked && !account.locked && AdministrativeAccount::count_active(&mut tx).await? <= 1 {
+        return Err(ApiError::bad_request(
+            "Can't lock the last active superadmin",
+        ));
+    }
+
+    account.set_locked(&mut tx, locked).await?;
+    if locked {
+        StoredSession::revoke_all_by_account(&mut tx, account_uuid).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
 #[delete("/club-admins/{uuid}")]
 #[instrument(name = "Api::admin::delete_club_admin")]
 pub async fn delete_club_admin(Path(account_uuid): Path<AccountUuid>) -> ApiResult<()> {
     let mut tx = Database::global().start_transaction().await?;
 
-    let club_admin = ClubAdminAccount::get_by_uuid(&mut tx, account_uuid)
-        .await?
-        .ok_or(ApiError::bad_request("Club admin doesn't exist"))?;
+    let mut admin_roles = ClubAdminAccount::find_all_by_account(&mut tx, account_uuid).await?;
+    let Some(username) = admin_roles.first().map(|x| x.username.clone()) else {
+        return Err(ApiError::bad_request("Club admin doesn't exist"));
+    };
+
+    // Locking the clubs serializes concurrent removals, so the last admin can't be removed.
+    // The clubs are locked in a fixed order to not deadlock with other removals.
+    admin_roles.sort_by_key(|club_admin| club_admin.club.0);
+    for club_admin in &admin_roles {
+        let club = Club::find_by_uuid_for_update(&mut tx, club_admin.club)
+            .await?
+            .ok_or(ApiError::server_error("Club of club admin vanished"))?;
+
+        if club.admin_count <= 1 {
+            return Err(ApiError::bad_request(
+                "Can't delete the last admin of a club",
+            ));
+        }
+    }
+
+    for club_admin in admin_roles {
+        club_admin.delete(&mut tx).await?;
+    }
 
-    Mailcow::global()
+    tx.commit().await?;
+
+    // The roles are gone even if mailcow is unreachable, the domain admin is repaired later
+    if let Err(error) = Mailcow::global()
         .sdk
-        .delete_domain_admins(vec![club_admin.username.clone().into_inner()])
+        .delete_domain_admins(vec![username.to_string()])
         .await
-        .map_err(ApiError::map_server_error(
-            "Couldn't delete domain administrator in mailcow",
-        ))?;
+    {
+        error!(
+            error.display = %error,
+            error.debug = ?error,
+            %username,
+            "Couldn't delete domain administrator in mailcow"
+        );
+
+        // Mark the domain admin, so a superadmin can repair it
+        if let Some(mut account) = Account::get_by_uuid(Database::global(), account_uuid).await? {
+            account
+                .update_domain_admin_synced(Database::global(), false)
+                .await?;
+        }
+    }
 
-    club_admin.delete(&mut tx).await?;
+    Ok(())
+}
+
+#[post("/club-admins/{uuid}/repair-domain-admin")]
+#[instrument(name = "Api::admin::repair_domain_admin")]
+pub async fn repair_domain_admin(Path(account_uuid): Path<AccountUuid>) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Target account doesn't exist"))?;
+
+    // Accounts without admin roles may still have a domain admin whose deletion failed
+    if account.admin_of.is_empty() && account.domain_admin_synced {
+        return Err(ApiError::bad_request("Target account isn't a club admin"));
+    }
+
+    Mailcow::global()
+        .repair_domain_admin(&mut tx, &mut account)
+        .await?;
 
     tx.commit().await?;
 
@@ -72,7 +209,25 @@ pub async fn reset_credentials(
 
     let reset = account.create_credential_reset(&mut tx).await?;
 
+    // Prefer the recovery email as the mailbox might not be accessible without the password
+    let recovery_email = RecoveryEmail::find_verified_by_account(&mut tx, account.uuid())
+        .await?
+        .map(|recovery_email| recovery_email.email);
+    if let Some(email) = recovery_email.as_ref().or(account.email()) {
+        Mailer::global()
+            .enqueue(
+                &mut tx,
+                email,
+                MailTemplate::CredentialReset {
+                    display_name: account.display_name().to_string(),
+                    link: reset.link(),
+                },
+            )
+            .await?;
+    }
+
     tx.commit().await?;
+    Mailer::global().deliver_enqueued();
 
     Ok(ApiJson(CredentialResetSchema::from(reset)))
 }
@@ -5,14 +5,59 @@ use galvyn::core::re_exports::axum::extract::Path;
 use galvyn::core::stuff::api_error::ApiError;
 use galvyn::core::stuff::api_error::ApiResult;
 use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::delete;
 use galvyn::post;
+use galvyn::put;
 use galvyn::rorm::Database;
+use galvyn::rorm::db::Executor;
 use tracing::instrument;
 
 use crate::http::handler_frontend::accounts::CredentialResetSchema;
+use crate::http::handler_frontend::accounts::SetLockedRequest;
+use crate::http::handler_frontend::accounts::SetValidUntilRequest;
 use crate::models::account::Account;
 use crate::models::account::AccountUuid;
+use crate::models::account::ClubAccount;
+use crate::models::account::ClubAdminAccount;
+use crate::models::club::Club;
 use crate::models::club::ClubUuid;
+use crate::models::recovery_email::RecoveryEmail;
+use crate::models::session::StoredSession;
+use crate::modules::mailcow::Mailcow;
+use crate::modules::mailer::MailTemplate;
+use crate::modules::mailer::Mailer;
+
+/// Get a member of the club a club admin may manage
+///
+/// Accounts with admin roles are rejected, as managing them would allow
+/// a club admin to take over a co-admin, an admin of another club or a superadmin.
+async fn get_managed_member(
+    exe: impl Executor<'_>,
+    club_uuid: ClubUuid,
+    account_uuid: AccountUuid,
+) -> ApiResult<Account> {
+    let account = Account::get_by_uuid(exe, account_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Target account doesn't exist"))?;
+
+    if account
+        .membership
+        .as_ref()
+        .is_none_or(|membership| membership.club != club_uuid)
+    {
+        return Err(ApiError::bad_request(
+            "Target account isn't part of the club of the executing admin",
+        ));
+    }
+
+    if account.superadmin || !account.admin_of.is_empty() {
+        return Err(ApiError::bad_request(
+            "Accounts with admin roles can't be managed by club admins",
+        ));
+    }
+
+    Ok(account)
+}
 
 #[post("/{uuid}/reset-credentials")]
 #[instrument(name = "Api::club_admin::reset_credentials")]
@@ -21,25 +66,184 @@ pub async fn reset_credentials(
 ) -> ApiResult<ApiJson<CredentialResetSchema>> {
     let mut tx = Database::global().start_transaction().await?;
 
-    let account = Account::get_by_uuid(&mut tx, account_uuid)
+    let account = get_managed_member(&mut tx, club_uuid, account_uuid).await?;
+
+    let reset = account.create_credential_reset(&mut tx).await?;
+
+    // Prefer the recovery email as the mailbox might not be accessible without the password
+    let recovery_email = RecoveryEmail::find_verified_by_account(&mut tx, account.uuid())
+        .await?
+        .map(|recovery_email| recovery_email.email);
+    if let Some(email) = recovery_email.as_ref().or(account.email()) {
+        Mailer::global()
+            .enqueue(
+                &mut tx,
+                email,
+                MailTemplate::CredentialReset {
+                    display_name: account.display_name().to_string(),
+                    link: reset.link(),
+                },
+            )
+            .await?;
+    }
+
+    tx.commit().await?;
+    Mailer::global().deliver_enqueued();
+
+    Ok(ApiJson(CredentialResetSchema::from(reset)))
+}
+
+/// Grant a member of the club the admin role of the club
+#[post("/{uuid}")]
+#[instrument(name = "Api::club_admin::promote_admin")]
+pub async fn promote_admin(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::get_by_uuid(&mut tx, account_uuid)
         .await?
         .ok_or(ApiError::bad_request("Target account doesn't exist"))?;
 
-    let Account::ClubMember(club_member) = &account else {
+    let Some(membership) = &account.membership else {
         return Err(ApiError::bad_request(
             "Target account isn't a club member account",
         ));
     };
 
-    if club_member.club != club_uuid {
+    if membership.club != club_uuid {
         return Err(ApiError::bad_request(
             "Target account isn't part of the club of the executing admin",
         ));
     }
 
-    let reset = account.create_credential_reset(&mut tx).await?;
+    if account.is_admin_of(club_uuid) {
+        return Err(ApiError::bad_request(
+            "Target account is already an admin of the club",
+        ));
+    }
+
+    ClubAdminAccount::create(&mut tx, account.uuid(), club_uuid).await?;
+
+    let has_domain_admin = !account.admin_of.is_empty();
+    account.admin_of.push(club_uuid);
+    if has_domain_admin {
+        Mailcow::global()
+            .update_domain_admin(&mut tx, &account)
+            .await?;
+    } else {
+        Mailcow::global()
+            .create_domain_admin(&mut tx, &account)
+            .await?;
+    }
 
     tx.commit().await?;
 
-    Ok(ApiJson(CredentialResetSchema::from(reset)))
+    Ok(())
+}
+
+/// Remove the admin role of the club from an account
+///
+/// Accounts which don't hold any other role are deleted.
+#[delete("/{uuid}")]
+#[instrument(name = "Api::club_admin::demote_admin")]
+pub async fn demote_admin(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let club_admin = ClubAdminAccount::find_by_account_and_club(&mut tx, account_uuid, club_uuid)
+        .await?
+        .ok_or(ApiError::bad_request(
+            "Target account isn't an admin of the club",
+        ))?;
+
+    // Locking the club serializes concurrent demotions, so the last admin can't be removed
+    let club = Club::find_by_uuid_for_update(&mut tx, club_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Club not found"))?;
+
+    if club.admin_count <= 1 {
+        return Err(ApiError::bad_request(
+            "Can't demote the last admin of the club",
+        ));
+    }
+
+    let mut account = Account::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Target account doesn't exist"))?;
+
+    account.admin_of.retain(|club| *club != club_uuid);
+    Mailcow::global()
+        .update_domain_admin(&mut tx, &account)
+        .await?;
+
+    club_admin.delete(&mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Lock or unlock a member of the club
+///
+/// The mailbox of a locked member is deactivated and all of its sessions are revoked.
+#[put("/{uuid}/locked")]
+#[instrument(name = "Api::club_admin::set_member_locked")]
+pub async fn set_member_locked(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+    ApiJson(SetLockedRequest { locked }): ApiJson<SetLockedRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = get_managed_member(&mut tx, club_uuid, account_uuid).await?;
+
+    let Some(email) = account
+        .membership
+        .as_ref()
+        .map(|membership| membership.email.to_string())
+    else {
+        return Err(ApiError::server_error("Member without membership"));
+    };
+
+    account.set_locked(&mut tx, locked).await?;
+    if locked {
+        StoredSession::revoke_all_by_account(&mut tx, account_uuid).await?;
+    }
+
+    Mailcow::global()
+        .sdk
+        .set_mailboxes_active(vec![email], !account.is_membership_locked())
+        .await
+        .map_err(ApiError::map_server_error("Couldn't update mailbox"))?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Set the point in time the membership of a member of the club ends
+///
+/// Expired members are locked automatically and stay locked until they're unlocked explicitly.
+#[put("/{uuid}/valid-until")]
+#[instrument(name = "Api::club_admin::set_member_valid_until")]
+pub async fn set_member_valid_until(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+    ApiJson(SetValidUntilRequest { valid_until }): ApiJson<SetValidUntilRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    get_managed_member(&mut tx, club_uuid, account_uuid).await?;
+
+    let mut member = ClubAccount::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .ok_or(ApiError::server_error("Member vanished"))?;
+
+    member
+        .set_valid_until(&mut tx, valid_until.map(|x| x.0))
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
 }
@@ -35,6 +35,52 @@ pub struct SimpleMemberAccountSchema {
     pub display_name: MaxStr<255>,
     /// The account's email
     pub email: MaxStr<255>,
+    /// Whether the account is locked and can't sign in
+    pub locked: bool,
+    /// The point in time the membership ends
+    pub valid_until: Option<SchemaDateTime>,
+}
+
+/// Representation of a superadmin
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SuperadminSchema {
+    /// The account's UUID.
+    pub uuid: AccountUuid,
+    /// The account's username.
+    pub username: MaxStr<255>,
+    /// The account's display name.
+    pub display_name: MaxStr<255>,
+    /// Whether the account is locked and can't sign in
+    pub locked: bool,
+}
+
+/// Request to lock or unlock an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetLockedRequest {
+    /// Whether the account should be locked
+    pub locked: bool,
+}
+
+/// Request to set the point in time a membership ends
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetValidUntilRequest {
+    /// The point in time the membership ends, unlimited if unset
+    pub valid_until: Option<SchemaDateTime>,
+}
+
+/// Representation of a club admin for superadmins
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClubAdminSchema {
+    /// The account's UUID.
+    pub uuid: AccountUuid,
+    /// The account's username.
+    pub username: MaxStr<255>,
+    /// The account's display name.
+    pub display_name: MaxStr<255>,
+    /// Whether the password of the mailcow domain admin is in sync with the account
+    ///
+    /// If unset, the domain admin should be repaired.
+    pub domain_admin_synced: bool,
 }
 
 /// Instance of the credential reset
@@ -65,12 +111,13 @@ impl From<CredentialReset> for CredentialResetSchema {
     }
 }
 
-impl From<AdministrativeAccount> for SimpleAccountSchema {
+impl From<AdministrativeAccount> for SuperadminSchema {
     fn from(value: AdministrativeAccount) -> Self {
         Self {
             uuid: value.uuid(),
             username: value.username,
             display_name: value.display_name,
+            locked: value.locked,
         }
     }
 }
@@ -85,6 +132,17 @@ impl From<ClubAdminAccount> for SimpleAccountSchema {
     }
 }
 
+impl From<ClubAdminAccount> for ClubAdminSchema {
+    fn from(value: ClubAdminAccount) -> Self {
+        Self {
+            uuid: value.uuid(),
+            username: value.username,
+            display_name: value.display_name,
+            domain_admin_synced: value.domain_admin_synced,
+        }
+    }
+}
+
 impl From<ClubAccount> for SimpleMemberAccountSchema {
     fn from(value: ClubAccount) -> Self {
         Self {
@@ -92,6 +150,8 @@ impl From<ClubAccount> for SimpleMemberAccountSchema {
             username: value.username,
             display_name: value.display_name,
             email: value.email,
+            locked: value.locked,
+            valid_until: value.valid_until.map(SchemaDateTime),
         }
     }
 }
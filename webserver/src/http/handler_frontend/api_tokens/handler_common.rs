@@ -0,0 +1,136 @@
+//! Common handlers for managing the api tokens of the currently logged-in user
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::FormResult;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::rorm::Database;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::CredentialUser;
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::api_tokens::API_TOKEN_MAX_VALID_DAYS;
+use crate::http::handler_frontend::api_tokens::ApiTokenSchema;
+use crate::http::handler_frontend::api_tokens::CreateApiTokenError;
+use crate::http::handler_frontend::api_tokens::CreateApiTokenRequest;
+use crate::http::handler_frontend::api_tokens::CreatedApiTokenSchema;
+use crate::models::account::Account;
+use crate::models::api_token::ApiToken;
+use crate::models::api_token::ApiTokenScope;
+use crate::models::api_token::ApiTokenUuid;
+use crate::models::api_token::CreateApiTokenParams;
+
+#[get("/")]
+#[instrument(name = "Api::common::get_api_tokens")]
+pub async fn get_api_tokens(
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<ApiJson<Vec<ApiTokenSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let api_tokens = ApiToken::find_all_by_account(&mut tx, uuid).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(
+        api_tokens.into_iter().map(ApiTokenSchema::from).collect(),
+    ))
+}
+
+/// Create an api token
+///
+/// Api tokens can only be created from a session, not using another api token
+/// or while impersonating.
+#[post("/")]
+#[instrument(name = "Api::common::create_api_token")]
+pub async fn create_api_token(
+    CredentialUser(SessionUser {
+        uuid, auth_methods, ..
+    }): CredentialUser,
+    ApiJson(CreateApiTokenRequest {
+        name,
+        scopes,
+        valid_days,
+    }): ApiJson<CreateApiTokenRequest>,
+) -> ApiResult<ApiJson<FormResult<CreatedApiTokenSchema, CreateApiTokenError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    let errors = CreateApiTokenError {
+        empty_name: name.trim().is_empty(),
+        empty_scopes: scopes.is_empty(),
+        forbidden_scope: scopes.iter().any(|scope| match scope {
+            ApiTokenScope::Common => false,
+            ApiTokenScope::ClubAdmin { club } => !account.is_admin_of(*club),
+            ApiTokenScope::SuperAdmin => !account.superadmin,
+        }),
+        invalid_valid_days: valid_days == 0 || valid_days > API_TOKEN_MAX_VALID_DAYS,
+    };
+    if errors.empty_name
+        || errors.empty_scopes
+        || errors.forbidden_scope
+        || errors.invalid_valid_days
+    {
+        return Ok(ApiJson(FormResult::err(errors)));
+    }
+
+    let mut deduplicated_scopes = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        if !deduplicated_scopes.contains(&scope) {
+            deduplicated_scopes.push(scope);
+        }
+    }
+
+    let (api_token, token) = ApiToken::create(
+        &mut tx,
+        CreateApiTokenParams {
+            account: uuid,
+            name,
+            scopes: deduplicated_scopes,
+            auth_methods,
+            expires_at: OffsetDateTime::now_utc() + Duration::days(i64::from(valid_days)),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    info!(account = ?uuid, api_token = ?api_token.uuid, "Api token created");
+
+    Ok(ApiJson(FormResult::ok(CreatedApiTokenSchema {
+        uuid: api_token.uuid,
+        token,
+    })))
+}
+
+#[delete("/{uuid}")]
+#[instrument(name = "Api::common::revoke_api_token")]
+pub async fn revoke_api_token(
+    SessionUser { uuid, .. }: SessionUser,
+    Path(SingleUuid {
+        uuid: api_token_uuid,
+    }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    if !ApiToken::revoke(&mut tx, uuid, ApiTokenUuid(api_token_uuid)).await? {
+        return Err(ApiError::bad_request("Api token not found"));
+    }
+
+    tx.commit().await?;
+
+    info!(account = ?uuid, api_token = ?api_token_uuid, "Api token revoked");
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Handlers for the personal api tokens of the currently logged-in user
+
+pub use schema::*;
+
+pub mod handler_common;
+mod schema;
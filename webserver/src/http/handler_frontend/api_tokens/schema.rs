@@ -0,0 +1,80 @@
+//! Schema for managing api tokens
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::api_token::ApiToken;
+use crate::models::api_token::ApiTokenScope;
+use crate::models::api_token::ApiTokenUuid;
+
+/// Longest lifetime of an api token in days
+pub const API_TOKEN_MAX_VALID_DAYS: u16 = 365;
+
+/// An api token of the user
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ApiTokenSchema {
+    /// Identifier of the token
+    pub uuid: ApiTokenUuid,
+    /// Name to recognize the token by
+    pub name: MaxStr<255>,
+    /// The parts of the api the token may be used for
+    pub scopes: Vec<ApiTokenScope>,
+    /// The point in time the token expires
+    pub expires_at: SchemaDateTime,
+    /// The last point in time the token was used
+    pub last_used_at: Option<SchemaDateTime>,
+    /// The point in time the token was created
+    pub created_at: SchemaDateTime,
+}
+
+impl From<ApiToken> for ApiTokenSchema {
+    fn from(value: ApiToken) -> Self {
+        Self {
+            uuid: value.uuid,
+            name: value.name,
+            scopes: value.scopes,
+            expires_at: SchemaDateTime(value.expires_at),
+            last_used_at: value.last_used_at.map(SchemaDateTime),
+            created_at: SchemaDateTime(value.created_at),
+        }
+    }
+}
+
+/// Request to create an api token
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateApiTokenRequest {
+    /// Name to recognize the token by
+    pub name: MaxStr<255>,
+    /// The parts of the api the token may be used for
+    pub scopes: Vec<ApiTokenScope>,
+    /// Number of days the token is valid for
+    pub valid_days: u16,
+}
+
+/// Errors that may occur while creating an api token
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct CreateApiTokenError {
+    /// The name is empty
+    pub empty_name: bool,
+    /// No scopes were requested
+    pub empty_scopes: bool,
+    /// A scope was requested the account doesn't hold the role for
+    pub forbidden_scope: bool,
+    /// The lifetime is zero or exceeds [`API_TOKEN_MAX_VALID_DAYS`]
+    pub invalid_valid_days: bool,
+}
+
+/// A newly created api token
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreatedApiTokenSchema {
+    /// Identifier of the token
+    pub uuid: ApiTokenUuid,
+    /// The token to pass in the `Authorization: Bearer` header
+    ///
+    /// It is only shown once.
+    pub token: String,
+}
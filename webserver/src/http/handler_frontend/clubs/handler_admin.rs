@@ -12,26 +12,35 @@ use galvyn::core::stuff::schema::SingleUuid;
 use galvyn::delete;
 use galvyn::get;
 use galvyn::post;
+use galvyn::put;
 use galvyn::rorm::Database;
-use mailcow::domain_admins::schema::EditDomainAdminsChanges;
-use mailcow::domain_admins::schema::EditDomainAdminsRequest;
 use tracing::instrument;
+use tracing::warn;
 
-use crate::http::handler_frontend::accounts::SimpleAccountSchema;
+use crate::http::handler_frontend::accounts::ClubAdminSchema;
 use crate::http::handler_frontend::accounts::SimpleMemberAccountSchema;
 use crate::http::handler_frontend::clubs::AssociateDomainRequest;
+use crate::http::handler_frontend::clubs::ChangePrimaryDomainError;
+use crate::http::handler_frontend::clubs::ChangePrimaryDomainRequest;
 use crate::http::handler_frontend::clubs::CreateClubError;
 use crate::http::handler_frontend::clubs::CreateClubRequest;
 use crate::http::handler_frontend::clubs::PageParams;
+use crate::http::handler_frontend::clubs::PrimaryDomainChangeReport;
 use crate::http::handler_frontend::clubs::UnassociateDomainRequest;
+use crate::http::handler_frontend::clubs::UpdateClubError;
+use crate::http::handler_frontend::clubs::UpdateClubRequest;
+use crate::http::handler_frontend::clubs::XAuthMigrationSchema;
 use crate::http::handler_frontend::clubs::schema;
 use crate::http::handler_frontend::domains::DomainSchema;
 use crate::http::handler_frontend::invites::GetInvite;
+use crate::models::account::Account;
 use crate::models::club::Club;
 use crate::models::club::ClubUuid;
 use crate::models::club::CreateClub;
+use crate::models::club::UpdateClub;
 use crate::models::domain::Domain;
 use crate::models::invite::Invite;
+use crate::models::member_address::MemberAddress;
 use crate::modules::mailcow::Mailcow;
 
 #[get("/")]
@@ -96,6 +105,86 @@ pub async fn create_club(
     Ok(ApiJson(FormResult::ok(club.uuid)))
 }
 
+#[put("/{uuid}")]
+#[instrument(name = "Api::admin::update_club")]
+pub async fn update_club(
+    Path(club_uuid): Path<ClubUuid>,
+    ApiJson(UpdateClubRequest { name, use_xauth }): ApiJson<UpdateClubRequest>,
+) -> ApiResult<ApiJson<FormResult<(), UpdateClubError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut club = Club::find_by_uuid(&mut tx, club_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Club not found"))?;
+
+    if Club::find_by_name(&mut tx, &name)
+        .await?
+        .is_some_and(|existing| existing.uuid != club.uuid)
+    {
+        return Ok(ApiJson(FormResult::err(UpdateClubError {
+            name_already_exists: true,
+            ..Default::default()
+        })));
+    }
+
+    let xauth_changed = club.use_xauth != use_xauth;
+    if xauth_changed
+        && Mailcow::global()
+            .get_xauth_migration(club.uuid)
+            .await
+            .is_some_and(|migration| migration.is_running())
+    {
+        return Ok(ApiJson(FormResult::err(UpdateClubError {
+            migration_running: true,
+            ..Default::default()
+        })));
+    }
+
+    club.update(&mut tx, UpdateClub { name, use_xauth }).await?;
+
+    tx.commit().await?;
+
+    if xauth_changed
+        && Mailcow::global()
+            .start_xauth_migration(club.uuid, use_xauth)
+            .await
+            .is_none()
+    {
+        warn!(club = ?club.uuid, "App password migration of club was started concurrently");
+    }
+
+    Ok(ApiJson(FormResult::ok(())))
+}
+
+#[get("/{uuid}/xauth-migration")]
+#[instrument(name = "Api::admin::get_xauth_migration")]
+pub async fn get_xauth_migration(
+    Path(club_uuid): Path<ClubUuid>,
+) -> ApiResult<ApiJson<Option<XAuthMigrationSchema>>> {
+    Ok(ApiJson(
+        Mailcow::global()
+            .get_xauth_migration(club_uuid)
+            .await
+            .map(XAuthMigrationSchema::from),
+    ))
+}
+
+/// Run the app password migration of a club again, e.g. after some mailboxes failed
+#[post("/{uuid}/xauth-migration")]
+#[instrument(name = "Api::admin::retry_xauth_migration")]
+pub async fn retry_xauth_migration(Path(club_uuid): Path<ClubUuid>) -> ApiResult<()> {
+    let club = Club::find_by_uuid(Database::global(), club_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Club not found"))?;
+
+    Mailcow::global()
+        .start_xauth_migration(club.uuid, club.use_xauth)
+        .await
+        .ok_or(ApiError::bad_request("Migration is already running"))?;
+
+    Ok(())
+}
+
 #[delete("/{uuid}")]
 #[instrument(name = "Api::admin::delete_club")]
 pub async fn delete_club(Path(SingleUuid { uuid }): Path<SingleUuid>) -> ApiResult<()> {
@@ -123,10 +212,15 @@ pub async fn delete_club(Path(SingleUuid { uuid }): Path<SingleUuid>) -> ApiResu
         let members = club
             .members_page(&mut tx, i64::MAX as u64, 0, None)
             .await?
-            .items
-            .into_iter()
-            .map(|x| x.email.into_inner())
-            .collect();
+            .items;
+
+        let mut addresses = vec![];
+        for member in &members {
+            addresses.extend(MemberAddress::find_all_by_account(&mut tx, member.uuid()).await?);
+        }
+        Mailcow::global().delete_member_aliases(&addresses).await?;
+
+        let members = members.into_iter().map(|x| x.email.into_inner()).collect();
 
         Mailcow::global()
             .sdk
@@ -195,7 +289,7 @@ pub async fn get_club_admins(
         offset,
         search,
     }): Query<PageParams>,
-) -> ApiResult<ApiJson<Page<SimpleAccountSchema>>> {
+) -> ApiResult<ApiJson<Page<ClubAdminSchema>>> {
     let mut tx = Database::global().start_transaction().await?;
 
     let club = Club::find_by_uuid(&mut tx, ClubUuid(uuid))
@@ -207,11 +301,7 @@ pub async fn get_club_admins(
     tx.commit().await?;
 
     Ok(ApiJson(Page {
-        items: page
-            .items
-            .into_iter()
-            .map(SimpleAccountSchema::from)
-            .collect(),
+        items: page.items.into_iter().map(ClubAdminSchema::from).collect(),
         limit: page.limit,
         offset: page.offset,
         total: page.total,
@@ -366,28 +456,17 @@ pub async fn associate_domain(
 
     club.associate_domain(&mut tx, &domain, false).await?;
 
+    // Admins of multiple clubs manage the domains of all of them
     let admins = club.admins_page(&mut tx, i64::MAX as u64, 0, None).await?;
+    for admin in admins.items {
+        let account = Account::get_by_uuid(&mut tx, admin.uuid())
+            .await?
+            .ok_or(ApiError::server_error("Club admin vanished"))?;
 
-    let domains = Domain::find_all_by_club(&mut tx, club_uuid)
-        .await?
-        .into_iter()
-        .map(|x| x.domain.into_inner())
-        .collect();
-
-    Mailcow::global()
-        .sdk
-        .edit_domain_admins(EditDomainAdminsRequest {
-            attr: EditDomainAdminsChanges { domains },
-            items: admins
-                .items
-                .into_iter()
-                .map(|x| x.username.into_inner())
-                .collect(),
-        })
-        .await
-        .map_err(ApiError::map_server_error(
-            "Couldn't edit domain admins in mailcow",
-        ))?;
+        Mailcow::global()
+            .update_domain_admin(&mut tx, &account)
+            .await?;
+    }
 
     tx.commit().await?;
 
@@ -412,30 +491,87 @@ pub async fn unassociate_domain(
 
     club.unassociate_domain(&mut tx, &domain).await?;
 
+    // Admins of multiple clubs manage the domains of all of them
     let admins = club.admins_page(&mut tx, i64::MAX as u64, 0, None).await?;
+    for admin in admins.items {
+        let account = Account::get_by_uuid(&mut tx, admin.uuid())
+            .await?
+            .ok_or(ApiError::server_error("Club admin vanished"))?;
 
-    let domains = Domain::find_all_by_club(&mut tx, club_uuid)
+        Mailcow::global()
+            .update_domain_admin(&mut tx, &account)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Change the primary domain of a club and move its members to it
+///
+/// Use `dry_run` to preview the changes first.
+#[post("/{uuid}/domains/primary")]
+#[instrument(name = "Api::admin::change_primary_domain")]
+pub async fn change_primary_domain(
+    Path(club_uuid): Path<ClubUuid>,
+    ApiJson(ChangePrimaryDomainRequest { domain, dry_run }): ApiJson<ChangePrimaryDomainRequest>,
+) -> ApiResult<ApiJson<FormResult<PrimaryDomainChangeReport, ChangePrimaryDomainError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let domain = Domain::find_by_uuid(&mut tx, domain)
         .await?
-        .into_iter()
-        .map(|x| x.domain.into_inner())
-        .collect();
+        .ok_or(ApiError::bad_request("Domain not found"))?;
+
+    let mut club = Club::find_by_uuid(&mut tx, club_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Club not found"))?;
+
+    if domain
+        .associated_club
+        .is_some_and(|associated| associated != club.uuid)
+    {
+        return Ok(ApiJson(FormResult::err(ChangePrimaryDomainError {
+            domain_already_associated: true,
+            ..Default::default()
+        })));
+    }
+    if domain.domain == club.primary_domain {
+        return Ok(ApiJson(FormResult::err(ChangePrimaryDomainError {
+            already_primary: true,
+            ..Default::default()
+        })));
+    }
+
+    let change = Mailcow::global()
+        .plan_primary_domain_change(&mut tx, &club, &domain)
+        .await?;
+
+    if dry_run {
+        tx.commit().await?;
+        return Ok(ApiJson(FormResult::ok(PrimaryDomainChangeReport::planned(
+            change,
+        ))));
+    }
+
+    if change.has_conflicts() {
+        return Ok(ApiJson(FormResult::err(ChangePrimaryDomainError {
+            address_conflicts: true,
+            ..Default::default()
+        })));
+    }
 
     Mailcow::global()
-        .sdk
-        .edit_domain_admins(EditDomainAdminsRequest {
-            attr: EditDomainAdminsChanges { domains },
-            items: admins
-                .items
-                .into_iter()
-                .map(|x| x.username.into_inner())
-                .collect(),
-        })
-        .await
-        .map_err(ApiError::map_server_error(
-            "Couldn't edit domain admins in mailcow",
-        ))?;
+        .apply_primary_domain_change(&mut tx, &mut club, &domain, &change)
+        .await?;
 
     tx.commit().await?;
 
-    Ok(())
+    let aliases = Mailcow::global()
+        .create_primary_domain_aliases(&change)
+        .await;
+
+    Ok(ApiJson(FormResult::ok(PrimaryDomainChangeReport::applied(
+        change, aliases,
+    ))))
 }
@@ -13,6 +13,7 @@ use galvyn::rorm::Database;
 use tracing::instrument;
 
 use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::accounts::SimpleAccountSchema;
 use crate::http::handler_frontend::accounts::SimpleMemberAccountSchema;
 use crate::http::handler_frontend::clubs::PageParams;
 use crate::http::handler_frontend::clubs::schema;
@@ -22,13 +23,16 @@ use crate::models::account::ClubAccount;
 use crate::models::club::Club;
 use crate::models::club::ClubUuid;
 use crate::models::invite::Invite;
+use crate::models::member_address::MemberAddress;
 use crate::modules::mailcow::Mailcow;
 
 #[get("/")]
 #[instrument(name = "Api::club_admin::get_club")]
 pub async fn get_club(
     Path(club_uuid): Path<ClubUuid>,
-    SessionUser { uuid: account_uuid }: SessionUser,
+    SessionUser {
+        uuid: account_uuid, ..
+    }: SessionUser,
 ) -> ApiResult<ApiJson<schema::ClubSchema>> {
     let mut tx = Database::global().start_transaction().await?;
 
@@ -91,6 +95,56 @@ pub async fn get_club_member_invites(
     Ok(ApiJson(invites))
 }
 
+#[get("/admins")]
+#[instrument(name = "Api::club_admin::get_club_admins")]
+pub async fn get_club_admins(
+    Path(club_uuid): Path<ClubUuid>,
+    Query(PageParams {
+        limit,
+        offset,
+        search,
+    }): Query<PageParams>,
+) -> ApiResult<ApiJson<Page<SimpleAccountSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let club = Club::find_by_uuid(&mut tx, club_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Club not found"))?;
+
+    let page = club.admins_page(&mut tx, limit, offset, search).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(Page {
+        items: page
+            .items
+            .into_iter()
+            .map(SimpleAccountSchema::from)
+            .collect(),
+        limit: page.limit,
+        offset: page.offset,
+        total: page.total,
+    }))
+}
+
+#[get("/admins/invites")]
+#[instrument(name = "Api::club_admin::get_club_admin_invites")]
+pub async fn get_club_admin_invites(
+    Path(club_uuid): Path<ClubUuid>,
+) -> ApiResult<ApiJson<Vec<GetInvite>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let invites = Invite::find_by_club(&mut tx, club_uuid)
+        .await?
+        .into_iter()
+        .filter_map(|x| x.email.is_none().then_some(GetInvite::from(x)))
+        .collect();
+
+    tx.commit().await?;
+
+    Ok(ApiJson(invites))
+}
+
 #[get("/dashboard-stats")]
 #[instrument(name = "Api::club_admin::get_dashboard_stats")]
 pub async fn get_dashboard_stats(
@@ -184,6 +238,9 @@ pub async fn delete_member(
         ));
     }
 
+    let addresses = MemberAddress::find_all_by_account(&mut tx, account_uuid).await?;
+    Mailcow::global().delete_member_aliases(&addresses).await?;
+
     Mailcow::global()
         .sdk
         .delete_mailbox(vec![account.email.clone().into_inner()])
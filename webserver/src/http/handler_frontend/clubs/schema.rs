@@ -5,9 +5,14 @@ use galvyn::rorm::fields::types::MaxStr;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::models::account::AccountUuid;
 use crate::models::club::Club;
 use crate::models::club::ClubUuid;
 use crate::models::domain::DomainUuid;
+use crate::modules::mailcow::primary_domain::AliasOutcome;
+use crate::modules::mailcow::primary_domain::PlannedAddressChange;
+use crate::modules::mailcow::primary_domain::PrimaryDomainChange;
+use crate::modules::mailcow::xauth_migration::XAuthMigrationProgress;
 
 /// A single club
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -26,6 +31,8 @@ pub struct ClubSchema {
     pub admin_count: u64,
     /// Primary domain of the club
     pub primary_domain: MaxStr<255>,
+    /// Whether to use X-Auth for authentication
+    pub use_xauth: bool,
 }
 
 /// Request to create a club
@@ -50,6 +57,45 @@ pub struct CreateClubError {
     pub domain_already_associated: bool,
 }
 
+/// Request to update a club
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateClubRequest {
+    /// Name of the club
+    pub name: MaxStr<255>,
+    /// Whether to use X-Auth for authentication
+    ///
+    /// Changing it migrates the app passwords of all members in the background.
+    pub use_xauth: bool,
+}
+
+/// Error when updating a club
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct UpdateClubError {
+    /// Whether the club name already exists
+    pub name_already_exists: bool,
+    /// The app passwords of the club are still being migrated
+    pub migration_running: bool,
+}
+
+/// Progress of the app password migration after the X-Auth setting changed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct XAuthMigrationSchema {
+    /// The X-Auth setting the club is migrated to
+    pub use_xauth: bool,
+    /// Number of members to migrate
+    pub total: u64,
+    /// Number of members which were already processed, including failed ones
+    pub processed: u64,
+    /// Mailboxes whose app passwords couldn't be migrated
+    pub failed: Vec<String>,
+    /// Error which aborted the migration
+    pub error: Option<String>,
+    /// The point in time the migration started
+    pub started_at: SchemaDateTime,
+    /// The point in time the migration finished
+    pub finished_at: Option<SchemaDateTime>,
+}
+
 /// Parameters for pagination
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PageParams {
@@ -75,6 +121,86 @@ pub struct UnassociateDomainRequest {
     pub domain: DomainUuid,
 }
 
+/// Request to change the primary domain of a club
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ChangePrimaryDomainRequest {
+    /// The new primary domain
+    ///
+    /// It must either be associated with the club already or not be associated at all.
+    pub domain: DomainUuid,
+    /// Only report the planned changes without applying them
+    pub dry_run: bool,
+}
+
+/// Error when changing the primary domain of a club
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct ChangePrimaryDomainError {
+    /// The domain is associated with another club
+    pub domain_already_associated: bool,
+    /// The domain is the primary domain already
+    pub already_primary: bool,
+    /// Some new addresses are already in use, see the dry run for details
+    pub address_conflicts: bool,
+}
+
+/// Report of a primary domain change
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PrimaryDomainChangeReport {
+    /// Whether nothing was changed yet
+    pub dry_run: bool,
+    /// The previous primary domain
+    pub old_domain: MaxStr<255>,
+    /// The new primary domain
+    pub new_domain: MaxStr<255>,
+    /// The members whose address changes
+    pub members: Vec<AddressChangeSchema>,
+    /// Usernames of the domain admins which are updated in mailcow
+    pub domain_admins: Vec<MaxStr<255>>,
+    /// Mailboxes on the old domain which are kept in mailcow
+    ///
+    /// Mailcow can't move mailboxes between domains,
+    /// their mails have to be moved to the new mailboxes manually.
+    /// The old addresses only forward to the new ones once these mailboxes are deleted.
+    pub retained_mailboxes: Vec<MaxStr<255>>,
+}
+
+/// The change of a member's address
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AddressChangeSchema {
+    /// The account of the member
+    pub account: AccountUuid,
+    /// The username of the member
+    pub username: MaxStr<255>,
+    /// The previous address, it becomes an alias of the new one unless its mailbox is retained
+    pub old_email: MaxStr<255>,
+    /// The new address
+    pub new_email: MaxStr<255>,
+    /// State of the change
+    pub status: AddressChangeStatus,
+}
+
+/// State of the change of a member's address
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum AddressChangeStatus {
+    /// The address will be changed
+    Planned,
+    /// The new address is already used by another mailbox, alias or member
+    Conflict,
+    /// The address will be or was changed, but the old address is still a mailbox
+    ///
+    /// Mailcow doesn't allow an alias on the address of a mailbox, so the old address
+    /// doesn't forward to the new one until the old mailbox is deleted.
+    MailboxRetained,
+    /// The address was changed and the old one forwards to it
+    Changed,
+    /// The address was changed, but the alias for the old one couldn't be created
+    AliasFailed {
+        /// Error reported by mailcow
+        error: String,
+    },
+}
+
 /// Combined dashboard statistics
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DashboardStatsSchema {
@@ -124,6 +250,93 @@ impl From<Club> for ClubSchema {
             member_count: value.member_count,
             admin_count: value.admin_count,
             primary_domain: value.primary_domain,
+            use_xauth: value.use_xauth,
+        }
+    }
+}
+
+impl From<XAuthMigrationProgress> for XAuthMigrationSchema {
+    fn from(value: XAuthMigrationProgress) -> Self {
+        Self {
+            use_xauth: value.use_xauth,
+            total: value.total,
+            processed: value.processed,
+            failed: value.failed,
+            error: value.error,
+            started_at: SchemaDateTime(value.started_at),
+            finished_at: value.finished_at.map(SchemaDateTime),
+        }
+    }
+}
+
+impl PrimaryDomainChangeReport {
+    /// Report of the planned changes
+    pub fn planned(change: PrimaryDomainChange) -> Self {
+        Self {
+            dry_run: true,
+            retained_mailboxes: change
+                .members
+                .iter()
+                .filter(|member| member.old_mailbox_retained)
+                .map(|member| member.old_email.clone())
+                .collect(),
+            members: change
+                .members
+                .into_iter()
+                .map(|member| {
+                    let status = if member.conflict {
+                        AddressChangeStatus::Conflict
+                    } else if member.old_mailbox_retained {
+                        AddressChangeStatus::MailboxRetained
+                    } else {
+                        AddressChangeStatus::Planned
+                    };
+                    AddressChangeSchema::new(member, status)
+                })
+                .collect(),
+            old_domain: change.old_domain,
+            new_domain: change.new_domain,
+            domain_admins: change.domain_admins,
+        }
+    }
+
+    /// Report of the applied changes
+    pub fn applied(change: PrimaryDomainChange, aliases: Vec<AliasOutcome>) -> Self {
+        Self {
+            dry_run: false,
+            retained_mailboxes: aliases
+                .iter()
+                .filter(|outcome| outcome.change.old_mailbox_retained)
+                .map(|outcome| outcome.change.old_email.clone())
+                .collect(),
+            members: aliases
+                .into_iter()
+                .map(|outcome| {
+                    let status = match outcome.error {
+                        None if outcome.change.old_mailbox_retained => {
+                            AddressChangeStatus::MailboxRetained
+                        }
+                        None => AddressChangeStatus::Changed,
+                        Some(error) => AddressChangeStatus::AliasFailed { error },
+                    };
+                    AddressChangeSchema::new(outcome.change, status)
+                })
+                .collect(),
+            old_domain: change.old_domain,
+            new_domain: change.new_domain,
+            domain_admins: change.domain_admins,
+        }
+    }
+}
+
+impl AddressChangeSchema {
+    fn new(change: PlannedAddressChange, status: AddressChangeStatus) -> Self {
+        Self {
+            account: change.account,
+            username: change.username,
+            old_email: change.old_email,
+            new_email: change.new_email,
+            status,
         }
     }
 }
@@ -10,54 +10,133 @@ use galvyn::core::stuff::schema::SingleUuid;
 use galvyn::get;
 use galvyn::post;
 use galvyn::rorm::Database;
+use galvyn::rorm::db::transaction::Transaction;
 use galvyn::rorm::fields::types::MaxStr;
+use tracing::info;
 use tracing::instrument;
-use zxcvbn::Score;
-use zxcvbn::zxcvbn;
 
+use crate::http::extractors::auth_attempt::AuthAttempt;
+use crate::http::handler_frontend::credential_reset::ForgotPasswordRequest;
 use crate::http::handler_frontend::credential_reset::ResetPasswordError;
 use crate::http::handler_frontend::credential_reset::ResetPasswordRequest;
 use crate::http::handler_frontend::credential_reset::VerifyResetCodeResponse;
 use crate::models::account::Account;
+use crate::models::api_token::ApiToken;
 use crate::models::club::Club;
 use crate::models::credential_reset::CredentialReset;
 use crate::models::credential_reset::CredentialResetUuid;
+use crate::models::recovery_email::RecoveryEmail;
+use crate::models::session::StoredSession;
 use crate::modules::mailcow::Mailcow;
+use crate::modules::mailer::MailTemplate;
+use crate::modules::mailer::Mailer;
+use crate::modules::password_policy::PasswordPolicy;
 
-fn display_name_of(account: &Account) -> MaxStr<255> {
-    match account {
-        Account::ClubMember(a) => a.display_name.clone(),
-        Account::ClubAdmin(a) => a.display_name.clone(),
-        Account::Superadmin(a) => a.display_name.clone(),
+/// Check the new password against the password policy
+async fn check_password(
+    account: &Account,
+    password: &MaxStr<72>,
+) -> ApiResult<Result<(), ApiJson<FormResult<(), ResetPasswordError>>>> {
+    if let Err(violation) = PasswordPolicy::global()
+        .check(
+            password,
+            &[&**account.username(), &**account.display_name()],
+        )
+        .await?
+    {
+        return Ok(Err(ApiJson(FormResult::err(ResetPasswordError {
+            password_policy: Some(violation),
+            ..Default::default()
+        }))));
     }
+
+    Ok(Ok(()))
 }
 
-fn do_reset(
-    account: &mut Account,
+/// Set the new password of a credential reset
+///
+/// The reset is consumed, and the account's sessions and api tokens are revoked.
+async fn complete_reset(
+    mut tx: Transaction,
+    reset: CredentialReset,
+    mut account: Account,
     password: &MaxStr<72>,
-) -> Result<(), ApiJson<FormResult<(), ResetPasswordError>>> {
-    let [display_name, username] = match account {
-        Account::ClubMember(a) => [
-            a.display_name.clone().into_inner(),
-            a.username.clone().into_inner(),
-        ],
-        Account::ClubAdmin(a) => [
-            a.display_name.clone().into_inner(),
-            a.username.clone().into_inner(),
-        ],
-        Account::Superadmin(a) => [
-            a.display_name.clone().into_inner(),
-            a.username.clone().into_inner(),
-        ],
-    };
+) -> ApiResult<ApiJson<FormResult<(), ResetPasswordError>>> {
+    if password.is_empty() {
+        return Err(ApiError::bad_request("Empty password"));
+    }
 
-    let entropy = zxcvbn(password, &[&display_name, &username]);
-    if entropy.score() < Score::Three {
-        return Err(ApiJson(FormResult::err(ResetPasswordError {
-            low_entropy: true,
-            ..Default::default()
-        })));
+    if let Err(err) = check_password(&account, password).await? {
+        return Ok(err);
+    }
+
+    account.set_password(&mut tx, password).await?;
+    CredentialReset::delete_by_uuid(&mut tx, reset.uuid).await?;
+    StoredSession::revoke_all_by_account(&mut tx, account.uuid()).await?;
+    ApiToken::revoke_all_by_account(&mut tx, account.uuid()).await?;
+    Mailer::global()
+        .notify_password_changed(&mut tx, &account)
+        .await?;
+
+    let mut app_password_mailbox = None;
+    if let Some(member) = &account.membership {
+        let club = Club::find_by_uuid(&mut tx, member.club)
+            .await?
+            .ok_or(ApiError::server_error("Club should exist"))?;
+
+        if !club.use_xauth {
+            app_password_mailbox = Some(member.email.clone());
+        }
+    }
+
+    tx.commit().await?;
+    Mailer::global().deliver_enqueued();
+
+    if let Some(member_mailbox) = app_password_mailbox {
+        Mailcow::global().create_app_password(member_mailbox);
     }
+    if !account.admin_of.is_empty() {
+        Mailcow::global().update_domain_admin_password(account.uuid());
+    }
+
+    Ok(ApiJson(FormResult::ok(())))
+}
+
+#[post("/forgot-password")]
+#[instrument(name = "Api::common::forgot_password")]
+pub async fn forgot_password(
+    ApiJson(ForgotPasswordRequest { username }): ApiJson<ForgotPasswordRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    // The response is the same whether the account exists or not,
+    // the link is only sent if it has a verified recovery email
+    let Some(account) = Account::get_by_username(&mut tx, &username).await? else {
+        return Ok(());
+    };
+    let Some(recovery_email) =
+        RecoveryEmail::find_verified_by_account(&mut tx, account.uuid()).await?
+    else {
+        return Ok(());
+    };
+
+    let reset = account.create_credential_reset(&mut tx).await?;
+
+    Mailer::global()
+        .enqueue(
+            &mut tx,
+            &recovery_email.email,
+            MailTemplate::CredentialReset {
+                display_name: account.display_name().to_string(),
+                link: reset.link(),
+            },
+        )
+        .await?;
+
+    tx.commit().await?;
+    Mailer::global().deliver_enqueued();
+
+    info!(account = ?account.uuid(), "Sent credential reset link to recovery email");
 
     Ok(())
 }
@@ -68,17 +147,19 @@ fn do_reset(
 #[instrument(name = "Api::common::verify_reset_code")]
 pub async fn verify_code(
     Path(code): Path<MaxStr<6>>,
+    attempt: AuthAttempt,
 ) -> ApiResult<ApiJson<VerifyResetCodeResponse>> {
     let mut tx = Database::global().start_transaction().await?;
 
-    let (_reset, account) = CredentialReset::find_by_code(&mut tx, &code)
-        .await?
-        .ok_or(ApiError::bad_request("Invalid or expired code"))?;
+    let Some((_reset, account)) = CredentialReset::find_by_code(&mut tx, &code).await? else {
+        attempt.failed_unknown();
+        return Err(ApiError::bad_request("Invalid or expired code"));
+    };
 
     tx.commit().await?;
 
     Ok(ApiJson(VerifyResetCodeResponse {
-        display_name: display_name_of(&account),
+        display_name: account.display_name.clone(),
     }))
 }
 
@@ -86,46 +167,20 @@ pub async fn verify_code(
 #[instrument(name = "Api::common::reset_password_by_code", skip(password))]
 pub async fn reset_password(
     Path(code): Path<MaxStr<6>>,
+    attempt: AuthAttempt,
     ApiJson(ResetPasswordRequest { password }): ApiJson<ResetPasswordRequest>,
 ) -> ApiResult<ApiJson<FormResult<(), ResetPasswordError>>> {
     let mut tx = Database::global().start_transaction().await?;
 
-    let Some((reset, mut account)) = CredentialReset::find_by_code(&mut tx, &code).await? else {
+    let Some((reset, account)) = CredentialReset::find_by_code(&mut tx, &code).await? else {
+        attempt.failed_unknown();
         return Ok(ApiJson(FormResult::err(ResetPasswordError {
             invalid_code: true,
             ..Default::default()
         })));
     };
 
-    if password.is_empty() {
-        return Err(ApiError::bad_request("Empty password"));
-    }
-
-    if let Err(err) = do_reset(&mut account, &password) {
-        return Ok(err);
-    }
-
-    account.set_password(&mut tx, &password).await?;
-    CredentialReset::delete_by_uuid(&mut tx, reset.uuid).await?;
-
-    let mut app_password_mailbox = None;
-    if let Account::ClubMember(ref member) = account {
-        let club = Club::find_by_uuid(&mut tx, member.club)
-            .await?
-            .ok_or(ApiError::server_error("Club should exist"))?;
-
-        if !club.use_xauth {
-            app_password_mailbox = Some(member.email.clone());
-        }
-    }
-
-    tx.commit().await?;
-
-    if let Some(member_mailbox) = app_password_mailbox {
-        Mailcow::global().create_app_password(member_mailbox);
-    }
-
-    Ok(ApiJson(FormResult::ok(())))
+    complete_reset(tx, reset, account, &password).await
 }
 
 // --- UUID-based endpoints ---
@@ -134,17 +189,21 @@ pub async fn reset_password(
 #[instrument(name = "Api::common::verify_reset_uuid")]
 pub async fn verify_uuid(
     Path(SingleUuid { uuid }): Path<SingleUuid>,
+    attempt: AuthAttempt,
 ) -> ApiResult<ApiJson<VerifyResetCodeResponse>> {
     let mut tx = Database::global().start_transaction().await?;
 
-    let (_reset, account) = CredentialReset::find_by_uuid(&mut tx, CredentialResetUuid(uuid))
-        .await?
-        .ok_or(ApiError::bad_request("Invalid or expired reset link"))?;
+    let Some((_reset, account)) =
+        CredentialReset::find_by_uuid(&mut tx, CredentialResetUuid(uuid)).await?
+    else {
+        attempt.failed_unknown();
+        return Err(ApiError::bad_request("Invalid or expired reset link"));
+    };
 
     tx.commit().await?;
 
     Ok(ApiJson(VerifyResetCodeResponse {
-        display_name: display_name_of(&account),
+        display_name: account.display_name.clone(),
     }))
 }
 
@@ -152,46 +211,20 @@ pub async fn verify_uuid(
 #[instrument(name = "Api::common::reset_password_by_uuid", skip(password))]
 pub async fn reset_password_by_uuid(
     Path(SingleUuid { uuid }): Path<SingleUuid>,
+    attempt: AuthAttempt,
     ApiJson(ResetPasswordRequest { password }): ApiJson<ResetPasswordRequest>,
 ) -> ApiResult<ApiJson<FormResult<(), ResetPasswordError>>> {
     let mut tx = Database::global().start_transaction().await?;
 
-    let Some((reset, mut account)) =
+    let Some((reset, account)) =
         CredentialReset::find_by_uuid(&mut tx, CredentialResetUuid(uuid)).await?
     else {
+        attempt.failed_unknown();
         return Ok(ApiJson(FormResult::err(ResetPasswordError {
             invalid_code: true,
             ..Default::default()
         })));
     };
 
-    if password.is_empty() {
-        return Err(ApiError::bad_request("Empty password"));
-    }
-
-    if let Err(err) = do_reset(&mut account, &password) {
-        return Ok(err);
-    }
-
-    account.set_password(&mut tx, &password).await?;
-    CredentialReset::delete_by_uuid(&mut tx, reset.uuid).await?;
-
-    let mut app_password_mailbox = None;
-    if let Account::ClubMember(ref member) = account {
-        let club = Club::find_by_uuid(&mut tx, member.club)
-            .await?
-            .ok_or(ApiError::server_error("Club should exist"))?;
-
-        if !club.use_xauth {
-            app_password_mailbox = Some(member.email.clone());
-        }
-    }
-
-    tx.commit().await?;
-
-    if let Some(member_mailbox) = app_password_mailbox {
-        Mailcow::global().create_app_password(member_mailbox);
-    }
-
-    Ok(ApiJson(FormResult::ok(())))
+    complete_reset(tx, reset, account, &password).await
 }
@@ -4,6 +4,8 @@ use galvyn::rorm::fields::types::MaxStr;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::modules::password_policy::PasswordPolicyViolation;
+
 /// Response for verifying a reset code
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct VerifyResetCodeResponse {
@@ -25,6 +27,13 @@ pub struct ResetPasswordError {
     pub invalid_code: bool,
     /// The code has expired
     pub expired: bool,
-    /// The password entropy is too low
-    pub low_entropy: bool,
+    /// The new password violates the password policy
+    pub password_policy: Option<PasswordPolicyViolation>,
+}
+
+/// Request a credential reset link for a forgotten password
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ForgotPasswordRequest {
+    /// The username of the account
+    pub username: MaxStr<255>,
 }
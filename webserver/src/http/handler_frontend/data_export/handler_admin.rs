@@ -0,0 +1,34 @@
+//! Admin handlers for data exports
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::instrument;
+
+use crate::http::handler_frontend::data_export::AccountExportSchema;
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::utils::data_export::export_account;
+
+/// Export all data stored about an account
+#[get("/{uuid}/export")]
+#[instrument(name = "Api::admin::export_account")]
+pub async fn export_account_data(
+    Path(account_uuid): Path<AccountUuid>,
+) -> ApiResult<ApiJson<AccountExportSchema>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account doesn't exist"))?;
+
+    let export = export_account(&mut tx, &account).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(export))
+}
@@ -0,0 +1,43 @@
+//! Club admin handlers for data exports
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::instrument;
+
+use crate::http::handler_frontend::data_export::AccountExportSchema;
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::models::club::ClubUuid;
+use crate::utils::data_export::export_account;
+
+/// Export all data stored about a member of the club
+#[get("/{uuid}/export")]
+#[instrument(name = "Api::club_admin::export_member")]
+pub async fn export_member(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+) -> ApiResult<ApiJson<AccountExportSchema>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .filter(|account| {
+            account
+                .membership
+                .as_ref()
+                .is_some_and(|membership| membership.club == club_uuid)
+        })
+        .ok_or(ApiError::bad_request(
+            "Target account isn't part of the club of the executing admin",
+        ))?;
+
+    let export = export_account(&mut tx, &account).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(export))
+}
@@ -0,0 +1,33 @@
+//! Common handlers for data exports
+
+use galvyn::core::Module;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::data_export::AccountExportSchema;
+use crate::models::account::Account;
+use crate::utils::data_export::export_account;
+
+/// Export all data stored about the logged-in user
+#[get("/export")]
+#[instrument(name = "Api::common::export_me")]
+pub async fn export_me(session_user: SessionUser) -> ApiResult<ApiJson<AccountExportSchema>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, session_user.uuid)
+        .await?
+        .ok_or(ApiError::server_error(
+            "Account not found, while session user was found",
+        ))?;
+
+    let export = export_account(&mut tx, &account).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(export))
+}
@@ -0,0 +1,8 @@
+//! Handlers for exporting the data stored about an account
+
+pub use schema::*;
+
+pub mod handler_admin;
+pub mod handler_club_admin;
+pub mod handler_common;
+mod schema;
@@ -0,0 +1,192 @@
+//! Schema for data exports
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+use url::Url;
+
+use crate::models::account::AccountUuid;
+use crate::models::account::AuthenticationMethod;
+use crate::models::club::ClubUuid;
+use crate::models::oidc_provider::OidcClientUuid;
+
+/// Machine-readable export of all data stored about an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AccountExportSchema {
+    /// The point in time the export was created
+    pub exported_at: SchemaDateTime,
+    /// The account itself
+    pub account: ExportedAccountSchema,
+    /// The membership in a club
+    pub membership: Option<ExportedMembershipSchema>,
+    /// The clubs the account is an admin of
+    pub admin_of: Vec<ExportedClubSchema>,
+    /// The pending invite that reserved the username
+    pub invite: Option<ExportedInviteSchema>,
+    /// Credential resets which are still stored
+    pub credential_resets: Vec<ExportedCredentialResetSchema>,
+    /// The recovery email
+    pub recovery_email: Option<ExportedRecoveryEmailSchema>,
+    /// Registered passkeys
+    pub passkeys: Vec<ExportedPasskeySchema>,
+    /// Active sessions
+    pub sessions: Vec<ExportedSessionSchema>,
+    /// Authorization codes issued to OIDC clients
+    pub oidc_tokens: Vec<ExportedOidcTokenSchema>,
+    /// Device authorizations granted to OIDC clients
+    pub oidc_grants: Vec<ExportedOidcGrantSchema>,
+    /// Metadata of the mailbox in mailcow
+    pub mailbox: Option<ExportedMailboxSchema>,
+}
+
+/// Fields of the exported account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedAccountSchema {
+    /// The account's UUID
+    pub uuid: AccountUuid,
+    /// The account's username
+    pub username: MaxStr<255>,
+    /// The account's display name
+    pub display_name: MaxStr<255>,
+    /// Whether the account has superadmin privileges
+    pub superadmin: bool,
+    /// Whether the account is locked and can't sign in
+    pub locked: bool,
+    /// Whether TOTP is enabled
+    pub totp_enabled: bool,
+    /// The last point in time the account was modified
+    pub modified_at: SchemaDateTime,
+    /// The point in time the account was created
+    pub created_at: SchemaDateTime,
+}
+
+/// The exported membership in a club
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedMembershipSchema {
+    /// The club
+    pub club: ExportedClubSchema,
+    /// Mailbox of the member
+    pub email: MaxStr<255>,
+    /// The point in time the membership ends
+    pub valid_until: Option<SchemaDateTime>,
+}
+
+/// A club referenced by the export
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedClubSchema {
+    /// The club's UUID
+    pub uuid: ClubUuid,
+    /// The club's name
+    pub name: MaxStr<255>,
+}
+
+/// An exported invite
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedInviteSchema {
+    /// Display name the invite was issued for
+    pub display_name: MaxStr<255>,
+    /// Club the invite is linked to
+    pub club: Option<ClubUuid>,
+    /// Primary mail of the invited member
+    pub email: Option<MaxStr<255>>,
+    /// The point in time the invite expires
+    pub expires_at: SchemaDateTime,
+    /// The point in time the invite was created
+    pub created_at: SchemaDateTime,
+}
+
+/// An exported credential reset
+///
+/// The secrets of the reset are not part of the export.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedCredentialResetSchema {
+    /// The point in time the code expires
+    pub code_expires_at: SchemaDateTime,
+    /// The point in time the link expires
+    pub link_expires_at: SchemaDateTime,
+}
+
+/// An exported recovery email
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedRecoveryEmailSchema {
+    /// The address
+    pub email: MaxStr<255>,
+    /// The point in time the address was verified
+    pub verified_at: Option<SchemaDateTime>,
+    /// The point in time the address was set
+    pub created_at: SchemaDateTime,
+}
+
+/// An exported passkey
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedPasskeySchema {
+    /// Name of the passkey
+    pub name: MaxStr<255>,
+    /// The last point in time the passkey was used
+    pub last_used_at: Option<SchemaDateTime>,
+    /// The point in time the passkey was registered
+    pub created_at: SchemaDateTime,
+}
+
+/// An exported session
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedSessionSchema {
+    /// The user agent of the device using the session
+    pub user_agent: Option<MaxStr<255>>,
+    /// The ip address the session was last used from
+    pub ip: Option<MaxStr<64>>,
+    /// The last point in time the session was used
+    pub last_seen_at: SchemaDateTime,
+    /// The point in time the session expires
+    pub expires_at: SchemaDateTime,
+    /// The point in time the session was created
+    pub created_at: SchemaDateTime,
+}
+
+/// An exported authorization code of an OIDC client
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedOidcTokenSchema {
+    /// The client the code was issued to
+    pub client_id: OidcClientUuid,
+    /// The redirect url of the request
+    pub redirect_url: Url,
+    /// Scopes the client requested
+    pub scopes: Vec<String>,
+    /// The methods the user authenticated with
+    pub auth_methods: Vec<AuthenticationMethod>,
+    /// The point in time the code expires
+    pub expires_at: SchemaDateTime,
+}
+
+/// An exported device authorization of an OIDC client
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedOidcGrantSchema {
+    /// The client the authorization was granted to
+    pub client_id: OidcClientUuid,
+    /// Scopes the client requested
+    pub scopes: Vec<String>,
+    /// The last point in time the device polled for the authorization
+    pub last_polled_at: Option<SchemaDateTime>,
+    /// The point in time the authorization expires
+    pub expires_at: SchemaDateTime,
+}
+
+/// Exported metadata of a mailbox
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ExportedMailboxSchema {
+    /// Address of the mailbox
+    pub address: String,
+    /// Quota limit in bytes
+    pub quota: u64,
+    /// Used quota in bytes
+    pub quota_used: u64,
+    /// Number of messages
+    pub messages: u64,
+    /// Aliases forwarding to the mailbox
+    pub aliases: Vec<String>,
+    /// Names of the app passwords
+    pub app_passwords: Vec<String>,
+}
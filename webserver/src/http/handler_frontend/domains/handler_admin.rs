@@ -1,14 +1,39 @@
 //! Administrative endpoints for domains
 
 use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
 use galvyn::core::stuff::api_error::ApiResult;
 use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::FormResult;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
 use galvyn::get;
+use galvyn::post;
+use galvyn::put;
 use galvyn::rorm::Database;
+use time::Duration;
 use tracing::instrument;
 
+use crate::config::DOMAIN_SYNC_GRACE_PERIOD_DAYS;
+use crate::http::handler_frontend::domains::CreateDomainError;
+use crate::http::handler_frontend::domains::CreateDomainRequest;
+use crate::http::handler_frontend::domains::CreatedDomainSchema;
+use crate::http::handler_frontend::domains::DeleteDomainError;
+use crate::http::handler_frontend::domains::DomainLimitsSchema;
 use crate::http::handler_frontend::domains::DomainSchema;
+use crate::http::handler_frontend::domains::DomainSyncReportSchema;
+use crate::http::handler_frontend::domains::DomainSyncSchema;
+use crate::http::handler_frontend::domains::MissingDomainSchema;
+use crate::http::handler_frontend::domains::RunDomainSyncRequest;
+use crate::http::handler_frontend::domains::UpdateDomainLimitsError;
+use crate::models::account::Account;
+use crate::models::club::Club;
 use crate::models::domain::Domain;
+use crate::models::domain::DomainUuid;
+use crate::modules::mailcow::Mailcow;
+use crate::modules::mailcow::domain_provisioning::DomainLimits;
 
 /// Retrieve all domains that aren't associated with a club
 #[get("/unassociated")]
@@ -26,3 +51,206 @@ pub async fn get_unassociated_domains() -> ApiResult<ApiJson<Vec<DomainSchema>>>
 
     Ok(ApiJson(domains))
 }
+
+/// Retrieve all domains missing from mailcow, which are deleted after their grace period
+#[get("/missing")]
+#[instrument(name = "Api::admin::get_missing_domains")]
+pub async fn get_missing_domains() -> ApiResult<ApiJson<Vec<MissingDomainSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let domains = Domain::find_all_missing(&mut tx).await?;
+
+    tx.commit().await?;
+
+    let grace_period = Duration::days(*DOMAIN_SYNC_GRACE_PERIOD_DAYS.get() as i64);
+
+    Ok(ApiJson(
+        domains
+            .into_iter()
+            .filter_map(|domain| {
+                let missing_since = domain.missing_since?;
+                Some(MissingDomainSchema {
+                    uuid: domain.uuid,
+                    domain: domain.domain,
+                    associated_club: domain.associated_club,
+                    is_primary: domain.is_primary,
+                    missing_since: SchemaDateTime(missing_since),
+                    deleted_after: SchemaDateTime(missing_since + grace_period),
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// Retrieve the outcome of the latest domain sync
+#[get("/sync")]
+#[instrument(name = "Api::admin::get_domain_sync")]
+pub async fn get_domain_sync() -> ApiResult<ApiJson<Option<DomainSyncSchema>>> {
+    Ok(ApiJson(
+        Mailcow::global()
+            .get_last_domain_sync()
+            .await
+            .map(DomainSyncSchema::from),
+    ))
+}
+
+/// Sync the domains from mailcow right away
+///
+/// Use `force` after checking that the domains missing from mailcow were removed on purpose.
+#[post("/sync")]
+#[instrument(name = "Api::admin::run_domain_sync")]
+pub async fn run_domain_sync(
+    ApiJson(RunDomainSyncRequest { force }): ApiJson<RunDomainSyncRequest>,
+) -> ApiResult<ApiJson<DomainSyncReportSchema>> {
+    let report = Mailcow::global().run_domain_sync(force).await?;
+
+    Ok(ApiJson(DomainSyncReportSchema::from(report)))
+}
+
+/// Create a domain in mailcow and generate its DKIM key
+///
+/// The domain is associated with the club right away, if one is given.
+#[post("/")]
+#[instrument(name = "Api::admin::create_domain")]
+pub async fn create_domain(
+    ApiJson(CreateDomainRequest {
+        domain,
+        limits,
+        club,
+    }): ApiJson<CreateDomainRequest>,
+) -> ApiResult<ApiJson<FormResult<CreatedDomainSchema, CreateDomainError>>> {
+    let limits = DomainLimits::from(limits);
+
+    let errors = CreateDomainError {
+        invalid_domain: !Domain::is_valid_name(&domain),
+        invalid_quota: !limits.has_valid_quotas(),
+        inconsistent_quotas: !limits.has_consistent_quotas(),
+        ..Default::default()
+    };
+    if errors.invalid_domain || errors.invalid_quota || errors.inconsistent_quotas {
+        return Ok(ApiJson(FormResult::err(errors)));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut club = match club {
+        Some(club) => Some(
+            Club::find_by_uuid(&mut tx, club)
+                .await?
+                .ok_or(ApiError::bad_request("Club not found"))?,
+        ),
+        None => None,
+    };
+
+    let exists_in_mailcow = Mailcow::global()
+        .sdk
+        .get_all_domains()
+        .await
+        .map_err(ApiError::map_server_error("Could not retrieve domains"))?
+        .iter()
+        .any(|existing| existing.domain_name.eq_ignore_ascii_case(&domain));
+    if exists_in_mailcow || Domain::find_by_domain(&mut tx, &domain).await?.is_some() {
+        return Ok(ApiJson(FormResult::err(CreateDomainError {
+            domain_already_exists: true,
+            ..Default::default()
+        })));
+    }
+
+    let provisioned = Mailcow::global()
+        .provision_domain(&mut tx, domain, limits)
+        .await?;
+
+    if let Some(club) = &mut club {
+        club.associate_domain(&mut tx, &provisioned.domain, false)
+            .await?;
+
+        // Admins of multiple clubs manage the domains of all of them
+        let admins = club.admins_page(&mut tx, i64::MAX as u64, 0, None).await?;
+        for admin in admins.items {
+            let account = Account::get_by_uuid(&mut tx, admin.uuid())
+                .await?
+                .ok_or(ApiError::server_error("Club admin vanished"))?;
+
+            Mailcow::global()
+                .update_domain_admin(&mut tx, &account)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(ApiJson(FormResult::ok(CreatedDomainSchema {
+        uuid: provisioned.domain.uuid,
+        dkim_generated: provisioned.dkim_generated,
+    })))
+}
+
+/// Change the limits of a domain in mailcow
+#[put("/{uuid}/limits")]
+#[instrument(name = "Api::admin::update_domain_limits")]
+pub async fn update_domain_limits(
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+    ApiJson(limits): ApiJson<DomainLimitsSchema>,
+) -> ApiResult<ApiJson<FormResult<(), UpdateDomainLimitsError>>> {
+    let limits = DomainLimits::from(limits);
+
+    let errors = UpdateDomainLimitsError {
+        invalid_quota: !limits.has_valid_quotas(),
+        inconsistent_quotas: !limits.has_consistent_quotas(),
+    };
+    if errors.invalid_quota || errors.inconsistent_quotas {
+        return Ok(ApiJson(FormResult::err(errors)));
+    }
+
+    let domain = Domain::find_by_uuid(Database::global(), DomainUuid(uuid))
+        .await?
+        .ok_or(ApiError::bad_request("Domain not found"))?;
+
+    Mailcow::global()
+        .update_domain_limits(&domain.domain, limits)
+        .await?;
+
+    Ok(ApiJson(FormResult::ok(())))
+}
+
+/// Delete a domain in mailcow
+///
+/// The domain must neither be associated with a club nor have mailboxes left.
+#[delete("/{uuid}")]
+#[instrument(name = "Api::admin::delete_domain")]
+pub async fn delete_domain(
+    Path(SingleUuid { uuid }): Path<SingleUuid>,
+) -> ApiResult<ApiJson<FormResult<(), DeleteDomainError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let domain = Domain::find_by_uuid(&mut tx, DomainUuid(uuid))
+        .await?
+        .ok_or(ApiError::bad_request("Domain not found"))?;
+
+    if domain.associated_club.is_some() {
+        return Ok(ApiJson(FormResult::err(DeleteDomainError {
+            domain_associated: true,
+            ..Default::default()
+        })));
+    }
+
+    let mailboxes = Mailcow::global()
+        .sdk
+        .get_all_mailboxes(&domain.domain)
+        .await
+        .map_err(ApiError::map_server_error("Could not retrieve mailboxes"))?;
+    if !mailboxes.is_empty() {
+        return Ok(ApiJson(FormResult::err(DeleteDomainError {
+            mailboxes_remaining: true,
+            ..Default::default()
+        })));
+    }
+
+    Mailcow::global()
+        .deprovision_domain(&mut tx, domain)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(FormResult::ok(())))
+}
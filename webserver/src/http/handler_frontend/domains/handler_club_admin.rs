@@ -0,0 +1,160 @@
+//! Club admin endpoints for the domains of the club
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::FormResult;
+use galvyn::get;
+use galvyn::put;
+use galvyn::rorm::Database;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::handler_frontend::domains::ClubDomainSchema;
+use crate::http::handler_frontend::domains::DnsRecordSchema;
+use crate::http::handler_frontend::domains::DomainDnsSchema;
+use crate::http::handler_frontend::domains::DomainUsageSchema;
+use crate::http::handler_frontend::domains::UpdateDomainSettingsError;
+use crate::http::handler_frontend::domains::UpdateDomainSettingsRequest;
+use crate::models::club::ClubUuid;
+use crate::models::domain::Domain;
+use crate::models::domain::DomainUuid;
+use crate::modules::mailcow::MIB;
+use crate::modules::mailcow::Mailcow;
+use crate::modules::mailcow::dns_records::DnsRecordPurpose;
+
+/// Retrieve all domains of the club
+#[get("/")]
+#[instrument(name = "Api::club_admin::get_club_domains")]
+pub async fn get_club_domains(
+    Path(club_uuid): Path<ClubUuid>,
+) -> ApiResult<ApiJson<Vec<ClubDomainSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let domains = Domain::find_all_by_club(&mut tx, club_uuid).await?;
+
+    tx.commit().await?;
+
+    let mut schemas = vec![];
+    for domain in domains {
+        schemas.push(club_domain_schema(domain).await);
+    }
+
+    Ok(ApiJson(schemas))
+}
+
+/// Retrieve a single domain of the club
+#[get("/{uuid}")]
+#[instrument(name = "Api::club_admin::get_club_domain")]
+pub async fn get_club_domain(
+    Path((club_uuid, domain_uuid)): Path<(ClubUuid, DomainUuid)>,
+) -> ApiResult<ApiJson<ClubDomainSchema>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let domain = Domain::find_by_uuid(&mut tx, domain_uuid)
+        .await?
+        .filter(|domain| domain.associated_club == Some(club_uuid))
+        .ok_or(ApiError::bad_request("Domain not found"))?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(club_domain_schema(domain).await))
+}
+
+/// Retrieve the DNS records a domain of the club is expected to publish
+#[get("/{uuid}/dns")]
+#[instrument(name = "Api::club_admin::get_domain_dns_records")]
+pub async fn get_domain_dns_records(
+    Path((club_uuid, domain_uuid)): Path<(ClubUuid, DomainUuid)>,
+) -> ApiResult<ApiJson<DomainDnsSchema>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let domain = Domain::find_by_uuid(&mut tx, domain_uuid)
+        .await?
+        .filter(|domain| domain.associated_club == Some(club_uuid))
+        .ok_or(ApiError::bad_request("Domain not found"))?;
+
+    tx.commit().await?;
+
+    let records = Mailcow::global()
+        .expected_dns_records(&domain.domain)
+        .await?;
+
+    Ok(ApiJson(DomainDnsSchema {
+        dkim_missing: !records
+            .iter()
+            .any(|record| record.purpose == DnsRecordPurpose::Dkim),
+        records: records.into_iter().map(DnsRecordSchema::from).collect(),
+    }))
+}
+
+/// Update the settings of a domain of the club
+///
+/// Only settings which don't affect other clubs or the limits set by the superadmins
+/// can be changed here.
+#[put("/{uuid}/settings")]
+#[instrument(name = "Api::club_admin::update_domain_settings")]
+pub async fn update_domain_settings(
+    Path((club_uuid, domain_uuid)): Path<(ClubUuid, DomainUuid)>,
+    ApiJson(UpdateDomainSettingsRequest {
+        default_mailbox_quota,
+    }): ApiJson<UpdateDomainSettingsRequest>,
+) -> ApiResult<ApiJson<FormResult<(), UpdateDomainSettingsError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let domain = Domain::find_by_uuid(&mut tx, domain_uuid)
+        .await?
+        .filter(|domain| domain.associated_club == Some(club_uuid))
+        .ok_or(ApiError::bad_request("Domain not found"))?;
+
+    tx.commit().await?;
+
+    if default_mailbox_quota == 0 || default_mailbox_quota % MIB != 0 {
+        return Ok(ApiJson(FormResult::err(UpdateDomainSettingsError {
+            invalid_quota: true,
+            ..Default::default()
+        })));
+    }
+
+    let mailcow_domain = Mailcow::global()
+        .sdk
+        .get_domain(&domain.domain)
+        .await
+        .map_err(ApiError::map_server_error("Could not retrieve domain"))?;
+
+    if default_mailbox_quota > mailcow_domain.max_quota_for_mbox {
+        return Ok(ApiJson(FormResult::err(UpdateDomainSettingsError {
+            exceeds_max_mailbox_quota: true,
+            ..Default::default()
+        })));
+    }
+
+    Mailcow::global()
+        .set_default_quota(&domain.domain, default_mailbox_quota)
+        .await?;
+
+    info!(
+        "Set default mailbox quota of {} to {} MiB",
+        domain.domain,
+        default_mailbox_quota / MIB
+    );
+
+    Ok(ApiJson(FormResult::ok(())))
+}
+
+/// Combine a domain with its cached usage
+async fn club_domain_schema(domain: Domain) -> ClubDomainSchema {
+    let usage = Mailcow::global()
+        .get_cached_domain_stats(&domain.domain)
+        .await
+        .map(|cached| DomainUsageSchema::from(cached.domain));
+
+    ClubDomainSchema {
+        uuid: domain.uuid,
+        domain: domain.domain,
+        is_primary: domain.is_primary,
+        usage,
+    }
+}
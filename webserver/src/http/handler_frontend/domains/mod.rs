@@ -3,4 +3,5 @@
 pub use schema::*;
 
 pub mod handler_admin;
+pub mod handler_club_admin;
 mod schema;
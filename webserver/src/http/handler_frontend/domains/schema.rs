@@ -1,11 +1,19 @@
 use galvyn::core::re_exports::schemars;
 use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
 use galvyn::rorm::fields::types::MaxStr;
+use mailcow::domains::schema::MailcowDomain;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::models::club::ClubUuid;
 use crate::models::domain::Domain;
+use crate::models::domain::DomainSyncReport;
 use crate::models::domain::DomainUuid;
+use crate::modules::mailcow::dns_records::DnsRecordPurpose;
+use crate::modules::mailcow::dns_records::ExpectedDnsRecord;
+use crate::modules::mailcow::domain_provisioning::DomainLimits;
+use crate::modules::mailcow::sync::DomainSyncOutcome;
 
 /// The representation of a domain
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -18,6 +26,262 @@ pub struct DomainSchema {
     pub is_primary: bool,
 }
 
+/// A domain of a club together with its usage
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClubDomainSchema {
+    /// Internal identifier of the domain
+    pub uuid: DomainUuid,
+    /// The domain
+    pub domain: MaxStr<255>,
+    /// Is the domain used to create mailboxes
+    pub is_primary: bool,
+    /// Usage of the domain, if its statistics were already fetched from mailcow
+    pub usage: Option<DomainUsageSchema>,
+}
+
+/// Usage and limits of a domain
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DomainUsageSchema {
+    /// Total bytes used across all mailboxes
+    pub bytes_used: u64,
+    /// Maximum quota for the domain in bytes
+    pub quota: u64,
+    /// Number of mailboxes on the domain
+    pub mailboxes_used: u64,
+    /// Maximum number of mailboxes on the domain
+    pub mailboxes_max: u64,
+    /// Total number of messages
+    pub messages: u64,
+    /// Default quota for new mailboxes in bytes
+    pub default_mailbox_quota: u64,
+    /// Maximum quota of a single mailbox in bytes
+    pub max_mailbox_quota: u64,
+}
+
+/// The DNS records a domain is expected to publish
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DomainDnsSchema {
+    /// The expected records
+    pub records: Vec<DnsRecordSchema>,
+    /// Mailcow didn't generate a DKIM key for the domain yet
+    pub dkim_missing: bool,
+}
+
+/// A DNS record a domain is expected to publish
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DnsRecordSchema {
+    /// Purpose of the record
+    pub purpose: DnsRecordPurpose,
+    /// Fully qualified name of the record
+    pub name: String,
+    /// Type of the record, e.g. `MX` or `TXT`
+    pub record_type: String,
+    /// Content of the record
+    pub value: String,
+}
+
+/// Request to update the settings of a domain
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UpdateDomainSettingsRequest {
+    /// Default quota for new mailboxes in bytes
+    ///
+    /// Must be a positive multiple of a MiB.
+    pub default_mailbox_quota: u64,
+}
+
+/// Errors that can occur while updating the settings of a domain
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct UpdateDomainSettingsError {
+    /// The quota isn't a positive multiple of a MiB
+    pub invalid_quota: bool,
+    /// The quota exceeds the maximum quota of a single mailbox
+    pub exceeds_max_mailbox_quota: bool,
+}
+
+/// Limits of a domain
+///
+/// Quotas must be positive multiples of a MiB.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct DomainLimitsSchema {
+    /// Maximum number of mailboxes
+    pub max_mailboxes: u64,
+    /// Maximum number of aliases
+    pub max_aliases: u64,
+    /// Default quota for new mailboxes in bytes
+    pub default_mailbox_quota: u64,
+    /// Maximum quota of a single mailbox in bytes
+    pub max_mailbox_quota: u64,
+    /// Maximum total quota of the domain in bytes
+    pub domain_quota: u64,
+}
+
+/// Request to create a domain in mailcow
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateDomainRequest {
+    /// The domain, in lowercase
+    pub domain: MaxStr<255>,
+    /// Limits of the domain
+    pub limits: DomainLimitsSchema,
+    /// The club to associate the domain with
+    ///
+    /// Leave empty to use the domain as primary domain of a new club.
+    pub club: Option<ClubUuid>,
+}
+
+/// Errors that can occur while creating a domain
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct CreateDomainError {
+    /// The domain isn't a valid, lowercase domain name
+    pub invalid_domain: bool,
+    /// The domain already exists in mailcow
+    pub domain_already_exists: bool,
+    /// A quota isn't a positive multiple of a MiB
+    pub invalid_quota: bool,
+    /// The default mailbox quota exceeds the maximum mailbox quota
+    /// or the maximum mailbox quota exceeds the domain quota
+    pub inconsistent_quotas: bool,
+}
+
+/// A domain created in mailcow
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreatedDomainSchema {
+    /// Internal identifier of the domain
+    pub uuid: DomainUuid,
+    /// Whether a DKIM key was generated for the domain
+    pub dkim_generated: bool,
+}
+
+/// Errors that can occur while updating the limits of a domain
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct UpdateDomainLimitsError {
+    /// A quota isn't a positive multiple of a MiB
+    pub invalid_quota: bool,
+    /// The default mailbox quota exceeds the maximum mailbox quota
+    /// or the maximum mailbox quota exceeds the domain quota
+    pub inconsistent_quotas: bool,
+}
+
+/// Errors that can occur while deleting a domain
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct DeleteDomainError {
+    /// The domain is still associated with a club
+    pub domain_associated: bool,
+    /// Mailcow still holds mailboxes on the domain
+    pub mailboxes_remaining: bool,
+}
+
+/// Outcome of the latest domain sync
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DomainSyncSchema {
+    /// The applied changes, if the sync succeeded
+    pub report: Option<DomainSyncReportSchema>,
+    /// Error which aborted the sync
+    pub error: Option<String>,
+    /// The point in time the sync finished
+    pub finished_at: SchemaDateTime,
+}
+
+/// Changes applied by a domain sync
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DomainSyncReportSchema {
+    /// Domains which were new in mailcow
+    pub added: Vec<MaxStr<255>>,
+    /// Missing domains which reappeared in mailcow
+    pub restored: Vec<MaxStr<255>>,
+    /// Domains missing from mailcow which are kept
+    pub missing: Vec<MaxStr<255>>,
+    /// Missing domains which were deleted after their grace period
+    pub deleted: Vec<MaxStr<255>>,
+    /// Missing primary domains whose grace period is over, but which are kept until their club gets a new primary domain
+    pub kept_primary: Vec<MaxStr<255>>,
+    /// Too many domains went missing at once, so none were removed
+    pub removal_refused: bool,
+}
+
+/// Request to sync the domains from mailcow right away
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RunDomainSyncRequest {
+    /// Remove missing domains even if too many of them went missing at once
+    pub force: bool,
+}
+
+/// A domain missing from mailcow, which is kept until its grace period is over
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MissingDomainSchema {
+    /// Internal identifier of the domain
+    pub uuid: DomainUuid,
+    /// The domain
+    pub domain: MaxStr<255>,
+    /// The club the domain is associated with
+    pub associated_club: Option<ClubUuid>,
+    /// Is the domain used to create mailboxes
+    pub is_primary: bool,
+    /// The first sync the domain was missing from mailcow
+    pub missing_since: SchemaDateTime,
+    /// The point in time the domain is deleted if it doesn't reappear
+    pub deleted_after: SchemaDateTime,
+}
+
+impl From<DomainSyncOutcome> for DomainSyncSchema {
+    fn from(value: DomainSyncOutcome) -> Self {
+        Self {
+            report: value.report.map(DomainSyncReportSchema::from),
+            error: value.error,
+            finished_at: SchemaDateTime(value.finished_at),
+        }
+    }
+}
+
+impl From<DomainSyncReport> for DomainSyncReportSchema {
+    fn from(value: DomainSyncReport) -> Self {
+        Self {
+            added: value.added,
+            restored: value.restored,
+            missing: value.missing,
+            deleted: value.deleted,
+            kept_primary: value.kept_primary,
+            removal_refused: value.removal_refused,
+        }
+    }
+}
+
+impl From<DomainLimitsSchema> for DomainLimits {
+    fn from(value: DomainLimitsSchema) -> Self {
+        Self {
+            max_mailboxes: value.max_mailboxes,
+            max_aliases: value.max_aliases,
+            default_mailbox_quota: value.default_mailbox_quota,
+            max_mailbox_quota: value.max_mailbox_quota,
+            domain_quota: value.domain_quota,
+        }
+    }
+}
+
+impl From<MailcowDomain> for DomainUsageSchema {
+    fn from(domain: MailcowDomain) -> Self {
+        Self {
+            bytes_used: domain.bytes_total,
+            quota: domain.max_quota_for_domain,
+            mailboxes_used: domain.mboxes_in_domain,
+            mailboxes_max: domain.max_num_mboxes_for_domain,
+            messages: domain.msgs_total,
+            default_mailbox_quota: domain.def_quota_for_mbox,
+            max_mailbox_quota: domain.max_quota_for_mbox,
+        }
+    }
+}
+
+impl From<ExpectedDnsRecord> for DnsRecordSchema {
+    fn from(record: ExpectedDnsRecord) -> Self {
+        Self {
+            purpose: record.purpose,
+            name: record.name,
+            record_type: record.record_type.to_string(),
+            value: record.value,
+        }
+    }
+}
+
 impl From<Domain> for DomainSchema {
     fn from(domain: Domain) -> Self {
         Self {
@@ -0,0 +1,83 @@
+//! Administrative endpoints for impersonating accounts
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::post;
+use galvyn::rorm::Database;
+use time::OffsetDateTime;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::Impersonation;
+use crate::http::extractors::session_user::RequestUser;
+use crate::http::extractors::session_user::SESSION_IMPERSONATION;
+use crate::http::extractors::session_user::SESSION_USER;
+use crate::http::extractors::session_user::SessionUser;
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+
+#[post("/{uuid}")]
+#[instrument(name = "Api::admin::start_impersonation")]
+pub async fn start_impersonation(
+    session: Session,
+    RequestUser {
+        user: session_user,
+        api_token,
+    }: RequestUser,
+    Path(account_uuid): Path<AccountUuid>,
+) -> ApiResult<()> {
+    // The impersonation would hand out a session for the impersonated account
+    if api_token.is_some() {
+        return Err(ApiError::unauthorized(
+            "Impersonation can't be started using an api token",
+        ));
+    }
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let impersonator = Account::get_by_uuid(&mut tx, session_user.uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    let account = Account::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Target account doesn't exist"))?;
+
+    tx.commit().await?;
+
+    if account.superadmin {
+        return Err(ApiError::bad_request("Superadmins can't be impersonated"));
+    }
+
+    info!(
+        impersonator = ?impersonator.uuid(),
+        account = ?account.uuid(),
+        "Impersonation started"
+    );
+
+    session
+        .insert(
+            SESSION_IMPERSONATION,
+            Impersonation {
+                impersonator: session_user.clone(),
+                impersonator_username: impersonator.username,
+                started_at: OffsetDateTime::now_utc().unix_timestamp(),
+            },
+        )
+        .await?;
+    session
+        .insert(
+            SESSION_USER,
+            SessionUser {
+                uuid: account.uuid(),
+                auth_methods: session_user.auth_methods,
+                active_club: None,
+            },
+        )
+        .await?;
+
+    Ok(())
+}
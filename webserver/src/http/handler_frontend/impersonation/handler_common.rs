@@ -0,0 +1,36 @@
+//! Common endpoints for impersonated sessions
+
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::post;
+use time::OffsetDateTime;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::Impersonation;
+use crate::http::extractors::session_user::SESSION_IMPERSONATION;
+use crate::http::extractors::session_user::SESSION_USER;
+use crate::http::extractors::session_user::SessionUser;
+
+#[post("/end")]
+#[instrument(name = "Api::common::end_impersonation")]
+pub async fn end_impersonation(session: Session, session_user: SessionUser) -> ApiResult<()> {
+    let impersonation: Impersonation = session
+        .remove(SESSION_IMPERSONATION)
+        .await?
+        .ok_or(ApiError::bad_request("No impersonation in progress"))?;
+
+    info!(
+        impersonator = ?impersonation.impersonator.uuid,
+        account = ?session_user.uuid,
+        duration_secs = OffsetDateTime::now_utc().unix_timestamp() - impersonation.started_at,
+        "Impersonation ended"
+    );
+
+    session
+        .insert(SESSION_USER, impersonation.impersonator)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,7 @@
+//! Superadmins can act as a club admin or member to see what they see.
+//!
+//! The [`Impersonation`](crate::http::extractors::session_user::Impersonation) is stored
+//! in the session, while the session user is replaced by the impersonated account.
+
+pub mod handler_admin;
+pub mod handler_common;
@@ -13,11 +13,16 @@ use time::Duration;
 use time::OffsetDateTime;
 use tracing::instrument;
 
+use crate::http::handler_frontend::invites::CreateAdminInviteRequest;
 use crate::http::handler_frontend::invites::CreateInviteError;
 use crate::http::handler_frontend::invites::CreateInviteRequestAdmin;
+use crate::models::domain::Domain;
 use crate::models::invite::CreateInviteParams;
 use crate::models::invite::Invite;
+use crate::models::invite::InviteType;
 use crate::models::invite::InviteUuid;
+use crate::modules::mailer::MailTemplate;
+use crate::modules::mailer::Mailer;
 use crate::utils::links::Link;
 
 #[post("/")]
@@ -28,10 +33,21 @@ pub async fn create_invite(
         display_name,
         valid_days,
         invite_type,
+        notify_email,
     }): ApiJson<CreateInviteRequestAdmin>,
 ) -> ApiResult<ApiJson<FormResult<SingleLink, CreateInviteError>>> {
     let mut tx = Database::global().start_transaction().await?;
 
+    if let InviteType::ClubMember { club, email } = &invite_type {
+        let domains = Domain::find_all_present_by_club(&mut tx, *club).await?;
+        if !domains.iter().any(|domain| domain.matches_address(email)) {
+            return Ok(ApiJson(FormResult::err(CreateInviteError {
+                domain_not_associated: true,
+                ..Default::default()
+            })));
+        }
+    }
+
     let invite = Invite::create(
         &mut tx,
         CreateInviteParams {
@@ -50,13 +66,88 @@ pub async fn create_invite(
                 crate::models::invite::CreateInviteError::UsernameTaken => {
                     Ok(ApiJson(FormResult::err(CreateInviteError {
                         username_already_occupied: true,
+                        ..Default::default()
+                    })))
+                }
+            };
+        }
+    };
+
+    if let Some(notify_email) = notify_email {
+        Mailer::global()
+            .enqueue(
+                &mut tx,
+                &notify_email,
+                MailTemplate::Invite {
+                    display_name: invite.display_name.to_string(),
+                    link: Link::invite(invite.uuid),
+                    expires_at: invite.expires_at(),
+                },
+            )
+            .await?;
+    }
+
+    tx.commit().await?;
+    Mailer::global().deliver_enqueued();
+
+    Ok(ApiJson(FormResult::ok(SingleLink {
+        link: Link::invite(invite.uuid).to_string(),
+    })))
+}
+
+#[post("/superadmins")]
+#[instrument(name = "Api::admin::create_superadmin_invite")]
+pub async fn create_superadmin_invite(
+    ApiJson(CreateAdminInviteRequest {
+        username,
+        display_name,
+        valid_days,
+        notify_email,
+    }): ApiJson<CreateAdminInviteRequest>,
+) -> ApiResult<ApiJson<FormResult<SingleLink, CreateInviteError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let invite = Invite::create(
+        &mut tx,
+        CreateInviteParams {
+            username,
+            display_name,
+            expires_at: OffsetDateTime::now_utc() + Duration::days(valid_days.get() as i64),
+            invite_type: InviteType::SuperAdmin,
+        },
+    )
+    .await?;
+
+    let invite = match invite {
+        Ok(invite) => invite,
+        Err(err) => {
+            return match err {
+                crate::models::invite::CreateInviteError::UsernameTaken => {
+                    Ok(ApiJson(FormResult::err(CreateInviteError {
+                        username_already_occupied: true,
+                        ..Default::default()
                     })))
                 }
             };
         }
     };
 
+    if let Some(notify_email) = notify_email {
+        Mailer::global()
+            .enqueue(
+                &mut tx,
+                &notify_email,
+                MailTemplate::Invite {
+                    display_name: invite.display_name.to_string(),
+                    link: Link::invite(invite.uuid),
+                    expires_at: invite.expires_at(),
+                },
+            )
+            .await?;
+    }
+
     tx.commit().await?;
+    Mailer::global().deliver_enqueued();
 
     Ok(ApiJson(FormResult::ok(SingleLink {
         link: Link::invite(invite.uuid).to_string(),
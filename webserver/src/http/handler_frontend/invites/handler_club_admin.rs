@@ -14,13 +14,17 @@ use time::OffsetDateTime;
 use tracing::instrument;
 
 use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::invites::CreateAdminInviteRequest;
 use crate::http::handler_frontend::invites::CreateInviteError;
 use crate::http::handler_frontend::invites::CreateMemberInviteRequest;
 use crate::models::club::ClubUuid;
+use crate::models::domain::Domain;
 use crate::models::invite::CreateInviteParams;
 use crate::models::invite::Invite;
 use crate::models::invite::InviteType;
 use crate::models::invite::InviteUuid;
+use crate::modules::mailer::MailTemplate;
+use crate::modules::mailer::Mailer;
 use crate::utils::links::Link;
 
 #[post("/")]
@@ -32,10 +36,19 @@ pub async fn create_member_invite(
         display_name,
         email,
         valid_days,
+        notify_email,
     }): ApiJson<CreateMemberInviteRequest>,
 ) -> ApiResult<ApiJson<FormResult<SingleLink, CreateInviteError>>> {
     let mut tx = Database::global().start_transaction().await?;
 
+    let domains = Domain::find_all_present_by_club(&mut tx, club_uuid).await?;
+    if !domains.iter().any(|domain| domain.matches_address(&email)) {
+        return Ok(ApiJson(FormResult::err(CreateInviteError {
+            domain_not_associated: true,
+            ..Default::default()
+        })));
+    }
+
     let invite = Invite::create(
         &mut tx,
         CreateInviteParams {
@@ -57,13 +70,89 @@ pub async fn create_member_invite(
                 crate::models::invite::CreateInviteError::UsernameTaken => {
                     Ok(ApiJson(FormResult::err(CreateInviteError {
                         username_already_occupied: true,
+                        ..Default::default()
                     })))
                 }
             };
         }
     };
 
+    if let Some(notify_email) = notify_email {
+        Mailer::global()
+            .enqueue(
+                &mut tx,
+                &notify_email,
+                MailTemplate::Invite {
+                    display_name: invite.display_name.to_string(),
+                    link: Link::invite(invite.uuid),
+                    expires_at: invite.expires_at(),
+                },
+            )
+            .await?;
+    }
+
     tx.commit().await?;
+    Mailer::global().deliver_enqueued();
+
+    Ok(ApiJson(FormResult::ok(SingleLink {
+        link: Link::invite(invite.uuid).to_string(),
+    })))
+}
+
+#[post("/admins")]
+#[instrument(name = "Api::club_admin::create_admin_invite")]
+pub async fn create_admin_invite(
+    Path(club_uuid): Path<ClubUuid>,
+    ApiJson(CreateAdminInviteRequest {
+        username,
+        display_name,
+        valid_days,
+        notify_email,
+    }): ApiJson<CreateAdminInviteRequest>,
+) -> ApiResult<ApiJson<FormResult<SingleLink, CreateInviteError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let invite = Invite::create(
+        &mut tx,
+        CreateInviteParams {
+            username,
+            display_name,
+            invite_type: InviteType::ClubAdmin { club: club_uuid },
+            expires_at: OffsetDateTime::now_utc() + Duration::days(valid_days.get() as i64),
+        },
+    )
+    .await?;
+
+    let invite = match invite {
+        Ok(invite) => invite,
+        Err(err) => {
+            return match err {
+                crate::models::invite::CreateInviteError::UsernameTaken => {
+                    Ok(ApiJson(FormResult::err(CreateInviteError {
+                        username_already_occupied: true,
+                        ..Default::default()
+                    })))
+                }
+            };
+        }
+    };
+
+    if let Some(notify_email) = notify_email {
+        Mailer::global()
+            .enqueue(
+                &mut tx,
+                &notify_email,
+                MailTemplate::Invite {
+                    display_name: invite.display_name.to_string(),
+                    link: Link::invite(invite.uuid),
+                    expires_at: invite.expires_at(),
+                },
+            )
+            .await?;
+    }
+
+    tx.commit().await?;
+    Mailer::global().deliver_enqueued();
 
     Ok(ApiJson(FormResult::ok(SingleLink {
         link: Link::invite(invite.uuid).to_string(),
@@ -74,7 +163,9 @@ pub async fn create_member_invite(
 #[instrument(name = "Api::club_admin::retract_invite")]
 pub async fn retract_invite(
     Path((club_uuid, invite_uuid)): Path<(ClubUuid, InviteUuid)>,
-    SessionUser { uuid: session_user }: SessionUser,
+    SessionUser {
+        uuid: session_user, ..
+    }: SessionUser,
 ) -> ApiResult<()> {
     let mut tx = Database::global().start_transaction().await?;
 
@@ -92,10 +183,6 @@ pub async fn retract_invite(
         ));
     }
 
-    if invite.email.is_none() {
-        return Err(ApiError::bad_request("Invite doesn't references a member"));
-    }
-
     invite.delete(&mut tx).await?;
 
     tx.commit().await?;
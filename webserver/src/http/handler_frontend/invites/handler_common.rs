@@ -15,15 +15,16 @@ use tracing::error;
 use tracing::info;
 use tracing::instrument;
 
+use crate::http::extractors::auth_attempt::AuthAttempt;
 use crate::http::handler_frontend::invites::AcceptInvite;
 use crate::http::handler_frontend::invites::AcceptInviteError;
 use crate::http::handler_frontend::invites::GetInvite;
 use crate::models::account::Account;
-use crate::models::domain::Domain;
 use crate::models::invite::AcceptInviteParams;
 use crate::models::invite::Invite;
 use crate::models::invite::InviteUuid;
 use crate::modules::mailcow::Mailcow;
+use crate::modules::password_policy::PasswordPolicy;
 
 #[get("/{uuid}")]
 #[instrument(name = "Api::common::get_invite")]
@@ -45,13 +46,32 @@ pub async fn get_invite_common(
 #[instrument(name = "Api::common::accept_invite", skip(password))]
 pub async fn accept_invite(
     Path(SingleUuid { uuid }): Path<SingleUuid>,
+    attempt: AuthAttempt,
     ApiJson(AcceptInvite { password }): ApiJson<AcceptInvite>,
 ) -> ApiResult<ApiJson<FormResult<(), AcceptInviteError>>> {
     let mut tx = Database::global().start_transaction().await?;
 
-    let invite = Invite::find_by_uuid(&mut tx, InviteUuid(uuid))
+    let Some(invite) = Invite::find_by_uuid(&mut tx, InviteUuid(uuid)).await? else {
+        attempt.failed_unknown();
+        return Err(ApiError::bad_request("Invite not found"));
+    };
+
+    if password.is_empty() {
+        return Ok(ApiJson(FormResult::err(AcceptInviteError {
+            empty_password: true,
+            ..Default::default()
+        })));
+    }
+
+    if let Err(violation) = PasswordPolicy::global()
+        .check(&password, &[&*invite.username, &*invite.display_name])
         .await?
-        .ok_or(ApiError::bad_request("Invite not found"))?;
+    {
+        return Ok(ApiJson(FormResult::err(AcceptInviteError {
+            password_policy: Some(violation),
+            ..Default::default()
+        })));
+    }
 
     let res = invite
         .accept_invite(
@@ -63,13 +83,12 @@ pub async fn accept_invite(
         .await?;
 
     match res {
-        Ok(Account::ClubAdmin(club_admin)) => {
-            let mut domains = vec![];
-            domains.extend(Domain::find_all_by_club(&mut tx, club_admin.club).await?);
+        Ok(mut account) if !account.admin_of.is_empty() => {
+            let domains = Mailcow::domain_admin_domains(&mut tx, &account).await?;
 
             // Associate corresponding domains
             tokio::spawn(async move {
-                let username = club_admin.username.into_inner();
+                let username = account.username.to_string();
 
                 let u = username.clone();
                 let res: anyhow::Result<()> = async move {
@@ -79,7 +98,7 @@ pub async fn accept_invite(
                         .sdk
                         .create_domain_admin(CreateDomainAdminRequest {
                             active: 1,
-                            domains: domains.into_iter().map(|x| x.domain.into_inner()).collect(),
+                            domains,
                             password: hashed_password.clone(),
                             password2: hashed_password,
                             username: u,
@@ -92,6 +111,14 @@ pub async fn accept_invite(
 
                 if let Err(err) = res {
                     error!(err = ?err, "Could not create domain admin for account {username}");
+
+                    // Mark the domain admin, so a superadmin can repair it
+                    if let Err(err) = account
+                        .update_domain_admin_synced(Database::global(), false)
+                        .await
+                    {
+                        error!(err = ?err, "Could not mark domain admin of account {username} as unsynced");
+                    }
                 } else {
                     info!("Successfully added domain admin in mailcow for account {username}");
                 }
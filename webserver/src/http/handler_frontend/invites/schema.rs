@@ -10,6 +10,7 @@ use serde::Serialize;
 use crate::models::invite::Invite;
 use crate::models::invite::InviteType;
 use crate::models::invite::InviteUuid;
+use crate::modules::password_policy::PasswordPolicyViolation;
 use crate::utils::links::Link;
 
 /// API representation of an invitation
@@ -43,6 +44,8 @@ pub struct AcceptInviteError {
     pub empty_password: bool,
     /// Invite has expired
     pub expired: bool,
+    /// The password violates the password policy
+    pub password_policy: Option<PasswordPolicyViolation>,
 }
 
 /// Request to create an invitation
@@ -56,6 +59,10 @@ pub struct CreateInviteRequestAdmin {
     pub valid_days: NonZeroU8,
     /// Type of the invite
     pub invite_type: InviteType,
+    /// Address the invite link is sent to
+    ///
+    /// If omitted, the link has to be passed on manually.
+    pub notify_email: Option<MaxStr<255>>,
 }
 
 /// Request to create an invitation
@@ -66,16 +73,41 @@ pub struct CreateMemberInviteRequest {
     /// Display-name of the user
     pub display_name: MaxStr<255>,
     /// Email of the user
+    ///
+    /// It may be on any of the domains associated with the club.
     pub email: MaxStr<255>,
     /// The point in time the invite expires
     pub valid_days: NonZeroU8,
+    /// Address the invite link is sent to
+    ///
+    /// The member's mailbox is only created once the invite is accepted,
+    /// so this has to be an external address.
+    /// If omitted, the link has to be passed on manually.
+    pub notify_email: Option<MaxStr<255>>,
 }
 
-/// Errors that can occur while creating an invitation
+/// Request to invite a club admin or superadmin
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CreateAdminInviteRequest {
+    /// Reserved username
+    pub username: MaxStr<255>,
+    /// Display-name of the user
+    pub display_name: MaxStr<255>,
+    /// The point in time the invite expires
+    pub valid_days: NonZeroU8,
+    /// Address the invite link is sent to
+    ///
+    /// If omitted, the link has to be passed on manually.
+    pub notify_email: Option<MaxStr<255>>,
+}
+
+/// Errors that can occur while creating an invitation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
 pub struct CreateInviteError {
     /// Username is already taken
     pub username_already_occupied: bool,
+    /// The email isn't on one of the domains associated with the club
+    pub domain_not_associated: bool,
 }
 
 impl From<Invite> for GetInvite {
@@ -0,0 +1,41 @@
+//! Endpoints for inspecting and clearing lockouts
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::handler_frontend::lockouts::LockoutSchema;
+use crate::models::lockout::Lockout;
+use crate::models::lockout::LockoutUuid;
+
+#[get("/")]
+#[instrument(name = "Api::admin::get_lockouts")]
+pub async fn get_lockouts() -> ApiResult<ApiJson<Vec<LockoutSchema>>> {
+    let lockouts = Lockout::find_all(Database::global())
+        .await?
+        .into_iter()
+        .map(LockoutSchema::from)
+        .collect();
+
+    Ok(ApiJson(lockouts))
+}
+
+#[delete("/{uuid}")]
+#[instrument(name = "Api::admin::clear_lockout")]
+pub async fn clear_lockout(Path(SingleUuid { uuid }): Path<SingleUuid>) -> ApiResult<()> {
+    if !Lockout::delete(Database::global(), LockoutUuid(uuid)).await? {
+        return Err(ApiError::bad_request("Lockout not found"));
+    }
+
+    info!(lockout = ?uuid, "Lockout cleared");
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Endpoints and schema for inspecting the brute force protection
+
+pub use schema::*;
+
+pub mod handler_admin;
+mod schema;
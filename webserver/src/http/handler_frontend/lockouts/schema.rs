@@ -0,0 +1,43 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::lockout::Lockout;
+use crate::models::lockout::LockoutKind;
+use crate::models::lockout::LockoutUuid;
+
+/// Failed attempts of a client or against a username
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LockoutSchema {
+    /// Primary key of the lockout
+    pub uuid: LockoutUuid,
+    /// The group of endpoints the attempts were made against
+    pub scope: MaxStr<32>,
+    /// Whether the attempts are counted per ip or per username
+    pub kind: LockoutKind,
+    /// The ip address or username
+    pub value: MaxStr<255>,
+    /// Number of failed attempts within the current window
+    pub failed_attempts: usize,
+    /// The point in time the lock ends, if the key is locked
+    pub locked_until: Option<SchemaDateTime>,
+    /// The point in time of the last failed attempt
+    pub last_failure_at: SchemaDateTime,
+}
+
+impl From<Lockout> for LockoutSchema {
+    fn from(value: Lockout) -> Self {
+        Self {
+            uuid: value.uuid,
+            scope: value.scope,
+            kind: value.kind,
+            value: value.value,
+            failed_attempts: value.failed_attempts,
+            locked_until: value.locked_until.map(SchemaDateTime),
+            last_failure_at: SchemaDateTime(value.last_failure_at),
+        }
+    }
+}
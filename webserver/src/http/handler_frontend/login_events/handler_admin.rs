@@ -0,0 +1,38 @@
+//! Admin handlers for the sign in history of accounts
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::instrument;
+
+use crate::http::handler_frontend::login_events::LOGIN_HISTORY_LIMIT;
+use crate::http::handler_frontend::login_events::LoginEventSchema;
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::models::login_event::LoginEvent;
+
+/// Retrieve the latest sign ins of an account
+#[get("/{uuid}/logins")]
+#[instrument(name = "Api::admin::get_account_logins")]
+pub async fn get_account_logins(
+    Path(account_uuid): Path<AccountUuid>,
+) -> ApiResult<ApiJson<Vec<LoginEventSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    Account::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Account doesn't exist"))?;
+
+    let events =
+        LoginEvent::find_latest_by_account(&mut tx, account_uuid, LOGIN_HISTORY_LIMIT).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(
+        events.into_iter().map(LoginEventSchema::from).collect(),
+    ))
+}
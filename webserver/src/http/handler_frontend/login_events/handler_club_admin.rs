@@ -0,0 +1,42 @@
+//! Club admin handlers for the sign in history of members
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::instrument;
+
+use crate::http::handler_frontend::login_events::LOGIN_HISTORY_LIMIT;
+use crate::http::handler_frontend::login_events::LoginEventSchema;
+use crate::models::account::AccountUuid;
+use crate::models::account::ClubAccount;
+use crate::models::club::ClubUuid;
+use crate::models::login_event::LoginEvent;
+
+/// Retrieve the latest sign ins of a member of the club
+#[get("/{uuid}/logins")]
+#[instrument(name = "Api::club_admin::get_member_logins")]
+pub async fn get_member_logins(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+) -> ApiResult<ApiJson<Vec<LoginEventSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    ClubAccount::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .filter(|member| member.club == club_uuid)
+        .ok_or(ApiError::bad_request(
+            "Target account isn't part of the club of the executing admin",
+        ))?;
+
+    let events =
+        LoginEvent::find_latest_by_account(&mut tx, account_uuid, LOGIN_HISTORY_LIMIT).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(
+        events.into_iter().map(LoginEventSchema::from).collect(),
+    ))
+}
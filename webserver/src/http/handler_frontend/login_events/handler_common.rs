@@ -0,0 +1,30 @@
+//! Common handlers for the sign in history of the currently logged-in user
+
+use galvyn::core::Module;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::login_events::LOGIN_HISTORY_LIMIT;
+use crate::http::handler_frontend::login_events::LoginEventSchema;
+use crate::models::login_event::LoginEvent;
+
+/// Retrieve the latest sign ins of the logged-in user
+#[get("/logins")]
+#[instrument(name = "Api::common::get_my_logins")]
+pub async fn get_my_logins(
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<ApiJson<Vec<LoginEventSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let events = LoginEvent::find_latest_by_account(&mut tx, uuid, LOGIN_HISTORY_LIMIT).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(
+        events.into_iter().map(LoginEventSchema::from).collect(),
+    ))
+}
@@ -0,0 +1,8 @@
+//! Handlers for the sign in history of accounts
+
+pub use schema::*;
+
+pub mod handler_admin;
+pub mod handler_club_admin;
+pub mod handler_common;
+mod schema;
@@ -0,0 +1,48 @@
+//! Schema for the sign in history
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::login_event::LoginEvent;
+use crate::models::login_event::LoginEventUuid;
+use crate::models::login_event::LoginMethod;
+
+/// Number of sign ins returned by the history endpoints
+pub const LOGIN_HISTORY_LIMIT: u64 = 100;
+
+/// A sign in of an account
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LoginEventSchema {
+    /// Identifier of the sign in
+    pub uuid: LoginEventUuid,
+    /// How the account was signed in to
+    pub method: LoginMethod,
+    /// Whether the sign in succeeded
+    pub success: bool,
+    /// The ip address of the client
+    pub ip: Option<MaxStr<64>>,
+    /// The user agent of the client
+    pub user_agent: Option<MaxStr<255>>,
+    /// Whether this was the first successful sign in from the ip range
+    pub new_ip_range: bool,
+    /// The point in time of the sign in
+    pub created_at: SchemaDateTime,
+}
+
+impl From<LoginEvent> for LoginEventSchema {
+    fn from(value: LoginEvent) -> Self {
+        Self {
+            uuid: value.uuid,
+            method: value.method,
+            success: value.success,
+            ip: value.ip,
+            user_agent: value.user_agent,
+            new_ip_range: value.new_ip_range,
+            created_at: SchemaDateTime(value.created_at),
+        }
+    }
+}
@@ -11,82 +11,125 @@ use galvyn::post;
 use galvyn::put;
 use galvyn::rorm::Database;
 use tracing::instrument;
-use zxcvbn::Score;
-use zxcvbn::zxcvbn;
 
+use crate::http::extractors::session_user::CredentialUser;
+use crate::http::extractors::session_user::Impersonation;
+use crate::http::extractors::session_user::SESSION_IMPERSONATION;
 use crate::http::extractors::session_user::SESSION_USER;
 use crate::http::extractors::session_user::SessionUser;
 use crate::http::handler_frontend::me::MeSchema;
 use crate::http::handler_frontend::me::RoleSchema;
+use crate::http::handler_frontend::me::SetActiveClubRequest;
 use crate::http::handler_frontend::me::SetPasswordErrors;
 use crate::http::handler_frontend::me::SetPasswordRequest;
 use crate::http::handler_frontend::me::UpdateMeRequest;
 use crate::models::account::Account;
+use crate::models::api_token::ApiToken;
 use crate::models::club::Club;
+use crate::models::session::StoredSession;
 use crate::modules::mailcow::Mailcow;
+use crate::modules::mailer::Mailer;
+use crate::modules::password_policy::PasswordPolicy;
 
 #[get("/")]
 #[instrument(name = "Api::common::get_me")]
-pub async fn get_me(SessionUser { uuid }: SessionUser) -> ApiResult<ApiJson<MeSchema>> {
+pub async fn get_me(session: Session, session_user: SessionUser) -> ApiResult<ApiJson<MeSchema>> {
+    let impersonation: Option<Impersonation> = session.get(SESSION_IMPERSONATION).await?;
+
     let mut tx = Database::global().start_transaction().await?;
 
-    let account = Account::get_by_uuid(&mut tx, uuid)
+    let account = Account::get_by_uuid(&mut tx, session_user.uuid)
         .await?
         .ok_or(ApiError::server_error(
             "Account not found, while session user was found",
         ))?;
 
-    if let Account::Superadmin(superadmin) = account {
-        return Ok(ApiJson(MeSchema {
-            uuid: superadmin.uuid(),
-            username: superadmin.username,
-            display_name: superadmin.display_name,
-            role: RoleSchema::SuperAdmin,
-        }));
+    let second_factor_required =
+        account.second_factor_enforced() && !session_user.has_second_factor();
+
+    let mut roles = Vec::new();
+    if account.superadmin {
+        roles.push(RoleSchema::SuperAdmin);
+    }
+    if let Some(membership) = &account.membership {
+        let club_name = Club::find_by_uuid(&mut tx, membership.club)
+            .await?
+            .ok_or(ApiError::server_error("Club should exist"))?
+            .name;
+
+        roles.push(RoleSchema::ClubMember {
+            club: membership.club,
+            club_name,
+            email: membership.email.clone(),
+        });
+    }
+    for club in &account.admin_of {
+        let club_name = Club::find_by_uuid(&mut tx, *club)
+            .await?
+            .ok_or(ApiError::server_error("Club should exist"))?
+            .name;
+
+        roles.push(RoleSchema::ClubAdmin {
+            club: *club,
+            club_name,
+        });
     }
 
-    let club_name = Club::find_by_uuid(
-        &mut tx,
-        match &account {
-            Account::ClubMember(account) => account.club,
-            Account::ClubAdmin(account) => account.club,
-            _ => unreachable!(),
-        },
-    )
-    .await?
-    .ok_or(ApiError::server_error("Club should exist"))?
-    .name;
+    // The club might have been switched to before the role was revoked
+    let active_club = session_user
+        .active_club
+        .filter(|club| account.clubs().contains(club))
+        .or(account.default_club());
 
     tx.commit().await?;
 
-    Ok(ApiJson(match account {
-        Account::ClubMember(club_member) => MeSchema {
-            uuid: club_member.uuid(),
-            username: club_member.username,
-            display_name: club_member.display_name,
-            role: RoleSchema::ClubMember {
-                club: club_member.club,
-                club_name: club_name.clone(),
-                email: club_member.email,
-            },
-        },
-        Account::ClubAdmin(club_admin) => MeSchema {
-            uuid: club_admin.uuid(),
-            username: club_admin.username,
-            display_name: club_admin.display_name,
-            role: RoleSchema::ClubAdmin {
-                club: club_admin.club,
-                club_name,
-            },
-        },
-        Account::Superadmin(_) => unreachable!(),
+    Ok(ApiJson(MeSchema {
+        uuid: account.uuid(),
+        username: account.username,
+        display_name: account.display_name,
+        roles,
+        active_club,
+        second_factor_required,
+        impersonated_by: impersonation.map(|impersonation| impersonation.impersonator_username),
     }))
 }
 
+#[put("/active-club")]
+#[instrument(name = "Api::common::set_active_club")]
+pub async fn set_active_club(
+    session: Session,
+    session_user: SessionUser,
+    ApiJson(SetActiveClubRequest { club }): ApiJson<SetActiveClubRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, session_user.uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    tx.commit().await?;
+
+    if !account.clubs().contains(&club) {
+        return Err(ApiError::bad_request("Account has no role in the club"));
+    }
+
+    session
+        .insert(
+            SESSION_USER,
+            SessionUser {
+                active_club: Some(club),
+                ..session_user
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
 #[put("/")]
 #[instrument(name = "Api::common::update_me")]
 pub async fn update_me(
-    SessionUser { uuid }: SessionUser,
+    SessionUser { uuid, .. }: SessionUser,
     ApiJson(UpdateMeRequest { display_name }): ApiJson<UpdateMeRequest>,
 ) -> ApiResult<()> {
     let mut tx = Database::global().start_transaction().await?;
@@ -106,7 +149,7 @@ pub async fn update_me(
 #[instrument(name = "Api::common::set_password", skip(password, old_password))]
 pub async fn set_password(
     session: Session,
-    SessionUser { uuid }: SessionUser,
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
     ApiJson(SetPasswordRequest {
         old_password,
         password,
@@ -124,37 +167,33 @@ pub async fn set_password(
 
     if !account.check_password(&old_password)? {
         return Ok(ApiJson(FormResult::err(SetPasswordErrors {
-            low_entropy: false,
+            password_policy: None,
             invalid_old_password: true,
         })));
     }
 
-    let [display_name, username] = match &account {
-        Account::ClubMember(account) => &[
-            account.display_name.clone().into_inner(),
-            account.username.clone().into_inner(),
-        ],
-        Account::ClubAdmin(account) => &[
-            account.display_name.clone().into_inner(),
-            account.username.clone().into_inner(),
-        ],
-        Account::Superadmin(account) => &[
-            account.display_name.clone().into_inner(),
-            account.username.clone().into_inner(),
-        ],
-    };
-
-    let entropy = zxcvbn(&password, &[display_name, username]);
-    if entropy.score() < Score::Three {
+    if let Err(violation) = PasswordPolicy::global()
+        .check(
+            &password,
+            &[&**account.username(), &**account.display_name()],
+        )
+        .await?
+    {
         return Ok(ApiJson(FormResult::err(SetPasswordErrors {
-            low_entropy: true,
+            password_policy: Some(violation),
             invalid_old_password: false,
         })));
     }
+
     account.set_password(&mut tx, &password).await?;
+    StoredSession::revoke_all_by_account(&mut tx, uuid).await?;
+    ApiToken::revoke_all_by_account(&mut tx, uuid).await?;
+    Mailer::global()
+        .notify_password_changed(&mut tx, &account)
+        .await?;
 
     let mut app_password_mailbox = None;
-    if let Account::ClubMember(ref member) = account {
+    if let Some(member) = &account.membership {
         let club = Club::find_by_uuid(&mut tx, member.club)
             .await?
             .ok_or(ApiError::server_error("Club should exist"))?;
@@ -165,12 +204,17 @@ pub async fn set_password(
     }
 
     tx.commit().await?;
+    Mailer::global().deliver_enqueued();
 
     if let Some(member_mailbox) = app_password_mailbox {
         Mailcow::global().create_app_password(member_mailbox);
     }
+    if !account.admin_of.is_empty() {
+        Mailcow::global().update_domain_admin_password(account.uuid());
+    }
 
-    // Invalidate the current session after a password change
+    // Invalidate the current session after a password change,
+    // all other sessions have already been revoked
     session.remove::<SessionUser>(SESSION_USER).await?;
 
     Ok(ApiJson(FormResult::ok(())))
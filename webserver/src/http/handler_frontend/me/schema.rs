@@ -8,6 +8,7 @@ use serde::Serialize;
 
 use crate::models::account::AccountUuid;
 use crate::models::club::ClubUuid;
+use crate::modules::password_policy::PasswordPolicyViolation;
 
 /// Representation of the currently logged-in user.
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -19,7 +20,16 @@ pub struct MeSchema {
     /// The user's display name.
     pub display_name: MaxStr<255>,
     /// The user's roles.
-    pub role: RoleSchema,
+    pub roles: Vec<RoleSchema>,
+    /// The club the user is currently acting in
+    pub active_club: Option<ClubUuid>,
+    /// The user has to enroll a second factor before being allowed to use
+    /// administrative privileges
+    pub second_factor_required: bool,
+    /// The username of the superadmin impersonating the user
+    ///
+    /// If set, a banner should be shown to make the impersonation visible.
+    pub impersonated_by: Option<MaxStr<255>>,
 }
 
 /// The roles of a user.
@@ -66,6 +76,13 @@ pub struct UpdateMeRequest {
     pub display_name: MaxStr<255>,
 }
 
+/// Request to switch the club the user is acting in
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetActiveClubRequest {
+    /// The club to switch to, the user must hold a role in it
+    pub club: ClubUuid,
+}
+
 /// Request to update the currently logged-in user
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SetPasswordRequest {
@@ -78,8 +95,8 @@ pub struct SetPasswordRequest {
 /// Errors that may occur while setting a new password
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SetPasswordErrors {
-    /// Entropy is too low
-    pub low_entropy: bool,
+    /// The new password violates the password policy
+    pub password_policy: Option<PasswordPolicyViolation>,
     /// The old password was invalid
     pub invalid_old_password: bool,
 }
@@ -0,0 +1,146 @@
+//! Club admin handlers for the additional addresses of members
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::FormResult;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::rorm::Database;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::handler_frontend::member_addresses::AddMemberAddressError;
+use crate::http::handler_frontend::member_addresses::AddMemberAddressRequest;
+use crate::http::handler_frontend::member_addresses::MemberAddressSchema;
+use crate::models::account::AccountUuid;
+use crate::models::account::ClubAccount;
+use crate::models::club::ClubUuid;
+use crate::models::domain::Domain;
+use crate::models::member_address::MemberAddress;
+use crate::models::member_address::MemberAddressUuid;
+use crate::modules::mailcow::Mailcow;
+use crate::modules::mailer;
+
+/// Retrieve the additional addresses of a member of the club
+#[get("/{uuid}/addresses")]
+#[instrument(name = "Api::club_admin::get_member_addresses")]
+pub async fn get_member_addresses(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+) -> ApiResult<ApiJson<Vec<MemberAddressSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    ClubAccount::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .filter(|member| member.club == club_uuid)
+        .ok_or(ApiError::bad_request(
+            "Target account isn't part of the club of the executing admin",
+        ))?;
+
+    let addresses = MemberAddress::find_all_by_account(&mut tx, account_uuid).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(
+        addresses
+            .into_iter()
+            .map(MemberAddressSchema::from)
+            .collect(),
+    ))
+}
+
+/// Add an additional address to a member of the club
+///
+/// The address is forwarded to the mailbox of the member by an alias in mailcow.
+#[post("/{uuid}/addresses")]
+#[instrument(name = "Api::club_admin::add_member_address")]
+pub async fn add_member_address(
+    Path((club_uuid, account_uuid)): Path<(ClubUuid, AccountUuid)>,
+    ApiJson(AddMemberAddressRequest { address }): ApiJson<AddMemberAddressRequest>,
+) -> ApiResult<ApiJson<FormResult<MemberAddressSchema, AddMemberAddressError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let member = ClubAccount::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .filter(|member| member.club == club_uuid)
+        .ok_or(ApiError::bad_request(
+            "Target account isn't part of the club of the executing admin",
+        ))?;
+
+    if !mailer::is_valid_address(&address) {
+        return Ok(ApiJson(FormResult::err(AddMemberAddressError {
+            invalid_address: true,
+            ..Default::default()
+        })));
+    }
+
+    let domains = Domain::find_all_present_by_club(&mut tx, club_uuid).await?;
+    if !domains
+        .iter()
+        .any(|domain| domain.matches_address(&address))
+    {
+        return Ok(ApiJson(FormResult::err(AddMemberAddressError {
+            domain_not_associated: true,
+            ..Default::default()
+        })));
+    }
+
+    let in_use = MemberAddress::is_in_use(&mut tx, &address).await?
+        || Mailcow::global().is_address_taken(&address).await?;
+    if in_use {
+        return Ok(ApiJson(FormResult::err(AddMemberAddressError {
+            address_in_use: true,
+            ..Default::default()
+        })));
+    }
+
+    let member_address = MemberAddress::create(&mut tx, account_uuid, &address).await?;
+
+    Mailcow::global()
+        .create_member_alias(&member_address.address, &member.email)
+        .await?;
+
+    tx.commit().await?;
+
+    info!(
+        "Added address {} to member {}",
+        member_address.address, member.email
+    );
+
+    Ok(ApiJson(FormResult::ok(MemberAddressSchema::from(
+        member_address,
+    ))))
+}
+
+/// Remove an additional address of a member of the club
+#[delete("/{uuid}/addresses/{address_uuid}")]
+#[instrument(name = "Api::club_admin::delete_member_address")]
+pub async fn delete_member_address(
+    Path((club_uuid, account_uuid, address_uuid)): Path<(ClubUuid, AccountUuid, MemberAddressUuid)>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    ClubAccount::get_by_uuid(&mut tx, account_uuid)
+        .await?
+        .filter(|member| member.club == club_uuid)
+        .ok_or(ApiError::bad_request(
+            "Target account isn't part of the club of the executing admin",
+        ))?;
+
+    let member_address = MemberAddress::find_by_uuid(&mut tx, account_uuid, address_uuid)
+        .await?
+        .ok_or(ApiError::bad_request("Address not found"))?;
+
+    Mailcow::global()
+        .delete_member_aliases(std::slice::from_ref(&member_address))
+        .await?;
+
+    member_address.delete(&mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
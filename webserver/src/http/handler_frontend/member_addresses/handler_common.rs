@@ -0,0 +1,32 @@
+//! Common handlers for the additional addresses of the currently logged-in user
+
+use galvyn::core::Module;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::rorm::Database;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::member_addresses::MemberAddressSchema;
+use crate::models::member_address::MemberAddress;
+
+/// Retrieve the additional addresses of the logged-in user
+#[get("/addresses")]
+#[instrument(name = "Api::common::get_my_addresses")]
+pub async fn get_my_addresses(
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<ApiJson<Vec<MemberAddressSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let addresses = MemberAddress::find_all_by_account(&mut tx, uuid).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(
+        addresses
+            .into_iter()
+            .map(MemberAddressSchema::from)
+            .collect(),
+    ))
+}
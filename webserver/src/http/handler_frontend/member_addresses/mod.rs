@@ -0,0 +1,7 @@
+//! Handlers for the additional addresses of club members
+
+pub use schema::*;
+
+pub mod handler_club_admin;
+pub mod handler_common;
+mod schema;
@@ -0,0 +1,52 @@
+//! Schema for the additional addresses of club members
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::member_address::MemberAddress;
+use crate::models::member_address::MemberAddressUuid;
+
+/// An additional address of a member
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct MemberAddressSchema {
+    /// Identifier of the address
+    pub uuid: MemberAddressUuid,
+    /// The address forwarded to the mailbox of the member
+    pub address: MaxStr<255>,
+    /// The point in time the address was added
+    pub created_at: SchemaDateTime,
+}
+
+/// Request to add an additional address to a member
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AddMemberAddressRequest {
+    /// The address to add
+    ///
+    /// Must be on one of the domains of the club.
+    pub address: MaxStr<255>,
+}
+
+/// Errors that can occur while adding an additional address
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct AddMemberAddressError {
+    /// The address is malformed
+    pub invalid_address: bool,
+    /// The domain of the address isn't associated with the club
+    pub domain_not_associated: bool,
+    /// The address is already used by a mailbox, alias or member
+    pub address_in_use: bool,
+}
+
+impl From<MemberAddress> for MemberAddressSchema {
+    fn from(value: MemberAddress) -> Self {
+        Self {
+            uuid: value.uuid,
+            address: value.address,
+            created_at: SchemaDateTime(value.created_at),
+        }
+    }
+}
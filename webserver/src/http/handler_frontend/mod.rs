@@ -8,14 +8,25 @@ use crate::http::middlewares;
 use crate::http::middlewares::AuthRateLimit;
 
 pub mod accounts;
+pub mod api_tokens;
 pub mod clubs;
 pub mod credential_reset;
+pub mod data_export;
 pub mod domains;
+pub mod impersonation;
 pub mod invites;
+pub mod lockouts;
+pub mod login_events;
 pub mod me;
+pub mod member_addresses;
 pub mod oidc_provider;
 pub mod openapi;
+pub mod passkeys;
+pub mod reconciliation;
+pub mod recovery_email;
+pub mod sessions;
 pub mod settings;
+pub mod totp;
 
 /// Openapi page for the admin API
 pub struct AdminAPI;
@@ -27,8 +38,14 @@ pub fn router_admin() -> GalvynRouter {
             "/accounts",
             GalvynRouter::new()
                 .handler(accounts::handler_admin::get_all_superadmins)
+                .handler(accounts::handler_admin::get_superadmin_invites)
+                .handler(accounts::handler_admin::delete_superadmin)
+                .handler(accounts::handler_admin::set_superadmin_locked)
                 .handler(accounts::handler_admin::delete_club_admin)
-                .handler(accounts::handler_admin::reset_credentials),
+                .handler(accounts::handler_admin::repair_domain_admin)
+                .handler(accounts::handler_admin::reset_credentials)
+                .handler(data_export::handler_admin::export_account_data)
+                .handler(login_events::handler_admin::get_account_logins),
         )
         .nest(
             "/clubs",
@@ -36,6 +53,9 @@ pub fn router_admin() -> GalvynRouter {
                 .handler(clubs::handler_admin::get_club)
                 .handler(clubs::handler_admin::get_clubs)
                 .handler(clubs::handler_admin::create_club)
+                .handler(clubs::handler_admin::update_club)
+                .handler(clubs::handler_admin::get_xauth_migration)
+                .handler(clubs::handler_admin::retry_xauth_migration)
                 .handler(clubs::handler_admin::delete_club)
                 .handler(clubs::handler_admin::get_club_admins)
                 .handler(clubs::handler_admin::get_club_members)
@@ -44,18 +64,44 @@ pub fn router_admin() -> GalvynRouter {
                 .handler(clubs::handler_admin::get_dashboard_stats)
                 .handler(clubs::handler_admin::get_club_domains)
                 .handler(clubs::handler_admin::associate_domain)
-                .handler(clubs::handler_admin::unassociate_domain),
+                .handler(clubs::handler_admin::unassociate_domain)
+                .handler(clubs::handler_admin::change_primary_domain),
         )
         .nest(
             "/domains",
-            GalvynRouter::new().handler(domains::handler_admin::get_unassociated_domains),
+            GalvynRouter::new()
+                .handler(domains::handler_admin::get_unassociated_domains)
+                .handler(domains::handler_admin::get_missing_domains)
+                .handler(domains::handler_admin::get_domain_sync)
+                .handler(domains::handler_admin::run_domain_sync)
+                .handler(domains::handler_admin::create_domain)
+                .handler(domains::handler_admin::update_domain_limits)
+                .handler(domains::handler_admin::delete_domain),
         )
         .nest(
             "/invites",
             GalvynRouter::new()
                 .handler(invites::handler_admin::create_invite)
+                .handler(invites::handler_admin::create_superadmin_invite)
                 .handler(invites::handler_admin::retract_invite),
         )
+        .nest(
+            "/impersonation",
+            GalvynRouter::new().handler(impersonation::handler_admin::start_impersonation),
+        )
+        .nest(
+            "/lockouts",
+            GalvynRouter::new()
+                .handler(lockouts::handler_admin::get_lockouts)
+                .handler(lockouts::handler_admin::clear_lockout),
+        )
+        .nest(
+            "/reconciliation",
+            GalvynRouter::new()
+                .handler(reconciliation::handler_admin::get_reconciliation_report)
+                .handler(reconciliation::handler_admin::repair_issues)
+                .handler(reconciliation::handler_admin::repair_categories),
+        )
         .nest(
             "/oidc-providers",
             GalvynRouter::new()
@@ -79,20 +125,43 @@ pub fn router_club_admin() -> GalvynRouter {
                     .handler(clubs::handler_club_admin::get_club)
                     .handler(clubs::handler_club_admin::get_club_members)
                     .handler(clubs::handler_club_admin::get_club_member_invites)
+                    .handler(clubs::handler_club_admin::get_club_admins)
+                    .handler(clubs::handler_club_admin::get_club_admin_invites)
                     .handler(clubs::handler_club_admin::get_dashboard_stats),
             )
-            .nest("/domains", GalvynRouter::new())
+            .nest(
+                "/admins",
+                GalvynRouter::new()
+                    .handler(accounts::handler_club_admin::promote_admin)
+                    .handler(accounts::handler_club_admin::demote_admin),
+            )
+            .nest(
+                "/domains",
+                GalvynRouter::new()
+                    .handler(domains::handler_club_admin::get_club_domains)
+                    .handler(domains::handler_club_admin::get_club_domain)
+                    .handler(domains::handler_club_admin::get_domain_dns_records)
+                    .handler(domains::handler_club_admin::update_domain_settings),
+            )
             .nest(
                 "/invites",
                 GalvynRouter::new()
                     .handler(invites::handler_club_admin::create_member_invite)
+                    .handler(invites::handler_club_admin::create_admin_invite)
                     .handler(invites::handler_club_admin::retract_invite),
             )
             .nest(
                 "/members",
                 GalvynRouter::new()
                     .handler(clubs::handler_club_admin::delete_member)
-                    .handler(accounts::handler_club_admin::reset_credentials),
+                    .handler(accounts::handler_club_admin::reset_credentials)
+                    .handler(accounts::handler_club_admin::set_member_locked)
+                    .handler(accounts::handler_club_admin::set_member_valid_until)
+                    .handler(data_export::handler_club_admin::export_member)
+                    .handler(login_events::handler_club_admin::get_member_logins)
+                    .handler(member_addresses::handler_club_admin::get_member_addresses)
+                    .handler(member_addresses::handler_club_admin::add_member_address)
+                    .handler(member_addresses::handler_club_admin::delete_member_address),
             )
             .layer(axum::middleware::from_fn(middlewares::auth_club_admin)),
     )
@@ -127,7 +196,7 @@ pub fn router_unauthenticated() -> GalvynRouter {
             .merge(
                 GalvynRouter::new()
                     .handler(invites::handler_common::accept_invite)
-                    .wrap(AuthRateLimit::new(25)),
+                    .wrap(AuthRateLimit::new("invite", 25)),
             ),
     );
 
@@ -138,7 +207,18 @@ pub fn router_unauthenticated() -> GalvynRouter {
             .handler(credential_reset::handler_common::verify_uuid)
             .handler(credential_reset::handler_common::reset_password)
             .handler(credential_reset::handler_common::reset_password_by_uuid)
-            .wrap(AuthRateLimit::new(5)),
+            .wrap(AuthRateLimit::new("credential-reset", 5)),
+    );
+
+    router = router.merge(
+        GalvynRouter::new()
+            .handler(credential_reset::handler_common::forgot_password)
+            .wrap(AuthRateLimit::new("forgot-password", 5).count_successes()),
+    );
+
+    router = router.nest(
+        "/recovery-email",
+        GalvynRouter::new().handler(recovery_email::handler_common::verify_recovery_email),
     );
 
     router
@@ -155,7 +235,53 @@ pub fn router_common() -> GalvynRouter {
             GalvynRouter::new()
                 .handler(me::handler_common::get_me)
                 .handler(me::handler_common::update_me)
-                .handler(me::handler_common::set_password),
+                .handler(me::handler_common::set_active_club)
+                .handler(me::handler_common::set_password)
+                .handler(data_export::handler_common::export_me)
+                .handler(login_events::handler_common::get_my_logins)
+                .handler(member_addresses::handler_common::get_my_addresses)
+                .nest(
+                    "/api-tokens",
+                    GalvynRouter::new()
+                        .handler(api_tokens::handler_common::get_api_tokens)
+                        .handler(api_tokens::handler_common::create_api_token)
+                        .handler(api_tokens::handler_common::revoke_api_token),
+                )
+                .nest(
+                    "/passkeys",
+                    GalvynRouter::new()
+                        .handler(passkeys::handler_common::get_passkeys)
+                        .handler(passkeys::handler_common::start_passkey_registration)
+                        .handler(passkeys::handler_common::finish_passkey_registration)
+                        .handler(passkeys::handler_common::delete_passkey),
+                )
+                .nest(
+                    "/recovery-email",
+                    GalvynRouter::new()
+                        .handler(recovery_email::handler_common::get_recovery_email)
+                        .handler(recovery_email::handler_common::set_recovery_email)
+                        .handler(recovery_email::handler_common::delete_recovery_email),
+                )
+                .nest(
+                    "/sessions",
+                    GalvynRouter::new()
+                        .handler(sessions::handler_common::get_sessions)
+                        .handler(sessions::handler_common::revoke_session)
+                        .handler(sessions::handler_common::revoke_all_sessions),
+                )
+                .nest(
+                    "/totp",
+                    GalvynRouter::new()
+                        .handler(totp::handler_common::get_totp_status)
+                        .handler(totp::handler_common::start_totp_enrollment)
+                        .handler(totp::handler_common::confirm_totp_enrollment)
+                        .handler(totp::handler_common::disable_totp)
+                        .handler(totp::handler_common::regenerate_recovery_codes),
+                ),
+        )
+        .nest(
+            "/impersonation",
+            GalvynRouter::new().handler(impersonation::handler_common::end_impersonation),
         )
         .nest(
             "/settings",
@@ -0,0 +1,156 @@
+//! Common handler_frontend for managing passkeys
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::rorm::Database;
+use tracing::info;
+use tracing::instrument;
+use webauthn_rs::prelude::PasskeyRegistration;
+use webauthn_rs::prelude::RegisterPublicKeyCredential;
+
+use crate::http::extractors::session_user::CredentialUser;
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::passkeys::FinishPasskeyRegistrationRequest;
+use crate::http::handler_frontend::passkeys::PasskeySchema;
+use crate::models::account::Account;
+use crate::models::passkey::Passkey;
+use crate::models::passkey::PasskeyUuid;
+use crate::modules::webauthn::WebAuthn;
+
+/// Access the state of a running passkey registration using this variable
+const SESSION_PASSKEY_REGISTRATION: &str = "passkey-registration";
+
+#[get("/")]
+#[instrument(name = "Api::common::get_passkeys")]
+pub async fn get_passkeys(
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<ApiJson<Vec<PasskeySchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let passkeys = Passkey::find_all_by_account(&mut tx, uuid)
+        .await?
+        .into_iter()
+        .map(PasskeySchema::from)
+        .collect();
+
+    tx.commit().await?;
+
+    Ok(ApiJson(passkeys))
+}
+
+#[post("/register/start")]
+#[instrument(name = "Api::common::start_passkey_registration")]
+pub async fn start_passkey_registration(
+    session: Session,
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+) -> ApiResult<ApiJson<serde_json::Value>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    let existing = Passkey::find_all_by_account(&mut tx, uuid)
+        .await?
+        .into_iter()
+        .map(|passkey| passkey.credential().cred_id().clone())
+        .collect();
+
+    tx.commit().await?;
+
+    let (challenge, state) = WebAuthn::global()
+        .webauthn
+        .start_passkey_registration(
+            account.uuid().0,
+            account.username(),
+            account.display_name(),
+            Some(existing),
+        )
+        .map_err(ApiError::map_server_error(
+            "Couldn't start passkey registration",
+        ))?;
+
+    session.insert(SESSION_PASSKEY_REGISTRATION, state).await?;
+
+    Ok(ApiJson(serde_json::to_value(challenge).map_err(
+        ApiError::map_server_error("Couldn't serialize challenge"),
+    )?))
+}
+
+#[post("/register/finish")]
+#[instrument(name = "Api::common::finish_passkey_registration", skip(credential))]
+pub async fn finish_passkey_registration(
+    session: Session,
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+    ApiJson(FinishPasskeyRegistrationRequest { name, credential }): ApiJson<
+        FinishPasskeyRegistrationRequest,
+    >,
+) -> ApiResult<ApiJson<PasskeySchema>> {
+    let state: PasskeyRegistration = session
+        .remove(SESSION_PASSKEY_REGISTRATION)
+        .await?
+        .ok_or(ApiError::bad_request("No passkey registration in progress"))?;
+
+    let credential: RegisterPublicKeyCredential = serde_json::from_value(credential)
+        .map_err(|_| ApiError::bad_request("Invalid credential"))?;
+
+    let credential = WebAuthn::global()
+        .webauthn
+        .finish_passkey_registration(&credential, &state)
+        .map_err(|_| ApiError::bad_request("Passkey registration failed"))?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    let passkey = Passkey::create(&mut tx, &account, name, credential).await?;
+
+    tx.commit().await?;
+
+    info!(account = ?uuid, passkey = ?passkey.uuid, "Passkey registered");
+
+    Ok(ApiJson(PasskeySchema::from(passkey)))
+}
+
+#[delete("/{uuid}")]
+#[instrument(name = "Api::common::delete_passkey")]
+pub async fn delete_passkey(
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+    Path(SingleUuid { uuid: passkey_uuid }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    let mut passkeys = Passkey::find_all_by_account(&mut tx, uuid).await?;
+    let index = passkeys
+        .iter()
+        .position(|passkey| passkey.uuid == PasskeyUuid(passkey_uuid))
+        .ok_or(ApiError::bad_request("Passkey not found"))?;
+
+    if account.second_factor_enforced() && !account.totp_enabled() && passkeys.len() == 1 {
+        return Err(ApiError::bad_request(
+            "The last second factor can't be removed",
+        ));
+    }
+
+    passkeys.swap_remove(index).delete(&mut tx).await?;
+
+    tx.commit().await?;
+
+    info!(account = ?uuid, passkey = ?passkey_uuid, "Passkey deleted");
+
+    Ok(())
+}
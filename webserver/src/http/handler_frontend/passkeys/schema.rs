@@ -0,0 +1,44 @@
+//! Schema for managing passkeys
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::passkey::Passkey;
+use crate::models::passkey::PasskeyUuid;
+
+/// A registered passkey
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PasskeySchema {
+    /// Primary key of the passkey
+    pub uuid: PasskeyUuid,
+    /// Name given by the user
+    pub name: MaxStr<255>,
+    /// The last point in time the passkey was used
+    pub last_used_at: Option<SchemaDateTime>,
+    /// The point in time the passkey was registered
+    pub created_at: SchemaDateTime,
+}
+
+/// Request to finish the registration of a passkey
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FinishPasskeyRegistrationRequest {
+    /// Name to identify the passkey
+    pub name: MaxStr<255>,
+    /// The `PublicKeyCredential` returned by `navigator.credentials.create()`
+    pub credential: serde_json::Value,
+}
+
+impl From<Passkey> for PasskeySchema {
+    fn from(value: Passkey) -> Self {
+        Self {
+            uuid: value.uuid,
+            name: value.name,
+            last_used_at: value.last_used_at.map(SchemaDateTime),
+            created_at: SchemaDateTime(value.created_at),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Administrative endpoints for the reconciliation of the database with mailcow
+
+use galvyn::core::Module;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::get;
+use galvyn::post;
+use tracing::instrument;
+
+use crate::http::handler_frontend::reconciliation::ReconcileReportSchema;
+use crate::http::handler_frontend::reconciliation::RepairCategoriesRequest;
+use crate::http::handler_frontend::reconciliation::RepairIssuesRequest;
+use crate::modules::mailcow::Mailcow;
+use crate::modules::mailcow::reconciler::RepairOutcome;
+
+/// Compare the database with mailcow
+#[get("/")]
+#[instrument(name = "Api::admin::get_reconciliation_report")]
+pub async fn get_reconciliation_report() -> ApiResult<ApiJson<ReconcileReportSchema>> {
+    let report = Mailcow::global().reconcile().await?;
+
+    Ok(ApiJson(ReconcileReportSchema::from(report)))
+}
+
+/// Repair selected issues of a report
+///
+/// Issues which were resolved in the meantime are skipped.
+#[post("/repair")]
+#[instrument(name = "Api::admin::repair_issues", skip_all)]
+pub async fn repair_issues(
+    ApiJson(RepairIssuesRequest { issues }): ApiJson<RepairIssuesRequest>,
+) -> ApiResult<ApiJson<Vec<RepairOutcome>>> {
+    let outcomes = Mailcow::global().repair_issues(issues).await?;
+
+    Ok(ApiJson(outcomes))
+}
+
+/// Repair all current issues of some categories
+#[post("/repair-all")]
+#[instrument(name = "Api::admin::repair_categories")]
+pub async fn repair_categories(
+    ApiJson(RepairCategoriesRequest { categories }): ApiJson<RepairCategoriesRequest>,
+) -> ApiResult<ApiJson<Vec<RepairOutcome>>> {
+    let outcomes = Mailcow::global().repair_categories(&categories).await?;
+
+    Ok(ApiJson(outcomes))
+}
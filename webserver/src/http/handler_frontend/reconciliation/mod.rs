@@ -0,0 +1,6 @@
+//! Handlers for comparing the database with mailcow and repairing drift
+
+pub use schema::*;
+
+pub mod handler_admin;
+mod schema;
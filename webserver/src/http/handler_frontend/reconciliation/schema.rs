@@ -0,0 +1,43 @@
+//! Schema for the reconciliation of the database with mailcow
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::modules::mailcow::reconciler::ReconcileCategory;
+use crate::modules::mailcow::reconciler::ReconcileIssue;
+use crate::modules::mailcow::reconciler::ReconcileReport;
+
+/// All differences between the database and mailcow
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReconcileReportSchema {
+    /// The found issues
+    pub issues: Vec<ReconcileIssue>,
+    /// The point in time the report was created
+    pub created_at: SchemaDateTime,
+}
+
+/// Request to repair selected issues of a report
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RepairIssuesRequest {
+    /// The issues to repair, as returned by the report
+    pub issues: Vec<ReconcileIssue>,
+}
+
+/// Request to repair all issues of some categories
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RepairCategoriesRequest {
+    /// The categories to repair
+    pub categories: Vec<ReconcileCategory>,
+}
+
+impl From<ReconcileReport> for ReconcileReportSchema {
+    fn from(value: ReconcileReport) -> Self {
+        Self {
+            issues: value.issues,
+            created_at: SchemaDateTime(value.created_at),
+        }
+    }
+}
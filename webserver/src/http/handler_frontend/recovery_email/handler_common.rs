@@ -0,0 +1,115 @@
+//! Common handlers for managing the recovery email
+
+use galvyn::core::Module;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::FormResult;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::put;
+use galvyn::rorm::Database;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::CredentialUser;
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::recovery_email::RecoveryEmailSchema;
+use crate::http::handler_frontend::recovery_email::SetRecoveryEmailError;
+use crate::http::handler_frontend::recovery_email::SetRecoveryEmailRequest;
+use crate::http::handler_frontend::recovery_email::VerifyRecoveryEmailRequest;
+use crate::models::account::Account;
+use crate::models::recovery_email::RecoveryEmail;
+use crate::modules::mailer;
+use crate::modules::mailer::MailTemplate;
+use crate::modules::mailer::Mailer;
+use crate::utils::links::Link;
+
+#[get("/")]
+#[instrument(name = "Api::common::get_recovery_email")]
+pub async fn get_recovery_email(
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<ApiJson<Option<RecoveryEmailSchema>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let recovery_email = RecoveryEmail::find_by_account(&mut tx, uuid).await?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(recovery_email.map(RecoveryEmailSchema::from)))
+}
+
+#[put("/")]
+#[instrument(name = "Api::common::set_recovery_email", skip(password))]
+pub async fn set_recovery_email(
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+    ApiJson(SetRecoveryEmailRequest { email, password }): ApiJson<SetRecoveryEmailRequest>,
+) -> ApiResult<ApiJson<FormResult<(), SetRecoveryEmailError>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    let errors = SetRecoveryEmailError {
+        invalid_email: !mailer::is_valid_address(&email),
+        own_mailbox: account
+            .email()
+            .is_some_and(|mailbox| mailbox.eq_ignore_ascii_case(&email)),
+        invalid_password: !account.check_password(&password)?,
+    };
+    if errors.invalid_email || errors.own_mailbox || errors.invalid_password {
+        return Ok(ApiJson(FormResult::err(errors)));
+    }
+
+    let (recovery_email, token) = RecoveryEmail::set(&mut tx, &account, email).await?;
+
+    Mailer::global()
+        .enqueue(
+            &mut tx,
+            &recovery_email.email,
+            MailTemplate::VerifyRecoveryEmail {
+                display_name: account.display_name().to_string(),
+                link: Link::verify_recovery_email(&token),
+            },
+        )
+        .await?;
+
+    tx.commit().await?;
+    Mailer::global().deliver_enqueued();
+
+    Ok(ApiJson(FormResult::ok(())))
+}
+
+#[delete("/")]
+#[instrument(name = "Api::common::delete_recovery_email")]
+pub async fn delete_recovery_email(
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    RecoveryEmail::delete_by_account(&mut tx, uuid).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+#[post("/verify")]
+#[instrument(name = "Api::common::verify_recovery_email", skip_all)]
+pub async fn verify_recovery_email(
+    ApiJson(VerifyRecoveryEmailRequest { token }): ApiJson<VerifyRecoveryEmailRequest>,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let recovery_email = RecoveryEmail::verify(&mut tx, &token)
+        .await?
+        .ok_or(ApiError::bad_request("Invalid or expired token"))?;
+
+    tx.commit().await?;
+
+    info!(account = ?recovery_email.account, "Verified recovery email");
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Handler for managing the recovery email of an account
+
+pub use schema::*;
+
+pub mod handler_common;
+mod schema;
@@ -0,0 +1,59 @@
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::recovery_email::RecoveryEmail;
+
+/// The recovery email of the logged-in user
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecoveryEmailSchema {
+    /// The external address
+    pub email: MaxStr<255>,
+    /// Whether the address has been verified
+    ///
+    /// Credential reset links are only sent to verified addresses.
+    pub verified: bool,
+    /// The point in time the address was set
+    pub created_at: SchemaDateTime,
+}
+
+impl From<RecoveryEmail> for RecoveryEmailSchema {
+    fn from(value: RecoveryEmail) -> Self {
+        Self {
+            verified: value.is_verified(),
+            email: value.email,
+            created_at: SchemaDateTime(value.created_at),
+        }
+    }
+}
+
+/// Request to set a new recovery email
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SetRecoveryEmailRequest {
+    /// The external address
+    pub email: MaxStr<255>,
+    /// The current password of the user
+    pub password: MaxStr<72>,
+}
+
+/// Errors that may occur while setting a recovery email
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct SetRecoveryEmailError {
+    /// The address is not a valid email address
+    pub invalid_email: bool,
+    /// The address is the account's own mailbox, which can't be accessed
+    /// once the password is forgotten
+    pub own_mailbox: bool,
+    /// The password was invalid
+    pub invalid_password: bool,
+}
+
+/// Request to verify a recovery email
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct VerifyRecoveryEmailRequest {
+    /// The token from the link sent to the address
+    pub token: MaxStr<64>,
+}
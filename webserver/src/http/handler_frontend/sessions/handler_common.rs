@@ -0,0 +1,103 @@
+//! Common handler_frontend for managing the sessions of the currently logged-in user
+
+use galvyn::core::Module;
+use galvyn::core::re_exports::axum::extract::Path;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::core::stuff::schema::SingleUuid;
+use galvyn::delete;
+use galvyn::get;
+use galvyn::post;
+use galvyn::rorm::Database;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::sessions::SessionSchema;
+use crate::models::session::StoredSession;
+use crate::models::session::StoredSessionUuid;
+
+#[get("/")]
+#[instrument(name = "Api::common::get_sessions")]
+pub async fn get_sessions(
+    session: Session,
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<ApiJson<Vec<SessionSchema>>> {
+    let current = session.id().map(|id| id.to_string());
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut sessions: Vec<_> = StoredSession::find_all_by_account(&mut tx, uuid)
+        .await?
+        .into_iter()
+        .map(|stored| SessionSchema {
+            current: current
+                .as_deref()
+                .is_some_and(|id| stored.is_session_id(id)),
+            uuid: stored.uuid,
+            user_agent: stored.user_agent,
+            ip: stored.ip,
+            last_seen_at: SchemaDateTime(stored.last_seen_at),
+            created_at: SchemaDateTime(stored.created_at),
+        })
+        .collect();
+
+    tx.commit().await?;
+
+    sessions.sort_by_key(|x| std::cmp::Reverse(x.last_seen_at.0));
+
+    Ok(ApiJson(sessions))
+}
+
+#[delete("/{uuid}")]
+#[instrument(name = "Api::common::revoke_session")]
+pub async fn revoke_session(
+    session: Session,
+    SessionUser { uuid, .. }: SessionUser,
+    Path(SingleUuid { uuid: session_uuid }): Path<SingleUuid>,
+) -> ApiResult<()> {
+    let current = session.id().map(|id| id.to_string());
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let is_current = StoredSession::find_all_by_account(&mut tx, uuid)
+        .await?
+        .into_iter()
+        .find(|stored| stored.uuid == StoredSessionUuid(session_uuid))
+        .ok_or(ApiError::bad_request("Session not found"))?
+        .is_session_id(current.as_deref().unwrap_or_default());
+
+    StoredSession::revoke(&mut tx, uuid, StoredSessionUuid(session_uuid)).await?;
+
+    tx.commit().await?;
+
+    if is_current {
+        session.flush().await?;
+    }
+
+    info!(account = ?uuid, session = ?session_uuid, "Session revoked");
+
+    Ok(())
+}
+
+#[post("/revoke-all")]
+#[instrument(name = "Api::common::revoke_all_sessions")]
+pub async fn revoke_all_sessions(
+    session: Session,
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<()> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    StoredSession::revoke_all_by_account(&mut tx, uuid).await?;
+
+    tx.commit().await?;
+
+    session.flush().await?;
+
+    info!(account = ?uuid, "Logged out everywhere");
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Endpoints for managing the sessions of the currently logged-in user
+
+pub use schema::*;
+
+pub mod handler_common;
+mod schema;
@@ -0,0 +1,27 @@
+//! Schema for managing sessions
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::models::session::StoredSessionUuid;
+
+/// An active session of the user
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SessionSchema {
+    /// Identifier of the session
+    pub uuid: StoredSessionUuid,
+    /// The user agent of the device using the session
+    pub user_agent: Option<MaxStr<255>>,
+    /// The ip address the session was last used from
+    pub ip: Option<MaxStr<64>>,
+    /// The last point in time the session was used
+    pub last_seen_at: SchemaDateTime,
+    /// The point in time the session was created
+    pub created_at: SchemaDateTime,
+    /// Whether this is the session of the current request
+    pub current: bool,
+}
@@ -0,0 +1,194 @@
+//! Common handler_frontend for managing the TOTP second factor
+
+use galvyn::core::Module;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiError;
+use galvyn::core::stuff::api_error::ApiResult;
+use galvyn::core::stuff::api_json::ApiJson;
+use galvyn::core::stuff::schema::FormResult;
+use galvyn::get;
+use galvyn::post;
+use galvyn::rorm::Database;
+use tracing::info;
+use tracing::instrument;
+
+use crate::http::extractors::session_user::CredentialUser;
+use crate::http::extractors::session_user::SESSION_USER;
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::handler_frontend::totp::ConfirmPasswordRequest;
+use crate::http::handler_frontend::totp::ConfirmTotpErrors;
+use crate::http::handler_frontend::totp::ConfirmTotpRequest;
+use crate::http::handler_frontend::totp::DisableTotpErrors;
+use crate::http::handler_frontend::totp::RecoveryCodesSchema;
+use crate::http::handler_frontend::totp::RegenerateRecoveryCodesErrors;
+use crate::http::handler_frontend::totp::TotpEnrollmentSchema;
+use crate::http::handler_frontend::totp::TotpStatusSchema;
+use crate::models::account::Account;
+use crate::models::account::AuthenticationMethod;
+use crate::models::passkey::Passkey;
+
+/// Access the TOTP secret which is waiting for confirmation using this variable
+const SESSION_TOTP_ENROLLMENT: &str = "totp-enrollment";
+
+#[get("/")]
+#[instrument(name = "Api::common::get_totp_status")]
+pub async fn get_totp_status(
+    SessionUser { uuid, .. }: SessionUser,
+) -> ApiResult<ApiJson<TotpStatusSchema>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(TotpStatusSchema {
+        enabled: account.totp_enabled(),
+        remaining_recovery_codes: account.remaining_recovery_codes(),
+        enforced: account.second_factor_enforced(),
+    }))
+}
+
+#[post("/enroll")]
+#[instrument(name = "Api::common::start_totp_enrollment")]
+pub async fn start_totp_enrollment(
+    session: Session,
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+) -> ApiResult<ApiJson<TotpEnrollmentSchema>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    tx.commit().await?;
+
+    if account.totp_enabled() {
+        return Err(ApiError::bad_request("TOTP is already enabled"));
+    }
+
+    let enrollment = account.start_totp_enrollment()?;
+
+    session
+        .insert(SESSION_TOTP_ENROLLMENT, enrollment.secret.clone())
+        .await?;
+
+    Ok(ApiJson(TotpEnrollmentSchema {
+        secret: enrollment.secret,
+        url: enrollment.url,
+    }))
+}
+
+#[post("/confirm")]
+#[instrument(name = "Api::common::confirm_totp_enrollment", skip(code))]
+pub async fn confirm_totp_enrollment(
+    session: Session,
+    CredentialUser(mut session_user): CredentialUser,
+    ApiJson(ConfirmTotpRequest { code }): ApiJson<ConfirmTotpRequest>,
+) -> ApiResult<ApiJson<FormResult<RecoveryCodesSchema, ConfirmTotpErrors>>> {
+    let secret: String = session
+        .get(SESSION_TOTP_ENROLLMENT)
+        .await?
+        .ok_or(ApiError::bad_request("No TOTP enrollment in progress"))?;
+
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::get_by_uuid(&mut tx, session_user.uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    if account.totp_enabled() {
+        return Err(ApiError::bad_request("TOTP is already enabled"));
+    }
+
+    let Some(recovery_codes) = account.enable_totp(&mut tx, &secret, &code).await? else {
+        return Ok(ApiJson(FormResult::err(ConfirmTotpErrors {
+            invalid_code: true,
+        })));
+    };
+
+    tx.commit().await?;
+
+    info!(account = ?session_user.uuid, "TOTP enabled");
+
+    session.remove::<String>(SESSION_TOTP_ENROLLMENT).await?;
+
+    // The user has just proven to be in possession of the second factor
+    if !session_user.has_second_factor() {
+        session_user.auth_methods.push(AuthenticationMethod::Otp);
+        session.insert(SESSION_USER, session_user).await?;
+    }
+
+    Ok(ApiJson(FormResult::ok(RecoveryCodesSchema {
+        recovery_codes,
+    })))
+}
+
+#[post("/disable")]
+#[instrument(name = "Api::common::disable_totp", skip(password))]
+pub async fn disable_totp(
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+    ApiJson(ConfirmPasswordRequest { password }): ApiJson<ConfirmPasswordRequest>,
+) -> ApiResult<ApiJson<FormResult<(), DisableTotpErrors>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    if !account.check_password(&password)? {
+        return Ok(ApiJson(FormResult::err(DisableTotpErrors {
+            invalid_password: true,
+            enforced: false,
+        })));
+    }
+
+    let has_passkeys = !Passkey::find_all_by_account(&mut tx, uuid)
+        .await?
+        .is_empty();
+    if account.second_factor_enforced() && !has_passkeys {
+        return Ok(ApiJson(FormResult::err(DisableTotpErrors {
+            invalid_password: false,
+            enforced: true,
+        })));
+    }
+
+    account.disable_totp(&mut tx).await?;
+
+    tx.commit().await?;
+
+    info!(account = ?uuid, "TOTP disabled");
+
+    Ok(ApiJson(FormResult::ok(())))
+}
+
+#[post("/recovery-codes")]
+#[instrument(name = "Api::common::regenerate_recovery_codes", skip(password))]
+pub async fn regenerate_recovery_codes(
+    CredentialUser(SessionUser { uuid, .. }): CredentialUser,
+    ApiJson(ConfirmPasswordRequest { password }): ApiJson<ConfirmPasswordRequest>,
+) -> ApiResult<ApiJson<FormResult<RecoveryCodesSchema, RegenerateRecoveryCodesErrors>>> {
+    let mut tx = Database::global().start_transaction().await?;
+
+    let mut account = Account::get_by_uuid(&mut tx, uuid)
+        .await?
+        .ok_or(ApiError::server_error("Account from session not found"))?;
+
+    if !account.check_password(&password)? {
+        return Ok(ApiJson(FormResult::err(RegenerateRecoveryCodesErrors {
+            invalid_password: true,
+        })));
+    }
+
+    let recovery_codes = account
+        .regenerate_recovery_codes(&mut tx)
+        .await?
+        .ok_or(ApiError::bad_request("TOTP is not enabled"))?;
+
+    tx.commit().await?;
+
+    Ok(ApiJson(FormResult::ok(RecoveryCodesSchema {
+        recovery_codes,
+    })))
+}
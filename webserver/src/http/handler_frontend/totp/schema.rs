@@ -0,0 +1,74 @@
+//! Schema for managing the TOTP second factor
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The TOTP state of the currently logged-in user
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TotpStatusSchema {
+    /// Whether TOTP is enabled
+    pub enabled: bool,
+    /// Number of recovery codes that have not been used yet
+    pub remaining_recovery_codes: usize,
+    /// Whether the configuration requires the account to use a second factor
+    pub enforced: bool,
+}
+
+/// A new TOTP secret that has to be confirmed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TotpEnrollmentSchema {
+    /// Base32 encoded secret for manual entry
+    pub secret: String,
+    /// `otpauth://` url to be displayed as QR code
+    pub url: String,
+}
+
+/// Request to confirm the TOTP enrollment
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConfirmTotpRequest {
+    /// A code generated by the authenticator app
+    pub code: MaxStr<6>,
+}
+
+/// Errors that may occur while confirming the TOTP enrollment
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConfirmTotpErrors {
+    /// The code was invalid
+    pub invalid_code: bool,
+}
+
+/// Freshly generated recovery codes
+///
+/// They are only shown once.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RecoveryCodesSchema {
+    /// The recovery codes
+    pub recovery_codes: Vec<String>,
+}
+
+/// Request which has to be confirmed with the current password
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConfirmPasswordRequest {
+    /// The current password of the user
+    pub password: MaxStr<72>,
+}
+
+/// Errors that may occur while disabling TOTP
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DisableTotpErrors {
+    /// The password was invalid
+    pub invalid_password: bool,
+    /// The configuration requires the account to use a second factor
+    /// and TOTP is the only one
+    pub enforced: bool,
+}
+
+/// Errors that may occur while regenerating recovery codes
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RegenerateRecoveryCodesErrors {
+    /// The password was invalid
+    pub invalid_password: bool,
+}
@@ -9,8 +9,11 @@ use galvyn::rorm::Database;
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::http::extractors::session_user::SessionUser;
-use crate::models::account::ClubAdminAccount;
+use crate::config::REQUIRE_2FA_CLUB_ADMINS;
+use crate::http::extractors::session_user::ApiTokenScopeGranted;
+use crate::http::extractors::session_user::RequestUser;
+use crate::models::account::Account;
+use crate::models::api_token::ApiTokenScope;
 use crate::models::club::ClubUuid;
 
 /// Represents the path or identifier for a specific club.
@@ -22,26 +25,44 @@ pub struct ClubPath {
 /// Asynchronous middleware function `auth_club_admin` for authenticating a club administrator.
 ///
 /// This function checks whether the authenticated user (SessionUser) has administrative privileges
-/// for the specified club (ClubPath). If the user is verified as an administrator, the middleware
-/// will proceed to the next handler in the chain. Otherwise, it will return an error.
+/// for the specified club (ClubPath). Api tokens additionally need the club admin scope for
+/// the club. If the user is verified as an administrator, the middleware will proceed to the
+/// next handler in the chain. Otherwise, it will return an error.
 pub async fn auth_club_admin(
     Path(ClubPath { club_uuid }): Path<ClubPath>,
-    SessionUser { uuid: account_uuid }: SessionUser,
-    req: Request,
+    RequestUser {
+        user: session_user,
+        api_token,
+    }: RequestUser,
+    mut req: Request,
     next: Next,
 ) -> ApiResult<Response> {
+    if let Some(api_token) = &api_token
+        && !api_token.allows(ApiTokenScope::ClubAdmin { club: club_uuid })
+    {
+        return Err(ApiError::unauthorized("Api token lacks the required scope"));
+    }
+
     let mut tx = Database::global().start_transaction().await?;
 
-    let account = ClubAdminAccount::get_by_uuid(&mut tx, account_uuid)
+    let account = Account::get_by_uuid(&mut tx, session_user.uuid)
         .await?
         .ok_or(ApiError::server_error("Account not found"))?;
-    if account.club != club_uuid {
-        return Err(ApiError::server_error(
+    if !account.is_admin_of(club_uuid) || account.is_locked() {
+        return Err(ApiError::unauthorized(
             "Account is not an admin for the club",
         ));
     }
 
+    if *REQUIRE_2FA_CLUB_ADMINS && !session_user.has_second_factor() {
+        return Err(ApiError::unauthorized(
+            "Two-factor authentication is required",
+        ));
+    }
+
     tx.commit().await?;
 
+    req.extensions_mut().insert(ApiTokenScopeGranted);
+
     Ok(next.run(req).await)
 }
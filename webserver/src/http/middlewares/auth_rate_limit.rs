@@ -1,115 +1,217 @@
-use std::collections::HashMap;
 use std::net::IpAddr;
 use std::ops::ControlFlow;
-use std::sync::Arc;
-use std::sync::Mutex;
 
+use galvyn::core::Module;
 use galvyn::core::middleware::SimpleGalvynMiddleware;
+use galvyn::core::re_exports::axum::body::Body;
+use galvyn::core::re_exports::axum::body::to_bytes;
 use galvyn::core::re_exports::axum::extract::Request;
 use galvyn::core::re_exports::axum::http::StatusCode;
+use galvyn::core::re_exports::axum::http::header;
 use galvyn::core::re_exports::axum::response::IntoResponse;
 use galvyn::core::re_exports::axum::response::Response;
-use time::Date;
+use galvyn::rorm::Database;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
 use time::OffsetDateTime;
+use tracing::error;
 use tracing::warn;
 
-/// Rate limit based on the client IPAddr.
+use crate::http::extractors::auth_attempt::AuthAttempt;
+use crate::http::extractors::auth_attempt::AuthOutcome;
+use crate::http::extractors::client_ip::ClientIp;
+use crate::models::account::Account;
+use crate::models::lockout::Lockout;
+use crate::models::lockout::LockoutKind;
+
+/// Maximum size of a request body which is inspected for a username
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Brute force protection for authentication endpoints
+///
+/// Failed attempts are counted in the database per client ip and per targeted account.
+/// Handlers report failed attempts using [`AuthAttempt`], other errors aren't counted.
+/// The targeted account is resolved from the `username` field of a JSON request body,
+/// which may contain a username or an email, unless the handler reports it itself.
+/// Once `max_tries` failures happened within a sliding window, the key is locked
+/// and requests are rejected with `429 Too Many Requests`.
 ///
-/// The IP Address is taken from the X-Real-IP header, so make sure the proxy sets this correctly.
+/// The client's ip address is resolved using [`ClientIp`].
 #[derive(Debug, Clone)]
 pub struct AuthRateLimit {
-    tries: Arc<Mutex<HashMap<IpAddr, u8>>>,
-    current_date: Arc<Mutex<Date>>,
+    scope: &'static str,
     max_tries: u8,
-    client_id: Option<IpAddr>,
+    count_successes: bool,
+    client_ip: Option<IpAddr>,
+    username: Option<String>,
+    attempt: AuthAttempt,
 }
 
 impl AuthRateLimit {
     /// Create a new rate limiter.
     ///
-    /// `max_tries` specifies the tries an IP has until is blocked.
-    /// The client will be blocked at the current day (based on the UTC date)
-    pub fn new(max_tries: u8) -> Self {
+    /// `scope` separates the counters of different groups of endpoints.
+    /// `max_tries` specifies the failed tries a client or account has until it is locked.
+    pub fn new(scope: &'static str, max_tries: u8) -> Self {
         Self {
-            tries: Default::default(),
-            current_date: Arc::new(Mutex::new(OffsetDateTime::now_utc().date())),
+            scope,
             max_tries,
-            client_id: None,
+            count_successes: false,
+            client_ip: None,
+            username: None,
+            attempt: AuthAttempt::default(),
         }
     }
-}
 
-impl SimpleGalvynMiddleware for AuthRateLimit {
-    async fn pre_handler(&mut self, request: Request) -> ControlFlow<Response, Request> {
-        let Some(header_value) = request.headers().get("x-real-ip") else {
-            return ControlFlow::Break(
-                (StatusCode::BAD_REQUEST, "Missing X-Real-IP Header").into_response(),
-            );
+    /// Count successful requests as failed attempts as well
+    ///
+    /// This is meant for endpoints which respond the same way whether they succeeded or not,
+    /// for example to not reveal whether an account exists.
+    pub fn count_successes(mut self) -> Self {
+        self.count_successes = true;
+        self
+    }
+
+    fn keys(&self, username: Option<&str>) -> Vec<(LockoutKind, String)> {
+        let mut keys = Vec::new();
+        if let Some(client_ip) = self.client_ip {
+            keys.push((LockoutKind::Ip, client_ip.to_string()));
+        }
+        if let Some(username) = username {
+            keys.push((LockoutKind::Username, username.to_string()));
+        }
+        keys
+    }
+
+    /// Resolve the account targeted by the username or email of a request body
+    async fn resolve_username(body: &[u8]) -> anyhow::Result<Option<String>> {
+        let Some(username) = serde_json::from_slice::<UsernameBody>(body)
+            .ok()
+            .and_then(|body| body.username)
+            .and_then(|username| MaxStr::new(username.trim().to_string()).ok())
+        else {
+            return Ok(None);
         };
 
-        let Ok(header_value) = header_value.to_str() else {
-            return ControlFlow::Break(
-                (StatusCode::BAD_REQUEST, "Invalid X-Real-IP Header").into_response(),
-            );
+        let mut tx = Database::global().start_transaction().await?;
+        let account = match Account::get_by_username(&mut tx, &username).await? {
+            Some(account) => Some(account),
+            None => Account::get_by_email(&mut tx, &username).await?,
         };
+        tx.commit().await?;
+
+        Ok(account.map(|account| account.username().to_string()))
+    }
+}
+
+/// The part of a request body the rate limiter is interested in
+#[derive(Deserialize)]
+struct UsernameBody {
+    username: Option<String>,
+}
 
-        let Ok(client_ip_addr) = header_value.parse::<IpAddr>() else {
+impl SimpleGalvynMiddleware for AuthRateLimit {
+    async fn pre_handler(&mut self, request: Request) -> ControlFlow<Response, Request> {
+        self.client_ip = ClientIp::from_request(request.headers(), request.extensions()).0;
+
+        let (mut parts, body) = request.into_parts();
+        let Ok(body) = to_bytes(body, MAX_BODY_SIZE).await else {
             return ControlFlow::Break(
-                (StatusCode::BAD_REQUEST, "Invalid X-Real-IP Header").into_response(),
+                (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large").into_response(),
             );
         };
-        self.client_id = Some(client_ip_addr);
-
-        let today = OffsetDateTime::now_utc().date();
-        {
-            #[allow(clippy::expect_used)]
-            let mut guard = self.current_date.lock().expect("Poison error");
-            if *guard != today {
-                *guard = today;
-
-                #[allow(clippy::expect_used)]
-                {
-                    *self.tries.lock().expect("Poison error") = HashMap::new();
-                }
+        self.username = match Self::resolve_username(&body).await {
+            Ok(username) => username,
+            Err(error) => {
+                error!(error.display = %error, error.debug = ?error, "Couldn't resolve account");
+                return ControlFlow::Break(StatusCode::INTERNAL_SERVER_ERROR.into_response());
             }
-        }
-
-        {
-            #[allow(clippy::expect_used)]
-            let mut guard = self.tries.lock().expect("Poison error");
+        };
+        // The middleware is cloned for every request, so the shared outcome has to be replaced
+        self.attempt = AuthAttempt::default();
+        parts.extensions.insert(self.attempt.clone());
+        let request = Request::from_parts(parts, Body::from(body));
 
-            let tries = guard.entry(client_ip_addr).or_insert(0);
+        let keys = self.keys(self.username.as_deref());
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|(kind, value)| (*kind, value.as_str()))
+            .collect();
 
-            if *tries > self.max_tries {
-                warn!("Blocking request due to too many tries");
-                return ControlFlow::Break(
-                    (StatusCode::BAD_REQUEST, "Invalid X-Real-IP Header").into_response(),
+        match Lockout::locked_until(Database::global(), self.scope, &keys).await {
+            Ok(None) => ControlFlow::Continue(request),
+            Ok(Some(locked_until)) => {
+                warn!(
+                    client_ip = ?self.client_ip,
+                    username = ?self.username,
+                    "Blocking request due to too many failed attempts"
                 );
+                ControlFlow::Break(too_many_requests(locked_until))
+            }
+            Err(error) => {
+                error!(error.display = %error, error.debug = ?error, "Couldn't check lockout");
+                ControlFlow::Break(StatusCode::INTERNAL_SERVER_ERROR.into_response())
             }
         }
-
-        ControlFlow::Continue(request)
     }
 
     async fn post_handler(&mut self, response: Response) -> Response {
-        #[allow(clippy::expect_used)]
-        let client_id = self.client_id.expect("Must be set in pre_handler");
-
-        if response.status().is_success() {
-            #[allow(clippy::expect_used)]
-            self.tries
-                .lock()
-                .expect("Poison error")
-                .insert(client_id, 0);
+        let outcome = if self.count_successes && response.status().is_success() {
+            AuthOutcome::Failed { username: None }
         } else {
-            #[allow(clippy::expect_used)]
-            self.tries
-                .lock()
-                .expect("Poison error")
-                .entry(client_id)
-                .and_modify(|x| *x += 1);
+            self.attempt.outcome()
+        };
+
+        match outcome {
+            AuthOutcome::Failed { username } => {
+                let username = username.or_else(|| self.username.clone());
+                for (kind, value) in self.keys(username.as_deref()) {
+                    if let Err(error) = Lockout::record_failure(
+                        Database::global(),
+                        self.scope,
+                        kind,
+                        &value,
+                        self.max_tries,
+                    )
+                    .await
+                    {
+                        error!(error.display = %error, error.debug = ?error, "Couldn't record attempt");
+                    }
+                }
+            }
+            // Failures per ip are only forgotten as the window slides, otherwise
+            // any successful request in the scope could be used to reset the counter
+            AuthOutcome::Succeeded { username } => {
+                if let Err(error) = Lockout::record_success(
+                    Database::global(),
+                    self.scope,
+                    LockoutKind::Username,
+                    &username,
+                )
+                .await
+                {
+                    error!(error.display = %error, error.debug = ?error, "Couldn't record attempt");
+                }
+            }
+            // A pending second factor must not reset the failures of the account,
+            // otherwise every password sign in would grant new guesses for the second factor
+            AuthOutcome::Pending | AuthOutcome::Unknown => {}
         }
 
         response
     }
 }
+
+/// Build the response for a locked client
+fn too_many_requests(locked_until: OffsetDateTime) -> Response {
+    let retry_after = (locked_until - OffsetDateTime::now_utc())
+        .whole_seconds()
+        .max(1);
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.to_string())],
+        "Too many failed attempts",
+    )
+        .into_response()
+}
@@ -6,22 +6,45 @@ use galvyn::core::stuff::api_error::ApiError;
 use galvyn::core::stuff::api_error::ApiResult;
 use galvyn::rorm::Database;
 
-use crate::http::extractors::session_user::SessionUser;
-use crate::models::account::AdministrativeAccount;
+use crate::config::REQUIRE_2FA_SUPERADMINS;
+use crate::http::extractors::session_user::ApiTokenScopeGranted;
+use crate::http::extractors::session_user::RequestUser;
+use crate::models::account::Account;
+use crate::models::api_token::ApiTokenScope;
 
 /// Middleware function to check for superadmins
 pub async fn auth_superadmin(
-    SessionUser { uuid: account_uuid }: SessionUser,
-    req: Request,
+    RequestUser {
+        user: session_user,
+        api_token,
+    }: RequestUser,
+    mut req: Request,
     next: Next,
 ) -> ApiResult<Response> {
+    if let Some(api_token) = &api_token
+        && !api_token.allows(ApiTokenScope::SuperAdmin)
+    {
+        return Err(ApiError::unauthorized("Api token lacks the required scope"));
+    }
+
     let mut tx = Database::global().start_transaction().await?;
 
-    AdministrativeAccount::get_by_uuid(&mut tx, account_uuid)
+    let account = Account::get_by_uuid(&mut tx, session_user.uuid)
         .await?
         .ok_or(ApiError::server_error("Account not found"))?;
+    if !account.superadmin || account.locked {
+        return Err(ApiError::unauthorized("Account is not a superadmin"));
+    }
+
+    if *REQUIRE_2FA_SUPERADMINS && !session_user.has_second_factor() {
+        return Err(ApiError::unauthorized(
+            "Two-factor authentication is required",
+        ));
+    }
 
     tx.commit().await?;
 
+    req.extensions_mut().insert(ApiTokenScopeGranted);
+
     Ok(next.run(req).await)
 }
@@ -3,7 +3,9 @@
 pub use auth_club_admin::*;
 pub use auth_rate_limit::*;
 pub use auth_superadmin::*;
+pub use track_session::*;
 
 mod auth_club_admin;
 mod auth_rate_limit;
 mod auth_superadmin;
+mod track_session;
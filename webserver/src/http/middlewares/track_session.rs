@@ -0,0 +1,50 @@
+use galvyn::core::re_exports::axum::extract::Request;
+use galvyn::core::re_exports::axum::http::header;
+use galvyn::core::re_exports::axum::middleware::Next;
+use galvyn::core::re_exports::axum::response::Response;
+use galvyn::core::session::Session;
+use galvyn::core::stuff::api_error::ApiResult;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::http::extractors::client_ip::ClientIp;
+use crate::http::extractors::session_user::SESSION_USER;
+use crate::http::extractors::session_user::SessionUser;
+
+/// The key of the client information in the session.
+pub const SESSION_CLIENT: &str = "session_client";
+
+/// Information about the client using a session
+///
+/// This is shown to the user when listing their sessions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionClient {
+    /// The user agent of the client
+    pub user_agent: Option<String>,
+    /// The ip address of the client
+    pub ip: Option<String>,
+}
+
+/// Middleware recording the client of signed in sessions
+///
+/// The client's ip address is resolved using [`ClientIp`].
+pub async fn track_session(session: Session, req: Request, next: Next) -> ApiResult<Response> {
+    if session.get::<SessionUser>(SESSION_USER).await?.is_some() {
+        let client = SessionClient {
+            user_agent: req
+                .headers()
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            ip: ClientIp::from_request(req.headers(), req.extensions())
+                .0
+                .map(|ip| ip.to_string()),
+        };
+
+        if session.get::<SessionClient>(SESSION_CLIENT).await?.as_ref() != Some(&client) {
+            session.insert(SESSION_CLIENT, client).await?;
+        }
+    }
+
+    Ok(next.run(req).await)
+}
@@ -5,3 +5,4 @@ pub mod handler_auth;
 pub mod handler_frontend;
 pub mod middlewares;
 pub mod server;
+pub mod session_store;
@@ -4,17 +4,27 @@ use std::net::SocketAddr;
 
 use galvyn::RouterBuilder;
 use galvyn::core::GalvynRouter;
+use galvyn::core::re_exports::axum;
 use galvyn::error::GalvynError;
 use tower::ServiceBuilder;
-use tower_http::trace::DefaultMakeSpan;
 use tower_http::trace::DefaultOnResponse;
 use tower_http::trace::TraceLayer;
+use tower_sessions::Expiry;
+use tower_sessions::SessionManagerLayer;
+use tower_sessions::cookie::SameSite;
 use tracing::Level;
 
 use crate::config::LISTEN_ADDRESS;
 use crate::config::LISTEN_PORT;
+use crate::config::ORIGIN;
+use crate::http::extractors::client_ip::ClientIp;
 use crate::http::handler_auth;
 use crate::http::handler_frontend;
+use crate::http::middlewares;
+use crate::http::session_store::DatabaseSessionStore;
+
+/// Sessions expire after being unused for this duration
+const SESSION_INACTIVITY_TIMEOUT: time::Duration = time::Duration::days(14);
 
 /// Start the http server
 pub async fn run(mut router: RouterBuilder) -> Result<(), GalvynError> {
@@ -28,13 +38,36 @@ pub async fn run(mut router: RouterBuilder) -> Result<(), GalvynError> {
                 .nest("/auth", handler_auth::initialize()),
         )
         .layer(
-            ServiceBuilder::new().layer(
-                TraceLayer::new_for_http()
-                    .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
-                    .on_response(DefaultOnResponse::new().level(Level::INFO))
-                    // Disable automatic failure logger because any handler_frontend returning a 500 should have already logged its reason™
-                    .on_failure(()),
-            ),
+            ServiceBuilder::new()
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(|request: &axum::extract::Request| {
+                            let ClientIp(client_ip) =
+                                ClientIp::from_request(request.headers(), request.extensions());
+
+                            tracing::info_span!(
+                                "request",
+                                method = %request.method(),
+                                uri = %request.uri(),
+                                version = ?request.version(),
+                                client_ip = ?client_ip,
+                            )
+                        })
+                        .on_response(DefaultOnResponse::new().level(Level::INFO))
+                        // Disable automatic failure logger because any handler_frontend returning a 500 should have already logged its reason™
+                        .on_failure(()),
+                )
+                // Replaces galvyn's in-memory sessions, so sessions survive restarts
+                // and can be shared between replicas
+                .layer(
+                    SessionManagerLayer::new(DatabaseSessionStore)
+                        .with_name("bnv_session")
+                        // Lax is required for the OIDC flow, which is entered from other sites
+                        .with_same_site(SameSite::Lax)
+                        .with_secure(ORIGIN.scheme() == "https")
+                        .with_expiry(Expiry::OnInactivity(SESSION_INACTIVITY_TIMEOUT)),
+                )
+                .layer(axum::middleware::from_fn(middlewares::track_session)),
         );
 
     router.add_listener(addr, routes).start().await
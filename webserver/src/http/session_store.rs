@@ -0,0 +1,122 @@
+//! Session store persisting the sessions in the database
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use galvyn::core::Module;
+use galvyn::rorm::Database;
+use galvyn::rorm::fields::types::MaxStr;
+use tower_sessions::session::Id;
+use tower_sessions::session::Record;
+use tower_sessions::session_store;
+use tower_sessions::session_store::SessionStore;
+
+use crate::http::extractors::session_user::Impersonation;
+use crate::http::extractors::session_user::SESSION_IMPERSONATION;
+use crate::http::extractors::session_user::SESSION_USER;
+use crate::http::extractors::session_user::SessionUser;
+use crate::http::middlewares::SESSION_CLIENT;
+use crate::http::middlewares::SessionClient;
+use crate::models::session::SaveSessionParams;
+use crate::models::session::StoredSession;
+use crate::utils::text::truncate;
+
+/// Session store backed by the database
+///
+/// Expired sessions are removed by the [`GarbageCollector`](crate::modules::garbage_collector::GarbageCollector).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DatabaseSessionStore;
+
+#[async_trait]
+impl SessionStore for DatabaseSessionStore {
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        let mut tx = Database::global()
+            .start_transaction()
+            .await
+            .map_err(backend_error)?;
+
+        while StoredSession::exists(&mut tx, &record.id.to_string())
+            .await
+            .map_err(backend_error)?
+        {
+            record.id = Id::default();
+        }
+
+        StoredSession::create(&mut tx, &record.id.to_string(), save_params(record))
+            .await
+            .map_err(backend_error)?;
+
+        tx.commit().await.map_err(backend_error)?;
+
+        Ok(())
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        StoredSession::update(
+            Database::global(),
+            &record.id.to_string(),
+            save_params(record),
+        )
+        .await
+        .map_err(backend_error)?;
+
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &Id) -> session_store::Result<Option<Record>> {
+        Ok(
+            StoredSession::load_data(Database::global(), &session_id.to_string())
+                .await
+                .map_err(backend_error)?
+                .map(|(data, expiry_date)| Record {
+                    id: *session_id,
+                    data,
+                    expiry_date,
+                }),
+        )
+    }
+
+    async fn delete(&self, session_id: &Id) -> session_store::Result<()> {
+        StoredSession::delete_by_id(Database::global(), &session_id.to_string())
+            .await
+            .map_err(backend_error)?;
+
+        Ok(())
+    }
+}
+
+/// Extract the columns stored next to the session's data
+fn save_params(record: &Record) -> SaveSessionParams {
+    // An impersonated session stays the session of the superadmin
+    let account = get_value::<Impersonation>(&record.data, SESSION_IMPERSONATION)
+        .map(|impersonation| impersonation.impersonator.uuid)
+        .or_else(|| get_value::<SessionUser>(&record.data, SESSION_USER).map(|user| user.uuid));
+    let client = get_value::<SessionClient>(&record.data, SESSION_CLIENT).unwrap_or_default();
+
+    SaveSessionParams {
+        data: record.data.clone(),
+        expires_at: record.expiry_date,
+        account,
+        user_agent: client
+            .user_agent
+            .map(|x| truncate(&x, 255))
+            .map(MaxStr::new)
+            .and_then(Result::ok),
+        ip: client
+            .ip
+            .map(|x| truncate(&x, 64))
+            .map(MaxStr::new)
+            .and_then(Result::ok),
+    }
+}
+
+fn get_value<T: serde::de::DeserializeOwned>(
+    data: &HashMap<String, serde_json::Value>,
+    key: &str,
+) -> Option<T> {
+    serde_json::from_value(data.get(key)?.clone()).ok()
+}
+
+fn backend_error(error: impl std::fmt::Display) -> session_store::Error {
+    session_store::Error::Backend(error.to_string())
+}
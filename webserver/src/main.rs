@@ -9,6 +9,7 @@ use ::tracing::instrument;
 use clap::Parser;
 use galvyn::Galvyn;
 use galvyn::GalvynSetup;
+use galvyn::core::Module;
 use galvyn::core::modules::database::DatabaseSetup;
 use galvyn::rorm;
 use galvyn::rorm::Database;
@@ -29,7 +30,10 @@ use crate::models::invite::Invite;
 use crate::models::invite::InviteType;
 use crate::modules::garbage_collector::GarbageCollector;
 use crate::modules::mailcow::Mailcow;
+use crate::modules::mailer::Mailer;
 use crate::modules::oidc::Oidc;
+use crate::modules::password_policy::PasswordPolicy;
+use crate::modules::webauthn::WebAuthn;
 use crate::tracing::opentelemetry_layer;
 use crate::utils::import::import_data;
 use crate::utils::links::Link;
@@ -50,7 +54,10 @@ async fn start() -> Result<(), Box<dyn Error>> {
         )))
         .register_module::<GarbageCollector>(())
         .register_module::<Mailcow>(())
+        .register_module::<Mailer>(())
         .register_module::<Oidc>(())
+        .register_module::<PasswordPolicy>(())
+        .register_module::<WebAuthn>(())
         .init_modules()
         .await?;
 
@@ -186,6 +193,24 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 }
             };
         }
+        Command::Reconcile { repair } => {
+            Galvyn::builder(GalvynSetup::default())
+                .register_module::<Database>(DatabaseSetup::Custom(DatabaseConfiguration::new(
+                    DB.clone(),
+                )))
+                .register_module::<Mailcow>(())
+                .init_modules()
+                .await?;
+
+            let report = Mailcow::global().reconcile().await?;
+            println!("{}", serde_json::to_string_pretty(&report.issues)?);
+            println!("Found {} issues.", report.issues.len());
+
+            if !repair.is_empty() {
+                let outcomes = Mailcow::global().repair_categories(&repair).await?;
+                println!("{}", serde_json::to_string_pretty(&outcomes)?);
+            }
+        }
     }
 
     Ok(())
@@ -1,38 +1,82 @@
 use galvyn::rorm;
+use galvyn::rorm::and;
 use galvyn::rorm::db::Executor;
-use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
 use tracing::instrument;
+use uuid::Uuid;
 
 use crate::models::account::AccountUuid;
 use crate::models::account::ClubAdminAccount;
-use crate::models::account::db::ClubAdminAccountModel;
+use crate::models::account::db::AccountModel;
+use crate::models::account::db::ClubAdminModel;
+use crate::models::account::db::ClubAdminModelInsert;
+use crate::models::account::delete_if_without_roles;
 use crate::models::club::ClubUuid;
 
 impl ClubAdminAccount {
-    /// Retrieve the account by its uuid
-    #[instrument(name = "ClubAdminAccount::get_by_uuid", skip(exe))]
-    pub async fn get_by_uuid(
+    /// Retrieve all admin roles of an account
+    #[instrument(name = "ClubAdminAccount::find_all_by_account", skip(exe))]
+    pub async fn find_all_by_account(
         exe: impl Executor<'_>,
         uuid: AccountUuid,
-    ) -> anyhow::Result<Option<Self>> {
-        Ok(rorm::query(exe, ClubAdminAccountModel)
-            .condition(ClubAdminAccountModel.uuid.equals(uuid.0))
-            .optional()
-            .await?
-            .map(Self::from))
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(
+            exe,
+            (
+                ClubAdminModel,
+                ClubAdminModel.account.query_as(AccountModel),
+            ),
+        )
+        .condition(ClubAdminModel.account.equals(uuid.0))
+        .all()
+        .await?
+        .into_iter()
+        .map(Self::from)
+        .collect())
     }
 
-    /// Retrieve the account by its username
-    #[instrument(name = "ClubAdminAccount::get_by_username", skip(exe))]
-    pub async fn get_by_username(
+    /// Retrieve the admin role of an account in a specific club
+    #[instrument(name = "ClubAdminAccount::find_by_account_and_club", skip(exe))]
+    pub async fn find_by_account_and_club(
         exe: impl Executor<'_>,
-        username: &MaxStr<255>,
+        uuid: AccountUuid,
+        club: ClubUuid,
     ) -> anyhow::Result<Option<Self>> {
-        Ok(rorm::query(exe, ClubAdminAccountModel)
-            .condition(ClubAdminAccountModel.username.equals(username))
-            .optional()
-            .await?
-            .map(Self::from))
+        Ok(rorm::query(
+            exe,
+            (
+                ClubAdminModel,
+                ClubAdminModel.account.query_as(AccountModel),
+            ),
+        )
+        .condition(and![
+            ClubAdminModel.account.equals(uuid.0),
+            ClubAdminModel.club.equals(club.0),
+        ])
+        .optional()
+        .await?
+        .map(Self::from))
+    }
+
+    /// Grant an existing account the admin role in a club
+    ///
+    /// The caller has to ensure the account isn't an admin of the club already.
+    #[instrument(name = "ClubAdminAccount::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        uuid: AccountUuid,
+        club: ClubUuid,
+    ) -> anyhow::Result<()> {
+        rorm::insert(exe, ClubAdminModel)
+            .return_nothing()
+            .single(&ClubAdminModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(uuid.0),
+                club: ForeignModelByField(club.0),
+            })
+            .await?;
+
+        Ok(())
     }
 }
 
@@ -42,27 +86,37 @@ impl ClubAdminAccount {
         self.uuid
     }
 
-    /// Delete the club admin
+    /// Remove the admin role from the account
+    ///
+    /// The account is deleted as well if it doesn't hold any other role.
     #[instrument(skip(self, exe), name = "ClubAdminAccount::delete")]
     pub async fn delete(self, exe: impl Executor<'_>) -> anyhow::Result<()> {
-        rorm::delete(exe, ClubAdminAccountModel)
-            .condition(ClubAdminAccountModel.uuid.equals(self.uuid.0))
+        let mut guard = exe.ensure_transaction().await?;
+
+        rorm::delete(guard.get_transaction(), ClubAdminModel)
+            .condition(and![
+                ClubAdminModel.account.equals(self.uuid.0),
+                ClubAdminModel.club.equals(self.club.0),
+            ])
             .await?;
 
+        delete_if_without_roles(guard.get_transaction(), self.uuid).await?;
+
+        guard.commit().await?;
         Ok(())
     }
 }
 
-impl From<ClubAdminAccountModel> for ClubAdminAccount {
-    fn from(value: ClubAdminAccountModel) -> Self {
+impl From<(ClubAdminModel, AccountModel)> for ClubAdminAccount {
+    fn from((admin, account): (ClubAdminModel, AccountModel)) -> Self {
         Self {
-            uuid: AccountUuid(value.uuid),
-            display_name: value.display_name,
-            username: value.username.0,
-            club: ClubUuid(value.club.0),
-            modified_at: value.modified_at,
-            created_at: value.created_at,
-            hashed_password: value.hashed_password,
+            uuid: AccountUuid(account.uuid),
+            display_name: account.display_name,
+            username: account.username.0,
+            club: ClubUuid(admin.club.0),
+            domain_admin_synced: account.domain_admin_synced,
+            modified_at: account.modified_at,
+            created_at: admin.created_at,
         }
     }
 }
@@ -1,56 +1,106 @@
+use std::collections::HashSet;
+
 use galvyn::rorm;
+use galvyn::rorm::and;
 use galvyn::rorm::db::Executor;
 use galvyn::rorm::fields::types::MaxStr;
 use galvyn::rorm::prelude::ForeignModelByField;
+use time::OffsetDateTime;
 use tracing::instrument;
 use uuid::Uuid;
 
 use crate::models::account::AccountUuid;
 use crate::models::account::ClubAccount;
 use crate::models::account::CreateManualClubMember;
-use crate::models::account::db::ClubAccountModel;
-use crate::models::account::db::ClubAccountModelInsert;
+use crate::models::account::db::AccountModel;
+use crate::models::account::db::AccountModelInsert;
+use crate::models::account::db::ClubAdminModel;
+use crate::models::account::db::ClubMemberModel;
+use crate::models::account::db::ClubMemberModelInsert;
 use crate::models::account::db::UsernameModel;
+use crate::models::account::delete_if_without_roles;
 use crate::models::club::ClubUuid;
 
 impl ClubAccount {
-    /// Get the account by its uuid
+    /// Get the member by the uuid of its account
     #[instrument(name = "ClubAccount::get_by_uuid", skip(exe))]
     pub async fn get_by_uuid(
         exe: impl Executor<'_>,
         uuid: AccountUuid,
     ) -> anyhow::Result<Option<Self>> {
-        Ok(rorm::query(exe, ClubAccountModel)
-            .condition(ClubAccountModel.uuid.equals(uuid.0))
-            .optional()
-            .await?
-            .map(Self::from))
-    }
-
-    /// Get the account by its username
-    #[instrument(name = "ClubAccount::get_by_username", skip(exe))]
-    pub async fn get_by_username(
-        exe: impl Executor<'_>,
-        username: &MaxStr<255>,
-    ) -> anyhow::Result<Option<Self>> {
-        Ok(rorm::query(exe, ClubAccountModel)
-            .condition(ClubAccountModel.username.equals(username))
-            .optional()
-            .await?
-            .map(Self::from))
+        Ok(rorm::query(
+            exe,
+            (
+                ClubMemberModel,
+                ClubMemberModel.account.query_as(AccountModel),
+            ),
+        )
+        .condition(ClubMemberModel.account.equals(uuid.0))
+        .optional()
+        .await?
+        .map(Self::from))
     }
 
-    /// Get the account by its email
+    /// Get the member by its email
     #[instrument(name = "ClubAccount::get_by_email", skip(exe))]
     pub async fn get_by_email(
         exe: impl Executor<'_>,
         email: &MaxStr<255>,
     ) -> anyhow::Result<Option<Self>> {
-        Ok(rorm::query(exe, ClubAccountModel)
-            .condition(ClubAccountModel.email.equals(email))
-            .optional()
+        Ok(rorm::query(
+            exe,
+            (
+                ClubMemberModel,
+                ClubMemberModel.account.query_as(AccountModel),
+            ),
+        )
+        .condition(ClubMemberModel.email.equals(email))
+        .optional()
+        .await?
+        .map(Self::from))
+    }
+
+    /// Retrieve all members whose membership expired but which aren't locked yet
+    ///
+    /// Members holding an admin role are returned separately as the second list.
+    /// Their roles don't depend on the membership, so only the membership ends.
+    #[instrument(name = "ClubAccount::find_expired", skip(exe))]
+    pub async fn find_expired(exe: impl Executor<'_>) -> anyhow::Result<(Vec<Self>, Vec<Self>)> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let expired = rorm::query(
+            guard.get_transaction(),
+            (
+                ClubMemberModel,
+                ClubMemberModel.account.query_as(AccountModel),
+            ),
+        )
+        .condition(and![
+            ClubMemberModel
+                .valid_until
+                .less_than(Some(OffsetDateTime::now_utc())),
+            ClubMemberModel.account.locked.equals(false),
+        ])
+        .all()
+        .await?;
+
+        let club_admins: HashSet<_> = rorm::query(guard.get_transaction(), ClubAdminModel.account)
+            .all()
             .await?
-            .map(Self::from))
+            .into_iter()
+            .map(|account| account.0)
+            .collect();
+
+        guard.commit().await?;
+
+        let (members, admins): (Vec<_>, Vec<_>) = expired
+            .into_iter()
+            .partition(|(_, account)| !account.superadmin && !club_admins.contains(&account.uuid));
+
+        Ok((
+            members.into_iter().map(Self::from).collect(),
+            admins.into_iter().map(Self::from).collect(),
+        ))
     }
 
     /// Create a new club account from the provided input
@@ -74,19 +124,27 @@ impl ClubAccount {
             })
             .await?;
 
-        let model = rorm::insert(guard.get_transaction(), ClubAccountModel)
-            .single(&ClubAccountModelInsert {
+        let account = rorm::insert(guard.get_transaction(), AccountModel)
+            .single(&AccountModelInsert {
                 uuid: Uuid::new_v4(),
                 username: ForeignModelByField(username.username),
                 display_name: new_member.display_name,
                 hashed_password: new_member.hashed_password,
-                email: new_member.email,
+                superadmin: false,
+            })
+            .await?;
+
+        let member = rorm::insert(guard.get_transaction(), ClubMemberModel)
+            .single(&ClubMemberModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(account.uuid),
                 club: ForeignModelByField(new_member.club.0),
+                email: new_member.email,
             })
             .await?;
 
         guard.commit().await?;
-        Ok(Self::from(model))
+        Ok(Self::from((member, account)))
     }
 }
 
@@ -96,12 +154,20 @@ impl ClubAccount {
         self.uuid
     }
 
-    /// Delete a club member account
+    /// Remove the account from its club
+    ///
+    /// The account is deleted as well if it doesn't hold any other role.
     #[instrument(name = "ClubAccount::delete", skip(self, exe))]
     pub async fn delete(self, exe: impl Executor<'_>) -> anyhow::Result<()> {
-        rorm::delete(exe, ClubAccountModel)
-            .condition(ClubAccountModel.uuid.equals(self.uuid.0))
+        let mut guard = exe.ensure_transaction().await?;
+
+        rorm::delete(guard.get_transaction(), ClubMemberModel)
+            .condition(ClubMemberModel.account.equals(self.uuid.0))
             .await?;
+
+        delete_if_without_roles(guard.get_transaction(), self.uuid).await?;
+
+        guard.commit().await?;
         Ok(())
     }
 
@@ -110,6 +176,44 @@ impl ClubAccount {
         self.hashed_password.clone()
     }
 
+    /// Set the point in time the membership ends
+    #[instrument(name = "ClubAccount::set_valid_until", skip(self, exe))]
+    pub async fn set_valid_until(
+        &mut self,
+        exe: impl Executor<'_>,
+        valid_until: Option<OffsetDateTime>,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, ClubMemberModel)
+            .set(ClubMemberModel.valid_until, valid_until)
+            .condition(ClubMemberModel.account.equals(self.uuid.0))
+            .await?;
+
+        self.valid_until = valid_until;
+
+        Ok(())
+    }
+
+    /// Change the address of the member's mailbox
+    ///
+    /// App passwords belong to the mailbox, so the member has none for the new address.
+    #[instrument(name = "ClubAccount::set_email", skip(self, exe))]
+    pub async fn set_email(
+        &mut self,
+        exe: impl Executor<'_>,
+        email: MaxStr<255>,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, ClubMemberModel)
+            .set(ClubMemberModel.email, email.clone())
+            .set(ClubMemberModel.has_app_password, false)
+            .condition(ClubMemberModel.account.equals(self.uuid.0))
+            .await?;
+
+        self.email = email;
+        self.has_app_password = false;
+
+        Ok(())
+    }
+
     /// Update the has_app_password flag of the account
     #[instrument(name = "ClubAccount::update_has_app_password_set", skip(self, exe))]
     pub async fn update_has_app_password_set(
@@ -117,9 +221,9 @@ impl ClubAccount {
         exe: impl Executor<'_>,
         has_password: bool,
     ) -> anyhow::Result<()> {
-        rorm::update(exe, ClubAccountModel)
-            .set(ClubAccountModel.has_app_password, has_password)
-            .condition(ClubAccountModel.uuid.equals(self.uuid.0))
+        rorm::update(exe, ClubMemberModel)
+            .set(ClubMemberModel.has_app_password, has_password)
+            .condition(ClubMemberModel.account.equals(self.uuid.0))
             .await?;
 
         self.has_app_password = has_password;
@@ -128,18 +232,20 @@ impl ClubAccount {
     }
 }
 
-impl From<ClubAccountModel> for ClubAccount {
-    fn from(value: ClubAccountModel) -> Self {
+impl From<(ClubMemberModel, AccountModel)> for ClubAccount {
+    fn from((member, account): (ClubMemberModel, AccountModel)) -> Self {
         Self {
-            uuid: AccountUuid(value.uuid),
-            display_name: value.display_name,
-            username: value.username.0,
-            email: value.email,
-            club: ClubUuid(value.club.0),
-            modified_at: value.modified_at,
-            created_at: value.created_at,
-            has_app_password: value.has_app_password,
-            hashed_password: value.hashed_password,
+            uuid: AccountUuid(account.uuid),
+            display_name: account.display_name,
+            username: account.username.0,
+            email: member.email,
+            club: ClubUuid(member.club.0),
+            modified_at: account.modified_at,
+            created_at: member.created_at,
+            has_app_password: member.has_app_password,
+            locked: account.locked,
+            valid_until: member.valid_until,
+            hashed_password: account.hashed_password,
         }
     }
 }
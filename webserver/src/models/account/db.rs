@@ -1,14 +1,19 @@
 use galvyn::rorm::Model;
 use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::Json;
 use galvyn::rorm::fields::types::MaxStr;
 use galvyn::rorm::prelude::ForeignModel;
 use uuid::Uuid;
 
 use crate::models::club::db::ClubModel;
 
+/// The login data of a person
+///
+/// The permissions are granted by the superadmin flag and the role memberships
+/// in [`ClubMemberModel`] and [`ClubAdminModel`].
 #[derive(Debug, Model)]
-#[rorm(rename = "AdministrativeAccount")]
-pub struct AdministrativeAccountModel {
+#[rorm(rename = "Account")]
+pub struct AccountModel {
     #[rorm(primary_key)]
     pub uuid: Uuid,
     #[rorm(unique, on_update = "Cascade", on_delete = "Cascade")]
@@ -17,25 +22,26 @@ pub struct AdministrativeAccountModel {
 
     pub hashed_password: MaxStr<255>,
 
-    #[rorm(auto_create_time, auto_update_time)]
-    pub modified_at: time::OffsetDateTime,
-    #[rorm(auto_create_time)]
-    pub created_at: time::OffsetDateTime,
-}
+    /// Base32 encoded TOTP secret, set when the account has enrolled TOTP
+    pub totp_secret: Option<MaxStr<64>>,
+    /// The last TOTP time step that was used, to prevent replaying codes
+    pub totp_last_used_step: Option<i64>,
+    /// Hashes of the unused recovery codes
+    pub totp_recovery_codes: Option<Json<Vec<String>>>,
 
-#[derive(Debug, Model)]
-#[rorm(rename = "ClubAdminAccount")]
-pub struct ClubAdminAccountModel {
-    #[rorm(primary_key)]
-    pub uuid: Uuid,
-    #[rorm(unique, on_update = "Cascade", on_delete = "Cascade")]
-    pub username: ForeignModel<UsernameModel>,
-    pub display_name: MaxStr<255>,
+    /// Whether the account has superadmin privileges
+    #[rorm(default = "false")]
+    pub superadmin: bool,
 
-    pub hashed_password: MaxStr<255>,
+    /// Whether the account is locked and can't sign in
+    #[rorm(default = "false")]
+    pub locked: bool,
 
-    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
-    pub club: ForeignModel<ClubModel>,
+    /// Whether the mailcow domain admin of the account matches its password
+    ///
+    /// Only meaningful for club admins.
+    #[rorm(default = "true")]
+    pub domain_admin_synced: bool,
 
     #[rorm(auto_create_time, auto_update_time)]
     pub modified_at: time::OffsetDateTime,
@@ -43,29 +49,49 @@ pub struct ClubAdminAccountModel {
     pub created_at: time::OffsetDateTime,
 }
 
+/// Membership of an account in a club
+///
+/// An account can be a member of a single club only, as the membership owns the mailbox.
 #[derive(Debug, Model)]
-#[rorm(rename = "ClubAccount")]
-pub struct ClubAccountModel {
+#[rorm(rename = "ClubMember")]
+pub struct ClubMemberModel {
     #[rorm(primary_key)]
     pub uuid: Uuid,
-
     #[rorm(unique, on_update = "Cascade", on_delete = "Cascade")]
-    pub username: ForeignModel<UsernameModel>,
-    pub display_name: MaxStr<255>,
+    pub account: ForeignModel<AccountModel>,
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub club: ForeignModel<ClubModel>,
+
     #[rorm(unique)]
     pub email: MaxStr<255>,
 
-    pub hashed_password: MaxStr<255>,
-
     /// Whether the account already has an app password set.
     #[rorm(default = "false")]
     pub has_app_password: bool,
 
+    /// The point in time the membership ends
+    ///
+    /// Expired memberships are locked automatically.
+    pub valid_until: Option<time::OffsetDateTime>,
+
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+/// Administrative role of an account in a club
+///
+/// The combination of `account` and `club` is unique,
+/// the index is created by the `0017_club_admin_unique` migration.
+#[derive(Debug, Model)]
+#[rorm(rename = "ClubAdmin")]
+pub struct ClubAdminModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
     #[rorm(on_update = "Cascade", on_delete = "Cascade")]
     pub club: ForeignModel<ClubModel>,
 
-    #[rorm(auto_create_time, auto_update_time)]
-    pub modified_at: time::OffsetDateTime,
     #[rorm(auto_create_time)]
     pub created_at: time::OffsetDateTime,
 }
@@ -78,31 +104,28 @@ pub struct UsernameModel {
 }
 
 #[derive(Debug, Patch)]
-#[rorm(model = "AdministrativeAccountModel")]
-pub struct AdministrativeAccountModelInsert {
+#[rorm(model = "AccountModel")]
+pub struct AccountModelInsert {
     pub uuid: Uuid,
     pub username: ForeignModel<UsernameModel>,
     pub display_name: MaxStr<255>,
     pub hashed_password: MaxStr<255>,
+    pub superadmin: bool,
 }
 
 #[derive(Debug, Patch)]
-#[rorm(model = "ClubAdminAccountModel")]
-pub struct ClubAdminAccountModelInsert {
+#[rorm(model = "ClubMemberModel")]
+pub struct ClubMemberModelInsert {
     pub uuid: Uuid,
-    pub username: ForeignModel<UsernameModel>,
-    pub display_name: MaxStr<255>,
-    pub hashed_password: MaxStr<255>,
+    pub account: ForeignModel<AccountModel>,
     pub club: ForeignModel<ClubModel>,
+    pub email: MaxStr<255>,
 }
 
 #[derive(Debug, Patch)]
-#[rorm(model = "ClubAccountModel")]
-pub struct ClubAccountModelInsert {
+#[rorm(model = "ClubAdminModel")]
+pub struct ClubAdminModelInsert {
     pub uuid: Uuid,
-    pub username: ForeignModel<UsernameModel>,
-    pub display_name: MaxStr<255>,
-    pub hashed_password: MaxStr<255>,
-    pub email: MaxStr<255>,
+    pub account: ForeignModel<AccountModel>,
     pub club: ForeignModel<ClubModel>,
 }
@@ -1,7 +1,7 @@
 //! Accounts are the login-related models of this platform.
 //!
-//! They are attached to the related models that grant access to clubs or the super administrative
-//! users.
+//! Every person has a single account. The access to clubs and the super administrative
+//! functions is granted by the roles attached to it.
 
 use galvyn::core::re_exports::schemars;
 use galvyn::core::re_exports::schemars::JsonSchema;
@@ -17,34 +17,63 @@ use time::OffsetDateTime;
 use tracing::instrument;
 use uuid::Uuid;
 
-use crate::models::account::db::AdministrativeAccountModel;
-use crate::models::account::db::ClubAccountModel;
-use crate::models::account::db::ClubAdminAccountModel;
+use crate::models::account::db::AccountModel;
+use crate::models::account::db::ClubAdminModel;
+use crate::models::account::db::ClubMemberModel;
+use crate::models::account::totp::AccountTotp;
 use crate::models::club::ClubUuid;
 use crate::models::club::db::ClubModel;
 use crate::models::credential_reset::CredentialReset;
 use crate::models::credential_reset::CredentialResetUuid;
-use crate::models::credential_reset::db::CredentialResetClubAccountModel;
-use crate::models::credential_reset::db::CredentialResetClubAdminModel;
-use crate::models::credential_reset::db::CredentialResetSuperadminModel;
+use crate::models::credential_reset::db::CredentialResetModel;
 use crate::models::credential_reset::generate_code;
 
 mod club_admin;
 mod club_member;
 pub(in crate::models) mod db;
 mod superadmin;
+mod totp;
 
-/// Helper for unifying the different account types
-pub enum Account {
-    /// Member of a club
-    ClubMember(ClubAccount),
-    /// Admin of a club
-    ClubAdmin(ClubAdminAccount),
-    /// Superadmin
-    Superadmin(AdministrativeAccount),
+pub use totp::TotpEnrollment;
+
+/// An account of a person together with the roles it holds
+pub struct Account {
+    /// Primary key of the account
+    uuid: AccountUuid,
+    /// Name to be used for displaying purposes
+    pub display_name: MaxStr<255>,
+    /// The username that should be used for logging in
+    pub username: MaxStr<255>,
+    /// Whether the account has superadmin privileges
+    pub superadmin: bool,
+    /// Whether the account is locked and can't sign in
+    pub locked: bool,
+    /// The membership in a club, if the account is a club member
+    pub membership: Option<ClubMembership>,
+    /// The clubs the account is an admin of
+    pub admin_of: Vec<ClubUuid>,
+    /// Whether the mailcow domain admin of the account matches its password
+    pub domain_admin_synced: bool,
+    /// The last point in time the account was modified
+    pub modified_at: OffsetDateTime,
+    /// The point in time the account was created
+    pub created_at: OffsetDateTime,
+    hashed_password: MaxStr<255>,
+    totp: Option<AccountTotp>,
+}
+
+/// The membership of an account in a club
+#[derive(Debug, Clone)]
+pub struct ClubMembership {
+    /// The club the account is a member of
+    pub club: ClubUuid,
+    /// Mailbox of the member
+    pub email: MaxStr<255>,
+    /// The point in time the membership ends
+    pub valid_until: Option<OffsetDateTime>,
 }
 
-/// Representation of the login data without any permission attached to it
+/// A superadmin account
 pub struct AdministrativeAccount {
     /// Primary key of the account
     uuid: AccountUuid,
@@ -52,14 +81,15 @@ pub struct AdministrativeAccount {
     pub display_name: MaxStr<255>,
     /// The username that should be used for logging in
     pub username: MaxStr<255>,
+    /// Whether the account is locked and can't sign in
+    pub locked: bool,
     /// The last point in time the account was modified
     pub modified_at: time::OffsetDateTime,
     /// The point in time the account was created
     pub created_at: time::OffsetDateTime,
-    hashed_password: MaxStr<255>,
 }
 
-/// Representation of the login data without any permission attached to it
+/// An account in its role as admin of a club
 pub struct ClubAdminAccount {
     /// Primary key of the account
     uuid: AccountUuid,
@@ -69,14 +99,16 @@ pub struct ClubAdminAccount {
     pub username: MaxStr<255>,
     /// The club this account is an admin for
     pub club: ClubUuid,
+    /// Whether the mailcow domain admin of the account matches its password
+    pub domain_admin_synced: bool,
     /// The last point in time the account was modified
     pub modified_at: time::OffsetDateTime,
-    /// The point in time the account was created
+    /// The point in time the account became an admin of the club
     pub created_at: time::OffsetDateTime,
-    hashed_password: MaxStr<255>,
 }
 
-/// Representation of the login data without any permission attached to it
+/// An account in its role as member of a club
+#[derive(Clone)]
 pub struct ClubAccount {
     /// Primary key of the account
     uuid: AccountUuid,
@@ -90,18 +122,64 @@ pub struct ClubAccount {
     pub club: ClubUuid,
     /// The last point in time the account was modified
     pub modified_at: OffsetDateTime,
-    /// The point in time the account was created
+    /// The point in time the account joined the club
     pub created_at: OffsetDateTime,
     /// Whether the account already has an app password set.
     pub has_app_password: bool,
+    /// Whether the account is locked and can't sign in
+    pub locked: bool,
+    /// The point in time the membership ends
+    pub valid_until: Option<OffsetDateTime>,
     hashed_password: MaxStr<255>,
 }
 
+/// Methods an account can authenticate with
+///
+/// The serialized values are the ones registered for the `amr` claim in RFC 8176.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum AuthenticationMethod {
+    /// Password based authentication
+    #[serde(rename = "pwd")]
+    Password,
+    /// One-time password, either TOTP or a recovery code
+    #[serde(rename = "otp")]
+    Otp,
+    /// Passkey with user verification
+    #[serde(rename = "hwk")]
+    Passkey,
+}
+
+impl AuthenticationMethod {
+    /// The `acr` value for a set of used authentication methods
+    pub fn acr(methods: &[AuthenticationMethod]) -> &'static str {
+        if AuthenticationMethod::is_multi_factor(methods) {
+            ACR_MULTI_FACTOR
+        } else {
+            ACR_SINGLE_FACTOR
+        }
+    }
+
+    /// Whether a set of used authentication methods counts as multi-factor authentication
+    ///
+    /// Passkeys require user verification and count as multi-factor on their own.
+    pub fn is_multi_factor(methods: &[AuthenticationMethod]) -> bool {
+        methods
+            .iter()
+            .any(|x| matches!(x, AuthenticationMethod::Otp | AuthenticationMethod::Passkey))
+    }
+}
+
+/// `acr` value of a session authenticated with a single factor
+pub const ACR_SINGLE_FACTOR: &str = "urn:bnv-manager:acr:sfa";
+
+/// `acr` value of a session authenticated with multiple factors
+pub const ACR_MULTI_FACTOR: &str = "urn:bnv-manager:acr:mfa";
+
 /// New-type for the account's primary key
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize, JsonSchema)]
 pub struct AccountUuid(pub Uuid);
 
-/// Helper to create new club members, used for [`ClubAccountModelInsert`].
+/// Helper to create new club members, used for [`ClubAccount::create_raw`].
 /// It should only be necessary to use this in maintenance features like data imports.
 #[derive(Debug, Clone)]
 pub struct CreateManualClubMember {
@@ -111,7 +189,7 @@ pub struct CreateManualClubMember {
     pub display_name: MaxStr<255>,
     /// Hashed password of the new club member, must be in bcrypt format
     pub hashed_password: MaxStr<255>,
-    /// E-mail address of the new club member, must end with the primary domain of the club
+    /// E-mail address of the new club member, must be on one of the domains of the club
     pub email: MaxStr<255>,
     /// Referenced club the new user should belong to
     pub club: ForeignModel<ClubModel>,
@@ -126,19 +204,14 @@ impl Account {
     ) -> anyhow::Result<Option<Self>> {
         let mut guard = exe.ensure_transaction().await?;
 
-        let mut account = None;
-
-        if let Some(club_member) = ClubAccount::get_by_uuid(guard.get_transaction(), uuid).await? {
-            account = Some(Account::ClubMember(club_member));
-        } else if let Some(club_admin) =
-            ClubAdminAccount::get_by_uuid(guard.get_transaction(), uuid).await?
-        {
-            account = Some(Account::ClubAdmin(club_admin));
-        } else if let Some(admin) =
-            AdministrativeAccount::get_by_uuid(guard.get_transaction(), uuid).await?
-        {
-            account = Some(Account::Superadmin(admin));
-        }
+        let model = rorm::query(guard.get_transaction(), AccountModel)
+            .condition(AccountModel.uuid.equals(uuid.0))
+            .optional()
+            .await?;
+        let account = match model {
+            Some(model) => Some(Self::with_roles(guard.get_transaction(), model).await?),
+            None => None,
+        };
 
         guard.commit().await?;
 
@@ -153,78 +226,180 @@ impl Account {
     ) -> anyhow::Result<Option<Self>> {
         let mut guard = exe.ensure_transaction().await?;
 
-        let mut account = None;
-
-        if let Some(club_member) =
-            ClubAccount::get_by_username(guard.get_transaction(), username).await?
-        {
-            account = Some(Account::ClubMember(club_member));
-        } else if let Some(club_admin) =
-            ClubAdminAccount::get_by_username(guard.get_transaction(), username).await?
-        {
-            account = Some(Account::ClubAdmin(club_admin));
-        } else if let Some(admin) =
-            AdministrativeAccount::get_by_username(guard.get_transaction(), username).await?
-        {
-            account = Some(Account::Superadmin(admin));
-        }
+        let model = rorm::query(guard.get_transaction(), AccountModel)
+            .condition(AccountModel.username.equals(username))
+            .optional()
+            .await?;
+        let account = match model {
+            Some(model) => Some(Self::with_roles(guard.get_transaction(), model).await?),
+            None => None,
+        };
 
         guard.commit().await?;
 
         Ok(account)
     }
 
-    /// Hash a password
-    #[instrument(name = "Account::hash_password", skip_all)]
-    pub fn hash_password(password: &MaxStr<72>) -> anyhow::Result<String> {
-        Ok(bcrypt::hash(password.as_bytes(), 12)?)
+    /// Retrieve the account by the mailbox of its club membership
+    #[instrument(name = "Account::get_by_email", skip(exe))]
+    pub async fn get_by_email(
+        exe: impl Executor<'_>,
+        email: &MaxStr<255>,
+    ) -> anyhow::Result<Option<Self>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let model = rorm::query(
+            guard.get_transaction(),
+            ClubMemberModel.account.query_as(AccountModel),
+        )
+        .condition(ClubMemberModel.email.equals(email))
+        .optional()
+        .await?;
+        let account = match model {
+            Some(model) => Some(Self::with_roles(guard.get_transaction(), model).await?),
+            None => None,
+        };
+
+        guard.commit().await?;
+
+        Ok(account)
     }
 
-    /// Update the display name of an account
-    #[instrument(name = "Account::update_display_name", skip(self, exe))]
-    pub async fn update_display_name(
-        &mut self,
-        exe: impl Executor<'_>,
-        display_name: MaxStr<255>,
-    ) -> anyhow::Result<()> {
-        match self {
-            Account::ClubMember(club_member) => {
-                rorm::update(exe, ClubAccountModel)
-                    .set(ClubAccountModel.display_name, display_name)
-                    .condition(ClubAccountModel.uuid.equals(club_member.uuid.0))
-                    .await?;
-            }
-            Account::ClubAdmin(club_admin) => {
-                rorm::update(exe, ClubAdminAccountModel)
-                    .set(ClubAdminAccountModel.display_name, display_name)
-                    .condition(ClubAdminAccountModel.uuid.equals(club_admin.uuid.0))
-                    .await?;
-            }
-            Account::Superadmin(super_admin) => {
-                rorm::update(exe, AdministrativeAccountModel)
-                    .set(AdministrativeAccountModel.display_name, display_name)
-                    .condition(AdministrativeAccountModel.uuid.equals(super_admin.uuid.0))
-                    .await?;
+    /// Load the roles of an account
+    async fn with_roles(exe: impl Executor<'_>, model: AccountModel) -> anyhow::Result<Self> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let membership = rorm::query(guard.get_transaction(), ClubMemberModel)
+            .condition(ClubMemberModel.account.equals(model.uuid))
+            .optional()
+            .await?
+            .map(|member| ClubMembership {
+                club: ClubUuid(member.club.0),
+                email: member.email,
+                valid_until: member.valid_until,
+            });
+
+        let admin_of = rorm::query(guard.get_transaction(), ClubAdminModel.club)
+            .condition(ClubAdminModel.account.equals(model.uuid))
+            .order_asc(ClubAdminModel.created_at)
+            .all()
+            .await?
+            .into_iter()
+            .map(|club| ClubUuid(club.0))
+            .collect();
+
+        guard.commit().await?;
+
+        Ok(Self {
+            uuid: AccountUuid(model.uuid),
+            display_name: model.display_name,
+            username: model.username.0,
+            superadmin: model.superadmin,
+            locked: model.locked,
+            membership,
+            admin_of,
+            domain_admin_synced: model.domain_admin_synced,
+            modified_at: model.modified_at,
+            created_at: model.created_at,
+            hashed_password: model.hashed_password,
+            totp: AccountTotp::from_fields(
+                model.totp_secret,
+                model.totp_last_used_step,
+                model.totp_recovery_codes,
+            ),
+        })
+    }
+
+    /// Retrieve the uuid of the account
+    pub fn uuid(&self) -> AccountUuid {
+        self.uuid
+    }
+
+    /// Retrieve the username of the account
+    pub fn username(&self) -> &MaxStr<255> {
+        &self.username
+    }
+
+    /// Retrieve the display name of the account
+    pub fn display_name(&self) -> &MaxStr<255> {
+        &self.display_name
+    }
+
+    /// Retrieve the address notifications for the account are sent to
+    ///
+    /// Only club members have a mailbox, other accounts can't be notified.
+    pub fn email(&self) -> Option<&MaxStr<255>> {
+        self.membership.as_ref().map(|membership| &membership.email)
+    }
+
+    /// Retrieve the hashed password of the account
+    pub fn hashed_password(&self) -> MaxStr<255> {
+        self.hashed_password.clone()
+    }
+
+    /// Whether the account is locked
+    ///
+    /// Expired memberships of accounts without an admin role are locked by a worker,
+    /// this covers the time in between. The admin roles don't depend on the membership,
+    /// so an expired membership doesn't lock an admin.
+    pub fn is_locked(&self) -> bool {
+        self.locked || (self.membership_expired() && !self.superadmin && self.admin_of.is_empty())
+    }
+
+    /// Whether the membership's mailbox and OIDC access are locked
+    pub fn is_membership_locked(&self) -> bool {
+        self.locked || self.membership_expired()
+    }
+
+    /// Whether the account's membership has expired
+    fn membership_expired(&self) -> bool {
+        self.membership
+            .as_ref()
+            .and_then(|membership| membership.valid_until)
+            .is_some_and(|valid_until| valid_until < OffsetDateTime::now_utc())
+    }
+
+    /// Whether the account is an admin of the club
+    pub fn is_admin_of(&self, club: ClubUuid) -> bool {
+        self.admin_of.contains(&club)
+    }
+
+    /// All clubs the account holds a role in
+    ///
+    /// The club the account is a member of comes first.
+    pub fn clubs(&self) -> Vec<ClubUuid> {
+        let mut clubs: Vec<ClubUuid> = self
+            .membership
+            .iter()
+            .map(|membership| membership.club)
+            .collect();
+        for club in &self.admin_of {
+            if !clubs.contains(club) {
+                clubs.push(*club);
             }
         }
+        clubs
+    }
 
-        Ok(())
+    /// The club a new session of the account acts in
+    pub fn default_club(&self) -> Option<ClubUuid> {
+        self.clubs().first().copied()
+    }
+
+    /// Hash a password
+    #[instrument(name = "Account::hash_password", skip_all)]
+    pub fn hash_password(password: &MaxStr<72>) -> anyhow::Result<String> {
+        Ok(bcrypt::hash(password.as_bytes(), 12)?)
     }
 
     /// Check a password
     #[instrument(name = "Account::check_password", skip_all)]
     pub fn check_password(&self, password: &MaxStr<72>) -> anyhow::Result<bool> {
-        let hashed_password = match self {
-            Account::ClubMember(club_member) => &*club_member.hashed_password,
-            Account::ClubAdmin(club_admin) => &*club_admin.hashed_password,
-            Account::Superadmin(superadmin) => &*superadmin.hashed_password,
-        };
-
-        Ok(bcrypt::verify(&**password, hashed_password)?)
+        Ok(bcrypt::verify(&**password, &self.hashed_password)?)
     }
 
     /// Set a new password for an account
-    #[instrument(name = "Account::check_password", skip_all)]
+    #[instrument(name = "Account::set_password", skip_all)]
     pub async fn set_password(
         &mut self,
         exe: impl Executor<'_>,
@@ -232,32 +407,44 @@ impl Account {
     ) -> anyhow::Result<()> {
         let hashed = MaxStr::new(Account::hash_password(password)?)?;
 
-        match self {
-            Account::ClubMember(club_member) => {
-                rorm::update(exe, ClubAccountModel)
-                    .set(ClubAccountModel.hashed_password, hashed.clone())
-                    .condition(ClubAccountModel.uuid.equals(club_member.uuid.0))
-                    .await?;
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.hashed_password, hashed.clone())
+            .condition(AccountModel.uuid.equals(self.uuid.0))
+            .await?;
 
-                club_member.hashed_password = hashed;
-            }
-            Account::ClubAdmin(club_admin) => {
-                rorm::update(exe, ClubAdminAccountModel)
-                    .set(ClubAdminAccountModel.hashed_password, hashed.clone())
-                    .condition(ClubAdminAccountModel.uuid.equals(club_admin.uuid.0))
-                    .await?;
+        self.hashed_password = hashed;
 
-                club_admin.hashed_password = hashed;
-            }
-            Account::Superadmin(superadmin) => {
-                rorm::update(exe, AdministrativeAccountModel)
-                    .set(AdministrativeAccountModel.hashed_password, hashed.clone())
-                    .condition(AdministrativeAccountModel.uuid.equals(superadmin.uuid.0))
-                    .await?;
+        Ok(())
+    }
 
-                superadmin.hashed_password = hashed;
-            }
-        }
+    /// Lock or unlock the account
+    ///
+    /// Locked accounts can't sign in anymore.
+    #[instrument(name = "Account::set_locked", skip(self, exe))]
+    pub async fn set_locked(&mut self, exe: impl Executor<'_>, locked: bool) -> anyhow::Result<()> {
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.locked, locked)
+            .condition(AccountModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        self.locked = locked;
+
+        Ok(())
+    }
+
+    /// Update whether the mailcow domain admin of the account matches its password
+    #[instrument(name = "Account::update_domain_admin_synced", skip(self, exe))]
+    pub async fn update_domain_admin_synced(
+        &mut self,
+        exe: impl Executor<'_>,
+        synced: bool,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.domain_admin_synced, synced)
+            .condition(AccountModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        self.domain_admin_synced = synced;
 
         Ok(())
     }
@@ -269,35 +456,12 @@ impl Account {
         exe: impl Executor<'_>,
         display_name: MaxStr<255>,
     ) -> anyhow::Result<()> {
-        match self {
-            Account::ClubMember(club_member) => {
-                rorm::update(exe, ClubAccountModel)
-                    .set(ClubAccountModel.display_name, display_name.clone())
-                    .condition(ClubAccountModel.uuid.equals(club_member.uuid.0))
-                    .await?;
-
-                club_member.display_name = display_name;
-            }
-            Account::ClubAdmin(club_admin) => {
-                rorm::update(exe, ClubAdminAccountModel)
-                    .set(ClubAdminAccountModel.display_name, display_name.clone())
-                    .condition(ClubAdminAccountModel.uuid.equals(club_admin.uuid.0))
-                    .await?;
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.display_name, display_name.clone())
+            .condition(AccountModel.uuid.equals(self.uuid.0))
+            .await?;
 
-                club_admin.display_name = display_name;
-            }
-            Account::Superadmin(superadmin) => {
-                rorm::update(exe, AdministrativeAccountModel)
-                    .set(
-                        AdministrativeAccountModel.display_name,
-                        display_name.clone(),
-                    )
-                    .condition(AdministrativeAccountModel.uuid.equals(superadmin.uuid.0))
-                    .await?;
-
-                superadmin.display_name = display_name;
-            }
-        }
+        self.display_name = display_name;
 
         Ok(())
     }
@@ -310,8 +474,6 @@ impl Account {
         &self,
         exe: impl Executor<'_>,
     ) -> anyhow::Result<CredentialReset> {
-        let mut guard = exe.ensure_transaction().await?;
-
         let now = OffsetDateTime::now_utc();
         let code_expires_at = now + Duration::minutes(10);
         let link_expires_at = now + Duration::days(7);
@@ -320,65 +482,52 @@ impl Account {
         #[allow(clippy::expect_used)]
         let code_field = MaxStr::new(code.clone()).expect("6-digit code is always <= 6 characters");
 
-        let reset = match self {
-            Account::ClubMember(club_member) => {
-                let reset = rorm::insert(guard.get_transaction(), CredentialResetClubAccountModel)
-                    .single(&CredentialResetClubAccountModel {
-                        uuid: Uuid::new_v4(),
-                        account: ForeignModelByField(club_member.uuid().0),
-                        code: code_field,
-                        code_expires_at,
-                        link_expires_at,
-                    })
-                    .await?;
-
-                CredentialReset {
-                    uuid: CredentialResetUuid(reset.uuid),
-                    code,
-                    code_expires_at: reset.code_expires_at,
-                    link_expires_at: reset.link_expires_at,
-                }
-            }
-            Account::ClubAdmin(club_admin) => {
-                let reset = rorm::insert(guard.get_transaction(), CredentialResetClubAdminModel)
-                    .single(&CredentialResetClubAdminModel {
-                        uuid: Uuid::new_v4(),
-                        account: ForeignModelByField(club_admin.uuid().0),
-                        code: code_field,
-                        code_expires_at,
-                        link_expires_at,
-                    })
-                    .await?;
-
-                CredentialReset {
-                    uuid: CredentialResetUuid(reset.uuid),
-                    code,
-                    code_expires_at: reset.code_expires_at,
-                    link_expires_at: reset.link_expires_at,
-                }
-            }
-            Account::Superadmin(superadmin) => {
-                let reset = rorm::insert(guard.get_transaction(), CredentialResetSuperadminModel)
-                    .single(&CredentialResetSuperadminModel {
-                        uuid: Uuid::new_v4(),
-                        account: ForeignModelByField(superadmin.uuid().0),
-                        code: code_field,
-                        code_expires_at,
-                        link_expires_at,
-                    })
-                    .await?;
-
-                CredentialReset {
-                    uuid: CredentialResetUuid(reset.uuid),
-                    code,
-                    code_expires_at: reset.code_expires_at,
-                    link_expires_at: reset.link_expires_at,
-                }
-            }
-        };
-
-        guard.commit().await?;
+        let reset = rorm::insert(exe, CredentialResetModel)
+            .single(&CredentialResetModel {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(self.uuid.0),
+                code: code_field,
+                code_expires_at,
+                link_expires_at,
+            })
+            .await?;
+
+        Ok(CredentialReset {
+            uuid: CredentialResetUuid(reset.uuid),
+            code,
+            code_expires_at: reset.code_expires_at,
+            link_expires_at: reset.link_expires_at,
+        })
+    }
+}
 
-        Ok(reset)
+/// Delete an account which doesn't hold any role anymore
+///
+/// The username stays reserved.
+#[instrument(skip(exe))]
+async fn delete_if_without_roles(exe: impl Executor<'_>, uuid: AccountUuid) -> anyhow::Result<()> {
+    let mut guard = exe.ensure_transaction().await?;
+
+    let is_member = rorm::query(guard.get_transaction(), ClubMemberModel.uuid)
+        .condition(ClubMemberModel.account.equals(uuid.0))
+        .optional()
+        .await?
+        .is_some();
+    let is_admin = rorm::query(guard.get_transaction(), ClubAdminModel.uuid)
+        .condition(ClubAdminModel.account.equals(uuid.0))
+        .optional()
+        .await?
+        .is_some();
+
+    if !is_member && !is_admin {
+        rorm::delete(guard.get_transaction(), AccountModel)
+            .condition(rorm::and![
+                AccountModel.uuid.equals(uuid.0),
+                AccountModel.superadmin.equals(false),
+            ])
+            .await?;
     }
+
+    guard.commit().await?;
+    Ok(())
 }
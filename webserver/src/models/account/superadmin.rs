@@ -1,48 +1,52 @@
 use galvyn::rorm;
+use galvyn::rorm::and;
 use galvyn::rorm::db::Executor;
-use galvyn::rorm::fields::types::MaxStr;
 use tracing::instrument;
 
 use crate::models::account::AccountUuid;
 use crate::models::account::AdministrativeAccount;
-use crate::models::account::db::AdministrativeAccountModel;
+use crate::models::account::db::AccountModel;
+use crate::models::account::delete_if_without_roles;
 
 impl AdministrativeAccount {
-    /// Get the account by its uuid
-    #[instrument(name = "AdministrativeAccount::get_by_uuid", skip(exe))]
-    pub async fn get_by_uuid(
-        exe: impl Executor<'_>,
-        uuid: AccountUuid,
-    ) -> anyhow::Result<Option<Self>> {
-        Ok(rorm::query(exe, AdministrativeAccountModel)
-            .condition(AdministrativeAccountModel.uuid.equals(uuid.0))
-            .optional()
+    /// Retrieve all superadmins
+    #[instrument(name = "AdministrativeAccount::get_all", skip(exe))]
+    pub async fn get_all(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, AccountModel)
+            .condition(AccountModel.superadmin.equals(true))
+            .all()
             .await?
-            .map(Self::from))
+            .into_iter()
+            .map(Self::from)
+            .collect())
     }
 
-    /// Get the account by its username
-    #[instrument(name = "AdministrativeAccount::get_by_username", skip(exe))]
-    pub async fn get_by_username(
+    /// Retrieve a superadmin by the uuid of its account
+    #[instrument(name = "AdministrativeAccount::get_by_uuid", skip(exe))]
+    pub async fn get_by_uuid(
         exe: impl Executor<'_>,
-        username: &MaxStr<255>,
+        uuid: AccountUuid,
     ) -> anyhow::Result<Option<Self>> {
-        Ok(rorm::query(exe, AdministrativeAccountModel)
-            .condition(AdministrativeAccountModel.username.equals(username))
+        Ok(rorm::query(exe, AccountModel)
+            .condition(and![
+                AccountModel.uuid.equals(uuid.0),
+                AccountModel.superadmin.equals(true),
+            ])
             .optional()
             .await?
             .map(Self::from))
     }
 
-    /// Retrieve all administrative accounts
-    #[instrument(name = "AdministrativeAccount::get_all", skip(exe))]
-    pub async fn get_all(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
-        Ok(rorm::query(exe, AdministrativeAccountModel)
-            .all()
-            .await?
-            .into_iter()
-            .map(Self::from)
-            .collect())
+    /// Count the superadmins which aren't locked
+    #[instrument(name = "AdministrativeAccount::count_active", skip(exe))]
+    pub async fn count_active(exe: impl Executor<'_>) -> anyhow::Result<i64> {
+        Ok(rorm::query(exe, AccountModel.uuid.count())
+            .condition(and![
+                AccountModel.superadmin.equals(true),
+                AccountModel.locked.equals(false),
+            ])
+            .one()
+            .await?)
     }
 }
 
@@ -51,17 +55,35 @@ impl AdministrativeAccount {
     pub fn uuid(&self) -> AccountUuid {
         self.uuid
     }
+
+    /// Remove the superadmin privileges from the account
+    ///
+    /// The account is deleted as well if it doesn't hold any other role.
+    #[instrument(skip(self, exe), name = "AdministrativeAccount::delete")]
+    pub async fn delete(self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        rorm::update(guard.get_transaction(), AccountModel)
+            .set(AccountModel.superadmin, false)
+            .condition(AccountModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        delete_if_without_roles(guard.get_transaction(), self.uuid).await?;
+
+        guard.commit().await?;
+        Ok(())
+    }
 }
 
-impl From<AdministrativeAccountModel> for AdministrativeAccount {
-    fn from(value: AdministrativeAccountModel) -> Self {
+impl From<AccountModel> for AdministrativeAccount {
+    fn from(value: AccountModel) -> Self {
         Self {
             uuid: AccountUuid(value.uuid),
             display_name: value.display_name,
             username: value.username.0,
+            locked: value.locked,
             modified_at: value.modified_at,
             created_at: value.created_at,
-            hashed_password: value.hashed_password,
         }
     }
 }
@@ -0,0 +1,308 @@
+use base64ct::Base64;
+use base64ct::Encoding;
+use galvyn::rorm;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use rand::RngExt;
+use sha2::Digest;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use time::OffsetDateTime;
+use totp_rs::Algorithm;
+use totp_rs::Secret;
+use totp_rs::TOTP;
+use tracing::instrument;
+
+use crate::config::REQUIRE_2FA_CLUB_ADMINS;
+use crate::config::REQUIRE_2FA_SUPERADMINS;
+use crate::models::account::Account;
+use crate::models::account::db::AccountModel;
+
+/// Issuer shown in authenticator apps
+const TOTP_ISSUER: &str = "BNV Manager";
+
+/// Duration of a single TOTP time step in seconds
+const TOTP_STEP: i64 = 30;
+
+/// Number of recovery codes generated at once
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Characters used for recovery codes
+///
+/// Characters which are easily confused (`0`, `o`, `1`, `l`, `i`) are omitted
+const RECOVERY_CODE_CHARSET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// The TOTP state of an account which has enrolled TOTP
+#[derive(Debug, Clone)]
+pub(super) struct AccountTotp {
+    secret: MaxStr<64>,
+    last_used_step: Option<i64>,
+    recovery_codes: Vec<String>,
+}
+
+impl AccountTotp {
+    /// Construct the state from the raw database fields
+    pub(super) fn from_fields(
+        secret: Option<MaxStr<64>>,
+        last_used_step: Option<i64>,
+        recovery_codes: Option<Json<Vec<String>>>,
+    ) -> Option<Self> {
+        Some(Self {
+            secret: secret?,
+            last_used_step,
+            recovery_codes: recovery_codes.map(|x| x.0).unwrap_or_default(),
+        })
+    }
+}
+
+/// A freshly generated TOTP secret which has not been confirmed yet
+pub struct TotpEnrollment {
+    /// Base32 encoded secret
+    pub secret: String,
+    /// `otpauth://` url to be rendered as QR code
+    pub url: String,
+}
+
+impl Account {
+    /// Whether the account has enrolled TOTP
+    pub fn totp_enabled(&self) -> bool {
+        self.totp().is_some()
+    }
+
+    /// The number of recovery codes that have not been used yet
+    pub fn remaining_recovery_codes(&self) -> usize {
+        self.totp()
+            .as_ref()
+            .map(|totp| totp.recovery_codes.len())
+            .unwrap_or_default()
+    }
+
+    /// Whether the configuration requires this account to use a second factor
+    ///
+    /// The strictest requirement of all roles the account holds applies.
+    pub fn second_factor_enforced(&self) -> bool {
+        (self.superadmin && *REQUIRE_2FA_SUPERADMINS)
+            || (!self.admin_of.is_empty() && *REQUIRE_2FA_CLUB_ADMINS)
+    }
+
+    /// Generate a new TOTP secret for this account
+    ///
+    /// The secret is not persisted, use [`Account::enable_totp`] once the
+    /// user has proven that their authenticator app is set up correctly.
+    #[instrument(name = "Account::start_totp_enrollment", skip(self))]
+    pub fn start_totp_enrollment(&self) -> anyhow::Result<TotpEnrollment> {
+        let secret: [u8; 20] = rand::rng().random();
+        let totp = build_totp(secret.to_vec(), self.account_name())?;
+
+        Ok(TotpEnrollment {
+            secret: totp.get_secret_base32(),
+            url: totp.get_url(),
+        })
+    }
+
+    /// Enable TOTP for the account after checking a code generated from the secret
+    ///
+    /// Returns the plain recovery codes or `None` if the code was invalid.
+    #[instrument(name = "Account::enable_totp", skip_all)]
+    pub async fn enable_totp(
+        &mut self,
+        exe: impl Executor<'_>,
+        secret: &str,
+        code: &str,
+    ) -> anyhow::Result<Option<Vec<String>>> {
+        let secret = MaxStr::new(secret.to_string())?;
+        let totp = build_totp(
+            Secret::Encoded(secret.to_string()).to_bytes()?,
+            self.account_name(),
+        )?;
+
+        let Some(step) = check_code(&totp, None, code) else {
+            return Ok(None);
+        };
+
+        let (recovery_codes, hashes) = generate_recovery_codes();
+        self.store_totp(
+            exe,
+            Some(AccountTotp {
+                secret,
+                last_used_step: Some(step),
+                recovery_codes: hashes,
+            }),
+        )
+        .await?;
+
+        Ok(Some(recovery_codes))
+    }
+
+    /// Check a TOTP code of the account
+    ///
+    /// Each code is only accepted once.
+    #[instrument(name = "Account::verify_totp", skip_all)]
+    pub async fn verify_totp(
+        &mut self,
+        exe: impl Executor<'_>,
+        code: &str,
+    ) -> anyhow::Result<bool> {
+        let Some(mut state) = self.totp().clone() else {
+            return Ok(false);
+        };
+
+        let totp = build_totp(
+            Secret::Encoded(state.secret.to_string()).to_bytes()?,
+            self.account_name(),
+        )?;
+
+        let Some(step) = check_code(&totp, state.last_used_step, code) else {
+            return Ok(false);
+        };
+
+        state.last_used_step = Some(step);
+        self.store_totp(exe, Some(state)).await?;
+
+        Ok(true)
+    }
+
+    /// Check and consume a recovery code of the account
+    #[instrument(name = "Account::use_recovery_code", skip_all)]
+    pub async fn use_recovery_code(
+        &mut self,
+        exe: impl Executor<'_>,
+        code: &str,
+    ) -> anyhow::Result<bool> {
+        let Some(mut state) = self.totp().clone() else {
+            return Ok(false);
+        };
+
+        let hash = hash_recovery_code(code);
+        let Some(index) = state
+            .recovery_codes
+            .iter()
+            .position(|x| bool::from(x.as_bytes().ct_eq(hash.as_bytes())))
+        else {
+            return Ok(false);
+        };
+
+        state.recovery_codes.remove(index);
+        self.store_totp(exe, Some(state)).await?;
+
+        Ok(true)
+    }
+
+    /// Replace all recovery codes of the account with new ones
+    ///
+    /// Returns `None` if the account has not enrolled TOTP.
+    #[instrument(name = "Account::regenerate_recovery_codes", skip_all)]
+    pub async fn regenerate_recovery_codes(
+        &mut self,
+        exe: impl Executor<'_>,
+    ) -> anyhow::Result<Option<Vec<String>>> {
+        let Some(mut state) = self.totp().clone() else {
+            return Ok(None);
+        };
+
+        let (recovery_codes, hashes) = generate_recovery_codes();
+        state.recovery_codes = hashes;
+        self.store_totp(exe, Some(state)).await?;
+
+        Ok(Some(recovery_codes))
+    }
+
+    /// Remove TOTP and all recovery codes from the account
+    #[instrument(name = "Account::disable_totp", skip_all)]
+    pub async fn disable_totp(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        self.store_totp(exe, None).await
+    }
+
+    fn totp(&self) -> &Option<AccountTotp> {
+        &self.totp
+    }
+
+    fn account_name(&self) -> String {
+        self.username.to_string()
+    }
+
+    async fn store_totp(
+        &mut self,
+        exe: impl Executor<'_>,
+        totp: Option<AccountTotp>,
+    ) -> anyhow::Result<()> {
+        let secret = totp.as_ref().map(|x| x.secret.clone());
+        let last_used_step = totp.as_ref().and_then(|x| x.last_used_step);
+        let recovery_codes = totp.as_ref().map(|x| Json(x.recovery_codes.clone()));
+
+        rorm::update(exe, AccountModel)
+            .set(AccountModel.totp_secret, secret)
+            .set(AccountModel.totp_last_used_step, last_used_step)
+            .set(AccountModel.totp_recovery_codes, recovery_codes)
+            .condition(AccountModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        self.totp = totp;
+
+        Ok(())
+    }
+}
+
+fn build_totp(secret: Vec<u8>, account_name: String) -> anyhow::Result<TOTP> {
+    Ok(TOTP::new(
+        Algorithm::SHA1,
+        6,
+        1,
+        TOTP_STEP as u64,
+        secret,
+        Some(TOTP_ISSUER.to_string()),
+        account_name,
+    )?)
+}
+
+/// Check a code against the previous, current and next time step
+///
+/// Steps up to `last_used_step` are rejected to prevent replay attacks.
+/// Returns the matching time step.
+fn check_code(totp: &TOTP, last_used_step: Option<i64>, code: &str) -> Option<i64> {
+    let code = code.trim();
+    let current_step = OffsetDateTime::now_utc().unix_timestamp() / TOTP_STEP;
+
+    (current_step - 1..=current_step + 1)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| {
+            let expected = totp.generate((step * TOTP_STEP) as u64);
+            bool::from(expected.as_bytes().ct_eq(code.as_bytes()))
+        })
+}
+
+/// Generate a new set of recovery codes
+///
+/// Returns the plain codes as well as their hashes
+fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let mut rng = rand::rng();
+
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut code = String::with_capacity(11);
+            for i in 0..10 {
+                if i == 5 {
+                    code.push('-');
+                }
+                let index = rng.random_range(0..RECOVERY_CODE_CHARSET.len());
+                code.push(RECOVERY_CODE_CHARSET[index] as char);
+            }
+            code
+        })
+        .collect();
+    let hashes = codes.iter().map(|code| hash_recovery_code(code)).collect();
+
+    (codes, hashes)
+}
+
+/// Hash a recovery code ignoring casing, whitespace and separators
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    Base64::encode_string(&Sha256::digest(normalized.as_bytes()))
+}
@@ -0,0 +1,53 @@
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use uuid::Uuid;
+
+use crate::models::account::AuthenticationMethod;
+use crate::models::account::db::AccountModel;
+use crate::models::api_token::ApiTokenScope;
+
+/// A personal api token of an account
+///
+/// The token itself is never stored, only its hash.
+#[derive(Debug, Model)]
+#[rorm(rename = "ApiToken")]
+pub struct ApiTokenModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    pub name: MaxStr<255>,
+
+    /// Base64 encoded SHA256 hash of the token
+    #[rorm(unique)]
+    pub token_hash: MaxStr<64>,
+
+    /// The parts of the api the token may be used for
+    pub scopes: Json<Vec<ApiTokenScope>>,
+
+    /// The methods the account authenticated with when creating the token
+    pub auth_methods: Json<Vec<AuthenticationMethod>>,
+
+    pub expires_at: time::OffsetDateTime,
+    pub last_used_at: Option<time::OffsetDateTime>,
+
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "ApiTokenModel")]
+pub struct ApiTokenModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub name: MaxStr<255>,
+    pub token_hash: MaxStr<64>,
+    pub scopes: Json<Vec<ApiTokenScope>>,
+    pub auth_methods: Json<Vec<AuthenticationMethod>>,
+    pub expires_at: time::OffsetDateTime,
+}
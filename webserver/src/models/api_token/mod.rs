@@ -0,0 +1,249 @@
+//! Personal api tokens allow automating the api without a browser session
+//!
+//! A token is bound to an account and restricted to a set of scopes.
+//! Only the hash of a token is stored, the token itself is shown once on creation.
+
+use base64ct::Base64;
+use base64ct::Encoding;
+use futures_util::TryStreamExt;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use rand::distr::Alphanumeric;
+use rand::distr::SampleString;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::account::AccountUuid;
+use crate::models::account::AuthenticationMethod;
+use crate::models::api_token::db::ApiTokenModel;
+use crate::models::api_token::db::ApiTokenModelInsert;
+use crate::models::club::ClubUuid;
+
+pub(in crate::models) mod db;
+
+/// A personal api token of an account
+#[derive(Debug, Clone)]
+pub struct ApiToken {
+    /// Primary key of the token
+    ///
+    /// This is not the token itself, which is only known to its owner.
+    pub uuid: ApiTokenUuid,
+    /// The account the token acts as
+    pub account: AccountUuid,
+    /// Name to recognize the token by
+    pub name: MaxStr<255>,
+    /// The parts of the api the token may be used for
+    pub scopes: Vec<ApiTokenScope>,
+    /// The methods the account authenticated with when creating the token
+    pub auth_methods: Vec<AuthenticationMethod>,
+    /// The point in time the token expires
+    pub expires_at: OffsetDateTime,
+    /// The last point in time the token was used
+    pub last_used_at: Option<OffsetDateTime>,
+    /// The point in time the token was created
+    pub created_at: OffsetDateTime,
+}
+
+/// New-type for the primary key of the api token
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiTokenUuid(pub Uuid);
+
+/// A part of the api an [`ApiToken`] may be used for
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum ApiTokenScope {
+    /// The common api, acting as the account itself
+    Common,
+    /// The club admin api of a single club
+    ClubAdmin {
+        /// The club which may be administrated
+        club: ClubUuid,
+    },
+    /// The superadmin api
+    SuperAdmin,
+}
+
+/// Parameters to create a new api token
+#[derive(Debug, Clone)]
+pub struct CreateApiTokenParams {
+    /// The account the token acts as
+    pub account: AccountUuid,
+    /// Name to recognize the token by
+    pub name: MaxStr<255>,
+    /// The parts of the api the token may be used for
+    pub scopes: Vec<ApiTokenScope>,
+    /// The methods the account authenticated with when creating the token
+    pub auth_methods: Vec<AuthenticationMethod>,
+    /// The point in time the token expires
+    pub expires_at: OffsetDateTime,
+}
+
+impl ApiToken {
+    /// Create a new api token
+    ///
+    /// Returns the token alongside its secret, which can't be retrieved later on.
+    #[instrument(name = "ApiToken::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        CreateApiTokenParams {
+            account,
+            name,
+            scopes,
+            auth_methods,
+            expires_at,
+        }: CreateApiTokenParams,
+    ) -> anyhow::Result<(Self, String)> {
+        let secret = Alphanumeric.sample_string(&mut rand::rng(), 64);
+
+        let model = rorm::insert(exe, ApiTokenModel)
+            .single(&ApiTokenModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(account.0),
+                name,
+                token_hash: hash_token(&secret)?,
+                scopes: Json(scopes),
+                auth_methods: Json(auth_methods),
+                expires_at,
+            })
+            .await?;
+
+        Ok((Self::from(model), secret))
+    }
+
+    /// Find all api tokens of an account
+    #[instrument(name = "ApiToken::find_all_by_account", skip(exe))]
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, ApiTokenModel)
+            .condition(ApiTokenModel.account.equals(account))
+            .order_desc(ApiTokenModel.created_at)
+            .stream()
+            .map_ok(ApiToken::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Look up the api token which has not expired yet by its secret
+    ///
+    /// The last-used timestamp of the token is updated.
+    #[instrument(name = "ApiToken::authenticate", skip_all)]
+    pub async fn authenticate(
+        exe: impl Executor<'_>,
+        secret: &str,
+    ) -> anyhow::Result<Option<Self>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let token_hash = hash_token(secret)?;
+        let Some(mut model) = rorm::query(guard.get_transaction(), ApiTokenModel)
+            .condition(and![
+                ApiTokenModel.token_hash.equals(&*token_hash),
+                ApiTokenModel
+                    .expires_at
+                    .greater_than(OffsetDateTime::now_utc()),
+            ])
+            .optional()
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let now = OffsetDateTime::now_utc();
+        rorm::update(guard.get_transaction(), ApiTokenModel)
+            .set(ApiTokenModel.last_used_at, Some(now))
+            .condition(ApiTokenModel.uuid.equals(model.uuid))
+            .await?;
+        model.last_used_at = Some(now);
+
+        guard.commit().await?;
+
+        Ok(Some(Self::from(model)))
+    }
+
+    /// Revoke a single api token of an account
+    ///
+    /// Returns `false` if the account has no such token.
+    #[instrument(name = "ApiToken::revoke", skip(exe))]
+    pub async fn revoke(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+        ApiTokenUuid(uuid): ApiTokenUuid,
+    ) -> anyhow::Result<bool> {
+        let deleted = rorm::delete(exe, ApiTokenModel)
+            .condition(and![
+                ApiTokenModel.uuid.equals(uuid),
+                ApiTokenModel.account.equals(account),
+            ])
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Revoke all api tokens of an account
+    #[instrument(name = "ApiToken::revoke_all_by_account", skip(exe))]
+    pub async fn revoke_all_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+    ) -> anyhow::Result<()> {
+        rorm::delete(exe, ApiTokenModel)
+            .condition(ApiTokenModel.account.equals(account))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether the token may be used for a part of the api
+    pub fn allows(&self, scope: ApiTokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    /// Remove all expired api tokens
+    #[instrument(name = "ApiToken::clear_expired", skip(exe))]
+    pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, ApiTokenModel)
+            .condition(
+                ApiTokenModel
+                    .expires_at
+                    .less_than(OffsetDateTime::now_utc()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl From<ApiTokenModel> for ApiToken {
+    fn from(model: ApiTokenModel) -> Self {
+        Self {
+            uuid: ApiTokenUuid(model.uuid),
+            account: AccountUuid(model.account.0),
+            name: model.name,
+            scopes: model.scopes.0,
+            auth_methods: model.auth_methods.0,
+            expires_at: model.expires_at,
+            last_used_at: model.last_used_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// Hash an api token
+///
+/// Only the hash is stored, so a leaked database can't be used to access the api.
+fn hash_token(secret: &str) -> anyhow::Result<MaxStr<64>> {
+    Ok(MaxStr::new(Base64::encode_string(&Sha256::digest(
+        secret.as_bytes(),
+    )))?)
+}
@@ -5,8 +5,8 @@ use galvyn::rorm::fields::types::MaxStr;
 use galvyn::rorm::prelude::BackRef;
 use uuid::Uuid;
 
-use crate::models::account::db::ClubAccountModel;
-use crate::models::account::db::ClubAdminAccountModel;
+use crate::models::account::db::ClubAdminModel;
+use crate::models::account::db::ClubMemberModel;
 use crate::models::domain::db::DomainModel;
 
 #[derive(Debug, Model)]
@@ -27,8 +27,8 @@ pub struct ClubModel {
     #[rorm(auto_create_time)]
     pub created_at: time::OffsetDateTime,
 
-    pub members: BackRef<field!(ClubAccountModel.club)>,
-    pub admins: BackRef<field!(ClubAdminAccountModel.club)>,
+    pub members: BackRef<field!(ClubMemberModel.club)>,
+    pub admins: BackRef<field!(ClubAdminModel.club)>,
 
     pub domains: BackRef<field!(DomainModel.club)>,
 }
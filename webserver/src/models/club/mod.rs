@@ -10,6 +10,8 @@ use galvyn::rorm::and;
 use galvyn::rorm::conditions::Condition;
 use galvyn::rorm::conditions::DynamicCollection;
 use galvyn::rorm::db::Executor;
+use galvyn::rorm::db::sql::select::LockAcquire;
+use galvyn::rorm::db::sql::select::LockStrength;
 use galvyn::rorm::db::transaction::Transaction;
 use galvyn::rorm::fields::types::MaxStr;
 use galvyn::rorm::prelude::ForeignModelByField;
@@ -20,8 +22,9 @@ use uuid::Uuid;
 
 use crate::models::account::ClubAccount;
 use crate::models::account::ClubAdminAccount;
-use crate::models::account::db::ClubAccountModel;
-use crate::models::account::db::ClubAdminAccountModel;
+use crate::models::account::db::AccountModel;
+use crate::models::account::db::ClubAdminModel;
+use crate::models::account::db::ClubMemberModel;
 use crate::models::club::db::ClubModel;
 use crate::models::club::db::ClubModelInsert;
 use crate::models::domain::Domain;
@@ -121,6 +124,27 @@ impl Club {
             .collect())
     }
 
+    /// Retrieve a club by uuid and lock it until the end of the transaction
+    ///
+    /// Used to serialize changes which depend on the club's admin count,
+    /// the count is read after the lock is acquired.
+    #[instrument(name = "Club::find_by_uuid_for_update", skip(tx))]
+    pub async fn find_by_uuid_for_update(
+        tx: &mut Transaction,
+        uuid: ClubUuid,
+    ) -> anyhow::Result<Option<Club>> {
+        let cm = rorm::query(&mut *tx, ClubModel)
+            .condition(ClubModel.uuid.equals(uuid.0))
+            .lock(LockStrength::Update, LockAcquire::Wait)
+            .optional()
+            .await?;
+
+        Ok(match cm {
+            Some(cm) => Some(Self::populate(tx, cm).await?),
+            None => None,
+        })
+    }
+
     /// Retrieve a club by uuid
     #[instrument(name = "Club::find_by_uuid", skip(exe))]
     pub async fn find_by_uuid(
@@ -212,6 +236,25 @@ impl Club {
         Ok(club)
     }
 
+    /// Update the name and the X-Auth setting of the club
+    #[instrument(name = "Club::update", skip(self, exe))]
+    pub async fn update(
+        &mut self,
+        exe: impl Executor<'_>,
+        UpdateClub { name, use_xauth }: UpdateClub,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, ClubModel)
+            .set(ClubModel.name, name.clone())
+            .set(ClubModel.use_xauth, use_xauth)
+            .condition(ClubModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        self.name = name;
+        self.use_xauth = use_xauth;
+
+        Ok(())
+    }
+
     /// Associate an existing domain with this club
     #[instrument(name = "Club::associate_domain", skip(exe, self))]
     pub async fn associate_domain(
@@ -285,20 +328,22 @@ impl Club {
     ) -> anyhow::Result<Page<ClubAccount>> {
         let mut guard = exe.ensure_transaction().await?;
 
-        let mut conditions = vec![ClubAccountModel.club.equals(self.uuid.0).boxed()];
+        let mut conditions = vec![ClubMemberModel.club.equals(self.uuid.0).boxed()];
         if let Some(search) = search {
             conditions.push(
                 DynamicCollection::or_unchecked(vec![
-                    ClubAccountModel
+                    ClubMemberModel
+                        .account
                         .username
                         .username
                         .like_ignore_case(format!("%{search}%"))
                         .boxed(),
-                    ClubAccountModel
+                    ClubMemberModel
                         .email
                         .like_ignore_case(format!("%{search}%"))
                         .boxed(),
-                    ClubAccountModel
+                    ClubMemberModel
+                        .account
                         .display_name
                         .like_ignore_case(format!("%{search}%"))
                         .boxed(),
@@ -308,18 +353,24 @@ impl Club {
         }
         let cond_collection = DynamicCollection::and_unchecked(conditions);
 
-        let account_models = rorm::query(guard.get_transaction(), ClubAccountModel)
-            .order_asc(ClubAccountModel.username)
-            .condition(&cond_collection)
-            .offset(offset)
-            .limit(limit)
-            .stream()
-            .map_ok(ClubAccount::from)
-            .try_collect::<Vec<_>>()
-            .await?;
-
-        let total = rorm::query(guard.get_transaction(), ClubAccountModel.uuid.count())
-            .condition(ClubAccountModel.club.equals(self.uuid.0))
+        let account_models = rorm::query(
+            guard.get_transaction(),
+            (
+                ClubMemberModel,
+                ClubMemberModel.account.query_as(AccountModel),
+            ),
+        )
+        .order_asc(ClubMemberModel.account.username)
+        .condition(&cond_collection)
+        .offset(offset)
+        .limit(limit)
+        .stream()
+        .map_ok(ClubAccount::from)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let total = rorm::query(guard.get_transaction(), ClubMemberModel.uuid.count())
+            .condition(ClubMemberModel.club.equals(self.uuid.0))
             .one()
             .await?;
 
@@ -344,10 +395,11 @@ impl Club {
     ) -> anyhow::Result<Page<ClubAdminAccount>> {
         let mut guard = exe.ensure_transaction().await?;
 
-        let mut conditions = vec![ClubAdminAccountModel.club.equals(self.uuid.0).boxed()];
+        let mut conditions = vec![ClubAdminModel.club.equals(self.uuid.0).boxed()];
         if let Some(search) = search {
             conditions.push(
-                ClubAdminAccountModel
+                ClubAdminModel
+                    .account
                     .username
                     .username
                     .like_ignore_case(format!("%{search}%"))
@@ -356,18 +408,24 @@ impl Club {
         }
         let cond_collection = DynamicCollection::and_unchecked(conditions);
 
-        let account_models = rorm::query(guard.get_transaction(), ClubAdminAccountModel)
-            .order_asc(ClubAdminAccountModel.username)
-            .condition(&cond_collection)
-            .offset(offset)
-            .limit(limit)
-            .stream()
-            .map_ok(ClubAdminAccount::from)
-            .try_collect::<Vec<_>>()
-            .await?;
-
-        let total = rorm::query(guard.get_transaction(), ClubAdminAccountModel.uuid.count())
-            .condition(ClubAdminAccountModel.club.equals(self.uuid.0))
+        let account_models = rorm::query(
+            guard.get_transaction(),
+            (
+                ClubAdminModel,
+                ClubAdminModel.account.query_as(AccountModel),
+            ),
+        )
+        .order_asc(ClubAdminModel.account.username)
+        .condition(&cond_collection)
+        .offset(offset)
+        .limit(limit)
+        .stream()
+        .map_ok(ClubAdminAccount::from)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+        let total = rorm::query(guard.get_transaction(), ClubAdminModel.uuid.count())
+            .condition(ClubAdminModel.club.equals(self.uuid.0))
             .one()
             .await?;
 
@@ -395,6 +453,15 @@ pub struct CreateClub<'a> {
     pub use_xauth: bool,
 }
 
+/// Parameters for updating a club
+#[derive(Debug, Clone)]
+pub struct UpdateClub {
+    /// Name of the club
+    pub name: MaxStr<255>,
+    /// Whether to use X-Auth for authentication
+    pub use_xauth: bool,
+}
+
 impl Club {
     async fn populate(
         tx: &mut Transaction,
@@ -14,15 +14,8 @@ use url::Url;
 use uuid::Uuid;
 
 use crate::models::account::Account;
-use crate::models::account::AdministrativeAccount;
-use crate::models::account::ClubAccount;
-use crate::models::account::ClubAdminAccount;
-use crate::models::account::db::AdministrativeAccountModel;
-use crate::models::account::db::ClubAccountModel;
-use crate::models::account::db::ClubAdminAccountModel;
-use crate::models::credential_reset::db::CredentialResetClubAccountModel;
-use crate::models::credential_reset::db::CredentialResetClubAdminModel;
-use crate::models::credential_reset::db::CredentialResetSuperadminModel;
+use crate::models::account::AccountUuid;
+use crate::models::credential_reset::db::CredentialResetModel;
 use crate::utils::links::Link;
 
 pub(in crate::models) mod db;
@@ -64,71 +57,25 @@ impl CredentialReset {
         let mut guard = exe.ensure_transaction().await?;
         let now = OffsetDateTime::now_utc();
 
-        if let Some(reset) = rorm::query(guard.get_transaction(), CredentialResetClubAccountModel)
+        let reset = rorm::query(guard.get_transaction(), CredentialResetModel)
             .condition(rorm::and![
-                CredentialResetClubAccountModel.code.equals(code),
-                CredentialResetClubAccountModel
-                    .code_expires_at
-                    .greater_than(now),
+                CredentialResetModel.code.equals(code),
+                CredentialResetModel.code_expires_at.greater_than(now),
             ])
             .optional()
-            .await?
-            && let Some(account) = rorm::query(guard.get_transaction(), ClubAccountModel)
-                .condition(ClubAccountModel.uuid.equals(reset.account.0))
-                .optional()
-                .await?
-        {
-            guard.commit().await?;
-            return Ok(Some((
-                CredentialReset::from_club_account_model(reset),
-                Account::ClubMember(ClubAccount::from(account)),
-            )));
-        }
+            .await?;
 
-        if let Some(reset) = rorm::query(guard.get_transaction(), CredentialResetClubAdminModel)
-            .condition(rorm::and![
-                CredentialResetClubAdminModel.code.equals(code),
-                CredentialResetClubAdminModel
-                    .code_expires_at
-                    .greater_than(now),
-            ])
-            .optional()
-            .await?
-            && let Some(account) = rorm::query(guard.get_transaction(), ClubAdminAccountModel)
-                .condition(ClubAdminAccountModel.uuid.equals(reset.account.0))
-                .optional()
-                .await?
-        {
-            guard.commit().await?;
-            return Ok(Some((
-                CredentialReset::from_club_admin_model(reset),
-                Account::ClubAdmin(ClubAdminAccount::from(account)),
-            )));
-        }
-
-        if let Some(reset) = rorm::query(guard.get_transaction(), CredentialResetSuperadminModel)
-            .condition(rorm::and![
-                CredentialResetSuperadminModel.code.equals(code),
-                CredentialResetSuperadminModel
-                    .code_expires_at
-                    .greater_than(now),
-            ])
-            .optional()
-            .await?
-            && let Some(account) = rorm::query(guard.get_transaction(), AdministrativeAccountModel)
-                .condition(AdministrativeAccountModel.uuid.equals(reset.account.0))
-                .optional()
-                .await?
-        {
-            guard.commit().await?;
-            return Ok(Some((
-                CredentialReset::from_superadmin_model(reset),
-                Account::Superadmin(AdministrativeAccount::from(account)),
-            )));
-        }
+        let result = match reset {
+            Some(reset) => {
+                Account::get_by_uuid(guard.get_transaction(), AccountUuid(reset.account.0))
+                    .await?
+                    .map(|account| (CredentialReset::from(reset), account))
+            }
+            None => None,
+        };
 
         guard.commit().await?;
-        Ok(None)
+        Ok(result)
     }
 
     /// Find a credential reset by its UUID, returning the reset and associated account
@@ -140,147 +87,75 @@ impl CredentialReset {
         let mut guard = exe.ensure_transaction().await?;
         let now = OffsetDateTime::now_utc();
 
-        if let Some(reset) = rorm::query(guard.get_transaction(), CredentialResetClubAccountModel)
+        let reset = rorm::query(guard.get_transaction(), CredentialResetModel)
             .condition(rorm::and![
-                CredentialResetClubAccountModel.uuid.equals(uuid),
-                CredentialResetClubAccountModel
-                    .link_expires_at
-                    .greater_than(now),
+                CredentialResetModel.uuid.equals(uuid),
+                CredentialResetModel.link_expires_at.greater_than(now),
             ])
             .optional()
-            .await?
-            && let Some(account) = rorm::query(guard.get_transaction(), ClubAccountModel)
-                .condition(ClubAccountModel.uuid.equals(reset.account.0))
-                .optional()
-                .await?
-        {
-            guard.commit().await?;
-            return Ok(Some((
-                CredentialReset::from_club_account_model(reset),
-                Account::ClubMember(ClubAccount::from(account)),
-            )));
-        }
-
-        if let Some(reset) = rorm::query(guard.get_transaction(), CredentialResetClubAdminModel)
-            .condition(rorm::and![
-                CredentialResetClubAdminModel.uuid.equals(uuid),
-                CredentialResetClubAdminModel
-                    .link_expires_at
-                    .greater_than(now),
-            ])
-            .optional()
-            .await?
-            && let Some(account) = rorm::query(guard.get_transaction(), ClubAdminAccountModel)
-                .condition(ClubAdminAccountModel.uuid.equals(reset.account.0))
-                .optional()
-                .await?
-        {
-            guard.commit().await?;
-            return Ok(Some((
-                CredentialReset::from_club_admin_model(reset),
-                Account::ClubAdmin(ClubAdminAccount::from(account)),
-            )));
-        }
+            .await?;
 
-        if let Some(reset) = rorm::query(guard.get_transaction(), CredentialResetSuperadminModel)
-            .condition(rorm::and![
-                CredentialResetSuperadminModel.uuid.equals(uuid),
-                CredentialResetSuperadminModel
-                    .link_expires_at
-                    .greater_than(now),
-            ])
-            .optional()
-            .await?
-            && let Some(account) = rorm::query(guard.get_transaction(), AdministrativeAccountModel)
-                .condition(AdministrativeAccountModel.uuid.equals(reset.account.0))
-                .optional()
-                .await?
-        {
-            guard.commit().await?;
-            return Ok(Some((
-                CredentialReset::from_superadmin_model(reset),
-                Account::Superadmin(AdministrativeAccount::from(account)),
-            )));
-        }
+        let result = match reset {
+            Some(reset) => {
+                Account::get_by_uuid(guard.get_transaction(), AccountUuid(reset.account.0))
+                    .await?
+                    .map(|account| (CredentialReset::from(reset), account))
+            }
+            None => None,
+        };
 
         guard.commit().await?;
-        Ok(None)
-    }
-
-    fn from_club_account_model(model: CredentialResetClubAccountModel) -> Self {
-        Self {
-            uuid: CredentialResetUuid(model.uuid),
-            code: model.code.into_inner(),
-            code_expires_at: model.code_expires_at,
-            link_expires_at: model.link_expires_at,
-        }
-    }
-
-    fn from_club_admin_model(model: CredentialResetClubAdminModel) -> Self {
-        Self {
-            uuid: CredentialResetUuid(model.uuid),
-            code: model.code.into_inner(),
-            code_expires_at: model.code_expires_at,
-            link_expires_at: model.link_expires_at,
-        }
+        Ok(result)
     }
 
-    fn from_superadmin_model(model: CredentialResetSuperadminModel) -> Self {
-        Self {
-            uuid: CredentialResetUuid(model.uuid),
-            code: model.code.into_inner(),
-            code_expires_at: model.code_expires_at,
-            link_expires_at: model.link_expires_at,
-        }
-    }
-
-    /// Delete a credential reset by its UUID from all tables
+    /// Delete a credential reset by its UUID
     #[instrument(name = "CredentialReset::delete_by_uuid", skip(exe))]
     pub async fn delete_by_uuid(
         exe: impl Executor<'_>,
         CredentialResetUuid(uuid): CredentialResetUuid,
     ) -> anyhow::Result<()> {
-        let mut guard = exe.ensure_transaction().await?;
-
-        rorm::delete(guard.get_transaction(), CredentialResetClubAccountModel)
-            .condition(CredentialResetClubAccountModel.uuid.equals(uuid))
-            .await?;
-        rorm::delete(guard.get_transaction(), CredentialResetClubAdminModel)
-            .condition(CredentialResetClubAdminModel.uuid.equals(uuid))
-            .await?;
-        rorm::delete(guard.get_transaction(), CredentialResetSuperadminModel)
-            .condition(CredentialResetSuperadminModel.uuid.equals(uuid))
+        rorm::delete(exe, CredentialResetModel)
+            .condition(CredentialResetModel.uuid.equals(uuid))
             .await?;
 
-        guard.commit().await?;
         Ok(())
     }
 
-    /// Clear expired credential resets from all tables
+    /// Find all stored credential resets of an account
+    #[instrument(name = "CredentialReset::find_all_by_account", skip(exe))]
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, CredentialResetModel)
+            .condition(CredentialResetModel.account.equals(account))
+            .all()
+            .await?
+            .into_iter()
+            .map(CredentialReset::from)
+            .collect())
+    }
+
+    /// Clear expired credential resets
     #[instrument(name = "CredentialReset::clear_expired", skip(exe))]
     pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
-        let mut guard = exe.ensure_transaction().await?;
         let now = OffsetDateTime::now_utc();
 
-        rorm::delete(guard.get_transaction(), CredentialResetClubAccountModel)
-            .condition(
-                CredentialResetClubAccountModel
-                    .link_expires_at
-                    .less_than(now),
-            )
-            .await?;
-        rorm::delete(guard.get_transaction(), CredentialResetClubAdminModel)
-            .condition(CredentialResetClubAdminModel.link_expires_at.less_than(now))
-            .await?;
-        rorm::delete(guard.get_transaction(), CredentialResetSuperadminModel)
-            .condition(
-                CredentialResetSuperadminModel
-                    .link_expires_at
-                    .less_than(now),
-            )
+        rorm::delete(exe, CredentialResetModel)
+            .condition(CredentialResetModel.link_expires_at.less_than(now))
             .await?;
 
-        guard.commit().await?;
         Ok(())
     }
 }
+
+impl From<CredentialResetModel> for CredentialReset {
+    fn from(model: CredentialResetModel) -> Self {
+        Self {
+            uuid: CredentialResetUuid(model.uuid),
+            code: model.code.into_inner(),
+            code_expires_at: model.code_expires_at,
+            link_expires_at: model.link_expires_at,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use galvyn::rorm::Model;
 use galvyn::rorm::fields::types::MaxStr;
 use galvyn::rorm::prelude::ForeignModel;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 use crate::models::club::db::ClubModel;
@@ -17,4 +18,9 @@ pub struct DomainModel {
     pub is_primary: bool,
     /// How many mailboxes can be created on this domain
     pub mailboxes_left: i64,
+    /// The first sync the domain was missing from mailcow
+    ///
+    /// The domain is kept as tombstone during a grace period, so its club association
+    /// survives a temporary outage or misconfiguration of mailcow.
+    pub missing_since: Option<OffsetDateTime>,
 }
@@ -7,12 +7,17 @@ use futures_util::TryStreamExt;
 use galvyn::core::re_exports::schemars;
 use galvyn::core::re_exports::schemars::JsonSchema;
 use galvyn::rorm;
+use galvyn::rorm::and;
 use galvyn::rorm::db::Executor;
 use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
 use mailcow::domains::schema::MailcowDomain;
 use serde::Deserialize;
 use serde::Serialize;
+use time::Duration;
+use time::OffsetDateTime;
 use tracing::instrument;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::models::club::ClubUuid;
@@ -31,6 +36,8 @@ pub struct Domain {
     pub associated_club: Option<ClubUuid>,
     /// The primary domain for a club
     pub is_primary: bool,
+    /// The first sync the domain was missing from mailcow
+    pub missing_since: Option<OffsetDateTime>,
 }
 
 /// Uuid of a domain
@@ -38,6 +45,30 @@ pub struct Domain {
 pub struct DomainUuid(pub Uuid);
 
 impl Domain {
+    /// Check whether an address belongs to this domain
+    pub fn matches_address(&self, address: &str) -> bool {
+        address
+            .rsplit_once('@')
+            .is_some_and(|(_, domain)| domain.eq_ignore_ascii_case(&self.domain))
+    }
+
+    /// Check whether a name is a valid, lowercase domain name
+    pub fn is_valid_name(name: &str) -> bool {
+        let labels: Vec<&str> = name.split('.').collect();
+
+        name.len() <= 253
+            && labels.len() >= 2
+            && labels.iter().all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            })
+    }
+
     /// Find all domains
     #[instrument(name = "Domain::find_all", skip(exe))]
     pub async fn find_all(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
@@ -53,7 +84,10 @@ impl Domain {
     #[instrument(name = "Domain::find_all_unassociated", skip(exe))]
     pub async fn find_all_unassociated(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
         Ok(rorm::query(exe, DomainModel)
-            .condition(DomainModel.club.is_none())
+            .condition(and![
+                DomainModel.club.is_none(),
+                DomainModel.missing_since.is_none()
+            ])
             .order_asc(DomainModel.domain)
             .stream()
             .map_ok(Domain::from)
@@ -76,6 +110,26 @@ impl Domain {
             .await?)
     }
 
+    /// Find all domains of a club which are present in mailcow
+    ///
+    /// Tombstones of domains missing from mailcow are skipped.
+    #[instrument(name = "Domain::find_all_present_by_club", skip(exe))]
+    pub async fn find_all_present_by_club(
+        exe: impl Executor<'_>,
+        club: ClubUuid,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, DomainModel)
+            .order_asc(DomainModel.domain)
+            .condition(and![
+                DomainModel.club.equals(Some(club.0)),
+                DomainModel.missing_since.is_none()
+            ])
+            .stream()
+            .map_ok(Domain::from)
+            .try_collect()
+            .await?)
+    }
+
     /// Find a single domain
     #[instrument(name = "Domain::find_by_domain", skip(exe))]
     pub async fn find_by_domain(
@@ -103,6 +157,25 @@ impl Domain {
             .map(Domain::from))
     }
 
+    /// Create a new domain
+    ///
+    /// The domain has to exist in mailcow already, otherwise the next sync marks it as missing.
+    #[instrument(name = "Domain::create", skip(exe))]
+    pub async fn create(exe: impl Executor<'_>, domain: CreateDomain) -> anyhow::Result<Self> {
+        let model = rorm::insert(exe, DomainModel)
+            .single(&DomainModel {
+                uuid: Uuid::new_v4(),
+                domain: domain.domain,
+                club: domain.club.map(|club| ForeignModelByField(club.0)),
+                is_primary: domain.is_primary,
+                mailboxes_left: domain.mailboxes_left as i64,
+                missing_since: None,
+            })
+            .await?;
+
+        Ok(Self::from(model))
+    }
+
     /// Delete a domain by its domain
     #[instrument(name = "Domain::delete_by_domain", skip(exe))]
     pub async fn delete_by_domain(
@@ -116,51 +189,142 @@ impl Domain {
         Ok(())
     }
 
+    /// Find all domains missing from mailcow which are kept as tombstones
+    #[instrument(name = "Domain::find_all_missing", skip(exe))]
+    pub async fn find_all_missing(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, DomainModel)
+            .condition(DomainModel.missing_since.is_some())
+            .order_asc(DomainModel.domain)
+            .stream()
+            .map_ok(Domain::from)
+            .try_collect()
+            .await?)
+    }
+
     /// Sync new mailcow domains
     ///
     /// Existing domains are updated, if necessary.
-    /// New domains will be created
-    #[instrument(name = "Domain::sync_mailcow_domains", skip(exe))]
+    /// New domains will be created.
+    /// Domains missing from mailcow are kept as tombstones until the grace period of
+    /// the policy is over, unless too many of them went missing at once.
+    #[instrument(name = "Domain::sync_mailcow_domains", skip(exe, mailcow_domains))]
     pub async fn sync_mailcow_domains(
         exe: impl Executor<'_>,
         mailcow_domains: Vec<MailcowDomain>,
-    ) -> anyhow::Result<()> {
+        policy: DomainSyncPolicy,
+    ) -> anyhow::Result<DomainSyncReport> {
         let mut guard = exe.ensure_transaction().await?;
+        let now = OffsetDateTime::now_utc();
 
         let existing = rorm::query(guard.get_transaction(), DomainModel)
             .all()
             .await?;
 
-        // Remove domains that don't exist in mailcow anymore
-        for domain in &existing {
-            if !mailcow_domains
-                .iter()
-                .any(|x| x.domain_name == *domain.domain)
-            {
-                rorm::delete(guard.get_transaction(), DomainModel)
+        let mut report = DomainSyncReport {
+            added: vec![],
+            restored: vec![],
+            missing: vec![],
+            deleted: vec![],
+            kept_primary: vec![],
+            removal_refused: false,
+        };
+
+        // Domains which went missing since the last sync
+        let newly_missing: Vec<&DomainModel> = existing
+            .iter()
+            .filter(|domain| domain.missing_since.is_none())
+            .filter(|domain| {
+                !mailcow_domains
+                    .iter()
+                    .any(|x| x.domain_name == *domain.domain)
+            })
+            .collect();
+
+        let present = existing
+            .iter()
+            .filter(|domain| domain.missing_since.is_none())
+            .count();
+        report.removal_refused = !policy.force
+            && !newly_missing.is_empty()
+            && newly_missing.len() * 100 > present * policy.max_removal_percent as usize;
+
+        if report.removal_refused {
+            report
+                .missing
+                .extend(newly_missing.iter().map(|domain| domain.domain.clone()));
+            warn!(
+                "Refusing to remove {} of {} domains missing from mailcow",
+                newly_missing.len(),
+                present
+            );
+        } else {
+            for domain in newly_missing {
+                rorm::update(guard.get_transaction(), DomainModel)
+                    .set(DomainModel.missing_since, Some(now))
                     .condition(DomainModel.uuid.equals(domain.uuid))
                     .await?;
+                report.missing.push(domain.domain.clone());
+            }
+
+            // Remove tombstones whose grace period is over
+            for domain in &existing {
+                let Some(missing_since) = domain.missing_since else {
+                    continue;
+                };
+                if mailcow_domains
+                    .iter()
+                    .any(|x| x.domain_name == *domain.domain)
+                {
+                    continue;
+                }
+
+                if missing_since + policy.grace_period <= now {
+                    // Deleting the primary domain would leave the club without one
+                    if domain.is_primary {
+                        warn!(
+                            domain = %domain.domain,
+                            "Keeping expired primary domain missing from mailcow"
+                        );
+                        report.kept_primary.push(domain.domain.clone());
+                        continue;
+                    }
+
+                    rorm::delete(guard.get_transaction(), DomainModel)
+                        .condition(DomainModel.uuid.equals(domain.uuid))
+                        .await?;
+                    report.deleted.push(domain.domain.clone());
+                } else {
+                    report.missing.push(domain.domain.clone());
+                }
             }
         }
 
         // Update existing domains
         let mut to_add = vec![];
         for domain in mailcow_domains {
-            if existing
+            if let Some(known) = existing
                 .iter()
-                .any(|x| &*x.domain == domain.domain_name.as_str())
+                .find(|x| &*x.domain == domain.domain_name.as_str())
             {
                 rorm::update(guard.get_transaction(), DomainModel)
                     .set(DomainModel.mailboxes_left, domain.mboxes_left as i64)
+                    .set(DomainModel.missing_since, None)
                     .condition(DomainModel.domain.equals(&domain.domain_name))
                     .await?;
+
+                if known.missing_since.is_some() {
+                    report.restored.push(known.domain.clone());
+                }
             } else {
+                let name = MaxStr::new(domain.domain_name)?;
+                report.added.push(name.clone());
                 to_add.push(DomainModel {
                     uuid: Uuid::new_v4(),
-                    domain: MaxStr::new(domain.domain_name)?,
+                    domain: name,
                     club: None,
                     is_primary: false,
                     mailboxes_left: domain.mboxes_left as i64,
+                    missing_since: None,
                 });
             }
         }
@@ -172,10 +336,38 @@ impl Domain {
 
         guard.commit().await?;
 
-        Ok(())
+        Ok(report)
     }
 }
 
+/// Rules for removing domains which are missing from mailcow
+#[derive(Debug, Clone, Copy)]
+pub struct DomainSyncPolicy {
+    /// Maximum percentage of domains which may go missing in a single sync
+    pub max_removal_percent: u8,
+    /// Duration missing domains are kept as tombstones
+    pub grace_period: Duration,
+    /// Remove missing domains even if they exceed `max_removal_percent`
+    pub force: bool,
+}
+
+/// Changes applied by a domain sync
+#[derive(Debug, Clone)]
+pub struct DomainSyncReport {
+    /// Domains which were new in mailcow
+    pub added: Vec<MaxStr<255>>,
+    /// Tombstones which reappeared in mailcow
+    pub restored: Vec<MaxStr<255>>,
+    /// Domains missing from mailcow which are kept
+    pub missing: Vec<MaxStr<255>>,
+    /// Tombstones which were deleted after their grace period
+    pub deleted: Vec<MaxStr<255>>,
+    /// Primary domains whose grace period is over, but which are kept until their club gets a new primary domain
+    pub kept_primary: Vec<MaxStr<255>>,
+    /// Too many domains went missing at once, so none were removed
+    pub removal_refused: bool,
+}
+
 /// Parameters for creating a new domain
 #[derive(Debug, Clone)]
 pub struct CreateDomain {
@@ -185,6 +377,8 @@ pub struct CreateDomain {
     pub club: Option<ClubUuid>,
     /// Whether this is the primary domain for the club
     pub is_primary: bool,
+    /// How many mailboxes can be created on this domain
+    pub mailboxes_left: u64,
 }
 
 impl From<DomainModel> for Domain {
@@ -194,6 +388,7 @@ impl From<DomainModel> for Domain {
             domain: value.domain,
             associated_club: value.club.map(|club| ClubUuid(club.0)),
             is_primary: value.is_primary,
+            missing_since: value.missing_since,
         }
     }
 }
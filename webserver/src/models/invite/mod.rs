@@ -1,6 +1,7 @@
 //! Invite related code lives in this module
 
 use anyhow::Context;
+use anyhow::anyhow;
 use futures_util::TryStreamExt;
 use galvyn::core::re_exports::schemars;
 use galvyn::core::re_exports::schemars::JsonSchema;
@@ -15,15 +16,13 @@ use tracing::instrument;
 use uuid::Uuid;
 
 use crate::models::account::Account;
-use crate::models::account::AdministrativeAccount;
-use crate::models::account::ClubAccount;
-use crate::models::account::ClubAdminAccount;
-use crate::models::account::db::AdministrativeAccountModel;
-use crate::models::account::db::AdministrativeAccountModelInsert;
-use crate::models::account::db::ClubAccountModel;
-use crate::models::account::db::ClubAccountModelInsert;
-use crate::models::account::db::ClubAdminAccountModel;
-use crate::models::account::db::ClubAdminAccountModelInsert;
+use crate::models::account::AccountUuid;
+use crate::models::account::db::AccountModel;
+use crate::models::account::db::AccountModelInsert;
+use crate::models::account::db::ClubAdminModel;
+use crate::models::account::db::ClubAdminModelInsert;
+use crate::models::account::db::ClubMemberModel;
+use crate::models::account::db::ClubMemberModelInsert;
 use crate::models::account::db::UsernameModel;
 use crate::models::club::ClubUuid;
 use crate::models::invite::db::InviteModel;
@@ -88,6 +87,30 @@ impl Invite {
             .await?)
     }
 
+    /// Find the invite which reserved a username
+    #[instrument(name = "Invite::find_by_username", skip(exe))]
+    pub async fn find_by_username(
+        exe: impl Executor<'_>,
+        username: &MaxStr<255>,
+    ) -> anyhow::Result<Option<Self>> {
+        Ok(rorm::query(exe, InviteModel)
+            .condition(InviteModel.username.equals(username))
+            .optional()
+            .await?
+            .map(Invite::from))
+    }
+
+    /// Find all pending invites for superadmins
+    #[instrument(name = "Invite::find_superadmin_invites", skip(exe))]
+    pub async fn find_superadmin_invites(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, InviteModel)
+            .condition(InviteModel.club.is_none())
+            .stream()
+            .map_ok(Invite::from)
+            .try_collect()
+            .await?)
+    }
+
     /// Get the point in time the invite expires
     pub fn expires_at(&self) -> time::OffsetDateTime {
         self.expires_at
@@ -121,50 +144,47 @@ impl Invite {
             MaxStr::new(Account::hash_password(&password).context("Hashing password failed")?)
                 .expect("Resulting hash must be <255 bytes");
 
+        let account_uuid = Uuid::new_v4();
+        rorm::insert(guard.get_transaction(), AccountModel)
+            .return_nothing()
+            .single(&AccountModelInsert {
+                uuid: account_uuid,
+                username: ForeignModelByField(self.username),
+                display_name: self.display_name,
+                hashed_password,
+                superadmin: self.club.is_none(),
+            })
+            .await?;
+
         // Club Member
-        let account = if let Some(email) = self.email
+        if let Some(email) = self.email
             && let Some(club) = self.club
         {
-            Account::ClubMember(ClubAccount::from(
-                rorm::insert(guard.get_transaction(), ClubAccountModel)
-                    .single(&ClubAccountModelInsert {
-                        uuid: Uuid::new_v4(),
-                        username: ForeignModelByField(self.username),
-                        display_name: self.display_name,
-                        hashed_password,
-                        email,
-                        club: ForeignModelByField(club.0),
-                    })
-                    .await?,
-            ))
+            rorm::insert(guard.get_transaction(), ClubMemberModel)
+                .return_nothing()
+                .single(&ClubMemberModelInsert {
+                    uuid: Uuid::new_v4(),
+                    account: ForeignModelByField(account_uuid),
+                    club: ForeignModelByField(club.0),
+                    email,
+                })
+                .await?;
         }
         // Club admin
         else if let Some(club) = self.club {
-            Account::ClubAdmin(ClubAdminAccount::from(
-                rorm::insert(guard.get_transaction(), ClubAdminAccountModel)
-                    .single(&ClubAdminAccountModelInsert {
-                        uuid: Uuid::new_v4(),
-                        username: ForeignModelByField(self.username),
-                        display_name: self.display_name,
-                        hashed_password,
-                        club: ForeignModelByField(club.0),
-                    })
-                    .await?,
-            ))
+            rorm::insert(guard.get_transaction(), ClubAdminModel)
+                .return_nothing()
+                .single(&ClubAdminModelInsert {
+                    uuid: Uuid::new_v4(),
+                    account: ForeignModelByField(account_uuid),
+                    club: ForeignModelByField(club.0),
+                })
+                .await?;
         }
-        // Superadmin
-        else {
-            Account::Superadmin(AdministrativeAccount::from(
-                rorm::insert(guard.get_transaction(), AdministrativeAccountModel)
-                    .single(&AdministrativeAccountModelInsert {
-                        uuid: Uuid::new_v4(),
-                        username: ForeignModelByField(self.username),
-                        display_name: self.display_name,
-                        hashed_password,
-                    })
-                    .await?,
-            ))
-        };
+
+        let account = Account::get_by_uuid(guard.get_transaction(), AccountUuid(account_uuid))
+            .await?
+            .ok_or(anyhow!("Account vanished after insert"))?;
 
         // Delete invite, the related invited roles will be deleted by cascade
         rorm::delete(guard.get_transaction(), InviteModel)
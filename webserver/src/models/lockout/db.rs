@@ -0,0 +1,44 @@
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use uuid::Uuid;
+
+/// Failed attempts of a single client or against a single username
+#[derive(Debug, Model)]
+#[rorm(rename = "Lockout")]
+pub struct LockoutModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// Combination of scope, kind and value to look up the counter
+    #[rorm(unique)]
+    pub key: MaxStr<320>,
+    /// The group of endpoints the attempts were made against
+    pub scope: MaxStr<32>,
+    /// Either `ip` or `username`
+    pub kind: MaxStr<16>,
+    pub value: MaxStr<255>,
+
+    /// Unix timestamps of the failed attempts within the current window
+    pub failures: Json<Vec<i64>>,
+    /// Number of times the key has been locked in a row
+    pub lockouts: i32,
+    pub locked_until: Option<time::OffsetDateTime>,
+
+    pub last_failure_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "LockoutModel")]
+pub struct LockoutModelInsert {
+    pub uuid: Uuid,
+    pub key: MaxStr<320>,
+    pub scope: MaxStr<32>,
+    pub kind: MaxStr<16>,
+    pub value: MaxStr<255>,
+    pub failures: Json<Vec<i64>>,
+    pub lockouts: i32,
+    pub locked_until: Option<time::OffsetDateTime>,
+    pub last_failure_at: time::OffsetDateTime,
+}
@@ -0,0 +1,347 @@
+//! Protection against brute force attacks
+//!
+//! Failed attempts are counted per client ip and per targeted username
+//! within a sliding window. Exceeding the allowed number of failures locks the key,
+//! with the duration doubling for every consecutive lockout.
+
+use anyhow::anyhow;
+use futures_util::TryStreamExt;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm;
+use galvyn::rorm::Database;
+use galvyn::rorm::and;
+use galvyn::rorm::conditions::Condition;
+use galvyn::rorm::conditions::DynamicCollection;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::db::transaction::Transaction;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::or;
+use serde::Deserialize;
+use serde::Serialize;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::instrument;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::lockout::db::LockoutModel;
+use crate::models::lockout::db::LockoutModelInsert;
+use crate::utils::text::truncate;
+
+pub(in crate::models) mod db;
+
+/// Failed attempts older than this are not counted
+pub const FAILURE_WINDOW: Duration = Duration::minutes(15);
+
+/// Duration of the first lockout
+const BASE_LOCKOUT: Duration = Duration::minutes(1);
+
+/// Number of tries to record a failure when racing concurrent failures
+const MAX_RECORD_ATTEMPTS: usize = 3;
+
+/// Upper bound for the lockout duration
+const MAX_LOCKOUT: Duration = Duration::hours(24);
+
+/// The lockout duration is reset after a key had no failures for this duration
+const LOCKOUT_RESET: Duration = Duration::hours(24);
+
+/// Failed attempts of a client or against a username
+#[derive(Debug, Clone)]
+pub struct Lockout {
+    /// Primary key of the lockout
+    pub uuid: LockoutUuid,
+    /// The group of endpoints the attempts were made against
+    pub scope: MaxStr<32>,
+    /// Whether the attempts are counted per ip or per username
+    pub kind: LockoutKind,
+    /// The ip address or username
+    pub value: MaxStr<255>,
+    /// Number of failed attempts within the current window
+    pub failed_attempts: usize,
+    /// The point in time the lock ends
+    pub locked_until: Option<OffsetDateTime>,
+    /// The point in time of the last failed attempt
+    pub last_failure_at: OffsetDateTime,
+}
+
+/// New-type for the primary key of the lockout
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LockoutUuid(pub Uuid);
+
+/// The kind of value failed attempts are counted for
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum LockoutKind {
+    /// The client's ip address
+    Ip,
+    /// The username an attempt was made against
+    Username,
+}
+
+impl LockoutKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            LockoutKind::Ip => "ip",
+            LockoutKind::Username => "username",
+        }
+    }
+}
+
+impl Lockout {
+    /// Get the point in time until which any of the given keys is locked
+    #[instrument(name = "Lockout::locked_until", skip(exe))]
+    pub async fn locked_until(
+        exe: impl Executor<'_>,
+        scope: &str,
+        keys: &[(LockoutKind, &str)],
+    ) -> anyhow::Result<Option<OffsetDateTime>> {
+        if keys.is_empty() {
+            return Ok(None);
+        }
+
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|(kind, value)| build_key(scope, *kind, value))
+            .collect();
+        let now = OffsetDateTime::now_utc();
+
+        let locked_until = rorm::query(exe, LockoutModel.locked_until)
+            .condition(&DynamicCollection::or_unchecked(
+                keys.iter()
+                    .map(|key| LockoutModel.key.equals(key.as_str()).boxed())
+                    .collect(),
+            ))
+            .all()
+            .await?
+            .into_iter()
+            .flatten()
+            .filter(|locked_until| *locked_until > now)
+            .max();
+
+        Ok(locked_until)
+    }
+
+    /// Record a failed attempt
+    ///
+    /// Returns the point in time until which the key is locked,
+    /// if the attempt exceeded the allowed number of failures.
+    ///
+    /// Concurrent failures against the same key are retried,
+    /// so every one of them is counted.
+    #[instrument(name = "Lockout::record_failure", skip(db))]
+    pub async fn record_failure(
+        db: &Database,
+        scope: &str,
+        kind: LockoutKind,
+        value: &str,
+        max_failures: u8,
+    ) -> anyhow::Result<Option<OffsetDateTime>> {
+        let mut attempt = 1;
+        loop {
+            let mut tx = db.start_transaction().await?;
+
+            match Self::try_record_failure(&mut tx, scope, kind, value, max_failures).await {
+                Ok(Some(locked_until)) => {
+                    tx.commit().await?;
+                    return Ok(locked_until);
+                }
+                // Another request has inserted or updated the key in the meantime
+                Ok(None) | Err(_) if attempt < MAX_RECORD_ATTEMPTS => {
+                    tx.rollback().await?;
+                    attempt += 1;
+                }
+                Ok(None) => {
+                    return Err(anyhow!(
+                        "Couldn't record failed attempt due to concurrent attempts"
+                    ));
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Record a failed attempt if the key wasn't modified concurrently
+    ///
+    /// Returns `None` if the counter changed since it was read.
+    /// Inserting a key, which was inserted concurrently, fails due to its unique constraint.
+    async fn try_record_failure(
+        tx: &mut Transaction,
+        scope: &str,
+        kind: LockoutKind,
+        value: &str,
+        max_failures: u8,
+    ) -> anyhow::Result<Option<Option<OffsetDateTime>>> {
+        let key = build_key(scope, kind, value);
+        let now = OffsetDateTime::now_utc();
+
+        let existing = rorm::query(&mut *tx, LockoutModel)
+            .condition(LockoutModel.key.equals(key.as_str()))
+            .optional()
+            .await?;
+
+        let Some(existing) = existing else {
+            let locked_until = (max_failures <= 1).then(|| now + BASE_LOCKOUT);
+
+            rorm::insert(&mut *tx, LockoutModel)
+                .return_nothing()
+                .single(&LockoutModelInsert {
+                    uuid: Uuid::new_v4(),
+                    key: MaxStr::new(key)?,
+                    scope: MaxStr::new(truncate(scope, 32))?,
+                    kind: MaxStr::new(kind.as_str().to_string())?,
+                    value: MaxStr::new(truncate(value, 255))?,
+                    failures: Json(vec![now.unix_timestamp()]),
+                    lockouts: locked_until.is_some() as i32,
+                    locked_until,
+                    last_failure_at: now,
+                })
+                .await?;
+
+            return Ok(Some(locked_until));
+        };
+
+        let window_start = (now - FAILURE_WINDOW).unix_timestamp();
+        let mut failures = existing.failures.0;
+        failures.retain(|timestamp| *timestamp > window_start);
+        failures.push(now.unix_timestamp());
+
+        let mut lockouts = existing.lockouts;
+        if existing.last_failure_at + LOCKOUT_RESET < now {
+            lockouts = 0;
+        }
+
+        let mut locked_until = existing.locked_until.filter(|until| *until > now);
+        if locked_until.is_none() && failures.len() >= max_failures as usize {
+            let duration = BASE_LOCKOUT
+                .checked_mul(1 << lockouts.clamp(0, 16))
+                .unwrap_or(MAX_LOCKOUT)
+                .min(MAX_LOCKOUT);
+
+            warn!(%scope, kind = kind.as_str(), %value, %duration, "Locking after too many failed attempts");
+
+            locked_until = Some(now + duration);
+            lockouts += 1;
+            failures.clear();
+        }
+
+        let updated = rorm::update(&mut *tx, LockoutModel)
+            .set(LockoutModel.failures, Json(failures))
+            .set(LockoutModel.lockouts, lockouts)
+            .set(LockoutModel.locked_until, locked_until)
+            .set(LockoutModel.last_failure_at, now)
+            .condition(and![
+                LockoutModel.uuid.equals(existing.uuid),
+                LockoutModel
+                    .last_failure_at
+                    .equals(existing.last_failure_at),
+            ])
+            .await?;
+
+        Ok((updated > 0).then_some(locked_until))
+    }
+
+    /// Forget the failed attempts of a key after a successful attempt
+    #[instrument(name = "Lockout::record_success", skip(exe))]
+    pub async fn record_success(
+        exe: impl Executor<'_>,
+        scope: &str,
+        kind: LockoutKind,
+        value: &str,
+    ) -> anyhow::Result<()> {
+        let key = build_key(scope, kind, value);
+
+        rorm::delete(exe, LockoutModel)
+            .condition(LockoutModel.key.equals(key.as_str()))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Retrieve all keys which are locked or have recent failures
+    #[instrument(name = "Lockout::find_all", skip(exe))]
+    pub async fn find_all(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
+        let now = OffsetDateTime::now_utc();
+
+        let lockouts: Vec<_> = rorm::query(exe, LockoutModel)
+            .order_desc(LockoutModel.last_failure_at)
+            .stream()
+            .map_ok(Lockout::from)
+            .try_collect()
+            .await?;
+
+        Ok(lockouts
+            .into_iter()
+            .filter(|lockout| {
+                lockout.failed_attempts > 0 || lockout.locked_until.is_some_and(|x| x > now)
+            })
+            .collect())
+    }
+
+    /// Delete a lockout including its failed attempts
+    ///
+    /// Returns `false` if the lockout doesn't exist.
+    #[instrument(name = "Lockout::delete", skip(exe))]
+    pub async fn delete(
+        exe: impl Executor<'_>,
+        LockoutUuid(uuid): LockoutUuid,
+    ) -> anyhow::Result<bool> {
+        let deleted = rorm::delete(exe, LockoutModel)
+            .condition(LockoutModel.uuid.equals(uuid))
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Remove all keys which are neither locked nor need to remember
+    /// their previous lockouts
+    #[instrument(name = "Lockout::clear_expired", skip(exe))]
+    pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
+        let now = OffsetDateTime::now_utc();
+
+        rorm::delete(exe, LockoutModel)
+            .condition(and![
+                LockoutModel.last_failure_at.less_than(now - LOCKOUT_RESET),
+                or![
+                    LockoutModel.locked_until.is_none(),
+                    LockoutModel.locked_until.less_than(Some(now)),
+                ],
+            ])
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl From<LockoutModel> for Lockout {
+    fn from(model: LockoutModel) -> Self {
+        let window_start = (OffsetDateTime::now_utc() - FAILURE_WINDOW).unix_timestamp();
+
+        Self {
+            uuid: LockoutUuid(model.uuid),
+            kind: match &*model.kind {
+                "ip" => LockoutKind::Ip,
+                _ => LockoutKind::Username,
+            },
+            scope: model.scope,
+            value: model.value,
+            failed_attempts: model
+                .failures
+                .0
+                .iter()
+                .filter(|timestamp| **timestamp > window_start)
+                .count(),
+            locked_until: model.locked_until,
+            last_failure_at: model.last_failure_at,
+        }
+    }
+}
+
+fn build_key(scope: &str, kind: LockoutKind, value: &str) -> String {
+    format!(
+        "{}:{}:{}",
+        truncate(scope, 32),
+        kind.as_str(),
+        truncate(value, 255)
+    )
+}
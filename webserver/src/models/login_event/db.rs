@@ -0,0 +1,46 @@
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use uuid::Uuid;
+
+use crate::models::account::db::AccountModel;
+
+/// A successful or failed sign in of an account
+#[derive(Debug, Model)]
+#[rorm(rename = "LoginEvent")]
+pub struct LoginEventModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    /// Either `password`, `passkey`, `oidc` or `app`
+    pub method: MaxStr<16>,
+    pub success: bool,
+
+    pub ip: Option<MaxStr<64>>,
+    /// The network the ip address is part of
+    pub ip_range: Option<MaxStr<64>>,
+    pub user_agent: Option<MaxStr<255>>,
+
+    /// Whether this was the first successful sign in from the ip range
+    pub new_ip_range: bool,
+
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "LoginEventModel")]
+pub struct LoginEventModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub method: MaxStr<16>,
+    pub success: bool,
+    pub ip: Option<MaxStr<64>>,
+    pub ip_range: Option<MaxStr<64>>,
+    pub user_agent: Option<MaxStr<255>>,
+    pub new_ip_range: bool,
+}
@@ -0,0 +1,220 @@
+//! History of the sign ins of an account
+//!
+//! Successful sign ins from an ip range the account never signed in from before are flagged,
+//! so the owner of the account can be notified about them.
+
+use std::net::IpAddr;
+
+use futures_util::TryStreamExt;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use serde::Deserialize;
+use serde::Serialize;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::account::AccountUuid;
+use crate::models::login_event::db::LoginEventModel;
+use crate::models::login_event::db::LoginEventModelInsert;
+use crate::utils::client_ip::IpCidr;
+
+pub(in crate::models) mod db;
+
+/// Sign ins older than this are removed
+const LOGIN_EVENT_RETENTION: Duration = Duration::days(180);
+
+/// Prefix length of the ip ranges of IPv4 addresses
+const IPV4_RANGE_PREFIX: u8 = 24;
+
+/// Prefix length of the ip ranges of IPv6 addresses
+const IPV6_RANGE_PREFIX: u8 = 48;
+
+/// A successful or failed sign in of an account
+#[derive(Debug, Clone)]
+pub struct LoginEvent {
+    /// Primary key of the event
+    pub uuid: LoginEventUuid,
+    /// The account that was signed in to
+    pub account: AccountUuid,
+    /// How the account was signed in to
+    pub method: LoginMethod,
+    /// Whether the sign in succeeded
+    pub success: bool,
+    /// The ip address of the client
+    pub ip: Option<MaxStr<64>>,
+    /// The user agent of the client
+    pub user_agent: Option<MaxStr<255>>,
+    /// Whether this was the first successful sign in from the ip range
+    pub new_ip_range: bool,
+    /// The point in time of the sign in
+    pub created_at: OffsetDateTime,
+}
+
+/// New-type for the primary key of the login event
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LoginEventUuid(pub Uuid);
+
+/// The way an account was signed in to
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum LoginMethod {
+    /// The sign in form, using a password and an optional second factor
+    Password,
+    /// The sign in form, using a passkey
+    Passkey,
+    /// Authorization of an OIDC client
+    Oidc,
+    /// Authorization of an app using the device flow
+    App,
+}
+
+impl LoginMethod {
+    fn as_str(self) -> &'static str {
+        match self {
+            LoginMethod::Password => "password",
+            LoginMethod::Passkey => "passkey",
+            LoginMethod::Oidc => "oidc",
+            LoginMethod::App => "app",
+        }
+    }
+}
+
+/// Parameters to record a sign in
+#[derive(Debug, Clone)]
+pub struct RecordLoginParams {
+    /// The account that was signed in to
+    pub account: AccountUuid,
+    /// How the account was signed in to
+    pub method: LoginMethod,
+    /// Whether the sign in succeeded
+    pub success: bool,
+    /// The ip address of the client
+    pub ip: Option<IpAddr>,
+    /// The user agent of the client
+    pub user_agent: Option<String>,
+}
+
+impl LoginEvent {
+    /// Record a sign in
+    ///
+    /// Successful sign ins are flagged if the account has signed in before,
+    /// but never from the client's ip range.
+    #[instrument(name = "LoginEvent::record", skip(exe))]
+    pub async fn record(
+        exe: impl Executor<'_>,
+        RecordLoginParams {
+            account,
+            method,
+            success,
+            ip,
+            user_agent,
+        }: RecordLoginParams,
+    ) -> anyhow::Result<Self> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let ip_range = ip
+            .map(|ip| {
+                let prefix = match ip {
+                    IpAddr::V4(_) => IPV4_RANGE_PREFIX,
+                    IpAddr::V6(_) => IPV6_RANGE_PREFIX,
+                };
+                MaxStr::new(IpCidr::network_of(ip, prefix).to_string())
+            })
+            .transpose()?;
+
+        let new_ip_range = match &ip_range {
+            Some(ip_range) if success => {
+                let previous = rorm::query(guard.get_transaction(), LoginEventModel.ip_range)
+                    .condition(and![
+                        LoginEventModel.account.equals(account.0),
+                        LoginEventModel.success.equals(true),
+                    ])
+                    .all()
+                    .await?;
+
+                !previous.is_empty()
+                    && !previous
+                        .iter()
+                        .any(|previous| previous.as_ref() == Some(ip_range))
+            }
+            _ => false,
+        };
+
+        let model = rorm::insert(guard.get_transaction(), LoginEventModel)
+            .single(&LoginEventModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(account.0),
+                method: MaxStr::new(method.as_str().to_string())?,
+                success,
+                ip: ip.map(|ip| MaxStr::new(ip.to_string())).transpose()?,
+                ip_range,
+                user_agent: user_agent
+                    .map(|user_agent| user_agent.chars().take(255).collect())
+                    .map(MaxStr::new)
+                    .transpose()?,
+                new_ip_range,
+            })
+            .await?;
+
+        guard.commit().await?;
+
+        Ok(Self::from(model))
+    }
+
+    /// Retrieve the latest sign ins of an account
+    #[instrument(name = "LoginEvent::find_latest_by_account", skip(exe))]
+    pub async fn find_latest_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+        limit: u64,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, LoginEventModel)
+            .condition(LoginEventModel.account.equals(account))
+            .order_desc(LoginEventModel.created_at)
+            .limit(limit)
+            .stream()
+            .map_ok(LoginEvent::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Clear sign ins which exceeded the retention period
+    #[instrument(name = "LoginEvent::clear_expired", skip(exe))]
+    pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, LoginEventModel)
+            .condition(
+                LoginEventModel
+                    .created_at
+                    .less_than(OffsetDateTime::now_utc() - LOGIN_EVENT_RETENTION),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl From<LoginEventModel> for LoginEvent {
+    fn from(model: LoginEventModel) -> Self {
+        Self {
+            uuid: LoginEventUuid(model.uuid),
+            account: AccountUuid(model.account.0),
+            method: match &*model.method {
+                "passkey" => LoginMethod::Passkey,
+                "oidc" => LoginMethod::Oidc,
+                "app" => LoginMethod::App,
+                _ => LoginMethod::Password,
+            },
+            success: model.success,
+            ip: model.ip,
+            user_agent: model.user_agent,
+            new_ip_range: model.new_ip_range,
+            created_at: model.created_at,
+        }
+    }
+}
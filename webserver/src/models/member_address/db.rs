@@ -0,0 +1,34 @@
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use uuid::Uuid;
+
+use crate::models::account::db::AccountModel;
+
+/// An additional address of a club member
+///
+/// Mails to it are forwarded to the member's mailbox by a mailcow alias.
+#[derive(Debug, Model)]
+#[rorm(rename = "MemberAddress")]
+pub struct MemberAddressModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    #[rorm(unique)]
+    pub address: MaxStr<255>,
+
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "MemberAddressModel")]
+pub struct MemberAddressModelInsert {
+    pub uuid: Uuid,
+    pub account: ForeignModel<AccountModel>,
+    pub address: MaxStr<255>,
+}
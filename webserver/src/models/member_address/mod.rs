@@ -0,0 +1,148 @@
+//! Additional addresses of club members
+//!
+//! Besides the address of its mailbox, a member may receive mails on further addresses
+//! on the domains of its club. Each of them is backed by an alias in mailcow.
+
+use futures_util::TryStreamExt;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::account::AccountUuid;
+use crate::models::account::db::ClubMemberModel;
+use crate::models::member_address::db::MemberAddressModel;
+use crate::models::member_address::db::MemberAddressModelInsert;
+
+pub(in crate::models) mod db;
+
+/// An additional address of a club member
+#[derive(Debug, Clone)]
+pub struct MemberAddress {
+    /// Primary key of the address
+    pub uuid: MemberAddressUuid,
+    /// The member receiving the mails
+    pub account: AccountUuid,
+    /// The address, in lowercase
+    pub address: MaxStr<255>,
+    /// The point in time the address was added
+    pub created_at: OffsetDateTime,
+}
+
+/// New-type for the primary key of the member address
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MemberAddressUuid(pub Uuid);
+
+impl MemberAddress {
+    /// Find all additional addresses of a member
+    #[instrument(name = "MemberAddress::find_all_by_account", skip(exe))]
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, MemberAddressModel)
+            .condition(MemberAddressModel.account.equals(account))
+            .order_asc(MemberAddressModel.address)
+            .stream()
+            .map_ok(MemberAddress::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Find the additional addresses of all members
+    #[instrument(name = "MemberAddress::find_all", skip(exe))]
+    pub async fn find_all(exe: impl Executor<'_>) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, MemberAddressModel)
+            .stream()
+            .map_ok(MemberAddress::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Find an additional address of a member
+    #[instrument(name = "MemberAddress::find_by_uuid", skip(exe))]
+    pub async fn find_by_uuid(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+        MemberAddressUuid(uuid): MemberAddressUuid,
+    ) -> anyhow::Result<Option<Self>> {
+        Ok(rorm::query(exe, MemberAddressModel)
+            .condition(and![
+                MemberAddressModel.uuid.equals(uuid),
+                MemberAddressModel.account.equals(account),
+            ])
+            .optional()
+            .await?
+            .map(MemberAddress::from))
+    }
+
+    /// Check whether an address is already used by a member, either for its mailbox or
+    /// as an additional address
+    #[instrument(name = "MemberAddress::is_in_use", skip(exe))]
+    pub async fn is_in_use(exe: impl Executor<'_>, address: &str) -> anyhow::Result<bool> {
+        let mut guard = exe.ensure_transaction().await?;
+        let address = address.to_lowercase();
+
+        let additional = rorm::query(guard.get_transaction(), MemberAddressModel.uuid)
+            .condition(MemberAddressModel.address.equals(&*address))
+            .optional()
+            .await?;
+        let mailbox = rorm::query(guard.get_transaction(), ClubMemberModel.uuid)
+            .condition(ClubMemberModel.email.equals(&*address))
+            .optional()
+            .await?;
+
+        guard.commit().await?;
+
+        Ok(additional.is_some() || mailbox.is_some())
+    }
+
+    /// Add an additional address to a member
+    ///
+    /// The caller has to ensure the address isn't in use.
+    #[instrument(name = "MemberAddress::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+        address: &str,
+    ) -> anyhow::Result<Self> {
+        let model = rorm::insert(exe, MemberAddressModel)
+            .single(&MemberAddressModelInsert {
+                uuid: Uuid::new_v4(),
+                account: ForeignModelByField(account),
+                address: MaxStr::new(address.to_lowercase())?,
+            })
+            .await?;
+
+        Ok(Self::from(model))
+    }
+
+    /// Remove the additional address
+    #[instrument(name = "MemberAddress::delete", skip(self, exe))]
+    pub async fn delete(self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, MemberAddressModel)
+            .condition(MemberAddressModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl From<MemberAddressModel> for MemberAddress {
+    fn from(model: MemberAddressModel) -> Self {
+        Self {
+            uuid: MemberAddressUuid(model.uuid),
+            account: AccountUuid(model.account.0),
+            address: model.address,
+            created_at: model.created_at,
+        }
+    }
+}
@@ -1,8 +1,16 @@
 //! This module holds the database models as well as the business representations and abstractions
 
 pub mod account;
+pub mod api_token;
 pub mod club;
 pub mod credential_reset;
 pub mod domain;
 pub mod invite;
+pub mod lockout;
+pub mod login_event;
+pub mod member_address;
 pub mod oidc_provider;
+pub mod outgoing_mail;
+pub mod passkey;
+pub mod recovery_email;
+pub mod session;
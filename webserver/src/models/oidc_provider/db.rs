@@ -5,7 +5,8 @@ use galvyn::rorm::prelude::ForeignModel;
 use url::Url;
 use uuid::Uuid;
 
-use crate::models::account::db::ClubAccountModel;
+use crate::models::account::AuthenticationMethod;
+use crate::models::account::db::AccountModel;
 
 #[derive(Debug, Model)]
 #[rorm(rename = "OidcClient")]
@@ -31,9 +32,41 @@ pub struct OidcAuthenticationTokenModel {
     pub code: MaxStr<64>,
     pub expires_at: time::OffsetDateTime,
     #[rorm(on_update = "Cascade", on_delete = "Cascade")]
-    pub account: ForeignModel<ClubAccountModel>,
+    pub account: ForeignModel<AccountModel>,
     pub nonce: Option<MaxStr<255>>,
     pub scopes: Json<Vec<String>>,
     /// PKCE code challenge (RFC 7636)
     pub code_challenge: Option<MaxStr<128>>,
+    /// The methods the user authenticated with
+    pub auth_methods: Option<Json<Vec<AuthenticationMethod>>>,
+}
+
+#[derive(Debug, Model)]
+#[rorm(rename = "OidcDeviceCode")]
+pub struct OidcDeviceCodeModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub client: ForeignModel<OidcClientModel>,
+    /// Secret code the device uses for polling the token endpoint
+    #[rorm(unique)]
+    pub device_code: MaxStr<64>,
+    /// Short code the user enters on the verification page
+    #[rorm(unique)]
+    pub user_code: MaxStr<9>,
+    pub scopes: Json<Vec<String>>,
+    pub expires_at: time::OffsetDateTime,
+    /// The last point in time the device polled for this code
+    pub last_polled_at: Option<time::OffsetDateTime>,
+    /// Seconds the device has to wait between polling requests
+    #[rorm(default = 5)]
+    pub poll_interval: i64,
+    /// The account which approved the request
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub account: Option<ForeignModel<AccountModel>>,
+    /// Whether the user denied the request
+    #[rorm(default = "false")]
+    pub denied: bool,
+    /// The methods the approving user authenticated with
+    pub auth_methods: Option<Json<Vec<AuthenticationMethod>>>,
 }
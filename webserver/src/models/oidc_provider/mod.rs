@@ -10,6 +10,7 @@ use galvyn::rorm::db::Executor;
 use galvyn::rorm::fields::types::Json;
 use galvyn::rorm::fields::types::MaxStr;
 use galvyn::rorm::prelude::ForeignModelByField;
+use rand::RngExt;
 use rand::distr::Alphanumeric;
 use rand::distr::SampleString;
 use serde::Deserialize;
@@ -21,10 +22,11 @@ use url::Url;
 use uuid::Uuid;
 
 use crate::models::account::AccountUuid;
+use crate::models::account::AuthenticationMethod;
 use crate::models::account::ClubAccount;
-use crate::models::account::db::ClubAccountModel;
 use crate::models::oidc_provider::db::OidcAuthenticationTokenModel;
 use crate::models::oidc_provider::db::OidcClientModel;
+use crate::models::oidc_provider::db::OidcDeviceCodeModel;
 
 pub(in crate::models) mod db;
 
@@ -110,6 +112,8 @@ pub struct OidcAuthenticationToken {
     pub scopes: Vec<String>,
     /// PKCE code challenge (RFC 7636)
     pub code_challenge: Option<MaxStr<128>>,
+    /// The methods the user authenticated with
+    pub auth_methods: Vec<AuthenticationMethod>,
 }
 
 impl OidcAuthenticationToken {
@@ -124,6 +128,7 @@ impl OidcAuthenticationToken {
             nonce,
             scopes,
             code_challenge,
+            auth_methods,
         }: CreateOidcAuthenticationToken,
     ) -> anyhow::Result<Self> {
         let mut guard = exe.ensure_transaction().await?;
@@ -141,6 +146,7 @@ impl OidcAuthenticationToken {
                 nonce,
                 scopes: Json(scopes),
                 code_challenge,
+                auth_methods: Some(Json(auth_methods)),
             })
             .await?;
 
@@ -159,6 +165,7 @@ impl OidcAuthenticationToken {
             nonce: token.nonce,
             scopes: token.scopes.0,
             code_challenge: token.code_challenge,
+            auth_methods: auth_methods_or_default(token.auth_methods),
         })
     }
 
@@ -168,34 +175,82 @@ impl OidcAuthenticationToken {
         exe: impl Executor<'_>,
         code: MaxStr<64>,
     ) -> anyhow::Result<Option<OidcAuthenticationToken>> {
+        let mut guard = exe.ensure_transaction().await?;
         let now = OffsetDateTime::now_utc();
-        Ok(rorm::query(
-            exe,
-            (
-                OidcAuthenticationTokenModel,
-                OidcAuthenticationTokenModel
-                    .account
-                    .query_as(ClubAccountModel),
-            ),
+
+        let Some(oidc_auth_token) =
+            rorm::query(guard.get_transaction(), OidcAuthenticationTokenModel)
+                .condition(and![
+                    OidcAuthenticationTokenModel.expires_at.greater_than(now),
+                    OidcAuthenticationTokenModel.code.equals(&*code)
+                ])
+                .optional()
+                .await?
+        else {
+            guard.commit().await?;
+            return Ok(None);
+        };
+
+        // The account might have left its club since the token was issued
+        let account = ClubAccount::get_by_uuid(
+            guard.get_transaction(),
+            AccountUuid(oidc_auth_token.account.0),
         )
-        .condition(and![
-            OidcAuthenticationTokenModel.expires_at.greater_than(now),
-            OidcAuthenticationTokenModel.code.equals(&*code)
-        ])
-        .optional()
-        .await?
-        .map(|(oidc_auth_token, account)| Self {
+        .await?;
+
+        guard.commit().await?;
+
+        Ok(account.map(|account| Self {
             code: oidc_auth_token.code,
             client_id: OidcClientUuid(oidc_auth_token.client.0),
             expires_at: oidc_auth_token.expires_at,
             redirect_url: oidc_auth_token.redirect_url,
-            account: ClubAccount::from(account),
+            account,
             nonce: oidc_auth_token.nonce,
             scopes: oidc_auth_token.scopes.0,
             code_challenge: oidc_auth_token.code_challenge,
+            auth_methods: auth_methods_or_default(oidc_auth_token.auth_methods),
         }))
     }
 
+    /// Retrieve all authentication tokens issued to an account
+    #[instrument(name = "OidcAuthenticationToken::find_all_by_account", skip(exe))]
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        account: AccountUuid,
+    ) -> anyhow::Result<Vec<OidcAuthenticationToken>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        // Tokens are only issued to club members
+        let Some(club_account) = ClubAccount::get_by_uuid(guard.get_transaction(), account).await?
+        else {
+            guard.commit().await?;
+            return Ok(Vec::new());
+        };
+
+        let tokens = rorm::query(guard.get_transaction(), OidcAuthenticationTokenModel)
+            .condition(OidcAuthenticationTokenModel.account.equals(account.0))
+            .all()
+            .await?;
+
+        guard.commit().await?;
+
+        Ok(tokens
+            .into_iter()
+            .map(|token| Self {
+                code: token.code,
+                client_id: OidcClientUuid(token.client.0),
+                expires_at: token.expires_at,
+                redirect_url: token.redirect_url,
+                account: club_account.clone(),
+                nonce: token.nonce,
+                scopes: token.scopes.0,
+                code_challenge: token.code_challenge,
+                auth_methods: auth_methods_or_default(token.auth_methods),
+            })
+            .collect())
+    }
+
     /// Delete an authentication token by its code
     #[instrument(name = "OidcAuthenticationToken::delete_by_code", skip(exe))]
     pub async fn delete_by_code(exe: impl Executor<'_>, code: &MaxStr<64>) -> anyhow::Result<()> {
@@ -222,6 +277,311 @@ pub struct CreateOidcAuthenticationToken {
     pub scopes: Vec<String>,
     /// PKCE code challenge (RFC 7636)
     pub code_challenge: Option<MaxStr<128>>,
+    /// The methods the user authenticated with
+    pub auth_methods: Vec<AuthenticationMethod>,
+}
+
+/// Characters used for user codes of the device authorization grant
+///
+/// Vowels are omitted to not accidentally generate words,
+/// as recommended by RFC 8628 Section 6.1
+const USER_CODE_CHARSET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+
+/// Lifetime of a device code
+const DEVICE_CODE_LIFETIME: Duration = Duration::minutes(10);
+
+/// Minimal interval in seconds a device has to wait between polling requests
+const DEVICE_CODE_POLL_INTERVAL: i64 = 5;
+
+/// Seconds the interval is increased by after a device polled too fast (RFC 8628 Section 3.5)
+const DEVICE_CODE_SLOW_DOWN_STEP: i64 = 5;
+
+/// A pending device authorization request (RFC 8628)
+pub struct OidcDeviceCode {
+    /// Primary key of the device code
+    pub uuid: Uuid,
+    /// The client which requested the authorization
+    pub client_id: OidcClientUuid,
+    /// Secret code the device uses for polling
+    pub device_code: MaxStr<64>,
+    /// Code the user has to enter on the verification page
+    pub user_code: MaxStr<9>,
+    /// Scopes the client has requested
+    pub scopes: Vec<String>,
+    /// The point in time the request will expire
+    pub expires_at: OffsetDateTime,
+    /// The last point in time the device polled the token endpoint
+    pub last_polled_at: Option<OffsetDateTime>,
+    /// Seconds the device has to wait between polling requests
+    pub poll_interval: i64,
+    /// The state of the request
+    pub state: OidcDeviceCodeState,
+}
+
+/// The state of a device authorization request
+pub enum OidcDeviceCodeState {
+    /// The user has not decided yet
+    Pending,
+    /// The user has approved the request
+    Approved {
+        /// The account that approved the request
+        account: AccountUuid,
+        /// The methods the approving user authenticated with
+        auth_methods: Vec<AuthenticationMethod>,
+    },
+    /// The user has denied the request
+    Denied,
+}
+
+impl OidcDeviceCode {
+    /// Create a new device authorization request
+    #[instrument(name = "OidcDeviceCode::create", skip(exe))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        client_id: OidcClientUuid,
+        scopes: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        let device_code = MaxStr::new(Alphanumeric.sample_string(&mut rand::rng(), 64))?;
+        let user_code = MaxStr::new(generate_user_code())?;
+
+        let model = rorm::insert(exe, OidcDeviceCodeModel)
+            .single(&OidcDeviceCodeModel {
+                uuid: Uuid::new_v4(),
+                client: ForeignModelByField(client_id.0),
+                device_code,
+                user_code,
+                scopes: Json(scopes),
+                expires_at: OffsetDateTime::now_utc() + DEVICE_CODE_LIFETIME,
+                last_polled_at: None,
+                poll_interval: DEVICE_CODE_POLL_INTERVAL,
+                account: None,
+                denied: false,
+                auth_methods: None,
+            })
+            .await?;
+
+        Ok(Self::from(model))
+    }
+
+    /// Find a non-expired request by the code the user entered
+    ///
+    /// The user code is normalized before the lookup,
+    /// so casing and separators are irrelevant.
+    #[instrument(name = "OidcDeviceCode::find_by_user_code", skip(exe))]
+    pub async fn find_by_user_code(
+        exe: impl Executor<'_>,
+        user_code: &str,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(user_code) = normalize_user_code(user_code) else {
+            return Ok(None);
+        };
+
+        Ok(rorm::query(exe, OidcDeviceCodeModel)
+            .condition(and![
+                OidcDeviceCodeModel.user_code.equals(&*user_code),
+                OidcDeviceCodeModel
+                    .expires_at
+                    .greater_than(OffsetDateTime::now_utc()),
+            ])
+            .optional()
+            .await?
+            .map(Self::from))
+    }
+
+    /// Find a request by its device code
+    ///
+    /// Expired requests are returned as well, so the caller can report `expired_token`.
+    #[instrument(name = "OidcDeviceCode::find_by_device_code", skip(exe))]
+    pub async fn find_by_device_code(
+        exe: impl Executor<'_>,
+        device_code: &MaxStr<64>,
+    ) -> anyhow::Result<Option<Self>> {
+        Ok(rorm::query(exe, OidcDeviceCodeModel)
+            .condition(OidcDeviceCodeModel.device_code.equals(&**device_code))
+            .optional()
+            .await?
+            .map(Self::from))
+    }
+
+    /// Record a polling attempt of the device
+    #[instrument(name = "OidcDeviceCode::update_last_polled", skip(self, exe))]
+    pub async fn update_last_polled(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        let now = OffsetDateTime::now_utc();
+
+        rorm::update(exe, OidcDeviceCodeModel)
+            .set(OidcDeviceCodeModel.last_polled_at, Some(now))
+            .condition(OidcDeviceCodeModel.uuid.equals(self.uuid))
+            .await?;
+
+        self.last_polled_at = Some(now);
+
+        Ok(())
+    }
+
+    /// Increase the polling interval of a device which polled too fast
+    #[instrument(name = "OidcDeviceCode::slow_down", skip(self, exe))]
+    pub async fn slow_down(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        let poll_interval = self.poll_interval + DEVICE_CODE_SLOW_DOWN_STEP;
+
+        rorm::update(exe, OidcDeviceCodeModel)
+            .set(OidcDeviceCodeModel.poll_interval, poll_interval)
+            .condition(OidcDeviceCodeModel.uuid.equals(self.uuid))
+            .await?;
+
+        self.poll_interval = poll_interval;
+
+        Ok(())
+    }
+
+    /// Approve the request on behalf of an account
+    #[instrument(name = "OidcDeviceCode::approve", skip(self, exe))]
+    pub async fn approve(
+        &mut self,
+        exe: impl Executor<'_>,
+        account: AccountUuid,
+        auth_methods: Vec<AuthenticationMethod>,
+    ) -> anyhow::Result<()> {
+        rorm::update(exe, OidcDeviceCodeModel)
+            .set(
+                OidcDeviceCodeModel.account,
+                Some(ForeignModelByField(account.0)),
+            )
+            .set(
+                OidcDeviceCodeModel.auth_methods,
+                Some(Json(auth_methods.clone())),
+            )
+            .condition(OidcDeviceCodeModel.uuid.equals(self.uuid))
+            .await?;
+
+        self.state = OidcDeviceCodeState::Approved {
+            account,
+            auth_methods,
+        };
+
+        Ok(())
+    }
+
+    /// Deny the request
+    #[instrument(name = "OidcDeviceCode::deny", skip(self, exe))]
+    pub async fn deny(&mut self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::update(exe, OidcDeviceCodeModel)
+            .set(OidcDeviceCodeModel.denied, true)
+            .condition(OidcDeviceCodeModel.uuid.equals(self.uuid))
+            .await?;
+
+        self.state = OidcDeviceCodeState::Denied;
+
+        Ok(())
+    }
+
+    /// Delete the request
+    ///
+    /// Returns whether the request was still present,
+    /// so concurrent polls can't redeem the same approval twice.
+    #[instrument(name = "OidcDeviceCode::delete", skip(self, exe))]
+    pub async fn delete(&self, exe: impl Executor<'_>) -> anyhow::Result<bool> {
+        let deleted = rorm::delete(exe, OidcDeviceCodeModel)
+            .condition(OidcDeviceCodeModel.uuid.equals(self.uuid))
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Find all device authorization requests an account approved
+    #[instrument(name = "OidcDeviceCode::find_approved_by_account", skip(exe))]
+    pub async fn find_approved_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, OidcDeviceCodeModel)
+            .condition(OidcDeviceCodeModel.account.equals(Some(account)))
+            .stream()
+            .map_ok(OidcDeviceCode::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Clear expired device codes
+    #[instrument(name = "OidcDeviceCode::clear_expired", skip(exe))]
+    pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, OidcDeviceCodeModel)
+            .condition(
+                OidcDeviceCodeModel
+                    .expires_at
+                    .less_than(OffsetDateTime::now_utc()),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Generate a user code in the format `XXXX-XXXX`
+fn generate_user_code() -> String {
+    let mut rng = rand::rng();
+    let mut code = String::with_capacity(9);
+    for i in 0..8 {
+        if i == 4 {
+            code.push('-');
+        }
+        let idx = rng.random_range(0..USER_CODE_CHARSET.len());
+        code.push(USER_CODE_CHARSET[idx] as char);
+    }
+    code
+}
+
+/// Authentication methods of rows created before they were recorded only used a password
+fn auth_methods_or_default(
+    auth_methods: Option<Json<Vec<AuthenticationMethod>>>,
+) -> Vec<AuthenticationMethod> {
+    auth_methods
+        .map(|x| x.0)
+        .unwrap_or_else(|| vec![AuthenticationMethod::Password])
+}
+
+/// Bring a user code entered by a human into the format `XXXX-XXXX`
+fn normalize_user_code(user_code: &str) -> Option<String> {
+    let chars: Vec<char> = user_code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    if chars.len() != 8 {
+        return None;
+    }
+
+    let (first, second) = chars.split_at(4);
+    Some(format!(
+        "{}-{}",
+        first.iter().collect::<String>(),
+        second.iter().collect::<String>()
+    ))
+}
+
+impl From<OidcDeviceCodeModel> for OidcDeviceCode {
+    fn from(model: OidcDeviceCodeModel) -> Self {
+        let state = match (model.account, model.denied) {
+            (_, true) => OidcDeviceCodeState::Denied,
+            (Some(account), false) => OidcDeviceCodeState::Approved {
+                account: AccountUuid(account.0),
+                auth_methods: auth_methods_or_default(model.auth_methods),
+            },
+            (None, false) => OidcDeviceCodeState::Pending,
+        };
+
+        Self {
+            uuid: model.uuid,
+            client_id: OidcClientUuid(model.client.0),
+            device_code: model.device_code,
+            user_code: model.user_code,
+            scopes: model.scopes.0,
+            expires_at: model.expires_at,
+            last_polled_at: model.last_polled_at,
+            poll_interval: model.poll_interval,
+            state,
+        }
+    }
 }
 
 impl From<OidcClientModel> for OidcClient {
@@ -0,0 +1,38 @@
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::MaxStr;
+use uuid::Uuid;
+
+/// A mail in the outbox
+#[derive(Debug, Model)]
+#[rorm(rename = "OutgoingMail")]
+pub struct OutgoingMailModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    pub recipient: MaxStr<255>,
+    pub subject: MaxStr<255>,
+    pub body: MaxStr<8192>,
+
+    /// Number of failed delivery attempts
+    pub attempts: i32,
+    pub next_attempt_at: time::OffsetDateTime,
+    pub last_error: Option<MaxStr<1024>>,
+    pub sent_at: Option<time::OffsetDateTime>,
+
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "OutgoingMailModel")]
+pub struct OutgoingMailModelInsert {
+    pub uuid: Uuid,
+    pub recipient: MaxStr<255>,
+    pub subject: MaxStr<255>,
+    pub body: MaxStr<8192>,
+    pub attempts: i32,
+    pub next_attempt_at: time::OffsetDateTime,
+    pub last_error: Option<MaxStr<1024>>,
+    pub sent_at: Option<time::OffsetDateTime>,
+}
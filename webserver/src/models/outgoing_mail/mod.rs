@@ -0,0 +1,197 @@
+//! The outbox of mails waiting to be delivered
+//!
+//! Mails are written to the outbox in the same transaction as the change they notify about
+//! and delivered by the [`Mailer`](crate::modules::mailer::Mailer) afterward.
+
+use futures_util::TryStreamExt;
+use galvyn::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::or;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::outgoing_mail::db::OutgoingMailModel;
+use crate::models::outgoing_mail::db::OutgoingMailModelInsert;
+
+pub(in crate::models) mod db;
+
+/// Number of delivery attempts after which a mail is given up
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 10;
+
+/// Time a worker may take to deliver a mail before another worker picks it up
+const DELIVERY_LEASE: Duration = Duration::minutes(10);
+
+/// Delivered and abandoned mails are kept for this duration
+const RETENTION: Duration = Duration::days(30);
+
+/// A mail in the outbox
+#[derive(Debug, Clone)]
+pub struct OutgoingMail {
+    /// Primary key of the mail
+    pub uuid: OutgoingMailUuid,
+    /// The address the mail is sent to
+    pub recipient: MaxStr<255>,
+    /// The mail's subject
+    pub subject: MaxStr<255>,
+    /// The mail's plain text body
+    pub body: MaxStr<8192>,
+    /// Number of failed delivery attempts
+    pub attempts: i32,
+    next_attempt_at: OffsetDateTime,
+}
+
+/// New-type for the primary key of the outgoing mail
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct OutgoingMailUuid(pub Uuid);
+
+impl OutgoingMail {
+    /// Add a mail to the outbox
+    #[instrument(name = "OutgoingMail::enqueue", skip(exe, body))]
+    pub async fn enqueue(
+        exe: impl Executor<'_>,
+        recipient: MaxStr<255>,
+        subject: MaxStr<255>,
+        body: MaxStr<8192>,
+    ) -> anyhow::Result<OutgoingMailUuid> {
+        let uuid = Uuid::new_v4();
+
+        rorm::insert(exe, OutgoingMailModel)
+            .return_nothing()
+            .single(&OutgoingMailModelInsert {
+                uuid,
+                recipient,
+                subject,
+                body,
+                attempts: 0,
+                next_attempt_at: OffsetDateTime::now_utc(),
+                last_error: None,
+                sent_at: None,
+            })
+            .await?;
+
+        Ok(OutgoingMailUuid(uuid))
+    }
+
+    /// Find mails which are due for a delivery attempt
+    #[instrument(name = "OutgoingMail::find_due", skip(exe))]
+    pub async fn find_due(exe: impl Executor<'_>, limit: u64) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, OutgoingMailModel)
+            .condition(and![
+                OutgoingMailModel.sent_at.is_none(),
+                OutgoingMailModel.attempts.less_than(MAX_DELIVERY_ATTEMPTS),
+                OutgoingMailModel
+                    .next_attempt_at
+                    .less_equals(OffsetDateTime::now_utc()),
+            ])
+            .order_asc(OutgoingMailModel.next_attempt_at)
+            .limit(limit)
+            .stream()
+            .map_ok(OutgoingMail::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Claim the mail for delivery
+    ///
+    /// Returns `false` if another worker has claimed the mail in the meantime.
+    #[instrument(name = "OutgoingMail::claim", skip(self, exe))]
+    pub async fn claim(&mut self, exe: impl Executor<'_>) -> anyhow::Result<bool> {
+        let lease = OffsetDateTime::now_utc() + DELIVERY_LEASE;
+
+        let updated = rorm::update(exe, OutgoingMailModel)
+            .set(OutgoingMailModel.next_attempt_at, lease)
+            .condition(and![
+                OutgoingMailModel.uuid.equals(self.uuid.0),
+                OutgoingMailModel
+                    .next_attempt_at
+                    .equals(self.next_attempt_at),
+            ])
+            .await?;
+
+        self.next_attempt_at = lease;
+
+        Ok(updated > 0)
+    }
+
+    /// Mark the mail as delivered
+    ///
+    /// The body is cleared, as it may contain links granting access to an account.
+    #[instrument(name = "OutgoingMail::mark_sent", skip(self, exe))]
+    pub async fn mark_sent(&self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::update(exe, OutgoingMailModel)
+            .set(OutgoingMailModel.sent_at, Some(OffsetDateTime::now_utc()))
+            .set(OutgoingMailModel.last_error, None)
+            .set(OutgoingMailModel.body, MaxStr::new(String::new())?)
+            .condition(OutgoingMailModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed delivery attempt and schedule the next one
+    ///
+    /// The delay doubles with every attempt, starting at one minute.
+    /// The body of a mail which is given up is cleared, like the one of a delivered mail.
+    #[instrument(name = "OutgoingMail::mark_failed", skip(self, exe))]
+    pub async fn mark_failed(&self, exe: impl Executor<'_>, error: &str) -> anyhow::Result<()> {
+        let attempts = self.attempts + 1;
+        let delay = Duration::minutes(1 << attempts.clamp(0, 12));
+        let error: String = error.chars().take(1024).collect();
+
+        let body = if attempts >= MAX_DELIVERY_ATTEMPTS {
+            MaxStr::new(String::new())?
+        } else {
+            self.body.clone()
+        };
+
+        rorm::update(exe, OutgoingMailModel)
+            .set(OutgoingMailModel.attempts, attempts)
+            .set(
+                OutgoingMailModel.next_attempt_at,
+                OffsetDateTime::now_utc() + delay,
+            )
+            .set(OutgoingMailModel.last_error, Some(MaxStr::new(error)?))
+            .set(OutgoingMailModel.body, body)
+            .condition(OutgoingMailModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove delivered and abandoned mails after the retention period
+    #[instrument(name = "OutgoingMail::clear_expired", skip(exe))]
+    pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
+        let threshold = OffsetDateTime::now_utc() - RETENTION;
+
+        rorm::delete(exe, OutgoingMailModel)
+            .condition(and![
+                OutgoingMailModel.created_at.less_than(threshold),
+                or![
+                    OutgoingMailModel.sent_at.is_some(),
+                    OutgoingMailModel
+                        .attempts
+                        .greater_equals(MAX_DELIVERY_ATTEMPTS),
+                ],
+            ])
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl From<OutgoingMailModel> for OutgoingMail {
+    fn from(model: OutgoingMailModel) -> Self {
+        Self {
+            uuid: OutgoingMailUuid(model.uuid),
+            recipient: model.recipient,
+            subject: model.subject,
+            body: model.body,
+            attempts: model.attempts,
+            next_attempt_at: model.next_attempt_at,
+        }
+    }
+}
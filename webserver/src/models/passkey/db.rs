@@ -0,0 +1,40 @@
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use uuid::Uuid;
+use webauthn_rs::prelude::Passkey;
+
+use crate::models::account::db::AccountModel;
+
+/// A passkey registered by an account
+#[derive(Debug, Model)]
+#[rorm(rename = "Passkey")]
+pub struct PasskeyModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+    pub name: MaxStr<255>,
+
+    /// Base64url encoded id of the credential
+    #[rorm(unique)]
+    pub credential_id: MaxStr<1400>,
+    pub credential: Json<Passkey>,
+
+    #[rorm(on_update = "Cascade", on_delete = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    pub last_used_at: Option<time::OffsetDateTime>,
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "PasskeyModel")]
+pub struct PasskeyModelInsert {
+    pub uuid: Uuid,
+    pub name: MaxStr<255>,
+    pub credential_id: MaxStr<1400>,
+    pub credential: Json<Passkey>,
+    pub account: ForeignModel<AccountModel>,
+}
@@ -0,0 +1,154 @@
+//! Passkeys are WebAuthn credentials which can be used for signing in
+//! or as second factor.
+
+use base64ct::Base64UrlUnpadded;
+use base64ct::Encoding;
+use futures_util::TryStreamExt;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+use webauthn_rs::prelude::AuthenticationResult;
+use webauthn_rs::prelude::Passkey as Credential;
+
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::models::passkey::db::PasskeyModel;
+use crate::models::passkey::db::PasskeyModelInsert;
+
+pub(in crate::models) mod db;
+
+/// A passkey registered by an account
+#[derive(Debug, Clone)]
+pub struct Passkey {
+    /// Primary key of the passkey
+    pub uuid: PasskeyUuid,
+    /// Name given by the user
+    pub name: MaxStr<255>,
+    /// The account the passkey belongs to
+    pub account: AccountUuid,
+    /// The last point in time the passkey was used
+    pub last_used_at: Option<OffsetDateTime>,
+    /// The point in time the passkey was registered
+    pub created_at: OffsetDateTime,
+    credential: Credential,
+}
+
+/// New-type for the primary key of the passkey
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PasskeyUuid(pub Uuid);
+
+impl Passkey {
+    /// Find all passkeys of an account
+    #[instrument(name = "Passkey::find_all_by_account", skip(exe))]
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        account: AccountUuid,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, PasskeyModel)
+            .condition(PasskeyModel.account.equals(account.0))
+            .stream()
+            .map_ok(Passkey::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Find a passkey by its credential id
+    #[instrument(name = "Passkey::find_by_credential_id", skip(exe))]
+    pub async fn find_by_credential_id(
+        exe: impl Executor<'_>,
+        credential_id: &[u8],
+    ) -> anyhow::Result<Option<Self>> {
+        let credential_id = Base64UrlUnpadded::encode_string(credential_id);
+
+        Ok(rorm::query(exe, PasskeyModel)
+            .condition(PasskeyModel.credential_id.equals(&*credential_id))
+            .optional()
+            .await?
+            .map(Passkey::from))
+    }
+
+    /// Register a new passkey for an account
+    #[instrument(name = "Passkey::create", skip(exe, account, credential))]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        account: &Account,
+        name: MaxStr<255>,
+        credential: Credential,
+    ) -> anyhow::Result<Self> {
+        let credential_id = MaxStr::new(Base64UrlUnpadded::encode_string(
+            credential.cred_id().as_ref(),
+        ))?;
+
+        let model = rorm::insert(exe, PasskeyModel)
+            .single(&PasskeyModelInsert {
+                uuid: Uuid::new_v4(),
+                name,
+                credential_id,
+                credential: Json(credential),
+                account: ForeignModelByField(account.uuid().0),
+            })
+            .await?;
+
+        Ok(Passkey::from(model))
+    }
+
+    /// The WebAuthn credential of the passkey
+    pub fn credential(&self) -> &Credential {
+        &self.credential
+    }
+
+    /// Record a successful authentication with the passkey
+    ///
+    /// This updates the stored credential counter if necessary.
+    #[instrument(name = "Passkey::record_authentication", skip(self, exe, result))]
+    pub async fn record_authentication(
+        &mut self,
+        exe: impl Executor<'_>,
+        result: &AuthenticationResult,
+    ) -> anyhow::Result<()> {
+        let now = OffsetDateTime::now_utc();
+        self.credential.update_credential(result);
+
+        rorm::update(exe, PasskeyModel)
+            .set(PasskeyModel.credential, Json(self.credential.clone()))
+            .set(PasskeyModel.last_used_at, Some(now))
+            .condition(PasskeyModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        self.last_used_at = Some(now);
+
+        Ok(())
+    }
+
+    /// Delete the passkey
+    #[instrument(name = "Passkey::delete", skip(self, exe))]
+    pub async fn delete(self, exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, PasskeyModel)
+            .condition(PasskeyModel.uuid.equals(self.uuid.0))
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl From<PasskeyModel> for Passkey {
+    fn from(model: PasskeyModel) -> Self {
+        Self {
+            uuid: PasskeyUuid(model.uuid),
+            name: model.name,
+            account: AccountUuid(model.account.0),
+            last_used_at: model.last_used_at,
+            created_at: model.created_at,
+            credential: model.credential.0,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModel;
+use uuid::Uuid;
+
+use crate::models::account::db::AccountModel;
+
+/// An external address used to recover an account
+#[derive(Debug, Model)]
+#[rorm(rename = "RecoveryEmail")]
+pub struct RecoveryEmailModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+    pub email: MaxStr<255>,
+
+    /// Base64 encoded SHA256 hash of the token sent to the address for verification
+    ///
+    /// Unset once the address has been verified.
+    #[rorm(unique)]
+    pub verification_token_hash: Option<MaxStr<64>>,
+    pub verification_expires_at: Option<time::OffsetDateTime>,
+    pub verified_at: Option<time::OffsetDateTime>,
+
+    #[rorm(unique, on_update = "Cascade", on_delete = "Cascade")]
+    pub account: ForeignModel<AccountModel>,
+
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "RecoveryEmailModel")]
+pub struct RecoveryEmailModelInsert {
+    pub uuid: Uuid,
+    pub email: MaxStr<255>,
+    pub verification_token_hash: Option<MaxStr<64>>,
+    pub verification_expires_at: Option<time::OffsetDateTime>,
+    pub verified_at: Option<time::OffsetDateTime>,
+    pub account: ForeignModel<AccountModel>,
+}
@@ -0,0 +1,197 @@
+//! External addresses members can recover their account with
+//!
+//! An address has to be verified before credential reset links are sent to it.
+
+use base64ct::Base64;
+use base64ct::Encoding;
+use galvyn::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use galvyn::rorm::prelude::ForeignModelByField;
+use rand::distr::Alphanumeric;
+use rand::distr::SampleString;
+use sha2::Digest;
+use sha2::Sha256;
+use time::Duration;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::models::recovery_email::db::RecoveryEmailModel;
+use crate::models::recovery_email::db::RecoveryEmailModelInsert;
+
+pub(in crate::models) mod db;
+
+/// Time the owner of an address has to verify it
+const VERIFICATION_VALIDITY: Duration = Duration::days(1);
+
+/// An external address used to recover an account
+#[derive(Debug, Clone)]
+pub struct RecoveryEmail {
+    /// Primary key of the recovery email
+    pub uuid: RecoveryEmailUuid,
+    /// The external address
+    pub email: MaxStr<255>,
+    /// The account the address belongs to
+    pub account: AccountUuid,
+    /// The point in time the address was verified
+    pub verified_at: Option<OffsetDateTime>,
+    /// The point in time the address was set
+    pub created_at: OffsetDateTime,
+}
+
+/// New-type for the primary key of the recovery email
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RecoveryEmailUuid(pub Uuid);
+
+impl RecoveryEmail {
+    /// Find the recovery email of an account
+    #[instrument(name = "RecoveryEmail::find_by_account", skip(exe))]
+    pub async fn find_by_account(
+        exe: impl Executor<'_>,
+        account: AccountUuid,
+    ) -> anyhow::Result<Option<Self>> {
+        Ok(rorm::query(exe, RecoveryEmailModel)
+            .condition(RecoveryEmailModel.account.equals(account.0))
+            .optional()
+            .await?
+            .map(RecoveryEmail::from))
+    }
+
+    /// Find the verified recovery email of an account
+    #[instrument(name = "RecoveryEmail::find_verified_by_account", skip(exe))]
+    pub async fn find_verified_by_account(
+        exe: impl Executor<'_>,
+        account: AccountUuid,
+    ) -> anyhow::Result<Option<Self>> {
+        Ok(Self::find_by_account(exe, account)
+            .await?
+            .filter(|recovery_email| recovery_email.is_verified()))
+    }
+
+    /// Set a new unverified recovery email for an account
+    ///
+    /// A previously set address is replaced.
+    /// Returns the token which has to be sent to the address to verify it.
+    #[instrument(name = "RecoveryEmail::set", skip(exe, account))]
+    pub async fn set(
+        exe: impl Executor<'_>,
+        account: &Account,
+        email: MaxStr<255>,
+    ) -> anyhow::Result<(Self, String)> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        Self::delete_by_account(guard.get_transaction(), account.uuid()).await?;
+
+        let token = Alphanumeric.sample_string(&mut rand::rng(), 64);
+
+        let model = rorm::insert(guard.get_transaction(), RecoveryEmailModel)
+            .single(&RecoveryEmailModelInsert {
+                uuid: Uuid::new_v4(),
+                email,
+                verification_token_hash: Some(hash_token(&token)?),
+                verification_expires_at: Some(OffsetDateTime::now_utc() + VERIFICATION_VALIDITY),
+                verified_at: None,
+                account: ForeignModelByField(account.uuid().0),
+            })
+            .await?;
+
+        guard.commit().await?;
+
+        Ok((RecoveryEmail::from(model), token))
+    }
+
+    /// Verify a recovery email using the token sent to it
+    ///
+    /// Returns `None` if the token is unknown or expired.
+    #[instrument(name = "RecoveryEmail::verify", skip_all)]
+    pub async fn verify(exe: impl Executor<'_>, token: &str) -> anyhow::Result<Option<Self>> {
+        let mut guard = exe.ensure_transaction().await?;
+
+        let now = OffsetDateTime::now_utc();
+        let Some(model) = rorm::query(guard.get_transaction(), RecoveryEmailModel)
+            .condition(and![
+                RecoveryEmailModel
+                    .verification_token_hash
+                    .equals(Some(&*hash_token(token)?)),
+                RecoveryEmailModel
+                    .verification_expires_at
+                    .greater_than(Some(now)),
+            ])
+            .optional()
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        rorm::update(guard.get_transaction(), RecoveryEmailModel)
+            .set(RecoveryEmailModel.verification_token_hash, None)
+            .set(RecoveryEmailModel.verification_expires_at, None)
+            .set(RecoveryEmailModel.verified_at, Some(now))
+            .condition(RecoveryEmailModel.uuid.equals(model.uuid))
+            .await?;
+
+        guard.commit().await?;
+
+        let mut recovery_email = RecoveryEmail::from(model);
+        recovery_email.verified_at = Some(now);
+        Ok(Some(recovery_email))
+    }
+
+    /// Remove the recovery email of an account
+    #[instrument(name = "RecoveryEmail::delete_by_account", skip(exe))]
+    pub async fn delete_by_account(
+        exe: impl Executor<'_>,
+        account: AccountUuid,
+    ) -> anyhow::Result<()> {
+        rorm::delete(exe, RecoveryEmailModel)
+            .condition(RecoveryEmailModel.account.equals(account.0))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove addresses which weren't verified in time
+    #[instrument(name = "RecoveryEmail::clear_expired", skip(exe))]
+    pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, RecoveryEmailModel)
+            .condition(and![
+                RecoveryEmailModel.verified_at.is_none(),
+                RecoveryEmailModel
+                    .verification_expires_at
+                    .less_than(Some(OffsetDateTime::now_utc())),
+            ])
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether the owner of the address has verified it
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+}
+
+impl From<RecoveryEmailModel> for RecoveryEmail {
+    fn from(model: RecoveryEmailModel) -> Self {
+        Self {
+            uuid: RecoveryEmailUuid(model.uuid),
+            email: model.email,
+            account: AccountUuid(model.account.0),
+            verified_at: model.verified_at,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// Hash a verification token
+///
+/// Only the hash is stored, so a leaked database can't be used to verify addresses.
+fn hash_token(token: &str) -> anyhow::Result<MaxStr<64>> {
+    Ok(MaxStr::new(Base64::encode_string(&Sha256::digest(
+        token.as_bytes(),
+    )))?)
+}
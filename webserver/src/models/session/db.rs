@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use galvyn::rorm::Model;
+use galvyn::rorm::Patch;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use uuid::Uuid;
+
+/// A session of the http server
+///
+/// The session id itself is never stored, only its hash.
+#[derive(Debug, Model)]
+#[rorm(rename = "Session")]
+pub struct SessionModel {
+    #[rorm(primary_key)]
+    pub uuid: Uuid,
+
+    /// Base64 encoded SHA256 hash of the session id
+    #[rorm(unique)]
+    pub id_hash: MaxStr<64>,
+    pub data: Json<HashMap<String, serde_json::Value>>,
+    pub expires_at: time::OffsetDateTime,
+
+    /// The account which is signed in using this session
+    ///
+    /// This is no foreign key as it may reference any of the account tables.
+    pub account: Option<Uuid>,
+    pub user_agent: Option<MaxStr<255>>,
+    pub ip: Option<MaxStr<64>>,
+
+    pub last_seen_at: time::OffsetDateTime,
+    #[rorm(auto_create_time)]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Patch)]
+#[rorm(model = "SessionModel")]
+pub struct SessionModelInsert {
+    pub uuid: Uuid,
+    pub id_hash: MaxStr<64>,
+    pub data: Json<HashMap<String, serde_json::Value>>,
+    pub expires_at: time::OffsetDateTime,
+    pub account: Option<Uuid>,
+    pub user_agent: Option<MaxStr<255>>,
+    pub ip: Option<MaxStr<64>>,
+    pub last_seen_at: time::OffsetDateTime,
+}
@@ -0,0 +1,273 @@
+//! Sessions of the http server are persisted in the database
+//!
+//! This allows sessions to survive restarts and to be shared between multiple replicas.
+
+use std::collections::HashMap;
+
+use base64ct::Base64;
+use base64ct::Encoding;
+use futures_util::TryStreamExt;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm;
+use galvyn::rorm::and;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::Json;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use time::OffsetDateTime;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::models::account::AccountUuid;
+use crate::models::session::db::SessionModel;
+use crate::models::session::db::SessionModelInsert;
+
+pub(in crate::models) mod db;
+
+/// A session stored in the database
+#[derive(Debug, Clone)]
+pub struct StoredSession {
+    /// Primary key of the session
+    ///
+    /// This is not the session id, which is only known to the client.
+    pub uuid: StoredSessionUuid,
+    /// The account which is signed in using this session
+    pub account: Option<AccountUuid>,
+    /// The user agent of the client
+    pub user_agent: Option<MaxStr<255>>,
+    /// The ip address of the client
+    pub ip: Option<MaxStr<64>>,
+    /// The last point in time the session was used
+    pub last_seen_at: OffsetDateTime,
+    /// The point in time the session expires
+    pub expires_at: OffsetDateTime,
+    /// The point in time the session was created
+    pub created_at: OffsetDateTime,
+    id_hash: MaxStr<64>,
+}
+
+/// New-type for the primary key of the stored session
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct StoredSessionUuid(pub Uuid);
+
+/// The state of a session which is written to the database
+#[derive(Debug, Clone)]
+pub struct SaveSessionParams {
+    /// The session's data
+    pub data: HashMap<String, serde_json::Value>,
+    /// The point in time the session expires
+    pub expires_at: OffsetDateTime,
+    /// The account which is signed in using this session
+    pub account: Option<AccountUuid>,
+    /// The user agent of the client
+    pub user_agent: Option<MaxStr<255>>,
+    /// The ip address of the client
+    pub ip: Option<MaxStr<64>>,
+}
+
+impl StoredSession {
+    /// Load the data of a session which has not expired yet
+    #[instrument(name = "StoredSession::load_data", skip_all)]
+    pub async fn load_data(
+        exe: impl Executor<'_>,
+        session_id: &str,
+    ) -> anyhow::Result<Option<(HashMap<String, serde_json::Value>, OffsetDateTime)>> {
+        let id_hash = hash_session_id(session_id)?;
+
+        Ok(
+            rorm::query(exe, (SessionModel.data, SessionModel.expires_at))
+                .condition(and![
+                    SessionModel.id_hash.equals(&*id_hash),
+                    SessionModel
+                        .expires_at
+                        .greater_than(OffsetDateTime::now_utc()),
+                ])
+                .optional()
+                .await?
+                .map(|(data, expires_at)| (data.0, expires_at)),
+        )
+    }
+
+    /// Check whether a session id is already in use
+    #[instrument(name = "StoredSession::exists", skip_all)]
+    pub async fn exists(exe: impl Executor<'_>, session_id: &str) -> anyhow::Result<bool> {
+        let id_hash = hash_session_id(session_id)?;
+
+        Ok(rorm::query(exe, SessionModel.uuid)
+            .condition(SessionModel.id_hash.equals(&*id_hash))
+            .optional()
+            .await?
+            .is_some())
+    }
+
+    /// Insert a new session
+    #[instrument(name = "StoredSession::create", skip_all)]
+    pub async fn create(
+        exe: impl Executor<'_>,
+        session_id: &str,
+        params: SaveSessionParams,
+    ) -> anyhow::Result<()> {
+        let SaveSessionParams {
+            data,
+            expires_at,
+            account,
+            user_agent,
+            ip,
+        } = params;
+
+        rorm::insert(exe, SessionModel)
+            .return_nothing()
+            .single(&SessionModelInsert {
+                uuid: Uuid::new_v4(),
+                id_hash: hash_session_id(session_id)?,
+                data: Json(data),
+                expires_at,
+                account: account.map(|x| x.0),
+                user_agent,
+                ip,
+                last_seen_at: OffsetDateTime::now_utc(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Update an existing session
+    ///
+    /// Returns `false` if the session doesn't exist (anymore).
+    /// Sessions are not recreated in this case, as this would
+    /// resurrect sessions which were revoked while a request was running.
+    #[instrument(name = "StoredSession::update", skip_all)]
+    pub async fn update(
+        exe: impl Executor<'_>,
+        session_id: &str,
+        params: SaveSessionParams,
+    ) -> anyhow::Result<bool> {
+        let SaveSessionParams {
+            data,
+            expires_at,
+            account,
+            user_agent,
+            ip,
+        } = params;
+        let id_hash = hash_session_id(session_id)?;
+
+        let updated = rorm::update(exe, SessionModel)
+            .set(SessionModel.data, Json(data))
+            .set(SessionModel.expires_at, expires_at)
+            .set(SessionModel.account, account.map(|x| x.0))
+            .set(SessionModel.user_agent, user_agent)
+            .set(SessionModel.ip, ip)
+            .set(SessionModel.last_seen_at, OffsetDateTime::now_utc())
+            .condition(SessionModel.id_hash.equals(&*id_hash))
+            .await?;
+
+        Ok(updated > 0)
+    }
+
+    /// Delete a session by its id
+    #[instrument(name = "StoredSession::delete_by_id", skip_all)]
+    pub async fn delete_by_id(exe: impl Executor<'_>, session_id: &str) -> anyhow::Result<()> {
+        let id_hash = hash_session_id(session_id)?;
+
+        rorm::delete(exe, SessionModel)
+            .condition(SessionModel.id_hash.equals(&*id_hash))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Find all active sessions of an account
+    #[instrument(name = "StoredSession::find_all_by_account", skip(exe))]
+    pub async fn find_all_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+    ) -> anyhow::Result<Vec<Self>> {
+        Ok(rorm::query(exe, SessionModel)
+            .condition(and![
+                SessionModel.account.equals(Some(account)),
+                SessionModel
+                    .expires_at
+                    .greater_than(OffsetDateTime::now_utc()),
+            ])
+            .stream()
+            .map_ok(StoredSession::from)
+            .try_collect()
+            .await?)
+    }
+
+    /// Revoke a single session of an account
+    ///
+    /// Returns `false` if the account has no such session.
+    #[instrument(name = "StoredSession::revoke", skip(exe))]
+    pub async fn revoke(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+        StoredSessionUuid(uuid): StoredSessionUuid,
+    ) -> anyhow::Result<bool> {
+        let deleted = rorm::delete(exe, SessionModel)
+            .condition(and![
+                SessionModel.uuid.equals(uuid),
+                SessionModel.account.equals(Some(account)),
+            ])
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Revoke all sessions of an account
+    #[instrument(name = "StoredSession::revoke_all_by_account", skip(exe))]
+    pub async fn revoke_all_by_account(
+        exe: impl Executor<'_>,
+        AccountUuid(account): AccountUuid,
+    ) -> anyhow::Result<()> {
+        rorm::delete(exe, SessionModel)
+            .condition(SessionModel.account.equals(Some(account)))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check whether this is the session with the given id
+    pub fn is_session_id(&self, session_id: &str) -> bool {
+        hash_session_id(session_id).is_ok_and(|id_hash| id_hash == self.id_hash)
+    }
+
+    /// Remove all expired sessions
+    #[instrument(name = "StoredSession::clear_expired", skip(exe))]
+    pub async fn clear_expired(exe: impl Executor<'_>) -> anyhow::Result<()> {
+        rorm::delete(exe, SessionModel)
+            .condition(SessionModel.expires_at.less_than(OffsetDateTime::now_utc()))
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl From<SessionModel> for StoredSession {
+    fn from(model: SessionModel) -> Self {
+        Self {
+            uuid: StoredSessionUuid(model.uuid),
+            account: model.account.map(AccountUuid),
+            user_agent: model.user_agent,
+            ip: model.ip,
+            last_seen_at: model.last_seen_at,
+            expires_at: model.expires_at,
+            created_at: model.created_at,
+            id_hash: model.id_hash,
+        }
+    }
+}
+
+/// Hash a session id
+///
+/// Only the hash is stored, so a leaked database can't be used to take over sessions.
+fn hash_session_id(session_id: &str) -> anyhow::Result<MaxStr<64>> {
+    Ok(MaxStr::new(Base64::encode_string(&Sha256::digest(
+        session_id.as_bytes(),
+    )))?)
+}
@@ -5,8 +5,15 @@ use galvyn::rorm::Database;
 use tracing::Instrument;
 use tracing::error;
 
+use crate::models::api_token::ApiToken;
 use crate::models::credential_reset::CredentialReset;
 use crate::models::invite::Invite;
+use crate::models::lockout::Lockout;
+use crate::models::login_event::LoginEvent;
+use crate::models::oidc_provider::OidcDeviceCode;
+use crate::models::outgoing_mail::OutgoingMail;
+use crate::models::recovery_email::RecoveryEmail;
+use crate::models::session::StoredSession;
 use crate::utils::worker::Worker;
 
 const GC_INTERVAL: Duration = Duration::from_secs(60 * 60);
@@ -35,6 +42,13 @@ impl GarbageCollectorWorker {
 
         Invite::clear_expired(&mut tx).await?;
         CredentialReset::clear_expired(&mut tx).await?;
+        OidcDeviceCode::clear_expired(&mut tx).await?;
+        StoredSession::clear_expired(&mut tx).await?;
+        Lockout::clear_expired(&mut tx).await?;
+        LoginEvent::clear_expired(&mut tx).await?;
+        ApiToken::clear_expired(&mut tx).await?;
+        OutgoingMail::clear_expired(&mut tx).await?;
+        RecoveryEmail::clear_expired(&mut tx).await?;
 
         tx.commit().await?;
 
@@ -47,28 +47,9 @@ impl AppPasswordInitializer {
             // before we try to initialize app passwords
             tokio::time::sleep(Duration::from_secs(5)).await;
 
-            let existing = self.sdk.get_app_passwords(self.mailbox.to_string()).await?;
+            delete_managed_app_passwords(&self.sdk, &self.mailbox).await?;
 
-            let mut to_delete = vec![];
-            for existing_app_password in existing {
-                if existing_app_password.name == APP_PASSWORD_NAME {
-                    to_delete.push(existing_app_password.id);
-                }
-            }
-
-            if !to_delete.is_empty() {
-                self.sdk.delete_app_passwords(to_delete).await?;
-            }
-
-            let res = self
-                .sdk
-                .create_app_password(CreateAppPasswordRequest {
-                    username: self.mailbox.to_string().clone(),
-                    app_name: APP_PASSWORD_NAME.to_string(),
-                    app_passwd: hashed_pw.clone(),
-                    app_passwd2: hashed_pw.clone(),
-                })
-                .await;
+            let res = create_managed_app_password(&self.sdk, &self.mailbox, &hashed_pw).await;
 
             if res.is_ok() {
                 account
@@ -85,3 +66,73 @@ impl AppPasswordInitializer {
         Ok(())
     }
 }
+
+/// Delete the app passwords of a mailbox which are managed by us
+pub(super) async fn delete_managed_app_passwords(
+    sdk: &MailcowClient,
+    mailbox: &str,
+) -> anyhow::Result<()> {
+    let existing = sdk.get_app_passwords(mailbox.to_string()).await?;
+
+    let mut to_delete = vec![];
+    for existing_app_password in existing {
+        if existing_app_password.name == APP_PASSWORD_NAME {
+            to_delete.push(existing_app_password.id);
+        }
+    }
+
+    if !to_delete.is_empty() {
+        sdk.delete_app_passwords(to_delete).await?;
+    }
+
+    Ok(())
+}
+
+/// Create the app password managed by us for a mailbox
+pub(super) async fn create_managed_app_password(
+    sdk: &MailcowClient,
+    mailbox: &str,
+    hashed_password: &str,
+) -> anyhow::Result<()> {
+    sdk.create_app_password(CreateAppPasswordRequest {
+        username: mailbox.to_string(),
+        app_name: APP_PASSWORD_NAME.to_string(),
+        app_passwd: hashed_password.to_string(),
+        app_passwd2: hashed_password.to_string(),
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Check whether a mailbox has an app password managed by us
+pub(super) async fn has_managed_app_password(
+    sdk: &MailcowClient,
+    mailbox: &str,
+) -> anyhow::Result<bool> {
+    Ok(sdk
+        .get_app_passwords(mailbox.to_string())
+        .await?
+        .iter()
+        .any(|app_password| app_password.name == APP_PASSWORD_NAME))
+}
+
+/// Create or remove the app password managed by us for a member and update its flag
+pub(super) async fn set_managed_app_password(
+    sdk: &MailcowClient,
+    member: &mut ClubAccount,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    delete_managed_app_passwords(sdk, &member.email).await?;
+
+    if enabled {
+        let hashed_password = format!("{{BLF-CRYPT}}{}", member.hashed_password());
+        create_managed_app_password(sdk, &member.email, &hashed_password).await?;
+    }
+
+    member
+        .update_has_app_password_set(Database::global(), enabled)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+//! Derives the DNS records a domain needs to receive and send mails through mailcow.
+
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::config::MAIL_HOSTNAME;
+use crate::modules::mailcow::Mailcow;
+
+/// Purpose of an expected DNS record
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum DnsRecordPurpose {
+    /// Routes incoming mails to the mail server
+    Mx,
+    /// Allows the mail server to send mails for the domain
+    Spf,
+    /// Publishes the key outgoing mails are signed with
+    Dkim,
+    /// Tells receivers how to handle mails failing SPF and DKIM
+    Dmarc,
+}
+
+/// A DNS record a domain is expected to publish
+#[derive(Debug, Clone)]
+pub struct ExpectedDnsRecord {
+    /// Purpose of the record
+    pub purpose: DnsRecordPurpose,
+    /// Fully qualified name of the record
+    pub name: String,
+    /// Type of the record, e.g. `MX` or `TXT`
+    pub record_type: &'static str,
+    /// Content of the record
+    pub value: String,
+}
+
+impl Mailcow {
+    /// Retrieve the DNS records a domain is expected to publish
+    ///
+    /// The DKIM record is missing if mailcow didn't generate a key for the domain yet.
+    #[instrument(name = "Mailcow::expected_dns_records", skip(self))]
+    pub async fn expected_dns_records(
+        &self,
+        domain: &str,
+    ) -> anyhow::Result<Vec<ExpectedDnsRecord>> {
+        let hostname = MAIL_HOSTNAME.get();
+
+        let mut records = vec![
+            ExpectedDnsRecord {
+                purpose: DnsRecordPurpose::Mx,
+                name: domain.to_string(),
+                record_type: "MX",
+                value: format!("10 {hostname}."),
+            },
+            ExpectedDnsRecord {
+                purpose: DnsRecordPurpose::Spf,
+                name: domain.to_string(),
+                record_type: "TXT",
+                value: "v=spf1 mx a -all".to_string(),
+            },
+        ];
+
+        if let Some(dkim) = self.sdk.get_dkim(domain).await? {
+            records.push(ExpectedDnsRecord {
+                purpose: DnsRecordPurpose::Dkim,
+                name: format!("{}._domainkey.{domain}", dkim.dkim_selector),
+                record_type: "TXT",
+                value: dkim.dkim_txt,
+            });
+        }
+
+        records.push(ExpectedDnsRecord {
+            purpose: DnsRecordPurpose::Dmarc,
+            name: format!("_dmarc.{domain}"),
+            record_type: "TXT",
+            value: "v=DMARC1; p=quarantine; adkim=s; aspf=s".to_string(),
+        });
+
+        Ok(records)
+    }
+}
@@ -0,0 +1,182 @@
+//! Keeps the domain admins in mailcow in line with the club admin roles.
+//!
+//! Every account with at least one club admin role owns a domain admin in mailcow
+//! with the same username. It manages the domains of all clubs the account is an admin of.
+
+use anyhow::anyhow;
+use galvyn::core::Module;
+use galvyn::rorm::Database;
+use galvyn::rorm::db::transaction::Transaction;
+use mailcow::MailcowClient;
+use mailcow::domain_admins::schema::CreateDomainAdminRequest;
+use mailcow::domain_admins::schema::EditDomainAdminPasswordChanges;
+use mailcow::domain_admins::schema::EditDomainAdminPasswordRequest;
+use mailcow::domain_admins::schema::EditDomainAdminsChanges;
+use mailcow::domain_admins::schema::EditDomainAdminsRequest;
+use tracing::Instrument;
+use tracing::error;
+use tracing::info_span;
+use tracing::instrument;
+
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::models::domain::Domain;
+use crate::modules::mailcow::Mailcow;
+use crate::utils::worker::Worker;
+use crate::utils::worker::WorkerHandle;
+
+impl Mailcow {
+    /// Retrieve the domains the domain admin of an account has to manage
+    #[instrument(name = "Mailcow::domain_admin_domains", skip_all)]
+    pub async fn domain_admin_domains(
+        tx: &mut Transaction,
+        account: &Account,
+    ) -> anyhow::Result<Vec<String>> {
+        let mut domains = vec![];
+        for club in &account.admin_of {
+            domains.extend(
+                Domain::find_all_present_by_club(&mut *tx, *club)
+                    .await?
+                    .into_iter()
+                    .map(|x| x.domain.into_inner()),
+            );
+        }
+
+        Ok(domains)
+    }
+
+    /// Create the domain admin for an account which received its first club admin role
+    ///
+    /// The domain admin uses the same password as the account.
+    #[instrument(name = "Mailcow::create_domain_admin", skip_all)]
+    pub async fn create_domain_admin(
+        &self,
+        tx: &mut Transaction,
+        account: &Account,
+    ) -> anyhow::Result<()> {
+        let domains = Self::domain_admin_domains(tx, account).await?;
+        let hashed_password = format!("{{BLF-CRYPT}}{}", account.hashed_password());
+
+        self.sdk
+            .create_domain_admin(CreateDomainAdminRequest {
+                active: 1,
+                domains,
+                password: hashed_password.clone(),
+                password2: hashed_password,
+                username: account.username.to_string(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Update the domains of the domain admin of an account
+    ///
+    /// The domain admin is deleted if the account isn't an admin of any club anymore.
+    #[instrument(name = "Mailcow::update_domain_admin", skip_all)]
+    pub async fn update_domain_admin(
+        &self,
+        tx: &mut Transaction,
+        account: &Account,
+    ) -> anyhow::Result<()> {
+        if account.admin_of.is_empty() {
+            self.sdk
+                .delete_domain_admins(vec![account.username.to_string()])
+                .await?;
+        } else {
+            let domains = Self::domain_admin_domains(tx, account).await?;
+
+            self.sdk
+                .edit_domain_admins(EditDomainAdminsRequest {
+                    attr: EditDomainAdminsChanges { domains },
+                    items: vec![account.username.to_string()],
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Recreate the domain admin of an account from the current state
+    ///
+    /// This repairs domain admins whose creation, password update or deletion failed.
+    /// Accounts without admin roles only have their domain admin deleted.
+    #[instrument(name = "Mailcow::repair_domain_admin", skip_all)]
+    pub async fn repair_domain_admin(
+        &self,
+        tx: &mut Transaction,
+        account: &mut Account,
+    ) -> anyhow::Result<()> {
+        self.sdk
+            .delete_domain_admins(vec![account.username.to_string()])
+            .await?;
+        if !account.admin_of.is_empty() {
+            self.create_domain_admin(tx, account).await?;
+        }
+
+        account.update_domain_admin_synced(&mut *tx, true).await?;
+
+        Ok(())
+    }
+
+    /// Propagate the password of an account to its domain admin in the background
+    ///
+    /// Accounts without club admin roles are skipped.
+    pub fn update_domain_admin_password(
+        &self,
+        account: AccountUuid,
+    ) -> WorkerHandle<DomainAdminPasswordUpdater> {
+        DomainAdminPasswordUpdater {
+            sdk: self.sdk.clone(),
+            account,
+        }
+        .spawn()
+    }
+}
+
+pub struct DomainAdminPasswordUpdater {
+    /// Mailcow client
+    pub sdk: MailcowClient,
+    /// Account whose password changed
+    pub account: AccountUuid,
+}
+
+impl Worker for DomainAdminPasswordUpdater {
+    async fn run(self) {
+        let span = info_span!("DomainAdminPasswordUpdater::run_once");
+        if let Err(error) = self.run_once().instrument(span.clone()).await {
+            span.in_scope(|| error!(error.debug = ?error, error.display = %error, "DomainAdminPasswordUpdater run exited with error"));
+        }
+    }
+}
+
+impl DomainAdminPasswordUpdater {
+    pub async fn run_once(&self) -> anyhow::Result<()> {
+        let mut account = Account::get_by_uuid(Database::global(), self.account)
+            .await?
+            .ok_or(anyhow!("Account not found"))?;
+
+        if account.admin_of.is_empty() {
+            return Ok(());
+        }
+
+        let hashed_password = format!("{{BLF-CRYPT}}{}", account.hashed_password());
+        let res = self
+            .sdk
+            .edit_domain_admin_password(EditDomainAdminPasswordRequest {
+                attr: EditDomainAdminPasswordChanges {
+                    password: hashed_password.clone(),
+                    password2: hashed_password,
+                },
+                items: vec![account.username.to_string()],
+            })
+            .await;
+
+        // Mark the domain admin, so a superadmin can repair it
+        account
+            .update_domain_admin_synced(Database::global(), res.is_ok())
+            .await?;
+
+        Ok(res?)
+    }
+}
@@ -0,0 +1,194 @@
+//! Creates and deletes domains in mailcow.
+//!
+//! Domains created here are inserted right away instead of waiting for the next sync,
+//! so they can be associated with a club immediately.
+
+use anyhow::anyhow;
+use galvyn::rorm::db::transaction::Transaction;
+use galvyn::rorm::fields::types::MaxStr;
+use mailcow::dkim::schema::CreateDkimRequest;
+use mailcow::domains::schema::CreateDomainRequest;
+use mailcow::domains::schema::EditDomainChanges;
+use mailcow::domains::schema::EditDomainRequest;
+use tracing::error;
+use tracing::info;
+use tracing::instrument;
+
+use crate::models::domain::CreateDomain;
+use crate::models::domain::Domain;
+use crate::modules::mailcow::MIB;
+use crate::modules::mailcow::Mailcow;
+
+/// Selector of the DKIM keys generated for new domains
+const DKIM_SELECTOR: &str = "dkim";
+
+/// Length of the DKIM keys generated for new domains in bits
+const DKIM_KEY_SIZE: u16 = 2048;
+
+/// Limits of a domain
+#[derive(Debug, Clone, Copy)]
+pub struct DomainLimits {
+    /// Maximum number of mailboxes
+    pub max_mailboxes: u64,
+    /// Maximum number of aliases
+    pub max_aliases: u64,
+    /// Default quota for new mailboxes in bytes
+    pub default_mailbox_quota: u64,
+    /// Maximum quota of a single mailbox in bytes
+    pub max_mailbox_quota: u64,
+    /// Maximum total quota of the domain in bytes
+    pub domain_quota: u64,
+}
+
+/// A domain created in mailcow
+#[derive(Debug, Clone)]
+pub struct ProvisionedDomain {
+    /// The domain
+    pub domain: Domain,
+    /// Whether a DKIM key was generated
+    ///
+    /// The domain is usable without it, but mails sent from it are likely treated as spam.
+    pub dkim_generated: bool,
+}
+
+impl DomainLimits {
+    /// Whether all quotas are positive multiples of a MiB
+    pub fn has_valid_quotas(&self) -> bool {
+        [
+            self.default_mailbox_quota,
+            self.max_mailbox_quota,
+            self.domain_quota,
+        ]
+        .iter()
+        .all(|quota| *quota > 0 && quota % MIB == 0)
+    }
+
+    /// Whether the quotas don't exceed each other
+    pub fn has_consistent_quotas(&self) -> bool {
+        self.default_mailbox_quota <= self.max_mailbox_quota
+            && self.max_mailbox_quota <= self.domain_quota
+    }
+}
+
+impl Mailcow {
+    /// Create a domain in mailcow, generate its DKIM key and insert it
+    ///
+    /// The caller has to ensure the domain doesn't exist yet.
+    #[instrument(name = "Mailcow::provision_domain", skip(self, tx))]
+    pub async fn provision_domain(
+        &self,
+        tx: &mut Transaction,
+        domain: MaxStr<255>,
+        limits: DomainLimits,
+    ) -> anyhow::Result<ProvisionedDomain> {
+        self.sdk
+            .create_domain(CreateDomainRequest {
+                domain: domain.to_string(),
+                description: domain.to_string(),
+                max_aliases: limits.max_aliases,
+                max_mailboxes: limits.max_mailboxes,
+                def_quota_for_mbox: limits.default_mailbox_quota / MIB,
+                max_quota_for_mbox: limits.max_mailbox_quota / MIB,
+                max_quota_for_domain: limits.domain_quota / MIB,
+            })
+            .await?;
+
+        // Mailcow reports errors in successful responses, so check the outcome
+        let mailcow_domain = self
+            .sdk
+            .get_domain(&domain)
+            .await
+            .map_err(|_| anyhow!("Mailcow didn't create the domain {domain}"))?;
+
+        let dkim = self
+            .sdk
+            .create_dkim(CreateDkimRequest {
+                domain: domain.to_string(),
+                dkim_selector: DKIM_SELECTOR.to_string(),
+                key_size: DKIM_KEY_SIZE,
+            })
+            .await;
+        if let Err(error) = &dkim {
+            error!(
+                error.debug = ?error,
+                error.display = %error,
+                "Could not generate DKIM key for {domain}"
+            );
+        }
+        let dkim_generated = dkim.is_ok() && self.sdk.get_dkim(&domain).await?.is_some();
+
+        // The sync might have picked up the domain in the meantime
+        let domain = match Domain::find_by_domain(&mut *tx, &domain).await? {
+            Some(existing) => existing,
+            None => {
+                Domain::create(
+                    &mut *tx,
+                    CreateDomain {
+                        domain,
+                        club: None,
+                        is_primary: false,
+                        mailboxes_left: mailcow_domain.mboxes_left,
+                    },
+                )
+                .await?
+            }
+        };
+
+        info!("Created domain {} in mailcow", domain.domain);
+
+        Ok(ProvisionedDomain {
+            domain,
+            dkim_generated,
+        })
+    }
+
+    /// Change the limits of a domain in mailcow
+    #[instrument(name = "Mailcow::update_domain_limits", skip(self))]
+    pub async fn update_domain_limits(
+        &self,
+        domain: &str,
+        limits: DomainLimits,
+    ) -> anyhow::Result<()> {
+        self.sdk
+            .edit_domains(EditDomainRequest {
+                attr: EditDomainChanges {
+                    max_aliases: Some(limits.max_aliases),
+                    max_mailboxes: Some(limits.max_mailboxes),
+                    def_quota_for_mbox: Some(limits.default_mailbox_quota / MIB),
+                    max_quota_for_mbox: Some(limits.max_mailbox_quota / MIB),
+                    max_quota_for_domain: Some(limits.domain_quota / MIB),
+                },
+                items: vec![domain.to_string()],
+            })
+            .await?;
+
+        info!("Updated limits of domain {domain}");
+
+        Ok(())
+    }
+
+    /// Delete a domain in mailcow and remove it
+    ///
+    /// The caller has to ensure the domain has no mailboxes left, mailcow refuses to delete it otherwise.
+    #[instrument(name = "Mailcow::deprovision_domain", skip(self, tx))]
+    pub async fn deprovision_domain(
+        &self,
+        tx: &mut Transaction,
+        domain: Domain,
+    ) -> anyhow::Result<()> {
+        self.sdk
+            .delete_domains(vec![domain.domain.to_string()])
+            .await?;
+
+        self.domain_stats_cache
+            .write()
+            .await
+            .remove(&*domain.domain);
+
+        Domain::delete_by_domain(&mut *tx, domain.domain.clone()).await?;
+
+        info!("Deleted domain {} in mailcow", domain.domain);
+
+        Ok(())
+    }
+}
@@ -49,7 +49,7 @@ impl DomainStatsWorker {
         let domains = Domain::find_all(&mut tx)
             .await?
             .into_iter()
-            .filter(|d| d.is_primary)
+            .filter(|d| d.associated_club.is_some() && d.missing_since.is_none())
             .collect::<Vec<_>>();
 
         tx.commit().await?;
@@ -0,0 +1,66 @@
+//! Backs the additional addresses of members with aliases in mailcow.
+
+use mailcow::aliases::schema::CreateAliasRequest;
+use tracing::instrument;
+
+use crate::models::member_address::MemberAddress;
+use crate::modules::mailcow::Mailcow;
+
+impl Mailcow {
+    /// Check whether an address is already used by a mailbox or an alias in mailcow
+    #[instrument(name = "Mailcow::is_address_taken", skip(self))]
+    pub async fn is_address_taken(&self, address: &str) -> anyhow::Result<bool> {
+        if self.sdk.get_mailbox(address).await?.is_some() {
+            return Ok(true);
+        }
+
+        Ok(self
+            .sdk
+            .get_all_aliases()
+            .await?
+            .iter()
+            .any(|alias| alias.address.eq_ignore_ascii_case(address)))
+    }
+
+    /// Create the alias forwarding an additional address to the mailbox of its member
+    #[instrument(name = "Mailcow::create_member_alias", skip(self))]
+    pub async fn create_member_alias(&self, address: &str, mailbox: &str) -> anyhow::Result<()> {
+        self.sdk
+            .create_alias(CreateAliasRequest {
+                address: address.to_string(),
+                goto: vec![mailbox.to_string()],
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete the aliases of additional addresses
+    ///
+    /// Addresses without an alias in mailcow are skipped.
+    #[instrument(name = "Mailcow::delete_member_aliases", skip_all)]
+    pub async fn delete_member_aliases(&self, addresses: &[MemberAddress]) -> anyhow::Result<()> {
+        if addresses.is_empty() {
+            return Ok(());
+        }
+
+        let ids: Vec<u64> = self
+            .sdk
+            .get_all_aliases()
+            .await?
+            .into_iter()
+            .filter(|alias| {
+                addresses
+                    .iter()
+                    .any(|address| alias.address.eq_ignore_ascii_case(&address.address))
+            })
+            .map(|alias| alias.id)
+            .collect();
+
+        if !ids.is_empty() {
+            self.sdk.delete_aliases(ids).await?;
+        }
+
+        Ok(())
+    }
+}
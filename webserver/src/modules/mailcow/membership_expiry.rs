@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use galvyn::core::Module;
+use galvyn::rorm::Database;
+use mailcow::MailcowClient;
+use tracing::Instrument;
+use tracing::error;
+use tracing::info;
+use tracing::info_span;
+
+use crate::models::account::Account;
+use crate::models::account::ClubAccount;
+use crate::models::session::StoredSession;
+use crate::utils::worker::Worker;
+
+/// Background worker that locks members whose membership expired
+///
+/// Members holding an admin role aren't locked, only their mailbox is deactivated.
+pub struct MembershipExpiryWorker {
+    /// Mailcow client
+    pub sdk: MailcowClient,
+}
+
+impl Worker for MembershipExpiryWorker {
+    async fn run(self) {
+        let mut timer = tokio::time::interval(Duration::from_secs(300));
+
+        loop {
+            timer.tick().await;
+
+            let span = info_span!("MembershipExpiryWorker::run_once");
+            if let Err(error) = self.run_once().instrument(span.clone()).await {
+                span.in_scope(|| {
+                    error!(
+                        error.debug = ?error,
+                        error.display = %error,
+                        "MembershipExpiryWorker run exited with error"
+                    )
+                });
+            }
+        }
+    }
+}
+
+impl MembershipExpiryWorker {
+    async fn run_once(&self) -> anyhow::Result<()> {
+        let (expired, expired_admins) = ClubAccount::find_expired(Database::global()).await?;
+
+        // Admins keep their account and roles, only the mailbox of the membership ends.
+        // The sign in to OIDC clients is rejected by the membership check.
+        if !expired_admins.is_empty() {
+            self.sdk
+                .set_mailboxes_active(
+                    expired_admins
+                        .iter()
+                        .map(|admin| admin.email.to_string())
+                        .collect(),
+                    false,
+                )
+                .await?;
+        }
+
+        for member in expired {
+            let username = member.username.clone();
+            if let Err(error) = self.lock(member).await {
+                error!(
+                    error.debug = ?error,
+                    error.display = %error,
+                    "Could not lock expired member {username}"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lock an expired member and deactivate its mailbox
+    async fn lock(&self, member: ClubAccount) -> anyhow::Result<()> {
+        let mut tx = Database::global().start_transaction().await?;
+
+        let mut account = Account::get_by_uuid(&mut tx, member.uuid())
+            .await?
+            .ok_or(anyhow!("Account of member vanished"))?;
+
+        account.set_locked(&mut tx, true).await?;
+        StoredSession::revoke_all_by_account(&mut tx, member.uuid()).await?;
+
+        self.sdk
+            .set_mailboxes_active(vec![member.email.to_string()], false)
+            .await?;
+
+        tx.commit().await?;
+
+        info!(
+            "Locked member {} as its membership expired",
+            member.username
+        );
+
+        Ok(())
+    }
+}
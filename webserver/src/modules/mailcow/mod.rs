@@ -18,6 +18,9 @@ use galvyn::core::PreInitError;
 use galvyn::rorm::Database;
 use galvyn::rorm::fields::types::MaxStr;
 use mailcow::MailcowClient;
+use mailcow::domains::schema::EditDomainChanges;
+use mailcow::domains::schema::EditDomainRequest;
+use time::OffsetDateTime;
 use tokio::sync::RwLock;
 use tracing::info;
 use tracing::instrument;
@@ -25,18 +28,37 @@ use tracing::instrument;
 use crate::config::DISABLE_MAILCOW;
 use crate::config::MAILCOW_API_KEY;
 use crate::config::MAILCOW_BASE_URL;
+use crate::models::club::ClubUuid;
+use crate::models::domain::DomainSyncReport;
 use crate::modules::mailcow::app_passwords::AppPasswordInitializer;
 use crate::modules::mailcow::domain_stats_cache::CachedDomainStats;
 use crate::modules::mailcow::domain_stats_cache::DomainStatsCache;
 use crate::modules::mailcow::domain_stats_worker::DomainStatsWorker;
+use crate::modules::mailcow::membership_expiry::MembershipExpiryWorker;
+use crate::modules::mailcow::sync::DomainSyncOutcome;
+use crate::modules::mailcow::sync::LastDomainSync;
 use crate::modules::mailcow::sync::SyncWorker;
+use crate::modules::mailcow::xauth_migration::XAuthMigrationProgress;
+use crate::modules::mailcow::xauth_migration::XAuthMigrationWorker;
+use crate::modules::mailcow::xauth_migration::XAuthMigrations;
 use crate::utils::worker::Worker;
 use crate::utils::worker::WorkerHandle;
 
 mod app_passwords;
+pub(crate) mod dns_records;
+mod domain_admins;
+pub(crate) mod domain_provisioning;
 pub(crate) mod domain_stats_cache;
 mod domain_stats_worker;
-mod sync;
+mod member_addresses;
+mod membership_expiry;
+pub(crate) mod primary_domain;
+pub(crate) mod reconciler;
+pub(crate) mod sync;
+pub(crate) mod xauth_migration;
+
+/// Number of bytes in a MiB, the unit mailcow expects quotas in
+pub const MIB: u64 = 1024 * 1024;
 
 /// galvyn module that serves as the main entry point for interacting with the Mailcow API.
 pub struct Mailcow {
@@ -44,10 +66,16 @@ pub struct Mailcow {
     pub sdk: MailcowClient,
     /// Synchronization worker
     pub sync_worker: Mutex<Option<WorkerHandle<SyncWorker>>>,
+    /// Outcome of the latest domain sync
+    pub last_domain_sync: LastDomainSync,
     /// Domain statistics cache
     pub domain_stats_cache: DomainStatsCache,
     /// Domain statistics worker
     pub stats_worker: Mutex<Option<WorkerHandle<DomainStatsWorker>>>,
+    /// Worker locking members whose membership expired
+    pub expiry_worker: Mutex<Option<WorkerHandle<MembershipExpiryWorker>>>,
+    /// Latest app password migration of each club
+    pub xauth_migrations: XAuthMigrations,
 }
 
 impl Mailcow {
@@ -63,10 +91,90 @@ impl Mailcow {
         .spawn()
     }
 
+    /// Start migrating the app passwords of a club's members to its current X-Auth setting
+    ///
+    /// Returns `None` if a migration of the club is already running.
+    pub async fn start_xauth_migration(
+        &self,
+        club: ClubUuid,
+        use_xauth: bool,
+    ) -> Option<WorkerHandle<XAuthMigrationWorker>> {
+        let mut migrations = self.xauth_migrations.write().await;
+        if migrations.get(&club).is_some_and(|x| x.is_running()) {
+            return None;
+        }
+
+        migrations.insert(
+            club,
+            XAuthMigrationProgress {
+                use_xauth,
+                total: 0,
+                processed: 0,
+                failed: vec![],
+                error: None,
+                started_at: OffsetDateTime::now_utc(),
+                finished_at: None,
+            },
+        );
+
+        Some(
+            XAuthMigrationWorker {
+                sdk: self.sdk.clone(),
+                migrations: self.xauth_migrations.clone(),
+                club,
+            }
+            .spawn(),
+        )
+    }
+
+    /// Retrieve the latest app password migration of a club
+    pub async fn get_xauth_migration(&self, club: ClubUuid) -> Option<XAuthMigrationProgress> {
+        self.xauth_migrations.read().await.get(&club).cloned()
+    }
+
+    /// Sync the domains from mailcow right away
+    ///
+    /// Set `force` to remove missing domains even if too many of them went missing at once.
+    pub async fn run_domain_sync(&self, force: bool) -> anyhow::Result<DomainSyncReport> {
+        SyncWorker {
+            sdk: self.sdk.clone(),
+            last_sync: self.last_domain_sync.clone(),
+        }
+        .run_once(force)
+        .await
+    }
+
+    /// Retrieve the outcome of the latest domain sync
+    pub async fn get_last_domain_sync(&self) -> Option<DomainSyncOutcome> {
+        self.last_domain_sync.read().await.clone()
+    }
+
     /// Retrieve cached domain statistics for a domain
     pub async fn get_cached_domain_stats(&self, domain: &str) -> Option<CachedDomainStats> {
         self.domain_stats_cache.read().await.get(domain).cloned()
     }
+
+    /// Set the default quota for new mailboxes of a domain
+    ///
+    /// The cached statistics of the domain are updated right away.
+    #[instrument(name = "Mailcow::set_default_quota", skip(self))]
+    pub async fn set_default_quota(&self, domain: &str, quota: u64) -> anyhow::Result<()> {
+        self.sdk
+            .edit_domains(EditDomainRequest {
+                attr: EditDomainChanges {
+                    def_quota_for_mbox: Some(quota / MIB),
+                    ..Default::default()
+                },
+                items: vec![domain.to_string()],
+            })
+            .await?;
+
+        if let Some(cached) = self.domain_stats_cache.write().await.get_mut(domain) {
+            cached.domain.def_quota_for_mbox = quota;
+        }
+
+        Ok(())
+    }
 }
 
 impl Module for Mailcow {
@@ -94,19 +202,23 @@ impl Module for Mailcow {
         Ok(Self {
             sdk: sdk.clone(),
             sync_worker: Mutex::new(None),
+            last_domain_sync: Arc::new(RwLock::new(None)),
             domain_stats_cache: Arc::new(RwLock::new(HashMap::new())),
             stats_worker: Mutex::new(None),
+            expiry_worker: Mutex::new(None),
+            xauth_migrations: Arc::new(RwLock::new(HashMap::new())),
         })
     }
 
     async fn post_init(&'static self) -> Result<(), PostInitError> {
         let sync_worker = SyncWorker {
             sdk: self.sdk.clone(),
+            last_sync: self.last_domain_sync.clone(),
         };
 
         // Run sync once to ensure domains are in the DB before starting the stats worker
         sync_worker
-            .run_once()
+            .run_once(false)
             .await
             .map_err(|e| PostInitError::from(anyhow::anyhow!("Initial domain sync failed: {e}")))?;
 
@@ -121,6 +233,13 @@ impl Module for Mailcow {
                 }
                 .spawn(),
             );
+
+            *self.expiry_worker.lock().expect("Poison error") = Some(
+                MembershipExpiryWorker {
+                    sdk: self.sdk.clone(),
+                }
+                .spawn(),
+            );
         }
 
         Ok(())
@@ -0,0 +1,254 @@
+//! Moves the members of a club to a new primary domain.
+//!
+//! Members on the old primary domain keep their local part on the new domain.
+//! Mailcow can't move mailboxes between domains, so the old address becomes an alias
+//! of the new one. Mailcow rejects aliases on addresses of existing mailboxes, so
+//! members whose old mailbox is retained get no alias. Their old mailbox is reported
+//! instead, it has to be emptied and deleted manually before the alias can be added.
+
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use galvyn::rorm::db::transaction::Transaction;
+use galvyn::rorm::fields::types::MaxStr;
+use mailcow::aliases::schema::CreateAliasRequest;
+use tracing::error;
+use tracing::info;
+use tracing::instrument;
+
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::models::account::ClubAccount;
+use crate::models::club::Club;
+use crate::models::domain::Domain;
+use crate::modules::mailcow::Mailcow;
+
+/// The planned change of a club's primary domain
+#[derive(Debug, Clone)]
+pub struct PrimaryDomainChange {
+    /// The current primary domain
+    pub old_domain: MaxStr<255>,
+    /// The new primary domain
+    pub new_domain: MaxStr<255>,
+    /// The members whose address changes
+    pub members: Vec<PlannedAddressChange>,
+    /// Usernames of the domain admins of the club
+    pub domain_admins: Vec<MaxStr<255>>,
+}
+
+/// The planned change of a member's address
+#[derive(Debug, Clone)]
+pub struct PlannedAddressChange {
+    /// The account of the member
+    pub account: AccountUuid,
+    /// The username of the member
+    pub username: MaxStr<255>,
+    /// The current address
+    pub old_email: MaxStr<255>,
+    /// The address on the new domain
+    pub new_email: MaxStr<255>,
+    /// The new address is already used by another mailbox, alias or member
+    pub conflict: bool,
+    /// The old address is still a mailbox, so no alias can be created for it
+    pub old_mailbox_retained: bool,
+}
+
+/// The outcome of creating the alias for a changed address
+#[derive(Debug, Clone)]
+pub struct AliasOutcome {
+    /// The change the alias was created for
+    pub change: PlannedAddressChange,
+    /// Error of mailcow, if the alias couldn't be created
+    ///
+    /// No alias is attempted for a retained old mailbox.
+    pub error: Option<String>,
+}
+
+impl PrimaryDomainChange {
+    /// Whether any new address is already in use
+    pub fn has_conflicts(&self) -> bool {
+        self.members.iter().any(|member| member.conflict)
+    }
+}
+
+impl Mailcow {
+    /// Plan the change of a club's primary domain without modifying anything
+    #[instrument(name = "Mailcow::plan_primary_domain_change", skip_all)]
+    pub async fn plan_primary_domain_change(
+        &self,
+        tx: &mut Transaction,
+        club: &Club,
+        domain: &Domain,
+    ) -> anyhow::Result<PrimaryDomainChange> {
+        let old_suffix = format!("@{}", club.primary_domain);
+
+        let members = club
+            .members_page(&mut *tx, i64::MAX as u64, 0, None)
+            .await?
+            .items;
+
+        let old_mailboxes: HashSet<String> = self
+            .sdk
+            .get_all_mailboxes(&club.primary_domain)
+            .await?
+            .into_iter()
+            .map(|mailbox| mailbox.username.to_lowercase())
+            .collect();
+
+        let mut used_addresses: HashSet<String> = self
+            .sdk
+            .get_all_mailboxes(&domain.domain)
+            .await?
+            .into_iter()
+            .map(|mailbox| mailbox.username.to_lowercase())
+            .collect();
+        used_addresses.extend(
+            self.sdk
+                .get_all_aliases()
+                .await?
+                .into_iter()
+                .map(|alias| alias.address.to_lowercase()),
+        );
+
+        let mut changes = vec![];
+        for member in members {
+            let Some(local_part) = member
+                .email
+                .to_lowercase()
+                .strip_suffix(&old_suffix.to_lowercase())
+                .map(str::to_string)
+            else {
+                // Members on other domains of the club are kept
+                continue;
+            };
+
+            let new_email = MaxStr::new(format!("{local_part}@{}", domain.domain))?;
+
+            let conflict = used_addresses.contains(&new_email.to_lowercase())
+                || ClubAccount::get_by_email(&mut *tx, &new_email)
+                    .await?
+                    .is_some_and(|other| other.uuid() != member.uuid());
+
+            changes.push(PlannedAddressChange {
+                account: member.uuid(),
+                username: member.username,
+                old_mailbox_retained: old_mailboxes.contains(&member.email.to_lowercase()),
+                old_email: member.email,
+                new_email,
+                conflict,
+            });
+        }
+
+        let domain_admins = club
+            .admins_page(&mut *tx, i64::MAX as u64, 0, None)
+            .await?
+            .items
+            .into_iter()
+            .map(|admin| admin.username)
+            .collect();
+
+        Ok(PrimaryDomainChange {
+            old_domain: club.primary_domain.clone(),
+            new_domain: domain.domain.clone(),
+            members: changes,
+            domain_admins,
+        })
+    }
+
+    /// Make the domain the club's primary domain and rename the member addresses
+    ///
+    /// The domain admins are updated as the domain might not have been associated before.
+    /// The aliases are created by [`Mailcow::create_primary_domain_aliases`]
+    /// once the transaction is committed.
+    #[instrument(name = "Mailcow::apply_primary_domain_change", skip_all)]
+    pub async fn apply_primary_domain_change(
+        &self,
+        tx: &mut Transaction,
+        club: &mut Club,
+        domain: &Domain,
+        change: &PrimaryDomainChange,
+    ) -> anyhow::Result<()> {
+        if change.has_conflicts() {
+            return Err(anyhow!(
+                "Can't change primary domain with conflicting addresses"
+            ));
+        }
+
+        club.associate_domain(&mut *tx, domain, true).await?;
+
+        for planned in &change.members {
+            let mut member = ClubAccount::get_by_uuid(&mut *tx, planned.account)
+                .await?
+                .ok_or(anyhow!("Member vanished"))?;
+
+            member
+                .set_email(&mut *tx, planned.new_email.clone())
+                .await?;
+        }
+
+        let admins = club.admins_page(&mut *tx, i64::MAX as u64, 0, None).await?;
+        for admin in admins.items {
+            let account = Account::get_by_uuid(&mut *tx, admin.uuid())
+                .await?
+                .ok_or(anyhow!("Club admin vanished"))?;
+
+            self.update_domain_admin(tx, &account).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Forward the old addresses of the members to their new ones
+    ///
+    /// Addresses of retained mailboxes are skipped, mailcow would reject their aliases.
+    /// Failures don't abort the remaining members, they are part of the outcome instead.
+    #[instrument(name = "Mailcow::create_primary_domain_aliases", skip_all)]
+    pub async fn create_primary_domain_aliases(
+        &self,
+        change: &PrimaryDomainChange,
+    ) -> Vec<AliasOutcome> {
+        let mut outcomes = vec![];
+
+        for planned in &change.members {
+            if planned.old_mailbox_retained {
+                outcomes.push(AliasOutcome {
+                    change: planned.clone(),
+                    error: None,
+                });
+                continue;
+            }
+
+            let res = self
+                .sdk
+                .create_alias(CreateAliasRequest {
+                    address: planned.old_email.to_string(),
+                    goto: vec![planned.new_email.to_string()],
+                })
+                .await;
+
+            if let Err(error) = &res {
+                error!(
+                    error.debug = ?error,
+                    error.display = %error,
+                    "Could not create alias {} for {}",
+                    planned.old_email,
+                    planned.new_email
+                );
+            }
+
+            outcomes.push(AliasOutcome {
+                change: planned.clone(),
+                error: res.err().map(|error| error.to_string()),
+            });
+        }
+
+        info!(
+            "Changed primary domain from {} to {} for {} members",
+            change.old_domain,
+            change.new_domain,
+            change.members.len()
+        );
+
+        outcomes
+    }
+}
@@ -0,0 +1,463 @@
+//! Compares the accounts in the database with the state of mailcow and repairs drift.
+//!
+//! Drift is caused by failed mailcow calls or changes made directly in mailcow.
+//! Repairs are only applied to issues which are still present when the repair runs.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use galvyn::core::Module;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use galvyn::rorm::Database;
+use galvyn::rorm::fields::types::MaxStr;
+use serde::Deserialize;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::error;
+use tracing::info;
+use tracing::instrument;
+
+use crate::models::account::Account;
+use crate::models::account::AccountUuid;
+use crate::models::account::ClubAccount;
+use crate::models::club::Club;
+use crate::models::domain::Domain;
+use crate::models::member_address::MemberAddress;
+use crate::modules::mailcow::Mailcow;
+use crate::modules::mailcow::app_passwords::has_managed_app_password;
+use crate::modules::mailcow::app_passwords::set_managed_app_password;
+
+/// A difference between the database and mailcow
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum ReconcileIssue {
+    /// A mailbox on a domain of a club which doesn't belong to a member
+    ///
+    /// Additional addresses of members aren't considered orphaned.
+    /// The repair deactivates the mailbox, it is never deleted. As the mailbox might still
+    /// hold mails, it is only repaired when selected individually, never by category.
+    OrphanedMailbox {
+        /// The address of the mailbox
+        mailbox: String,
+    },
+    /// A member without a mailbox
+    ///
+    /// Mailcow creates the mailbox on the first sign in of the member,
+    /// so there is no repair.
+    MissingMailbox {
+        /// The account of the member
+        account: AccountUuid,
+        /// The address of the member
+        email: MaxStr<255>,
+    },
+    /// The managed app password or the flag of a member doesn't match the club's X-Auth setting
+    AppPasswordMismatch {
+        /// The account of the member
+        account: AccountUuid,
+        /// The address of the member
+        email: MaxStr<255>,
+        /// Whether the member should have a managed app password
+        expected: bool,
+        /// Whether the member is flagged to have an app password
+        flagged: bool,
+        /// Whether mailcow holds a managed app password for the member
+        present: bool,
+    },
+    /// A club admin without a domain admin
+    MissingDomainAdmin {
+        /// The account of the club admin
+        account: AccountUuid,
+        /// The username of the club admin
+        username: MaxStr<255>,
+    },
+    /// A domain admin in mailcow which doesn't belong to any club admin
+    ///
+    /// The repair deletes the domain admin.
+    OrphanedDomainAdmin {
+        /// The username of the domain admin
+        username: String,
+    },
+    /// The domains of a domain admin don't match the clubs of its account
+    DomainAdminDomainsMismatch {
+        /// The account of the club admin
+        account: AccountUuid,
+        /// The username of the club admin
+        username: MaxStr<255>,
+        /// Domains of the clubs the domain admin can't manage
+        missing_domains: Vec<String>,
+        /// Domains the domain admin manages without being admin of their club
+        extra_domains: Vec<String>,
+    },
+}
+
+/// Category of a [`ReconcileIssue`]
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema, clap::ValueEnum,
+)]
+pub enum ReconcileCategory {
+    /// [`ReconcileIssue::OrphanedMailbox`]
+    OrphanedMailbox,
+    /// [`ReconcileIssue::MissingMailbox`]
+    MissingMailbox,
+    /// [`ReconcileIssue::AppPasswordMismatch`]
+    AppPasswordMismatch,
+    /// [`ReconcileIssue::MissingDomainAdmin`]
+    MissingDomainAdmin,
+    /// [`ReconcileIssue::OrphanedDomainAdmin`]
+    OrphanedDomainAdmin,
+    /// [`ReconcileIssue::DomainAdminDomainsMismatch`]
+    DomainAdminDomainsMismatch,
+}
+
+/// All differences between the database and mailcow
+#[derive(Debug, Clone)]
+pub struct ReconcileReport {
+    /// The found issues
+    pub issues: Vec<ReconcileIssue>,
+    /// The point in time the report was created
+    pub created_at: OffsetDateTime,
+}
+
+/// The outcome of repairing an issue
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RepairOutcome {
+    /// The issue to repair
+    pub issue: ReconcileIssue,
+    /// What happened to the issue
+    pub status: RepairStatus,
+}
+
+/// What happened to an issue during a repair
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type")]
+pub enum RepairStatus {
+    /// The issue was repaired
+    Repaired,
+    /// The issue isn't present anymore
+    Resolved,
+    /// The issue has no repair
+    NotRepairable,
+    /// The issue is only repaired when selected individually
+    ConfirmationRequired,
+    /// The repair failed
+    Failed {
+        /// The error of the repair
+        error: String,
+    },
+}
+
+impl ReconcileIssue {
+    /// The category of the issue
+    pub fn category(&self) -> ReconcileCategory {
+        match self {
+            ReconcileIssue::OrphanedMailbox { .. } => ReconcileCategory::OrphanedMailbox,
+            ReconcileIssue::MissingMailbox { .. } => ReconcileCategory::MissingMailbox,
+            ReconcileIssue::AppPasswordMismatch { .. } => ReconcileCategory::AppPasswordMismatch,
+            ReconcileIssue::MissingDomainAdmin { .. } => ReconcileCategory::MissingDomainAdmin,
+            ReconcileIssue::OrphanedDomainAdmin { .. } => ReconcileCategory::OrphanedDomainAdmin,
+            ReconcileIssue::DomainAdminDomainsMismatch { .. } => {
+                ReconcileCategory::DomainAdminDomainsMismatch
+            }
+        }
+    }
+}
+
+impl Mailcow {
+    /// Compare the database with the mailboxes, app passwords and domain admins in mailcow
+    ///
+    /// The database state is loaded first, so no transaction is held while querying mailcow.
+    #[instrument(name = "Mailcow::reconcile", skip(self))]
+    pub async fn reconcile(&self) -> anyhow::Result<ReconcileReport> {
+        let mut tx = Database::global().start_transaction().await?;
+
+        let mut clubs = vec![];
+        let mut admin_uuids = vec![];
+        for club in Club::find_all(&mut tx).await? {
+            let domains = Domain::find_all_by_club(&mut tx, club.uuid).await?;
+            let members = club
+                .members_page(&mut tx, i64::MAX as u64, 0, None)
+                .await?
+                .items;
+
+            for admin in club
+                .admins_page(&mut tx, i64::MAX as u64, 0, None)
+                .await?
+                .items
+            {
+                if !admin_uuids.contains(&admin.uuid()) {
+                    admin_uuids.push(admin.uuid());
+                }
+            }
+
+            clubs.push((club, domains, members));
+        }
+
+        let mut admins = vec![];
+        for admin in admin_uuids {
+            let account = Account::get_by_uuid(&mut tx, admin)
+                .await?
+                .ok_or(anyhow!("Club admin vanished"))?;
+            let expected: BTreeSet<String> = Self::domain_admin_domains(&mut tx, &account)
+                .await?
+                .into_iter()
+                .map(|domain| domain.to_lowercase())
+                .collect();
+            admins.push((account, expected));
+        }
+
+        let member_addresses: HashSet<String> = MemberAddress::find_all(&mut tx)
+            .await?
+            .into_iter()
+            .map(|address| address.address.to_lowercase())
+            .collect();
+
+        tx.commit().await?;
+
+        let mut issues = vec![];
+
+        for (club, domains, members) in clubs {
+            let mut mailboxes = BTreeSet::new();
+            for domain in domains.iter().filter(|x| x.missing_since.is_none()) {
+                mailboxes.extend(
+                    self.sdk
+                        .get_all_mailboxes(&domain.domain)
+                        .await?
+                        .into_iter()
+                        .map(|mailbox| mailbox.username.to_lowercase()),
+                );
+            }
+
+            let member_emails: HashSet<String> = members
+                .iter()
+                .map(|member| member.email.to_lowercase())
+                .collect();
+
+            for mailbox in &mailboxes {
+                if !member_emails.contains(mailbox) && !member_addresses.contains(mailbox) {
+                    issues.push(ReconcileIssue::OrphanedMailbox {
+                        mailbox: mailbox.clone(),
+                    });
+                }
+            }
+
+            for member in members {
+                // Mailboxes on domains missing from mailcow are unknown
+                if domains
+                    .iter()
+                    .any(|x| x.missing_since.is_some() && x.matches_address(&member.email))
+                {
+                    continue;
+                }
+
+                if !mailboxes.contains(&member.email.to_lowercase()) {
+                    issues.push(ReconcileIssue::MissingMailbox {
+                        account: member.uuid(),
+                        email: member.email,
+                    });
+                    continue;
+                }
+
+                let expected = !club.use_xauth;
+                let present = has_managed_app_password(&self.sdk, &member.email).await?;
+                if present != expected || member.has_app_password != expected {
+                    issues.push(ReconcileIssue::AppPasswordMismatch {
+                        account: member.uuid(),
+                        email: member.email,
+                        expected,
+                        flagged: member.has_app_password,
+                        present,
+                    });
+                }
+            }
+        }
+
+        let domain_admins: HashMap<String, BTreeSet<String>> = self
+            .sdk
+            .get_all_domain_admins()
+            .await?
+            .into_iter()
+            .map(|admin| {
+                let domains = admin
+                    .selected_domains
+                    .iter()
+                    .map(|domain| domain.to_lowercase())
+                    .collect();
+                (admin.username, domains)
+            })
+            .collect();
+
+        let admin_usernames: HashSet<&str> = admins
+            .iter()
+            .map(|(account, _)| &**account.username())
+            .collect();
+        let mut orphaned_domain_admins: Vec<&String> = domain_admins
+            .keys()
+            .filter(|username| !admin_usernames.contains(username.as_str()))
+            .collect();
+        orphaned_domain_admins.sort();
+        issues.extend(orphaned_domain_admins.into_iter().map(|username| {
+            ReconcileIssue::OrphanedDomainAdmin {
+                username: username.clone(),
+            }
+        }));
+
+        for (account, expected) in &admins {
+            let Some(actual) = domain_admins.get(&**account.username()) else {
+                issues.push(ReconcileIssue::MissingDomainAdmin {
+                    account: account.uuid(),
+                    username: account.username().clone(),
+                });
+                continue;
+            };
+
+            let missing_domains: Vec<String> = expected.difference(actual).cloned().collect();
+            let extra_domains: Vec<String> = actual.difference(expected).cloned().collect();
+            if !missing_domains.is_empty() || !extra_domains.is_empty() {
+                issues.push(ReconcileIssue::DomainAdminDomainsMismatch {
+                    account: account.uuid(),
+                    username: account.username().clone(),
+                    missing_domains,
+                    extra_domains,
+                });
+            }
+        }
+
+        info!("Reconciliation found {} issues", issues.len());
+
+        Ok(ReconcileReport {
+            issues,
+            created_at: OffsetDateTime::now_utc(),
+        })
+    }
+
+    /// Repair selected issues
+    ///
+    /// Issues which aren't present anymore are skipped.
+    #[instrument(name = "Mailcow::repair_issues", skip_all)]
+    pub async fn repair_issues(
+        &self,
+        issues: Vec<ReconcileIssue>,
+    ) -> anyhow::Result<Vec<RepairOutcome>> {
+        let report = self.reconcile().await?;
+
+        let mut outcomes = vec![];
+        for issue in issues {
+            let status = if report.issues.contains(&issue) {
+                self.repair_issue(&issue).await
+            } else {
+                RepairStatus::Resolved
+            };
+            outcomes.push(RepairOutcome { issue, status });
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Repair all issues of the given categories
+    ///
+    /// Orphaned mailboxes are skipped, they have to be repaired individually.
+    #[instrument(name = "Mailcow::repair_categories", skip(self))]
+    pub async fn repair_categories(
+        &self,
+        categories: &[ReconcileCategory],
+    ) -> anyhow::Result<Vec<RepairOutcome>> {
+        let report = self.reconcile().await?;
+
+        let mut outcomes = vec![];
+        for issue in report.issues {
+            if !categories.contains(&issue.category()) {
+                continue;
+            }
+
+            let status = if matches!(issue, ReconcileIssue::OrphanedMailbox { .. }) {
+                RepairStatus::ConfirmationRequired
+            } else {
+                self.repair_issue(&issue).await
+            };
+            outcomes.push(RepairOutcome { issue, status });
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Repair a single issue
+    async fn repair_issue(&self, issue: &ReconcileIssue) -> RepairStatus {
+        let res = match issue {
+            ReconcileIssue::MissingMailbox { .. } => return RepairStatus::NotRepairable,
+            ReconcileIssue::OrphanedMailbox { mailbox } => self
+                .sdk
+                .set_mailboxes_active(vec![mailbox.clone()], false)
+                .await
+                .map_err(anyhow::Error::from),
+            ReconcileIssue::AppPasswordMismatch {
+                account, expected, ..
+            } => self.repair_app_password(*account, *expected).await,
+            ReconcileIssue::MissingDomainAdmin { account, .. } => {
+                self.recreate_domain_admin(*account).await
+            }
+            ReconcileIssue::OrphanedDomainAdmin { username } => self
+                .sdk
+                .delete_domain_admins(vec![username.clone()])
+                .await
+                .map_err(anyhow::Error::from),
+            ReconcileIssue::DomainAdminDomainsMismatch { account, .. } => {
+                self.resync_domain_admin(*account).await
+            }
+        };
+
+        match res {
+            Ok(()) => RepairStatus::Repaired,
+            Err(error) => {
+                error!(
+                    error.debug = ?error,
+                    error.display = %error,
+                    "Could not repair {issue:?}"
+                );
+                RepairStatus::Failed {
+                    error: error.to_string(),
+                }
+            }
+        }
+    }
+
+    async fn repair_app_password(
+        &self,
+        account: AccountUuid,
+        expected: bool,
+    ) -> anyhow::Result<()> {
+        let mut member = ClubAccount::get_by_uuid(Database::global(), account)
+            .await?
+            .ok_or(anyhow!("Member not found"))?;
+
+        set_managed_app_password(&self.sdk, &mut member, expected).await
+    }
+
+    async fn recreate_domain_admin(&self, account: AccountUuid) -> anyhow::Result<()> {
+        let mut tx = Database::global().start_transaction().await?;
+
+        let mut account = Account::get_by_uuid(&mut tx, account)
+            .await?
+            .ok_or(anyhow!("Account not found"))?;
+        self.repair_domain_admin(&mut tx, &mut account).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn resync_domain_admin(&self, account: AccountUuid) -> anyhow::Result<()> {
+        let mut tx = Database::global().start_transaction().await?;
+
+        let account = Account::get_by_uuid(&mut tx, account)
+            .await?
+            .ok_or(anyhow!("Account not found"))?;
+        self.update_domain_admin(&mut tx, &account).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
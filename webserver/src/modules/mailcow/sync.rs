@@ -1,18 +1,43 @@
+use std::sync::Arc;
+
 use galvyn::core::Module;
 use galvyn::rorm::Database;
 use mailcow::MailcowClient;
+use time::Duration;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
 use tracing::Instrument;
 use tracing::error;
 use tracing::info;
 use tracing::info_span;
 
+use crate::config::DOMAIN_SYNC_GRACE_PERIOD_DAYS;
+use crate::config::DOMAIN_SYNC_MAX_REMOVAL_PERCENT;
 use crate::models::domain::Domain;
+use crate::models::domain::DomainSyncPolicy;
+use crate::models::domain::DomainSyncReport;
 use crate::utils::worker::Worker;
 
+/// Outcome of a domain sync
+#[derive(Debug, Clone)]
+pub struct DomainSyncOutcome {
+    /// The applied changes, if the sync succeeded
+    pub report: Option<DomainSyncReport>,
+    /// Error which aborted the sync
+    pub error: Option<String>,
+    /// The point in time the sync finished
+    pub finished_at: OffsetDateTime,
+}
+
+/// Thread-safe store for the outcome of the latest domain sync
+pub type LastDomainSync = Arc<RwLock<Option<DomainSyncOutcome>>>;
+
 /// Synchronization worker
 pub struct SyncWorker {
     /// Mailcow client
     pub sdk: MailcowClient,
+    /// Shared store to report the outcome to
+    pub last_sync: LastDomainSync,
 }
 
 impl Worker for SyncWorker {
@@ -23,7 +48,7 @@ impl Worker for SyncWorker {
             timer.tick().await;
 
             let span = info_span!("SyncWorker::run_once");
-            if let Err(error) = self.run_once().instrument(span.clone()).await {
+            if let Err(error) = self.run_once(false).instrument(span.clone()).await {
                 span.in_scope(|| error!(error.debug = ?error, error.display = %error, "SyncWorker run exited with error"));
             }
         }
@@ -31,12 +56,46 @@ impl Worker for SyncWorker {
 }
 
 impl SyncWorker {
-    pub async fn run_once(&self) -> anyhow::Result<()> {
+    /// Sync the domains once and store the outcome
+    ///
+    /// Set `force` to remove missing domains even if too many of them went missing at once.
+    pub async fn run_once(&self, force: bool) -> anyhow::Result<DomainSyncReport> {
+        let res = self.sync(force).await;
+
+        *self.last_sync.write().await = Some(DomainSyncOutcome {
+            report: res.as_ref().ok().cloned(),
+            error: res.as_ref().err().map(|error| error.to_string()),
+            finished_at: OffsetDateTime::now_utc(),
+        });
+
+        res
+    }
+
+    async fn sync(&self, force: bool) -> anyhow::Result<DomainSyncReport> {
         let domains = self.sdk.get_all_domains().await?;
         info!(domains = ?domains, "Got domains");
 
-        Domain::sync_mailcow_domains(Database::global(), domains).await?;
+        let report = Domain::sync_mailcow_domains(
+            Database::global(),
+            domains,
+            DomainSyncPolicy {
+                max_removal_percent: *DOMAIN_SYNC_MAX_REMOVAL_PERCENT.get(),
+                grace_period: Duration::days(*DOMAIN_SYNC_GRACE_PERIOD_DAYS.get() as i64),
+                force,
+            },
+        )
+        .await?;
+
+        info!(
+            added = ?report.added,
+            restored = ?report.restored,
+            missing = ?report.missing,
+            deleted = ?report.deleted,
+            kept_primary = ?report.kept_primary,
+            removal_refused = report.removal_refused,
+            "Synced domains"
+        );
 
-        Ok(())
+        Ok(report)
     }
 }
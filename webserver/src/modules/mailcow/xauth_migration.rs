@@ -0,0 +1,140 @@
+//! Migrates the app passwords of a club's members after its X-Auth setting changed.
+//!
+//! Clubs without X-Auth rely on an app password managed by us for every member.
+//! Enabling X-Auth removes these app passwords, disabling it creates them for all members.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use galvyn::core::Module;
+use galvyn::rorm::Database;
+use mailcow::MailcowClient;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+use tracing::Instrument;
+use tracing::error;
+use tracing::info;
+use tracing::info_span;
+
+use crate::models::club::Club;
+use crate::models::club::ClubUuid;
+use crate::modules::mailcow::app_passwords::set_managed_app_password;
+use crate::utils::worker::Worker;
+
+/// Progress of the app password migration of a club
+#[derive(Debug, Clone)]
+pub struct XAuthMigrationProgress {
+    /// The X-Auth setting the club is migrated to
+    pub use_xauth: bool,
+    /// Number of members to migrate
+    pub total: u64,
+    /// Number of members which were already processed, including failed ones
+    pub processed: u64,
+    /// Mailboxes whose app passwords couldn't be migrated
+    pub failed: Vec<String>,
+    /// Error which aborted the migration
+    pub error: Option<String>,
+    /// The point in time the migration started
+    pub started_at: OffsetDateTime,
+    /// The point in time the migration finished
+    pub finished_at: Option<OffsetDateTime>,
+}
+
+impl XAuthMigrationProgress {
+    /// Whether the migration is still running
+    pub fn is_running(&self) -> bool {
+        self.finished_at.is_none()
+    }
+}
+
+/// Thread-safe store for the latest app password migration of each club
+pub type XAuthMigrations = Arc<RwLock<HashMap<ClubUuid, XAuthMigrationProgress>>>;
+
+/// Background worker migrating the app passwords of a club's members
+pub struct XAuthMigrationWorker {
+    /// Mailcow client
+    pub sdk: MailcowClient,
+    /// Shared store to report the progress to
+    pub migrations: XAuthMigrations,
+    /// The club to migrate
+    pub club: ClubUuid,
+}
+
+impl Worker for XAuthMigrationWorker {
+    async fn run(self) {
+        let span = info_span!("XAuthMigrationWorker::run_once", club = ?self.club);
+        let res = self.run_once().instrument(span.clone()).await;
+
+        if let Err(error) = &res {
+            span.in_scope(|| {
+                error!(
+                    error.debug = ?error,
+                    error.display = %error,
+                    "XAuthMigrationWorker run exited with error"
+                )
+            });
+        }
+
+        self.update(|progress| {
+            progress.error = res.err().map(|error| error.to_string());
+            progress.finished_at = Some(OffsetDateTime::now_utc());
+        })
+        .await;
+    }
+}
+
+impl XAuthMigrationWorker {
+    async fn run_once(&self) -> anyhow::Result<()> {
+        // The setting is read again, so overlapping migrations converge to the latest one
+        let club = Club::find_by_uuid(Database::global(), self.club)
+            .await?
+            .ok_or(anyhow!("Club not found"))?;
+
+        let members = club
+            .members_page(Database::global(), i64::MAX as u64, 0, None)
+            .await?
+            .items;
+
+        self.update(|progress| {
+            progress.use_xauth = club.use_xauth;
+            progress.total = members.len() as u64;
+        })
+        .await;
+
+        for mut member in members {
+            let res = set_managed_app_password(&self.sdk, &mut member, !club.use_xauth).await;
+
+            if let Err(error) = &res {
+                error!(
+                    error.debug = ?error,
+                    error.display = %error,
+                    "Could not migrate app password of {}",
+                    member.email
+                );
+            }
+
+            self.update(|progress| {
+                progress.processed += 1;
+                if res.is_err() {
+                    progress.failed.push(member.email.to_string());
+                }
+            })
+            .await;
+        }
+
+        info!(
+            "Migrated app passwords of club {} to use_xauth={}",
+            club.name, club.use_xauth
+        );
+
+        Ok(())
+    }
+
+    /// Update the progress of this migration
+    async fn update(&self, f: impl FnOnce(&mut XAuthMigrationProgress)) {
+        if let Some(progress) = self.migrations.write().await.get_mut(&self.club) {
+            f(progress);
+        }
+    }
+}
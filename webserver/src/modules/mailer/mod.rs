@@ -0,0 +1,304 @@
+//! Sending of notification mails
+//!
+//! Mails are rendered from [`MailTemplate`]s and written to a persistent outbox.
+//! A background worker delivers them via SMTP and retries failed deliveries.
+
+mod templates;
+mod worker;
+
+pub use templates::MailTemplate;
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use galvyn::core::InitError;
+use galvyn::core::Module;
+use galvyn::core::PostInitError;
+use galvyn::core::PreInitError;
+use galvyn::rorm::Database;
+use galvyn::rorm::db::Executor;
+use galvyn::rorm::fields::types::MaxStr;
+use lettre::Address;
+use lettre::AsyncSmtpTransport;
+use lettre::Tokio1Executor;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::Notify;
+use tracing::info;
+use tracing::instrument;
+use tracing::warn;
+
+use crate::config::MAIL_FROM;
+use crate::config::MAIL_LANGUAGE;
+use crate::config::SMTP_HOST;
+use crate::config::SMTP_PASSWORD;
+use crate::config::SMTP_PORT;
+use crate::config::SMTP_TLS;
+use crate::config::SMTP_USERNAME;
+use crate::models::account::Account;
+use crate::models::login_event::LoginEvent;
+use crate::models::outgoing_mail::OutgoingMail;
+use crate::models::outgoing_mail::OutgoingMailUuid;
+use crate::modules::mailer::worker::MailerWorker;
+use crate::utils::worker::Worker;
+use crate::utils::worker::WorkerHandle;
+
+/// Sends mails through the outbox
+pub struct Mailer {
+    transport: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Mailbox,
+    language: Language,
+    notify: Arc<Notify>,
+    worker: OnceLock<WorkerHandle<MailerWorker>>,
+}
+
+/// How the connection to the SMTP server is secured
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SmtpTls {
+    /// Plain text connection, only suitable for local testing
+    None,
+    /// Upgrade a plain text connection using `STARTTLS`
+    StartTls,
+    /// Connect using TLS
+    Tls,
+}
+
+impl FromStr for SmtpTls {
+    type Err = ParseMailConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "starttls" => Ok(Self::StartTls),
+            "tls" => Ok(Self::Tls),
+            _ => Err(ParseMailConfigError::InvalidTls(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for SmtpTls {
+    type Error = ParseMailConfigError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// The language mails are written in
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Language {
+    /// German
+    German,
+    /// English
+    English,
+}
+
+impl FromStr for Language {
+    type Err = ParseMailConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "de" => Ok(Self::German),
+            "en" => Ok(Self::English),
+            _ => Err(ParseMailConfigError::InvalidLanguage(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for Language {
+    type Error = ParseMailConfigError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Errors of the mail configuration
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum ParseMailConfigError {
+    #[error("Unsupported SMTP TLS mode: {0}, expected one of none, starttls or tls")]
+    InvalidTls(String),
+    #[error("Unsupported mail language: {0}, expected one of de or en")]
+    InvalidLanguage(String),
+}
+
+/// Check whether an address can be used as recipient
+pub fn is_valid_address(address: &str) -> bool {
+    address.parse::<Address>().is_ok()
+}
+
+impl Mailer {
+    /// Render a mail and add it to the outbox
+    ///
+    /// The mail is delivered in the background once `exe`'s transaction is committed.
+    /// Call [`Mailer::deliver_enqueued`] after the commit to deliver it right away,
+    /// otherwise it is picked up by the worker's next poll.
+    #[instrument(name = "Mailer::enqueue", skip(self, exe, template))]
+    pub async fn enqueue(
+        &self,
+        exe: impl Executor<'_>,
+        recipient: &str,
+        template: MailTemplate,
+    ) -> anyhow::Result<OutgoingMailUuid> {
+        // Reject invalid recipients early instead of retrying them in the worker
+        let recipient: Mailbox = recipient.parse()?;
+        let (subject, body) = template.render(self.language);
+
+        let uuid = OutgoingMail::enqueue(
+            exe,
+            MaxStr::new(recipient.to_string())?,
+            MaxStr::new(subject)?,
+            MaxStr::new(body)?,
+        )
+        .await?;
+
+        Ok(uuid)
+    }
+
+    /// Wake up the worker to deliver the mails enqueued by a committed transaction
+    ///
+    /// The worker would miss mails of a transaction which wasn't committed yet,
+    /// so this must not be called before the commit.
+    pub fn deliver_enqueued(&self) {
+        self.notify.notify_one();
+    }
+
+    /// Notify an account's owner that its password was changed
+    ///
+    /// Does nothing if the account has no address to notify.
+    #[instrument(name = "Mailer::notify_password_changed", skip_all)]
+    pub async fn notify_password_changed(
+        &self,
+        exe: impl Executor<'_>,
+        account: &Account,
+    ) -> anyhow::Result<()> {
+        if let Some(email) = account.email() {
+            self.enqueue(
+                exe,
+                email,
+                MailTemplate::PasswordChanged {
+                    display_name: account.display_name().to_string(),
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Notify an account's owner about a sign in from an unseen ip range
+    ///
+    /// Does nothing if the account has no address to notify.
+    #[instrument(name = "Mailer::notify_new_login_location", skip_all)]
+    pub async fn notify_new_login_location(
+        &self,
+        exe: impl Executor<'_>,
+        account: &Account,
+        event: &LoginEvent,
+    ) -> anyhow::Result<()> {
+        if let Some(email) = account.email() {
+            self.enqueue(
+                exe,
+                email,
+                MailTemplate::NewLoginLocation {
+                    display_name: account.display_name().to_string(),
+                    ip: event
+                        .ip
+                        .as_ref()
+                        .map(|ip| ip.to_string())
+                        .unwrap_or_default(),
+                    user_agent: event
+                        .user_agent
+                        .as_ref()
+                        .map(|user_agent| user_agent.to_string())
+                        .unwrap_or_default(),
+                    signed_in_at: event.created_at,
+                },
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Module for Mailer {
+    type Setup = ();
+    type PreInit = ();
+
+    async fn pre_init(_setup: Self::Setup) -> Result<Self::PreInit, PreInitError> {
+        Ok(())
+    }
+
+    type Dependencies = (Database,);
+
+    #[instrument(name = "Mailer::init", skip_all)]
+    async fn init(
+        _pre_init: Self::PreInit,
+        _dependencies: &mut Self::Dependencies,
+    ) -> Result<Self, InitError> {
+        let transport = if SMTP_HOST.is_empty() {
+            warn!("No SMTP server configured, mails are kept in the outbox");
+            None
+        } else {
+            let mut builder = match *SMTP_TLS {
+                SmtpTls::None => {
+                    AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(SMTP_HOST.as_str())
+                }
+                SmtpTls::StartTls => {
+                    AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(SMTP_HOST.as_str())?
+                }
+                SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(SMTP_HOST.as_str())?,
+            }
+            .port(*SMTP_PORT);
+
+            if !SMTP_USERNAME.is_empty() {
+                builder = builder.credentials(Credentials::new(
+                    SMTP_USERNAME.clone(),
+                    SMTP_PASSWORD.clone(),
+                ));
+            }
+
+            info!(
+                host = SMTP_HOST.as_str(),
+                port = *SMTP_PORT,
+                "Sending mails via SMTP"
+            );
+            Some(builder.build())
+        };
+
+        Ok(Self {
+            transport,
+            from: MAIL_FROM.parse()?,
+            language: *MAIL_LANGUAGE,
+            notify: Arc::new(Notify::new()),
+            worker: Default::default(),
+        })
+    }
+
+    async fn post_init(&'static self) -> Result<(), PostInitError> {
+        let Some(transport) = self.transport.clone() else {
+            return Ok(());
+        };
+
+        self.worker
+            .set(
+                MailerWorker {
+                    transport,
+                    from: self.from.clone(),
+                    notify: self.notify.clone(),
+                }
+                .spawn(),
+            )
+            .map_err(|_| anyhow!("Failed to initialize mailer worker"))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use time::OffsetDateTime;
+use time::UtcOffset;
+use url::Url;
+
+use crate::modules::mailer::Language;
+
+/// The mails the application sends
+#[derive(Debug, Clone)]
+pub enum MailTemplate {
+    /// An invitation to create an account
+    Invite {
+        /// Display name of the invited user
+        display_name: String,
+        /// Link to accept the invite
+        link: Url,
+        /// The point in time the invite expires
+        expires_at: OffsetDateTime,
+    },
+    /// A link to reset the credentials of an account
+    CredentialReset {
+        /// Display name of the account
+        display_name: String,
+        /// Link to the reset form
+        link: Url,
+    },
+    /// A link to verify a recovery email
+    VerifyRecoveryEmail {
+        /// Display name of the account
+        display_name: String,
+        /// Link to verify the address
+        link: Url,
+    },
+    /// Notice that the password of an account was changed
+    PasswordChanged {
+        /// Display name of the account
+        display_name: String,
+    },
+    /// Notice about a sign in from an ip range the account wasn't used from before
+    NewLoginLocation {
+        /// Display name of the account
+        display_name: String,
+        /// The ip address of the client
+        ip: String,
+        /// The user agent of the client
+        user_agent: String,
+        /// The point in time of the sign in
+        signed_in_at: OffsetDateTime,
+    },
+}
+
+impl MailTemplate {
+    /// Render the mail's subject and plain text body
+    pub fn render(&self, language: Language) -> (String, String) {
+        match (self, language) {
+            (
+                Self::Invite {
+                    display_name,
+                    link,
+                    expires_at,
+                },
+                Language::German,
+            ) => (
+                "Einladung zum BNV Manager".to_string(),
+                format!(
+                    "Hallo {display_name},\n\
+                     \n\
+                     du wurdest zum BNV Manager eingeladen.\n\
+                     Über den folgenden Link kannst du dein Passwort festlegen:\n\
+                     \n\
+                     {link}\n\
+                     \n\
+                     Der Link ist bis zum {date} gültig.\n",
+                    date = german_date(*expires_at),
+                ),
+            ),
+            (
+                Self::Invite {
+                    display_name,
+                    link,
+                    expires_at,
+                },
+                Language::English,
+            ) => (
+                "Invitation to BNV Manager".to_string(),
+                format!(
+                    "Hello {display_name},\n\
+                     \n\
+                     you have been invited to BNV Manager.\n\
+                     Use the following link to set your password:\n\
+                     \n\
+                     {link}\n\
+                     \n\
+                     The link is valid until {date}.\n",
+                    date = english_date(*expires_at),
+                ),
+            ),
+            (Self::CredentialReset { display_name, link }, Language::German) => (
+                "Zugangsdaten zurücksetzen".to_string(),
+                format!(
+                    "Hallo {display_name},\n\
+                     \n\
+                     für dein Konto wurde das Zurücksetzen der Zugangsdaten angefordert.\n\
+                     Über den folgenden Link kannst du ein neues Passwort festlegen:\n\
+                     \n\
+                     {link}\n\
+                     \n\
+                     Falls du das nicht angefordert hast, wende dich bitte an deinen Administrator.\n"
+                ),
+            ),
+            (Self::CredentialReset { display_name, link }, Language::English) => (
+                "Reset your credentials".to_string(),
+                format!(
+                    "Hello {display_name},\n\
+                     \n\
+                     a reset of your account's credentials was requested.\n\
+                     Use the following link to choose a new password:\n\
+                     \n\
+                     {link}\n\
+                     \n\
+                     If you didn't request this, please contact your administrator.\n"
+                ),
+            ),
+            (Self::VerifyRecoveryEmail { display_name, link }, Language::German) => (
+                "Wiederherstellungsadresse bestätigen".to_string(),
+                format!(
+                    "Hallo {display_name},\n\
+                     \n\
+                     diese Adresse wurde als Wiederherstellungsadresse für dein Konto hinterlegt.\n\
+                     Über den folgenden Link kannst du sie bestätigen:\n\
+                     \n\
+                     {link}\n\
+                     \n\
+                     Der Link ist 24 Stunden gültig.\n\
+                     Falls du das nicht warst, kannst du diese Nachricht ignorieren.\n"
+                ),
+            ),
+            (Self::VerifyRecoveryEmail { display_name, link }, Language::English) => (
+                "Confirm your recovery address".to_string(),
+                format!(
+                    "Hello {display_name},\n\
+                     \n\
+                     this address was set as recovery address for your account.\n\
+                     Use the following link to confirm it:\n\
+                     \n\
+                     {link}\n\
+                     \n\
+                     The link is valid for 24 hours.\n\
+                     If this wasn't you, you can ignore this message.\n"
+                ),
+            ),
+            (Self::PasswordChanged { display_name }, Language::German) => (
+                "Dein Passwort wurde geändert".to_string(),
+                format!(
+                    "Hallo {display_name},\n\
+                     \n\
+                     das Passwort deines Kontos wurde soeben geändert\n\
+                     und alle bestehenden Sitzungen wurden abgemeldet.\n\
+                     \n\
+                     Falls du das nicht warst, wende dich bitte umgehend an deinen Administrator.\n"
+                ),
+            ),
+            (Self::PasswordChanged { display_name }, Language::English) => (
+                "Your password was changed".to_string(),
+                format!(
+                    "Hello {display_name},\n\
+                     \n\
+                     the password of your account was just changed\n\
+                     and all existing sessions were signed out.\n\
+                     \n\
+                     If this wasn't you, please contact your administrator immediately.\n"
+                ),
+            ),
+            (
+                Self::NewLoginLocation {
+                    display_name,
+                    ip,
+                    user_agent,
+                    signed_in_at,
+                },
+                Language::German,
+            ) => (
+                "Neue Anmeldung bei deinem Konto".to_string(),
+                format!(
+                    "Hallo {display_name},\n\
+                     \n\
+                     am {date} hat sich jemand aus einem unbekannten Netzwerk\n\
+                     bei deinem Konto angemeldet:\n\
+                     \n\
+                     IP-Adresse: {ip}\n\
+                     Browser: {user_agent}\n\
+                     \n\
+                     Falls du das nicht warst, ändere bitte umgehend dein Passwort\n\
+                     und wende dich an deinen Administrator.\n",
+                    date = german_date(*signed_in_at),
+                ),
+            ),
+            (
+                Self::NewLoginLocation {
+                    display_name,
+                    ip,
+                    user_agent,
+                    signed_in_at,
+                },
+                Language::English,
+            ) => (
+                "New sign in to your account".to_string(),
+                format!(
+                    "Hello {display_name},\n\
+                     \n\
+                     on {date} your account was signed in to from an unknown network:\n\
+                     \n\
+                     IP address: {ip}\n\
+                     Browser: {user_agent}\n\
+                     \n\
+                     If this wasn't you, please change your password immediately\n\
+                     and contact your administrator.\n",
+                    date = english_date(*signed_in_at),
+                ),
+            ),
+        }
+    }
+}
+
+fn german_date(date: OffsetDateTime) -> String {
+    let date = date.to_offset(UtcOffset::UTC);
+    format!(
+        "{:02}.{:02}.{} {:02}:{:02} UTC",
+        date.day(),
+        u8::from(date.month()),
+        date.year(),
+        date.hour(),
+        date.minute()
+    )
+}
+
+fn english_date(date: OffsetDateTime) -> String {
+    let date = date.to_offset(UtcOffset::UTC);
+    format!(
+        "{}-{:02}-{:02} {:02}:{:02} UTC",
+        date.year(),
+        u8::from(date.month()),
+        date.day(),
+        date.hour(),
+        date.minute()
+    )
+}
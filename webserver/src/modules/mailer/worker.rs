@@ -0,0 +1,105 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use galvyn::core::Module;
+use galvyn::rorm::Database;
+use lettre::AsyncSmtpTransport;
+use lettre::AsyncTransport;
+use lettre::Message;
+use lettre::Tokio1Executor;
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use tokio::sync::Notify;
+use tracing::Instrument;
+use tracing::error;
+use tracing::info_span;
+use tracing::warn;
+
+use crate::models::outgoing_mail::MAX_DELIVERY_ATTEMPTS;
+use crate::models::outgoing_mail::OutgoingMail;
+use crate::utils::worker::Worker;
+
+/// Interval in which the outbox is checked for due retries
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Number of mails delivered in one run
+const BATCH_SIZE: u64 = 50;
+
+/// Background worker delivering the mails in the outbox
+pub struct MailerWorker {
+    /// Transport to the SMTP server
+    pub transport: AsyncSmtpTransport<Tokio1Executor>,
+    /// Sender of all mails
+    pub from: Mailbox,
+    /// Wakes the worker up when a new mail was enqueued
+    pub notify: Arc<Notify>,
+}
+
+impl Worker for MailerWorker {
+    async fn run(self) {
+        let mut timer = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            let span = info_span!("MailerWorker::run_once");
+            if let Err(error) = self.run_once().instrument(span.clone()).await {
+                span.in_scope(|| {
+                    error!(
+                        error.debug = ?error,
+                        error.display = %error,
+                        "MailerWorker run exited with error"
+                    )
+                });
+            }
+
+            tokio::select! {
+                _ = timer.tick() => {}
+                _ = self.notify.notified() => {}
+            }
+        }
+    }
+}
+
+impl MailerWorker {
+    async fn run_once(&self) -> anyhow::Result<()> {
+        loop {
+            let mails = OutgoingMail::find_due(Database::global(), BATCH_SIZE).await?;
+            if mails.is_empty() {
+                return Ok(());
+            }
+
+            for mut mail in mails {
+                if !mail.claim(Database::global()).await? {
+                    continue;
+                }
+
+                match self.send(&mail).await {
+                    Ok(()) => mail.mark_sent(Database::global()).await?,
+                    Err(error) => {
+                        warn!(
+                            mail = ?mail.uuid,
+                            attempts = mail.attempts + 1,
+                            max_attempts = MAX_DELIVERY_ATTEMPTS,
+                            error.display = %error,
+                            "Couldn't deliver mail"
+                        );
+                        mail.mark_failed(Database::global(), &error.to_string())
+                            .await?;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send(&self, mail: &OutgoingMail) -> anyhow::Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(mail.recipient.parse()?)
+            .subject(&*mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body.to_string())?;
+
+        self.transport.send(message).await?;
+
+        Ok(())
+    }
+}
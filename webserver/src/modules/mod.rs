@@ -2,4 +2,7 @@
 
 pub mod garbage_collector;
 pub mod mailcow;
+pub mod mailer;
 pub mod oidc;
+pub mod password_policy;
+pub mod webauthn;
@@ -0,0 +1,167 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+
+use sha1::Digest;
+use sha1::Sha1;
+
+/// Length of a hex encoded SHA1 hash
+const HASH_LENGTH: usize = 40;
+
+/// A local list of breached passwords
+///
+/// The file uses the format of the "Have I Been Pwned" password list ordered by hash:
+/// one upper case hex encoded SHA1 hash per line, optionally followed by `:<count>`.
+/// Only the password's hash is ever compared, the password itself never leaves the process.
+///
+/// The whole list has to be a single file sorted by full hash. The k-anonymity layout,
+/// with one file per 5 character prefix containing only the hash suffixes, isn't supported.
+pub(super) struct BreachList {
+    path: PathBuf,
+}
+
+impl BreachList {
+    pub(super) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Check whether a password is contained in the list
+    pub(super) async fn contains(&self, password: &str) -> anyhow::Result<bool> {
+        let hash = hex_upper(&Sha1::digest(password.as_bytes()));
+        let path = self.path.clone();
+
+        Ok(tokio::task::spawn_blocking(move || search(&path, hash.as_bytes())).await??)
+    }
+}
+
+/// Binary search for a hash in the sorted file
+fn search(path: &PathBuf, hash: &[u8]) -> io::Result<bool> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut low = 0;
+    let mut high = reader.get_ref().metadata()?.len();
+    let mut line = Vec::new();
+
+    while low < high {
+        let middle = low + (high - low) / 2;
+
+        // Move to the first line starting at or after `middle`
+        let mut line_start = middle;
+        if middle > 0 {
+            reader.seek(SeekFrom::Start(middle - 1))?;
+            line.clear();
+            line_start = middle - 1 + reader.read_until(b'\n', &mut line)? as u64;
+        } else {
+            reader.seek(SeekFrom::Start(0))?;
+        }
+
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 || line_start >= high {
+            high = middle;
+            continue;
+        }
+
+        let Some(line_hash) = line.get(..HASH_LENGTH) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Malformed line in password breach list",
+            ));
+        };
+
+        match line_hash.cmp(hash) {
+            Ordering::Equal => return Ok(true),
+            Ordering::Less => low = line_start + read as u64,
+            Ordering::Greater => high = middle,
+        }
+    }
+
+    Ok(false)
+}
+
+fn hex_upper(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::fs;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    /// Write a sorted list containing the hashes of the given passwords
+    fn write_list(passwords: &[&str], line_ending: &str) -> PathBuf {
+        let mut hashes: Vec<_> = passwords
+            .iter()
+            .map(|password| hex_upper(&Sha1::digest(password.as_bytes())))
+            .collect();
+        hashes.sort();
+
+        let content: String = hashes
+            .iter()
+            .enumerate()
+            .map(|(count, hash)| format!("{hash}:{}{line_ending}", count + 1))
+            .collect();
+
+        let path = std::env::temp_dir().join(format!("breach-list-{}.txt", Uuid::new_v4()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn contains(path: &PathBuf, password: &str) -> bool {
+        search(
+            path,
+            hex_upper(&Sha1::digest(password.as_bytes())).as_bytes(),
+        )
+        .unwrap()
+    }
+
+    const PASSWORDS: &[&str] = &["123456", "password", "qwerty", "letmein", "dragon"];
+
+    /// The passwords ordered like their hashes in the list
+    fn sorted_passwords() -> Vec<&'static str> {
+        let mut passwords = PASSWORDS.to_vec();
+        passwords.sort_by_key(|password| hex_upper(&Sha1::digest(password.as_bytes())));
+        passwords
+    }
+
+    #[test]
+    fn finds_first_middle_and_last_line() {
+        let path = write_list(PASSWORDS, "\n");
+        let passwords = sorted_passwords();
+
+        assert!(contains(&path, passwords[0]));
+        assert!(contains(&path, passwords[passwords.len() / 2]));
+        assert!(contains(&path, passwords[passwords.len() - 1]));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn misses_unlisted_password() {
+        let path = write_list(PASSWORDS, "\n");
+
+        assert!(!contains(&path, "correct horse battery staple"));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn handles_crlf_line_endings() {
+        let path = write_list(PASSWORDS, "\r\n");
+
+        for password in PASSWORDS {
+            assert!(contains(&path, password));
+        }
+        assert!(!contains(&path, "correct horse battery staple"));
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+//! Central password policy applied whenever a password is chosen
+//!
+//! Passwords are checked for their length, their estimated strength and optionally
+//! against a local list of breached passwords.
+
+mod breach_list;
+
+use std::path::PathBuf;
+
+use galvyn::core::InitError;
+use galvyn::core::Module;
+use galvyn::core::PreInitError;
+use galvyn::core::re_exports::schemars;
+use galvyn::core::re_exports::schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::info;
+use tracing::instrument;
+use zxcvbn::zxcvbn;
+
+use crate::config::PASSWORD_BREACH_FILE;
+use crate::config::PASSWORD_MIN_LENGTH;
+use crate::config::PASSWORD_MIN_SCORE;
+use crate::modules::password_policy::breach_list::BreachList;
+
+/// Checks new passwords against the configured policy
+pub struct PasswordPolicy {
+    min_length: usize,
+    min_score: u8,
+    breach_list: Option<BreachList>,
+}
+
+/// The reasons a password was rejected
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PasswordPolicyViolation {
+    /// The password is shorter than `min_length` characters
+    pub too_short: bool,
+    /// The minimal number of characters
+    pub min_length: usize,
+    /// The password's estimated strength is below `min_score`
+    pub low_entropy: bool,
+    /// The minimal strength on a scale from 0 to 4
+    pub min_score: u8,
+    /// The password's estimated strength on a scale from 0 to 4
+    pub score: u8,
+    /// The password was found in a list of breached passwords
+    pub breached: bool,
+    /// Explanation why the password is weak
+    pub warning: Option<String>,
+    /// Suggestions to create a stronger password
+    pub suggestions: Vec<String>,
+}
+
+impl PasswordPolicy {
+    /// Check a new password against the policy
+    ///
+    /// `user_inputs` should contain user specific strings like the username,
+    /// which make a password weaker when used in it.
+    #[instrument(name = "PasswordPolicy::check", skip_all)]
+    pub async fn check(
+        &self,
+        password: &str,
+        user_inputs: &[&str],
+    ) -> anyhow::Result<Result<(), PasswordPolicyViolation>> {
+        let entropy = zxcvbn(password, user_inputs);
+        let score = u8::from(entropy.score());
+
+        let mut violation = PasswordPolicyViolation {
+            too_short: password.chars().count() < self.min_length,
+            min_length: self.min_length,
+            low_entropy: score < self.min_score,
+            min_score: self.min_score,
+            score,
+            breached: false,
+            warning: None,
+            suggestions: Vec::new(),
+        };
+
+        if let Some(breach_list) = &self.breach_list {
+            violation.breached = breach_list.contains(password).await?;
+        }
+
+        if !violation.too_short && !violation.low_entropy && !violation.breached {
+            return Ok(Ok(()));
+        }
+
+        if let Some(feedback) = entropy.feedback() {
+            violation.warning = feedback.warning().map(|warning| warning.to_string());
+            violation.suggestions = feedback
+                .suggestions()
+                .iter()
+                .map(|suggestion| suggestion.to_string())
+                .collect();
+        }
+
+        Ok(Err(violation))
+    }
+}
+
+impl Module for PasswordPolicy {
+    type Setup = ();
+    type PreInit = ();
+
+    async fn pre_init(_setup: Self::Setup) -> Result<Self::PreInit, PreInitError> {
+        Ok(())
+    }
+
+    type Dependencies = ();
+
+    #[instrument(name = "PasswordPolicy::init")]
+    async fn init(
+        _pre_init: Self::PreInit,
+        _dependencies: &mut Self::Dependencies,
+    ) -> Result<Self, InitError> {
+        let breach_list = if PASSWORD_BREACH_FILE.is_empty() {
+            None
+        } else {
+            let path = PathBuf::from(PASSWORD_BREACH_FILE.as_str());
+            // Fail early instead of on the first password change
+            tokio::fs::metadata(&path).await?;
+            info!(path = %path.display(), "Checking passwords against breach list");
+            Some(BreachList::new(path))
+        };
+
+        Ok(Self {
+            min_length: *PASSWORD_MIN_LENGTH,
+            min_score: (*PASSWORD_MIN_SCORE).min(4),
+            breach_list,
+        })
+    }
+}
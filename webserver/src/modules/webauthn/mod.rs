@@ -0,0 +1,43 @@
+//! Holds the WebAuthn relying party used for passkeys
+
+use galvyn::core::InitError;
+use galvyn::core::Module;
+use galvyn::core::PreInitError;
+use tracing::instrument;
+use webauthn_rs::Webauthn;
+use webauthn_rs::WebauthnBuilder;
+
+use crate::config::WEBAUTHN_ID;
+use crate::config::WEBAUTHN_ORIGIN;
+
+/// Name of the relying party shown by authenticators
+const RP_NAME: &str = "BNV Manager";
+
+/// Holds the WebAuthn relying party used for passkeys
+pub struct WebAuthn {
+    /// The relying party
+    pub webauthn: Webauthn,
+}
+
+impl Module for WebAuthn {
+    type Setup = ();
+    type PreInit = ();
+
+    async fn pre_init(_setup: Self::Setup) -> Result<Self::PreInit, PreInitError> {
+        Ok(())
+    }
+
+    type Dependencies = ();
+
+    #[instrument(name = "WebAuthn::init")]
+    async fn init(
+        _pre_init: Self::PreInit,
+        _dependencies: &mut Self::Dependencies,
+    ) -> Result<Self, InitError> {
+        let webauthn = WebauthnBuilder::new(&WEBAUTHN_ID, &WEBAUTHN_ORIGIN)?
+            .rp_name(RP_NAME)
+            .build()?;
+
+        Ok(Self { webauthn })
+    }
+}
@@ -0,0 +1,396 @@
+//! Resolution of the client's ip address behind reverse proxies
+
+use std::fmt;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use galvyn::core::re_exports::axum::http::HeaderMap;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config::CLIENT_IP_HEADER;
+use crate::config::TRUSTED_PROXIES;
+
+/// A range of ip addresses in CIDR notation
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct IpCidr {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    /// The range with the given prefix length an address is part of
+    ///
+    /// The prefix is clamped to the length of the address.
+    pub fn network_of(address: IpAddr, prefix: u8) -> Self {
+        match address.to_canonical() {
+            IpAddr::V4(address) => {
+                let prefix = prefix.min(32);
+                let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+                Self {
+                    address: IpAddr::V4((u32::from(address) & mask).into()),
+                    prefix,
+                }
+            }
+            IpAddr::V6(address) => {
+                let prefix = prefix.min(128);
+                let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+                Self {
+                    address: IpAddr::V6((u128::from(address) & mask).into()),
+                    prefix,
+                }
+            }
+        }
+    }
+
+    /// Check whether an address is part of the range
+    pub fn contains(&self, address: IpAddr) -> bool {
+        match (self.address, address.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(address) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(address)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(address) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = ParseClientIpConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseClientIpConfigError::InvalidCidr(s.to_string());
+
+        let (address, prefix) = match s.split_once('/') {
+            Some((address, prefix)) => (
+                address.parse::<IpAddr>().map_err(|_| invalid())?,
+                Some(prefix.parse::<u8>().map_err(|_| invalid())?),
+            ),
+            None => (s.parse::<IpAddr>().map_err(|_| invalid())?, None),
+        };
+
+        let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max_prefix);
+        if prefix > max_prefix {
+            return Err(invalid());
+        }
+
+        Ok(Self { address, prefix })
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix)
+    }
+}
+
+/// Comma separated list of proxies whose headers are trusted
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TrustedProxies(pub Vec<IpCidr>);
+
+impl TrustedProxies {
+    /// Check whether an address belongs to a trusted proxy
+    pub fn contains(&self, address: IpAddr) -> bool {
+        self.0.iter().any(|cidr| cidr.contains(address))
+    }
+}
+
+impl FromStr for TrustedProxies {
+    type Err = ParseClientIpConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|cidr| !cidr.is_empty())
+            .map(IpCidr::from_str)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl TryFrom<String> for TrustedProxies {
+    type Error = ParseClientIpConfigError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// The header a trusted proxy puts the client's address into
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ClientIpHeader {
+    /// `X-Real-IP: <address>`
+    XRealIp,
+    /// `X-Forwarded-For: <client>, <proxy1>, <proxy2>`
+    XForwardedFor,
+    /// `Forwarded: for=<client>, for=<proxy1>` as defined in RFC 7239
+    Forwarded,
+}
+
+impl FromStr for ClientIpHeader {
+    type Err = ParseClientIpConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "x-real-ip" => Ok(Self::XRealIp),
+            "x-forwarded-for" => Ok(Self::XForwardedFor),
+            "forwarded" => Ok(Self::Forwarded),
+            _ => Err(ParseClientIpConfigError::InvalidHeader(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for ClientIpHeader {
+    type Error = ParseClientIpConfigError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Errors of the client ip configuration
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum ParseClientIpConfigError {
+    #[error("Invalid CIDR: {0}")]
+    InvalidCidr(String),
+    #[error(
+        "Unsupported client ip header: {0}, expected one of x-real-ip, x-forwarded-for or forwarded"
+    )]
+    InvalidHeader(String),
+}
+
+/// Resolve the address of the client which sent a request
+///
+/// The configured [`CLIENT_IP_HEADER`] is only used if the request was received from
+/// one of the [`TRUSTED_PROXIES`]. Proxies listing multiple addresses are walked from
+/// the right, skipping trusted proxies. Otherwise, the socket's peer address is used.
+///
+/// If the peer address is unknown, the header can't be trusted and `None` is returned.
+pub fn resolve_client_ip(headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+    let peer = peer?.ip().to_canonical();
+
+    if !TRUSTED_PROXIES.contains(peer) {
+        return Some(peer);
+    }
+
+    from_header(headers, *CLIENT_IP_HEADER, &TRUSTED_PROXIES).or(Some(peer))
+}
+
+/// Take the client's address from the header set by a trusted proxy
+///
+/// Elements which aren't an address, like `for=unknown` or obfuscated identifiers
+/// in the `Forwarded` header, are skipped.
+fn from_header(
+    headers: &HeaderMap,
+    header: ClientIpHeader,
+    trusted_proxies: &TrustedProxies,
+) -> Option<IpAddr> {
+    let values = headers.get_all(match header {
+        ClientIpHeader::XRealIp => "x-real-ip",
+        ClientIpHeader::XForwardedFor => "x-forwarded-for",
+        ClientIpHeader::Forwarded => "forwarded",
+    });
+
+    let mut chain = Vec::new();
+    for value in values {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        for element in value.split(',') {
+            let address = match header {
+                ClientIpHeader::XRealIp | ClientIpHeader::XForwardedFor => {
+                    parse_node(element.trim())
+                }
+                ClientIpHeader::Forwarded => element.split(';').find_map(|pair| {
+                    let (key, value) = pair.trim().split_once('=')?;
+                    key.eq_ignore_ascii_case("for")
+                        .then(|| parse_node(value.trim().trim_matches('"')))
+                        .flatten()
+                }),
+            };
+            chain.extend(address);
+        }
+    }
+
+    chain
+        .iter()
+        .rev()
+        .find(|address| !trusted_proxies.contains(**address))
+        .or(chain.first())
+        .copied()
+}
+
+/// Parse an address which may contain a port, IPv6 addresses may be bracketed
+fn parse_node(node: &str) -> Option<IpAddr> {
+    if let Ok(address) = node.parse::<IpAddr>() {
+        return Some(address.to_canonical());
+    }
+    if let Ok(address) = node.parse::<SocketAddr>() {
+        return Some(address.ip().to_canonical());
+    }
+    node.strip_prefix('[')
+        .and_then(|node| node.strip_suffix(']'))
+        .and_then(|node| node.parse::<IpAddr>().ok())
+        .map(|address| address.to_canonical())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use galvyn::core::re_exports::axum::http::HeaderValue;
+
+    use super::*;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn headers(name: &'static str, values: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn parse_cidr() {
+        let cidr: IpCidr = "10.0.0.0/8".parse().unwrap();
+        assert!(cidr.contains(ip("10.1.2.3")));
+        assert!(!cidr.contains(ip("11.0.0.1")));
+        assert!(cidr.contains(ip("::ffff:10.1.2.3")));
+
+        let cidr: IpCidr = "fc00::/7".parse().unwrap();
+        assert!(cidr.contains(ip("fd12::1")));
+        assert!(!cidr.contains(ip("fe80::1")));
+        assert!(!cidr.contains(ip("10.0.0.1")));
+
+        let cidr: IpCidr = "192.168.1.1".parse().unwrap();
+        assert_eq!(cidr.to_string(), "192.168.1.1/32");
+        assert!(cidr.contains(ip("192.168.1.1")));
+        assert!(!cidr.contains(ip("192.168.1.2")));
+
+        let cidr: IpCidr = "0.0.0.0/0".parse().unwrap();
+        assert!(cidr.contains(ip("1.2.3.4")));
+    }
+
+    #[test]
+    fn parse_invalid_cidr() {
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("::/129".parse::<IpCidr>().is_err());
+        assert!("10.0.0.0/".parse::<IpCidr>().is_err());
+        assert!("localhost".parse::<IpCidr>().is_err());
+    }
+
+    #[test]
+    fn network_of() {
+        assert_eq!(
+            IpCidr::network_of(ip("192.168.17.5"), 24),
+            "192.168.17.0/24".parse().unwrap()
+        );
+        assert_eq!(
+            IpCidr::network_of(ip("2001:db8::1"), 200),
+            "2001:db8::1/128".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_trusted_proxies() {
+        let proxies: TrustedProxies = " 10.0.0.0/8 , ,::1".parse().unwrap();
+        assert_eq!(proxies.0.len(), 2);
+        assert!(proxies.contains(ip("10.0.0.1")));
+        assert!(proxies.contains(ip("::1")));
+        assert!(!proxies.contains(ip("127.0.0.1")));
+
+        assert!("10.0.0.0/8,nope".parse::<TrustedProxies>().is_err());
+    }
+
+    #[test]
+    fn x_forwarded_for_skips_trusted_proxies() {
+        let proxies: TrustedProxies = "10.0.0.0/8".parse().unwrap();
+        let headers = headers("x-forwarded-for", &["203.0.113.7, 198.51.100.1, 10.0.0.2"]);
+
+        assert_eq!(
+            from_header(&headers, ClientIpHeader::XForwardedFor, &proxies),
+            Some(ip("198.51.100.1"))
+        );
+    }
+
+    #[test]
+    fn x_forwarded_for_of_only_trusted_proxies() {
+        let proxies: TrustedProxies = "10.0.0.0/8".parse().unwrap();
+        let headers = headers("x-forwarded-for", &["10.0.0.3", "10.0.0.2"]);
+
+        assert_eq!(
+            from_header(&headers, ClientIpHeader::XForwardedFor, &proxies),
+            Some(ip("10.0.0.3"))
+        );
+    }
+
+    #[test]
+    fn forwarded() {
+        let proxies: TrustedProxies = "10.0.0.0/8".parse().unwrap();
+        let headers = headers(
+            "forwarded",
+            &[
+                r#"for=192.0.2.60;proto=http;by=203.0.113.43, For="[2001:db8:cafe::17]:4711", for=10.0.0.2"#,
+            ],
+        );
+
+        assert_eq!(
+            from_header(&headers, ClientIpHeader::Forwarded, &proxies),
+            Some(ip("2001:db8:cafe::17"))
+        );
+    }
+
+    #[test]
+    fn forwarded_skips_unknown_elements() {
+        let proxies: TrustedProxies = "10.0.0.0/8".parse().unwrap();
+        let headers = headers(
+            "forwarded",
+            &["for=192.0.2.60, for=unknown, for=_hidden;proto=https, for=10.0.0.2"],
+        );
+
+        assert_eq!(
+            from_header(&headers, ClientIpHeader::Forwarded, &proxies),
+            Some(ip("192.0.2.60"))
+        );
+    }
+
+    #[test]
+    fn missing_header() {
+        let proxies = TrustedProxies::default();
+
+        assert_eq!(
+            from_header(&HeaderMap::new(), ClientIpHeader::XRealIp, &proxies),
+            None
+        );
+        assert_eq!(
+            from_header(
+                &headers("x-real-ip", &["unknown"]),
+                ClientIpHeader::XRealIp,
+                &proxies
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_nodes() {
+        assert_eq!(parse_node("192.0.2.1"), Some(ip("192.0.2.1")));
+        assert_eq!(parse_node("192.0.2.1:8080"), Some(ip("192.0.2.1")));
+        assert_eq!(parse_node("[2001:db8::1]"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("[2001:db8::1]:443"), Some(ip("2001:db8::1")));
+        assert_eq!(parse_node("::ffff:192.0.2.1"), Some(ip("192.0.2.1")));
+        assert_eq!(parse_node("unknown"), None);
+    }
+}
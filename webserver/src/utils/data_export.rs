@@ -0,0 +1,195 @@
+//! Collection of all data stored about an account
+
+use anyhow::anyhow;
+use galvyn::core::Module;
+use galvyn::core::stuff::schema::SchemaDateTime;
+use galvyn::rorm::db::transaction::Transaction;
+use time::OffsetDateTime;
+
+use crate::http::handler_frontend::data_export::AccountExportSchema;
+use crate::http::handler_frontend::data_export::ExportedAccountSchema;
+use crate::http::handler_frontend::data_export::ExportedClubSchema;
+use crate::http::handler_frontend::data_export::ExportedCredentialResetSchema;
+use crate::http::handler_frontend::data_export::ExportedInviteSchema;
+use crate::http::handler_frontend::data_export::ExportedMailboxSchema;
+use crate::http::handler_frontend::data_export::ExportedMembershipSchema;
+use crate::http::handler_frontend::data_export::ExportedOidcGrantSchema;
+use crate::http::handler_frontend::data_export::ExportedOidcTokenSchema;
+use crate::http::handler_frontend::data_export::ExportedPasskeySchema;
+use crate::http::handler_frontend::data_export::ExportedRecoveryEmailSchema;
+use crate::http::handler_frontend::data_export::ExportedSessionSchema;
+use crate::models::account::Account;
+use crate::models::club::Club;
+use crate::models::club::ClubUuid;
+use crate::models::credential_reset::CredentialReset;
+use crate::models::invite::Invite;
+use crate::models::oidc_provider::OidcAuthenticationToken;
+use crate::models::oidc_provider::OidcDeviceCode;
+use crate::models::passkey::Passkey;
+use crate::models::recovery_email::RecoveryEmail;
+use crate::models::session::StoredSession;
+use crate::modules::mailcow::Mailcow;
+
+/// Collect everything stored about an account, including the metadata of its mailbox
+///
+/// Secrets like password hashes, codes and tokens are never part of the export.
+pub async fn export_account(
+    tx: &mut Transaction,
+    account: &Account,
+) -> anyhow::Result<AccountExportSchema> {
+    let membership = match &account.membership {
+        Some(membership) => Some(ExportedMembershipSchema {
+            club: export_club(tx, membership.club).await?,
+            email: membership.email.clone(),
+            valid_until: membership.valid_until.map(SchemaDateTime),
+        }),
+        None => None,
+    };
+
+    let mut admin_of = Vec::new();
+    for club in &account.admin_of {
+        admin_of.push(export_club(tx, *club).await?);
+    }
+
+    let invite = Invite::find_by_username(&mut *tx, account.username())
+        .await?
+        .map(|invite| ExportedInviteSchema {
+            expires_at: SchemaDateTime(invite.expires_at()),
+            display_name: invite.display_name,
+            club: invite.club,
+            email: invite.email,
+            created_at: SchemaDateTime(invite.created_at),
+        });
+
+    let credential_resets = CredentialReset::find_all_by_account(&mut *tx, account.uuid())
+        .await?
+        .into_iter()
+        .map(|reset| ExportedCredentialResetSchema {
+            code_expires_at: SchemaDateTime(reset.code_expires_at),
+            link_expires_at: SchemaDateTime(reset.link_expires_at),
+        })
+        .collect();
+
+    let recovery_email = RecoveryEmail::find_by_account(&mut *tx, account.uuid())
+        .await?
+        .map(|recovery_email| ExportedRecoveryEmailSchema {
+            email: recovery_email.email,
+            verified_at: recovery_email.verified_at.map(SchemaDateTime),
+            created_at: SchemaDateTime(recovery_email.created_at),
+        });
+
+    let passkeys = Passkey::find_all_by_account(&mut *tx, account.uuid())
+        .await?
+        .into_iter()
+        .map(|passkey| ExportedPasskeySchema {
+            name: passkey.name,
+            last_used_at: passkey.last_used_at.map(SchemaDateTime),
+            created_at: SchemaDateTime(passkey.created_at),
+        })
+        .collect();
+
+    let sessions = StoredSession::find_all_by_account(&mut *tx, account.uuid())
+        .await?
+        .into_iter()
+        .map(|session| ExportedSessionSchema {
+            user_agent: session.user_agent,
+            ip: session.ip,
+            last_seen_at: SchemaDateTime(session.last_seen_at),
+            expires_at: SchemaDateTime(session.expires_at),
+            created_at: SchemaDateTime(session.created_at),
+        })
+        .collect();
+
+    let oidc_tokens = OidcAuthenticationToken::find_all_by_account(&mut *tx, account.uuid())
+        .await?
+        .into_iter()
+        .map(|token| ExportedOidcTokenSchema {
+            client_id: token.client_id,
+            redirect_url: token.redirect_url,
+            scopes: token.scopes,
+            auth_methods: token.auth_methods,
+            expires_at: SchemaDateTime(token.expires_at),
+        })
+        .collect();
+
+    let oidc_grants = OidcDeviceCode::find_approved_by_account(&mut *tx, account.uuid())
+        .await?
+        .into_iter()
+        .map(|device_code| ExportedOidcGrantSchema {
+            client_id: device_code.client_id,
+            scopes: device_code.scopes,
+            last_polled_at: device_code.last_polled_at.map(SchemaDateTime),
+            expires_at: SchemaDateTime(device_code.expires_at),
+        })
+        .collect();
+
+    let mailbox = match account.email() {
+        Some(email) => export_mailbox(email).await?,
+        None => None,
+    };
+
+    Ok(AccountExportSchema {
+        exported_at: SchemaDateTime(OffsetDateTime::now_utc()),
+        account: ExportedAccountSchema {
+            uuid: account.uuid(),
+            username: account.username.clone(),
+            display_name: account.display_name.clone(),
+            superadmin: account.superadmin,
+            locked: account.locked,
+            totp_enabled: account.totp_enabled(),
+            modified_at: SchemaDateTime(account.modified_at),
+            created_at: SchemaDateTime(account.created_at),
+        },
+        membership,
+        admin_of,
+        invite,
+        credential_resets,
+        recovery_email,
+        passkeys,
+        sessions,
+        oidc_tokens,
+        oidc_grants,
+        mailbox,
+    })
+}
+
+async fn export_club(tx: &mut Transaction, club: ClubUuid) -> anyhow::Result<ExportedClubSchema> {
+    let name = Club::find_by_uuid(&mut *tx, club)
+        .await?
+        .map(|club| club.name)
+        .ok_or(anyhow!("Club of account vanished"))?;
+
+    Ok(ExportedClubSchema { uuid: club, name })
+}
+
+async fn export_mailbox(email: &str) -> anyhow::Result<Option<ExportedMailboxSchema>> {
+    let sdk = &Mailcow::global().sdk;
+
+    let Some(mailbox) = sdk.get_mailbox(email).await? else {
+        return Ok(None);
+    };
+
+    let aliases = sdk
+        .get_all_aliases()
+        .await?
+        .into_iter()
+        .filter(|alias| alias.address != email && alias.forwards_to(email))
+        .map(|alias| alias.address)
+        .collect();
+
+    let app_passwords = sdk
+        .get_app_passwords(email.to_string())
+        .await?
+        .into_iter()
+        .map(|app_password| app_password.name)
+        .collect();
+
+    Ok(Some(ExportedMailboxSchema {
+        address: mailbox.username,
+        quota: mailbox.quota,
+        quota_used: mailbox.quota_used,
+        messages: mailbox.messages,
+        aliases,
+        app_passwords,
+    }))
+}
@@ -19,6 +19,7 @@ use crate::models::account::ClubAccount;
 use crate::models::account::CreateManualClubMember;
 use crate::models::club::Club;
 use crate::models::club::ClubUuid;
+use crate::models::domain::Domain;
 use crate::modules::mailcow::Mailcow;
 
 /// Import users from a JSON payload
@@ -119,7 +120,12 @@ async fn prepare_db_import(
             }
             Some((_, domain)) => MaxStr::new(domain.to_string())?,
         };
-        let club = match clubs.iter().find(|c| c.primary_domain == domain) {
+        // Members may be placed on any domain associated with their club
+        let club = match Domain::find_by_domain(&mut *tx, &domain)
+            .await?
+            .and_then(|domain| domain.associated_club)
+            .and_then(|associated| clubs.iter().find(|c| c.uuid == associated))
+        {
             None => return Err(format!("No club found for domain: {domain}").into()),
             Some(club) => club,
         };
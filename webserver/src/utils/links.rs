@@ -54,4 +54,31 @@ impl Link {
             .join(&format!("/links/reset/{uuid}"))
             .expect("UUIDs are fine in urls")
     }
+
+    /// Create a link to verify a recovery email
+    pub fn verify_recovery_email(token: &str) -> Url {
+        #[allow(clippy::expect_used)]
+        let mut url = ORIGIN
+            .join("/links/recovery-email/verify")
+            .expect("Static url");
+
+        url.query_pairs_mut().append_pair("token", token);
+
+        url
+    }
+
+    /// Create a link to the page where users enter the code of a device authorization request
+    pub fn device_verification() -> Url {
+        #[allow(clippy::expect_used)]
+        ORIGIN.join("/links/device").expect("Static url")
+    }
+
+    /// Create a link to the device verification page with the user code already filled in
+    pub fn device_verification_complete(user_code: &str) -> Url {
+        let mut url = Link::device_verification();
+
+        url.query_pairs_mut().append_pair("user_code", user_code);
+
+        url
+    }
 }
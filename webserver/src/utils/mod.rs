@@ -1,5 +1,8 @@
 //! Utility modules
 
+pub mod client_ip;
+pub mod data_export;
 pub mod import;
 pub mod links;
+pub mod text;
 pub mod worker;
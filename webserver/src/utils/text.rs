@@ -0,0 +1,9 @@
+//! Helpers for working with strings
+
+/// Truncate a string to at most `max` characters
+pub fn truncate(value: &str, max: usize) -> String {
+    match value.char_indices().nth(max) {
+        Some((index, _)) => value[..index].to_string(),
+        None => value.to_string(),
+    }
+}